@@ -323,6 +323,15 @@ pub fn expand_test_or_bench(
                                             }
                                         },
                                     ),
+                                    // timeout: Some(seconds) | None
+                                    field(
+                                        "timeout",
+                                        if let Some(secs) = test_timeout(cx, &item) {
+                                            cx.expr_some(sp, cx.expr_u64(sp, secs))
+                                        } else {
+                                            cx.expr_none(sp)
+                                        },
+                                    ),
                                     // },
                                 ],
                             ),
@@ -435,6 +444,23 @@ fn should_panic(cx: &ExtCtxt<'_>, i: &ast::Item) -> ShouldPanic {
     }
 }
 
+fn test_timeout(cx: &ExtCtxt<'_>, i: &ast::Item) -> Option<u64> {
+    let attr = cx.sess.find_by_name(&i.attrs, sym::test_timeout)?;
+    // Handle #[test_timeout = "seconds"], other forms are reported by the attribute template
+    // check.
+    let secs = attr.value_str()?;
+    match secs.as_str().parse::<u64>() {
+        Ok(secs) if secs > 0 => Some(secs),
+        _ => {
+            cx.struct_span_err(attr.span, "malformed `test_timeout` attribute input")
+                .span_label(attr.span, "expected a positive number of seconds")
+                .help("use `#[test_timeout = \"60\"]` to fail the test after 60 seconds")
+                .emit();
+            None
+        }
+    }
+}
+
 enum TestType {
     UnitTest,
     IntegrationTest,
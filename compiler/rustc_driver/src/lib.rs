@@ -398,6 +398,10 @@ fn run_compiler(
                 sess.code_stats.print_type_sizes();
             }
 
+            if sess.opts.prints.contains(&PrintRequest::TypeLayouts) {
+                sess.code_stats.print_type_layouts_json();
+            }
+
             let linker = queries.linker()?;
             Ok(Some(linker))
         })?;
@@ -631,9 +635,11 @@ fn print_crate_info(
     temps_dir: &Option<PathBuf>,
 ) -> Compilation {
     use rustc_session::config::PrintRequest::*;
-    // NativeStaticLibs and LinkArgs are special - printed during linking
+    // NativeStaticLibs and LinkArgs are special - printed during linking, and
+    // TypeLayouts is printed once codegen has computed all the layouts
     // (empty iterator returns true)
-    if sess.opts.prints.iter().all(|&p| p == NativeStaticLibs || p == LinkArgs) {
+    if sess.opts.prints.iter().all(|&p| p == NativeStaticLibs || p == LinkArgs || p == TypeLayouts)
+    {
         return Compilation::Continue;
     }
 
@@ -732,6 +738,7 @@ fn print_crate_info(
             // Any output here interferes with Cargo's parsing of other printed output
             NativeStaticLibs => {}
             LinkArgs => {}
+            TypeLayouts => {}
             SplitDebuginfo => {
                 use rustc_target::spec::SplitDebuginfo::{Off, Packed, Unpacked};
 
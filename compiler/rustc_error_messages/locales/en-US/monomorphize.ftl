@@ -21,3 +21,16 @@ monomorphize_large_assignments =
     moving {$size} bytes
     .label = value moved from here
     .note = The current maximum size is {$limit}, but it can be customized with the move_size_limit attribute: `#![move_size_limit = "..."]`
+
+monomorphize_large_futures =
+    large {$kind} with a size of {$size} bytes
+    .note = the size limit is {$limit} bytes, and can be changed with `-Z future-size-limit`
+    .help = consider boxing large values, or dropping them before the {$suspension} point
+
+monomorphize_large_futures_saved_local =
+    this value of type `{$ty}` takes {$local_size} bytes and is held across {$suspension_points} {$suspension} {$suspension_points ->
+        [one] point
+        *[other] points
+    }
+
+monomorphize_large_futures_more_locals = {$more} more values are held across {$suspension} points
@@ -225,6 +225,11 @@ fn should_show_explain(&self) -> bool {
         true
     }
 
+    /// Checks if should show the number of errors and warnings at the end of the compilation
+    fn should_show_error_count(&self) -> bool {
+        true
+    }
+
     /// Checks if we can use colors in the current output stream.
     fn supports_color(&self) -> bool {
         false
@@ -57,6 +57,7 @@
 pub mod json;
 mod lock;
 pub mod registry;
+pub mod sarif;
 mod snippet;
 mod styled_buffer;
 pub mod translation;
@@ -1388,6 +1389,7 @@ fn print_error_count(&mut self, registry: &Registry) {
 
         match (errors.len(), warnings.len()) {
             (0, 0) => return,
+            _ if !self.emitter.should_show_error_count() => {}
             (0, _) => self.emitter.emit_diagnostic(&Diagnostic::new(
                 Level::Warning(None),
                 DiagnosticMessage::Str(warnings),
@@ -0,0 +1,490 @@
+//! A SARIF emitter for errors.
+//!
+//! [SARIF 2.1.0] is a JSON based format understood by code scanning tools. Unlike the JSON
+//! emitter, which prints one object per diagnostic, a SARIF log is a single document, so this
+//! emitter buffers every diagnostic and writes the whole log once it is dropped.
+//!
+//! Each diagnostic becomes a `result`. Its code (an error code or a lint name) is used as the
+//! rule ID, spans become `locations` and `relatedLocations`, and structured suggestions become
+//! `fixes`. Notes and helps without a span are appended to the result's message.
+//!
+//! [SARIF 2.1.0]: https://docs.oasis-open.org/sarif/sarif/v2.1.0/sarif-v2.1.0.html
+
+use rustc_span::source_map::SourceMap;
+
+use crate::emitter::Emitter;
+use crate::registry::Registry;
+use crate::translation::{to_fluent_args, Translate};
+use crate::{CodeSuggestion, DiagnosticId, FluentBundle, LazyFallbackBundle, Level, SubDiagnostic};
+use rustc_lint_defs::Applicability;
+
+use rustc_data_structures::fx::FxIndexMap;
+use rustc_data_structures::sync::Lrc;
+use rustc_error_messages::FluentArgs;
+use rustc_span::Span;
+use std::io::{self, Write};
+
+use serde::Serialize;
+
+#[cfg(test)]
+mod tests;
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+const SARIF_VERSION: &str = "2.1.0";
+
+pub struct SarifEmitter {
+    dst: Box<dyn Write + Send>,
+    registry: Option<Registry>,
+    sm: Lrc<SourceMap>,
+    fluent_bundle: Option<Lrc<FluentBundle>>,
+    fallback_bundle: LazyFallbackBundle,
+    tool_version: Option<&'static str>,
+    /// The rules referenced by the results so far, indexed by their ID.
+    rules: FxIndexMap<String, Rule>,
+    results: Vec<SarifResult>,
+}
+
+impl SarifEmitter {
+    pub fn stderr(
+        registry: Option<Registry>,
+        source_map: Lrc<SourceMap>,
+        fluent_bundle: Option<Lrc<FluentBundle>>,
+        fallback_bundle: LazyFallbackBundle,
+        tool_version: Option<&'static str>,
+    ) -> SarifEmitter {
+        SarifEmitter::new(
+            Box::new(io::BufWriter::new(io::stderr())),
+            registry,
+            source_map,
+            fluent_bundle,
+            fallback_bundle,
+            tool_version,
+        )
+    }
+
+    pub fn new(
+        dst: Box<dyn Write + Send>,
+        registry: Option<Registry>,
+        source_map: Lrc<SourceMap>,
+        fluent_bundle: Option<Lrc<FluentBundle>>,
+        fallback_bundle: LazyFallbackBundle,
+        tool_version: Option<&'static str>,
+    ) -> SarifEmitter {
+        SarifEmitter {
+            dst,
+            registry,
+            sm: source_map,
+            fluent_bundle,
+            fallback_bundle,
+            tool_version,
+            rules: FxIndexMap::default(),
+            results: Vec::new(),
+        }
+    }
+
+    fn rule_index(&mut self, code: &DiagnosticId) -> (String, usize) {
+        let id = match code {
+            DiagnosticId::Error(s) => s,
+            DiagnosticId::Lint { name, .. } => name,
+        };
+        if let Some(index) = self.rules.get_index_of(id) {
+            return (id.clone(), index);
+        }
+
+        let rule = match code {
+            DiagnosticId::Error(code) => {
+                let explanation = self
+                    .registry
+                    .as_ref()
+                    .and_then(|registry| registry.try_find_description(code).ok().flatten());
+                Rule {
+                    id: code.clone(),
+                    full_description: explanation.map(|text| Message::new(text.trim().to_owned())),
+                    help_uri: Some(format!("https://doc.rust-lang.org/error-index.html#{code}")),
+                }
+            }
+            DiagnosticId::Lint { name, .. } => {
+                Rule { id: name.clone(), full_description: None, help_uri: None }
+            }
+        };
+        let (index, _) = self.rules.insert_full(id.clone(), rule);
+        (id.clone(), index)
+    }
+
+    fn write_log(&mut self) -> io::Result<()> {
+        let execution_successful = !self.results.iter().any(|result| result.level == "error");
+        let log = SarifLog {
+            schema: SARIF_SCHEMA,
+            version: SARIF_VERSION,
+            runs: vec![Run {
+                tool: Tool {
+                    driver: ToolComponent {
+                        name: "rustc",
+                        information_uri: "https://www.rust-lang.org/",
+                        version: self.tool_version,
+                        rules: self.rules.values().collect(),
+                    },
+                },
+                invocations: vec![Invocation { execution_successful }],
+                column_kind: "unicodeCodePoints",
+                results: &self.results,
+            }],
+        };
+        writeln!(&mut self.dst, "{}", serde_json::to_string(&log).unwrap())?;
+        self.dst.flush()
+    }
+}
+
+impl Translate for SarifEmitter {
+    fn fluent_bundle(&self) -> Option<&Lrc<FluentBundle>> {
+        self.fluent_bundle.as_ref()
+    }
+
+    fn fallback_fluent_bundle(&self) -> &FluentBundle {
+        &self.fallback_bundle
+    }
+}
+
+impl Emitter for SarifEmitter {
+    fn emit_diagnostic(&mut self, diag: &crate::Diagnostic) {
+        // Failure notes such as "For more information about this error" describe the compilation
+        // session rather than the code, so they don't make sense as results.
+        let Some(level) = sarif_level(diag.level) else { return };
+
+        let args = to_fluent_args(diag.args());
+        let mut text = self.translate_messages(&diag.message, &args).into_owned();
+        let mut related_locations = Vec::new();
+        for span_label in diag.span.span_labels() {
+            if !span_label.is_primary {
+                let label = span_label
+                    .label
+                    .as_ref()
+                    .map(|label| self.translate_message(label, &args).into_owned());
+                related_locations.push(self.location(span_label.span, label));
+            }
+        }
+        for child in &diag.children {
+            self.add_child(child, &args, &mut text, &mut related_locations);
+        }
+
+        let locations = diag
+            .span
+            .span_labels()
+            .into_iter()
+            .filter(|span_label| span_label.is_primary)
+            .map(|span_label| {
+                let label = span_label
+                    .label
+                    .as_ref()
+                    .map(|label| self.translate_message(label, &args).into_owned());
+                self.location(span_label.span, label)
+            })
+            .collect();
+        let fixes = diag
+            .suggestions
+            .iter()
+            .flatten()
+            .flat_map(|suggestion| self.fixes(suggestion, &args))
+            .collect();
+        let (rule_id, rule_index) = match &diag.code {
+            Some(code) => {
+                let (id, index) = self.rule_index(code);
+                (Some(id), Some(index))
+            }
+            None => (None, None),
+        };
+
+        self.results.push(SarifResult {
+            rule_id,
+            rule_index,
+            level,
+            message: Message::new(text),
+            locations,
+            related_locations,
+            fixes,
+        });
+    }
+
+    fn source_map(&self) -> Option<&Lrc<SourceMap>> {
+        Some(&self.sm)
+    }
+
+    fn should_show_explain(&self) -> bool {
+        false
+    }
+
+    // Like failure notes, "aborting due to previous error" isn't about the code, and the number of
+    // results is in the log anyway.
+    fn should_show_error_count(&self) -> bool {
+        false
+    }
+}
+
+impl Drop for SarifEmitter {
+    fn drop(&mut self) {
+        if let Err(e) = self.write_log() {
+            panic!("failed to print diagnostics: {:?}", e);
+        }
+    }
+}
+
+impl SarifEmitter {
+    /// Children with spans become related locations, the others are appended to the message
+    /// the way they would be printed below the diagnostic.
+    fn add_child(
+        &self,
+        child: &SubDiagnostic,
+        args: &FluentArgs<'_>,
+        text: &mut String,
+        related_locations: &mut Vec<Location>,
+    ) {
+        let message =
+            format!("{}: {}", child.level.to_str(), self.translate_messages(&child.message, args));
+        let span = child.render_span.as_ref().unwrap_or(&child.span);
+        if span.primary_spans().is_empty() {
+            text.push('\n');
+            text.push_str(&message);
+            return;
+        }
+        for span_label in span.span_labels() {
+            let label = match &span_label.label {
+                Some(label) if !span_label.is_primary => {
+                    self.translate_message(label, args).into_owned()
+                }
+                _ => message.clone(),
+            };
+            related_locations.push(self.location(span_label.span, Some(label)));
+        }
+    }
+
+    fn fixes(&self, suggestion: &CodeSuggestion, args: &FluentArgs<'_>) -> Vec<Fix> {
+        let description = self.translate_message(&suggestion.msg, args).into_owned();
+        suggestion
+            .substitutions
+            .iter()
+            .map(|substitution| {
+                let mut changes: FxIndexMap<String, Vec<Replacement>> = FxIndexMap::default();
+                for part in &substitution.parts {
+                    let (uri, region) = self.region(part.span);
+                    changes.entry(uri).or_default().push(Replacement {
+                        deleted_region: region,
+                        inserted_content: ArtifactContent { text: part.snippet.clone() },
+                    });
+                }
+                Fix {
+                    description: Message::new(description.clone()),
+                    artifact_changes: changes
+                        .into_iter()
+                        .map(|(uri, replacements)| ArtifactChange {
+                            artifact_location: ArtifactLocation { uri },
+                            replacements,
+                        })
+                        .collect(),
+                    properties: FixProperties { applicability: suggestion.applicability },
+                }
+            })
+            .collect()
+    }
+
+    fn location(&self, span: Span, label: Option<String>) -> Location {
+        let (uri, region) = self.region(span);
+        Location {
+            physical_location: PhysicalLocation {
+                artifact_location: ArtifactLocation { uri },
+                region,
+            },
+            message: label.map(Message::new),
+        }
+    }
+
+    fn region(&self, span: Span) -> (String, Region) {
+        let start = self.sm.lookup_char_pos(span.lo());
+        let end = self.sm.lookup_char_pos(span.hi());
+        let byte_offset = start.file.original_relative_byte_pos(span.lo()).0;
+        let byte_end = start.file.original_relative_byte_pos(span.hi()).0;
+        let uri = path_to_uri(&self.sm.filename_for_diagnostics(&start.file.name).to_string());
+        let region = Region {
+            start_line: start.line,
+            start_column: start.col.0 + 1,
+            end_line: end.line,
+            end_column: end.col.0 + 1,
+            byte_offset,
+            byte_length: byte_end - byte_offset,
+        };
+        (uri, region)
+    }
+}
+
+fn sarif_level(level: Level) -> Option<&'static str> {
+    match level {
+        Level::Bug | Level::DelayedBug | Level::Fatal | Level::Error { .. } => Some("error"),
+        Level::Warning(_) => Some("warning"),
+        Level::Note | Level::OnceNote | Level::Help => Some("note"),
+        Level::FailureNote | Level::Allow | Level::Expect(_) => None,
+    }
+}
+
+/// Turns a file name into a URI reference, percent-encoding everything that may not appear in
+/// the path of one.
+fn path_to_uri(path: &str) -> String {
+    let mut uri = String::with_capacity(path.len());
+    for byte in path.bytes() {
+        match byte {
+            b'\\' => uri.push('/'),
+            b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' => uri.push(byte as char),
+            b'-' | b'.' | b'_' | b'~' | b'/' | b':' | b'@' | b'!' | b'$' | b'&' | b'\'' => {
+                uri.push(byte as char)
+            }
+            b'(' | b')' | b'*' | b'+' | b',' | b';' | b'=' => uri.push(byte as char),
+            _ => uri.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    uri
+}
+
+// The following data types are provided just for serialisation.
+
+#[derive(Serialize)]
+struct SarifLog<'a> {
+    #[serde(rename = "$schema")]
+    schema: &'static str,
+    version: &'static str,
+    runs: Vec<Run<'a>>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Run<'a> {
+    tool: Tool<'a>,
+    invocations: Vec<Invocation>,
+    /// How columns are counted; rustc counts characters rather than UTF-16 code units.
+    column_kind: &'static str,
+    results: &'a [SarifResult],
+}
+
+#[derive(Serialize)]
+struct Tool<'a> {
+    driver: ToolComponent<'a>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ToolComponent<'a> {
+    name: &'static str,
+    information_uri: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    version: Option<&'static str>,
+    rules: Vec<&'a Rule>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Invocation {
+    execution_successful: bool,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Rule {
+    /// The error code or the name of the lint.
+    id: String,
+    /// The long form explanation of an error code.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    full_description: Option<Message>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    help_uri: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifResult {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rule_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rule_index: Option<usize>,
+    /// "error", "warning" or "note".
+    level: &'static str,
+    message: Message,
+    locations: Vec<Location>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    related_locations: Vec<Location>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    fixes: Vec<Fix>,
+}
+
+#[derive(Serialize)]
+struct Message {
+    text: String,
+}
+
+impl Message {
+    fn new(text: String) -> Message {
+        Message { text }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Location {
+    physical_location: PhysicalLocation,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message: Option<Message>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PhysicalLocation {
+    artifact_location: ArtifactLocation,
+    region: Region,
+}
+
+#[derive(Serialize)]
+struct ArtifactLocation {
+    uri: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Region {
+    /// 1-based.
+    start_line: usize,
+    /// 1-based, character offset.
+    start_column: usize,
+    end_line: usize,
+    /// The column of the character following the region.
+    end_column: usize,
+    byte_offset: u32,
+    byte_length: u32,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Fix {
+    description: Message,
+    artifact_changes: Vec<ArtifactChange>,
+    properties: FixProperties,
+}
+
+#[derive(Serialize)]
+struct FixProperties {
+    applicability: Applicability,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ArtifactChange {
+    artifact_location: ArtifactLocation,
+    replacements: Vec<Replacement>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Replacement {
+    deleted_region: Region,
+    inserted_content: ArtifactContent,
+}
+
+#[derive(Serialize)]
+struct ArtifactContent {
+    text: String,
+}
@@ -0,0 +1,136 @@
+use super::*;
+
+use crate::{DiagnosticId, Handler};
+use rustc_span::source_map::FilePathMapping;
+use rustc_span::{BytePos, Span};
+
+use std::path::Path;
+use std::str;
+use std::sync::{Arc, Mutex};
+
+use serde_json::Value;
+
+struct Shared<T> {
+    data: Arc<Mutex<T>>,
+}
+
+impl<T: Write> Write for Shared<T> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.data.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.data.lock().unwrap().flush()
+    }
+}
+
+/// Emits the diagnostics created by `f` for a file containing `code`, and returns the SARIF log.
+fn with_sarif_log(code: &str, f: impl FnOnce(&Handler)) -> Value {
+    rustc_span::create_default_session_globals_then(|| {
+        let sm = Lrc::new(SourceMap::new(FilePathMapping::empty()));
+        sm.new_source_file(Path::new("src/my file.rs").to_owned().into(), code.to_owned());
+        let fallback_bundle =
+            crate::fallback_fluent_bundle(rustc_error_messages::DEFAULT_LOCALE_RESOURCES, false);
+
+        let output = Arc::new(Mutex::new(Vec::new()));
+        let emitter = SarifEmitter::new(
+            Box::new(Shared { data: output.clone() }),
+            None,
+            sm,
+            None,
+            fallback_bundle,
+            Some("1.0.0"),
+        );
+
+        let handler = Handler::with_emitter(true, None, Box::new(emitter));
+        f(&handler);
+        drop(handler);
+
+        let bytes = output.lock().unwrap();
+        serde_json::from_str(str::from_utf8(&bytes).unwrap()).unwrap()
+    })
+}
+
+fn span(lo: u32, hi: u32) -> Span {
+    Span::with_root_ctxt(BytePos(lo), BytePos(hi))
+}
+
+#[test]
+fn empty_log() {
+    let log = with_sarif_log("fn main() {}", |_| {});
+    assert_eq!(log["version"], "2.1.0");
+    let run = &log["runs"][0];
+    assert_eq!(run["tool"]["driver"]["name"], "rustc");
+    assert_eq!(run["tool"]["driver"]["version"], "1.0.0");
+    assert_eq!(run["results"].as_array().unwrap().len(), 0);
+    assert_eq!(run["invocations"][0]["executionSuccessful"], true);
+}
+
+#[test]
+fn error_with_code_and_suggestion() {
+    let log = with_sarif_log("let x = 1u8 + 1u16;", |handler| {
+        handler
+            .struct_span_err_with_code(
+                span(14, 18),
+                "mismatched types",
+                DiagnosticId::Error("E0308".to_owned()),
+            )
+            .span_suggestion(span(14, 18), "change the type", "1u8", Applicability::MaybeIncorrect)
+            .note("a note without a span")
+            .emit();
+    });
+    let run = &log["runs"][0];
+    assert_eq!(run["invocations"][0]["executionSuccessful"], false);
+    assert_eq!(run["tool"]["driver"]["rules"][0]["id"], "E0308");
+
+    let result = &run["results"][0];
+    assert_eq!(result["ruleId"], "E0308");
+    assert_eq!(result["ruleIndex"], 0);
+    assert_eq!(result["level"], "error");
+    assert_eq!(result["message"]["text"], "mismatched types\nnote: a note without a span");
+
+    let location = &result["locations"][0]["physicalLocation"];
+    assert_eq!(location["artifactLocation"]["uri"], "src/my%20file.rs");
+    assert_eq!(location["region"]["startLine"], 1);
+    assert_eq!(location["region"]["startColumn"], 15);
+    assert_eq!(location["region"]["endColumn"], 19);
+    assert_eq!(location["region"]["byteOffset"], 14);
+    assert_eq!(location["region"]["byteLength"], 4);
+
+    let fix = &result["fixes"][0];
+    assert_eq!(fix["description"]["text"], "change the type");
+    assert_eq!(fix["properties"]["applicability"], "MaybeIncorrect");
+    let replacement = &fix["artifactChanges"][0]["replacements"][0];
+    assert_eq!(replacement["deletedRegion"]["byteOffset"], 14);
+    assert_eq!(replacement["insertedContent"]["text"], "1u8");
+}
+
+#[test]
+fn lints_share_rules() {
+    let log = with_sarif_log("let x = 1;\nlet y = 2;", |handler| {
+        for (lo, hi) in [(4, 5), (15, 16)] {
+            handler
+                .struct_span_warn(span(lo, hi), "unused variable")
+                .code(DiagnosticId::Lint {
+                    name: "unused_variables".to_owned(),
+                    has_future_breakage: false,
+                    is_force_warn: false,
+                })
+                .span_note(span(0, 3), "declared here")
+                .emit();
+        }
+    });
+    let run = &log["runs"][0];
+    assert_eq!(run["invocations"][0]["executionSuccessful"], true);
+    assert_eq!(run["tool"]["driver"]["rules"].as_array().unwrap().len(), 1);
+
+    let results = run["results"].as_array().unwrap();
+    assert_eq!(results.len(), 2);
+    for result in results {
+        assert_eq!(result["ruleId"], "unused_variables");
+        assert_eq!(result["ruleIndex"], 0);
+        assert_eq!(result["level"], "warning");
+        assert_eq!(result["relatedLocations"][0]["message"]["text"], "note: declared here");
+    }
+    assert_eq!(results[1]["locations"][0]["physicalLocation"]["region"]["startLine"], 2);
+}
@@ -510,6 +510,8 @@ pub fn set(&self, features: &mut Features, span: Span) {
     (active, string_deref_patterns, "CURRENT_RUSTC_VERSION", Some(87121), None),
     /// Allows the use of `#[target_feature]` on safe functions.
     (active, target_feature_11, "1.45.0", Some(69098), None),
+    /// Allows setting a per-test timeout with `#[test_timeout = "seconds"]`.
+    (active, test_timeout, "CURRENT_RUSTC_VERSION", None, None),
     /// Allows using `#[thread_local]` on `static` items.
     (active, thread_local, "1.0.0", Some(29594), None),
     /// Allows defining `trait X = A + B;` alias items.
@@ -4,10 +4,15 @@
 //! dependency graph and the exported metadata hashes.
 //!
 //! In practice "compatible compiler version" means "exactly the same compiler
-//! version", since the header encodes the git commit hash of the compiler.
-//! Since we can always just ignore the incremental compilation cache and
-//! compiler versions don't change frequently for the typical user, being
-//! conservative here practically has no downside.
+//! build", since the header encodes the git commit hash of the compiler and
+//! the host it was built for. Since we can always just ignore the incremental
+//! compilation cache and compiler versions don't change frequently for the
+//! typical user, being conservative here practically has no downside.
+//!
+//! The files themselves do not contain absolute paths: source files are
+//! referred to by their (possibly remapped) names, so a cache written with
+//! `--remap-path-prefix` can be reused from a different checkout that is
+//! remapped to the same path.
 
 use std::env;
 use std::fs;
@@ -23,22 +28,51 @@
 const FILE_MAGIC: &[u8] = b"RSIC";
 
 /// Change this if the header format changes.
-const HEADER_FORMAT_VERSION: u16 = 0;
+const HEADER_FORMAT_VERSION: u16 = 1;
 
 /// A version string that hopefully is always different for compiler versions
 /// with different encodings of incremental compilation artifacts. Contains
 /// the Git commit hash.
 const RUSTC_VERSION: Option<&str> = option_env!("CFG_VERSION");
 
+/// The host the compiler was built for. The same compiler version built for
+/// different hosts is considered to be a different compiler build.
+const RUSTC_HOST: Option<&str> = option_env!("CFG_COMPILER_HOST_TRIPLE");
+
+/// The outcome of reading a file with a file header as defined in this module.
+pub enum ReadResult {
+    /// The file exists and was generated by a compatible compiler. `data` is
+    /// the entire contents of the file and `start_pos` points to the first
+    /// byte after the header.
+    Ok { data: Mmap, start_pos: usize },
+    /// The file does not exist, or is not an incremental compilation file in a
+    /// format this compiler understands.
+    NotFoundOrOutdated,
+    /// The file was generated by a different compiler build.
+    IncompatibleCompiler { expected: String, found: String },
+}
+
 pub(crate) fn write_file_header(stream: &mut FileEncoder, nightly_build: bool) {
     stream.emit_raw_bytes(FILE_MAGIC);
     stream
         .emit_raw_bytes(&[(HEADER_FORMAT_VERSION >> 0) as u8, (HEADER_FORMAT_VERSION >> 8) as u8]);
 
-    let rustc_version = rustc_version(nightly_build);
-    assert_eq!(rustc_version.len(), (rustc_version.len() as u8) as usize);
-    stream.emit_raw_bytes(&[rustc_version.len() as u8]);
-    stream.emit_raw_bytes(rustc_version.as_bytes());
+    write_header_str(stream, &rustc_version(nightly_build));
+    write_header_str(stream, rustc_host());
+}
+
+fn write_header_str(stream: &mut FileEncoder, s: &str) {
+    assert_eq!(s.len(), (s.len() as u8) as usize);
+    stream.emit_raw_bytes(&[s.len() as u8]);
+    stream.emit_raw_bytes(s.as_bytes());
+}
+
+fn read_header_str(file: &mut io::Cursor<&[u8]>) -> io::Result<Vec<u8>> {
+    let mut len = [0u8; 1];
+    file.read_exact(&mut len)?;
+    let mut buffer = vec![0; len[0] as usize];
+    file.read_exact(&mut buffer)?;
+    Ok(buffer)
 }
 
 pub(crate) fn save_in<F>(sess: &Session, path_buf: PathBuf, name: &str, encode: F)
@@ -97,21 +131,17 @@ pub(crate) fn save_in<F>(sess: &Session, path_buf: PathBuf, name: &str, encode:
 
 /// Reads the contents of a file with a file header as defined in this module.
 ///
-/// - Returns `Ok(Some(data, pos))` if the file existed and was generated by a
-///   compatible compiler version. `data` is the entire contents of the file
-///   and `pos` points to the first byte after the header.
-/// - Returns `Ok(None)` if the file did not exist or was generated by an
-///   incompatible version of the compiler.
-/// - Returns `Err(..)` if some kind of IO error occurred while reading the
-///   file.
+/// Returns `Err(..)` if some kind of IO error occurred while reading the file.
 pub fn read_file(
     report_incremental_info: bool,
     path: &Path,
     nightly_build: bool,
-) -> io::Result<Option<(Mmap, usize)>> {
+) -> io::Result<ReadResult> {
     let file = match fs::File::open(path) {
         Ok(file) => file,
-        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {
+            return Ok(ReadResult::NotFoundOrOutdated);
+        }
         Err(err) => return Err(err),
     };
     // SAFETY: This process must not modify nor remove the backing file while the memory map lives.
@@ -131,7 +161,7 @@ pub fn read_file(
         file.read_exact(&mut file_magic)?;
         if file_magic != FILE_MAGIC {
             report_format_mismatch(report_incremental_info, path, "Wrong FILE_MAGIC");
-            return Ok(None);
+            return Ok(ReadResult::NotFoundOrOutdated);
         }
     }
 
@@ -145,26 +175,32 @@ pub fn read_file(
 
         if header_format_version != HEADER_FORMAT_VERSION {
             report_format_mismatch(report_incremental_info, path, "Wrong HEADER_FORMAT_VERSION");
-            return Ok(None);
+            return Ok(ReadResult::NotFoundOrOutdated);
         }
     }
 
-    // Check RUSTC_VERSION
+    // Check RUSTC_VERSION and RUSTC_HOST
     {
-        let mut rustc_version_str_len = [0u8; 1];
-        file.read_exact(&mut rustc_version_str_len)?;
-        let rustc_version_str_len = rustc_version_str_len[0] as usize;
-        let mut buffer = vec![0; rustc_version_str_len];
-        file.read_exact(&mut buffer)?;
+        let found_version = read_header_str(&mut file)?;
+        let found_host = read_header_str(&mut file)?;
 
-        if buffer != rustc_version(nightly_build).as_bytes() {
+        let expected_version = rustc_version(nightly_build);
+        let expected_host = rustc_host();
+        if found_version != expected_version.as_bytes() || found_host != expected_host.as_bytes() {
             report_format_mismatch(report_incremental_info, path, "Different compiler version");
-            return Ok(None);
+            return Ok(ReadResult::IncompatibleCompiler {
+                expected: format!("{expected_version} for {expected_host}"),
+                found: format!(
+                    "{} for {}",
+                    String::from_utf8_lossy(&found_version),
+                    String::from_utf8_lossy(&found_host)
+                ),
+            });
         }
     }
 
-    let post_header_start_pos = file.position() as usize;
-    Ok(Some((mmap, post_header_start_pos)))
+    let start_pos = file.position() as usize;
+    Ok(ReadResult::Ok { data: mmap, start_pos })
 }
 
 fn report_format_mismatch(report_incremental_info: bool, file: &Path, message: &str) {
@@ -193,3 +229,7 @@ fn rustc_version(nightly_build: bool) -> String {
         )
         .to_string()
 }
+
+fn rustc_host() -> &'static str {
+    RUSTC_HOST.expect("Cannot use rustc without explicit host for incremental compilation")
+}
@@ -50,6 +50,13 @@
 //! any more and will delete those. It will also delete any finalized session
 //! directories for a given crate except for the most recent one.
 //!
+//! If `-Z incremental-cache-limit` is given, the compiler additionally deletes
+//! finalized session directories of any crate in the incremental compilation
+//! directory after finalizing its own session, least recently used first, until
+//! the directory fits into the given size. Object files in the session
+//! directory that do not belong to any current work product are deleted when
+//! the work product index is saved.
+//!
 //! ## Synchronization
 //!
 //! There is some synchronization needed in order for the compiler to be able to
@@ -394,6 +401,10 @@ pub fn finalize_session_directory(sess: &Session, svh: Svh) {
     }
 
     let _ = garbage_collect_session_directories(sess);
+
+    if let Some(limit) = sess.opts.unstable_opts.incremental_cache_limit {
+        let _ = enforce_cache_size_limit(sess, limit.saturating_mul(1024 * 1024));
+    }
 }
 
 pub fn delete_all_session_dir_contents(sess: &Session) -> io::Result<()> {
@@ -917,6 +928,147 @@ fn delete_old(sess: &Session, path: &Path) {
     }
 }
 
+/// Deletes finalized session directories of any crate in the incremental compilation directory,
+/// least recently used first, until the directory is no larger than `limit` bytes.
+///
+/// Garbage collection only ever keeps the most recent finalized session directory of a crate, so
+/// the timestamp of that directory is the last time the crate was compiled in this configuration.
+/// The session directory of the current session is never deleted.
+fn enforce_cache_size_limit(sess: &Session, limit: u64) -> io::Result<()> {
+    debug!("enforce_cache_size_limit() - begin");
+
+    let incr_dir = sess.opts.incremental.as_ref().unwrap();
+    let current_session_dir = sess.incr_comp_session_dir().clone();
+
+    let mut total_size = 0;
+    let mut candidates = vec![];
+    for crate_dir_entry in incr_dir.read_dir()? {
+        let Ok(crate_dir_entry) = crate_dir_entry else { continue };
+        if !crate_dir_entry.file_type().map_or(false, |file_type| file_type.is_dir()) {
+            continue;
+        }
+        let crate_directory = crate_dir_entry.path();
+        let Ok(dir_entries) = crate_directory.read_dir() else { continue };
+
+        for dir_entry in dir_entries.flatten() {
+            let file_name = dir_entry.file_name();
+            let Some(directory_name) = file_name.to_str() else { continue };
+            if !is_session_directory(directory_name)
+                || !dir_entry.file_type().map_or(false, |file_type| file_type.is_dir())
+            {
+                continue;
+            }
+
+            let path = dir_entry.path();
+            let size = directory_size(&path);
+            total_size += size;
+
+            if is_finalized(directory_name) && path != current_session_dir {
+                if let Ok(timestamp) = extract_timestamp_from_session_dir(directory_name) {
+                    candidates.push((timestamp, path, size));
+                }
+            }
+        }
+    }
+
+    debug!("enforce_cache_size_limit() - total size: {} bytes, limit: {} bytes", total_size, limit);
+    if total_size <= limit {
+        return Ok(());
+    }
+
+    let mut removed = vec![];
+    for (path, size) in least_recently_used_exceeding(candidates, total_size - limit) {
+        // Directories that another compiler instance is currently copying from are locked.
+        let lock_file_path = lock_file_path(&path);
+        let Ok(lock) = flock::Lock::new(
+            &lock_file_path,
+            false, // don't wait
+            false, // don't create the lock-file
+            true,  // get an exclusive lock
+        ) else {
+            debug!("enforce_cache_size_limit() - not deleting `{}`, still in use", path.display());
+            continue;
+        };
+
+        debug!("enforce_cache_size_limit() - deleting `{}`", path.display());
+        match safe_remove_dir_all(&path) {
+            Ok(()) => {
+                delete_session_dir_lock_file(sess, &lock_file_path);
+                total_size -= size;
+                removed.push((path, size));
+            }
+            Err(err) => sess.warn(&format!(
+                "Failed to delete incremental compilation session directory `{}` \
+                 to enforce the cache size limit: {}",
+                path.display(),
+                err
+            )),
+        }
+
+        drop(lock);
+    }
+
+    // Don't leave behind the directories of crates that have no sessions left. This fails
+    // harmlessly for directories that are not empty.
+    for (path, _) in &removed {
+        let _ = std_fs::remove_dir(path.parent().unwrap());
+    }
+
+    if sess.opts.unstable_opts.incremental_info {
+        for (path, size) in &removed {
+            let path = path.strip_prefix(incr_dir).unwrap_or(path);
+            sess.note_without_error(&format!(
+                "incremental compilation cache size limit: removed `{}` ({} bytes)",
+                path.display(),
+                size
+            ));
+        }
+        sess.note_without_error(&format!(
+            "incremental compilation cache size limit: {} session directories removed, \
+             {} of {} bytes used",
+            removed.len(),
+            total_size,
+            limit
+        ));
+    }
+
+    Ok(())
+}
+
+/// Returns the least recently used of the given session directories whose sizes add up to at
+/// least `excess` bytes, or all of them if they are not large enough.
+fn least_recently_used_exceeding(
+    mut candidates: Vec<(SystemTime, PathBuf, u64)>,
+    excess: u64,
+) -> Vec<(PathBuf, u64)> {
+    candidates.sort_by_key(|&(timestamp, ..)| timestamp);
+
+    let mut freed = 0;
+    candidates
+        .into_iter()
+        .take_while(|&(_, _, size)| {
+            let done = freed >= excess;
+            freed += size;
+            !done
+        })
+        .map(|(_, path, size)| (path, size))
+        .collect()
+}
+
+/// Returns the total size of the files in a directory and its subdirectories.
+fn directory_size(path: &Path) -> u64 {
+    let Ok(dir_entries) = path.read_dir() else { return 0 };
+
+    dir_entries
+        .flatten()
+        .map(|dir_entry| match dir_entry.metadata() {
+            Ok(metadata) if metadata.is_dir() => directory_size(&dir_entry.path()),
+            Ok(metadata) => metadata.len(),
+            Err(_) => 0,
+        })
+        .sum()
+}
+
 fn all_except_most_recent(
     deletion_candidates: Vec<(SystemTime, PathBuf, Option<flock::Lock>)>,
 ) -> FxHashMap<PathBuf, Option<flock::Lock>> {
@@ -24,6 +24,35 @@ fn test_all_except_most_recent() {
     );
 }
 
+#[test]
+fn test_least_recently_used_exceeding() {
+    let candidates = || {
+        vec![
+            (UNIX_EPOCH + Duration::new(4, 0), PathBuf::from("4"), 40),
+            (UNIX_EPOCH + Duration::new(1, 0), PathBuf::from("1"), 10),
+            (UNIX_EPOCH + Duration::new(3, 0), PathBuf::from("3"), 30),
+            (UNIX_EPOCH + Duration::new(2, 0), PathBuf::from("2"), 20),
+        ]
+    };
+
+    assert_eq!(least_recently_used_exceeding(candidates(), 0), vec![]);
+    assert_eq!(least_recently_used_exceeding(candidates(), 10), vec![(PathBuf::from("1"), 10)]);
+    assert_eq!(
+        least_recently_used_exceeding(candidates(), 11),
+        vec![(PathBuf::from("1"), 10), (PathBuf::from("2"), 20)]
+    );
+    assert_eq!(
+        least_recently_used_exceeding(candidates(), 1000),
+        vec![
+            (PathBuf::from("1"), 10),
+            (PathBuf::from("2"), 20),
+            (PathBuf::from("3"), 30),
+            (PathBuf::from("4"), 40),
+        ]
+    );
+    assert_eq!(least_recently_used_exceeding(vec![], 10), vec![]);
+}
+
 #[test]
 fn test_timestamp_serialization() {
     for i in 0..1_000u64 {
@@ -11,7 +11,7 @@
 use std::path::Path;
 
 use super::data::*;
-use super::file_format;
+use super::file_format::{self, ReadResult};
 use super::fs::*;
 use super::work_product;
 
@@ -27,6 +27,11 @@ pub enum LoadResult<T> {
     },
     /// The file either didn't exist or was produced by an incompatible compiler version.
     DataOutOfDate,
+    /// The file was produced by a different compiler build, and is ignored.
+    IncompatibleCompiler {
+        #[allow(missing_docs)]
+        message: String,
+    },
     /// An error occurred.
     Error {
         #[allow(missing_docs)]
@@ -47,7 +52,9 @@ pub fn open(self, sess: &Session) -> T {
             }
             (
                 Some(IncrementalStateAssertion::Loaded),
-                LoadResult::Error { .. } | LoadResult::DataOutOfDate,
+                LoadResult::Error { .. }
+                | LoadResult::DataOutOfDate
+                | LoadResult::IncompatibleCompiler { .. },
             ) => {
                 sess.fatal(
                     "We asserted that an existing incremental cache directory should \
@@ -57,12 +64,25 @@ pub fn open(self, sess: &Session) -> T {
             _ => {}
         };
 
+        // Switching between compiler builds is routine, so this is only worth reporting when
+        // asked to explain why the cache was not reused.
+        if let LoadResult::IncompatibleCompiler { message } = &self {
+            if sess.opts.unstable_opts.incremental_info {
+                sess.struct_note_without_error(message.clone())
+                    .note(
+                        "incremental compilation caches can only be reused by the exact \
+                         compiler build that created them",
+                    )
+                    .emit();
+            }
+        }
+
         match self {
             LoadResult::Error { message } => {
                 sess.warn(&message);
                 Default::default()
             }
-            LoadResult::DataOutOfDate => {
+            LoadResult::DataOutOfDate | LoadResult::IncompatibleCompiler { .. } => {
                 if let Err(err) = delete_all_session_dir_contents(sess) {
                     sess.err(&format!(
                         "Failed to delete invalidated or incompatible \
@@ -84,12 +104,23 @@ fn load_data(
     nightly_build: bool,
 ) -> LoadResult<(Mmap, usize)> {
     match file_format::read_file(report_incremental_info, path, nightly_build) {
-        Ok(Some(data_and_pos)) => LoadResult::Ok { data: data_and_pos },
-        Ok(None) => {
+        Ok(ReadResult::Ok { data, start_pos }) => LoadResult::Ok { data: (data, start_pos) },
+        Ok(ReadResult::NotFoundOrOutdated) => {
             // The file either didn't exist or was produced by an incompatible
-            // compiler version. Neither is an error.
+            // version of the file format. Neither is an error.
             LoadResult::DataOutOfDate
         }
+        Ok(ReadResult::IncompatibleCompiler { expected, found }) => {
+            LoadResult::IncompatibleCompiler {
+                message: format!(
+                    "ignoring incremental compilation cache `{}` created by a different \
+                     compiler: it was created by `{}`, but this is `{}`",
+                    path.display(),
+                    found,
+                    expected
+                ),
+            }
+        }
         Err(err) => LoadResult::Error {
             message: format!("could not load dep-graph from `{}`: {}", path.display(), err),
         },
@@ -185,6 +216,9 @@ pub fn load_dep_graph(sess: &Session) -> DepGraphFuture {
 
         match load_data(report_incremental_info, &path, nightly_build) {
             LoadResult::DataOutOfDate => LoadResult::DataOutOfDate,
+            LoadResult::IncompatibleCompiler { message } => {
+                LoadResult::IncompatibleCompiler { message }
+            }
             LoadResult::Error { message } => LoadResult::Error { message },
             LoadResult::Ok { data: (bytes, start_pos) } => {
                 let mut decoder = MemDecoder::new(&bytes, start_pos);
@@ -120,6 +120,27 @@ pub fn save_work_product_index(
             wp.saved_files.iter().all(|(_, path)| in_incr_comp_dir_sess(sess, path).exists())
         })
     });
+
+    // Object files that no work product refers to are only deleted when a size limit was set for
+    // the incremental cache, like the session directories that `fs` prunes for it.
+    if sess.opts.unstable_opts.incremental_cache_limit.is_some() {
+        let (orphans_removed, orphans_size) =
+            work_product::delete_orphaned_workproduct_files(sess, &new_work_products);
+        if sess.opts.unstable_opts.incremental_info {
+            sess.note_without_error(&format!(
+                "incremental compilation session directory: \
+                 {orphans_removed} orphaned object files removed ({orphans_size} bytes)"
+            ));
+        }
+    }
+
+    if sess.opts.unstable_opts.incremental_info {
+        sess.note_without_error(&format!(
+            "incremental compilation session directory: {} work products taking {} bytes",
+            new_work_products.len(),
+            work_product::workproduct_files_size(sess, &new_work_products)
+        ));
+    }
 }
 
 fn encode_work_product_index(
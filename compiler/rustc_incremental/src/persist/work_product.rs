@@ -3,7 +3,7 @@
 //! [work products]: WorkProduct
 
 use crate::persist::fs::*;
-use rustc_data_structures::fx::FxHashMap;
+use rustc_data_structures::fx::{FxHashMap, FxHashSet};
 use rustc_fs_util::link_or_copy;
 use rustc_middle::dep_graph::{WorkProduct, WorkProductId};
 use rustc_session::Session;
@@ -57,3 +57,70 @@ pub fn delete_workproduct_files(sess: &Session, work_product: &WorkProduct) {
         }
     }
 }
+
+/// File extensions of the artifacts that codegen stores in the session directory.
+const OBJECT_FILE_EXTENSIONS: &[&str] = &["o", "dwo", "bc"];
+
+/// Removes object files from the session directory that do not belong to any of the current
+/// work products. Such files are left behind, for example, when the work product index of the
+/// previous session could not be loaded, so that its work products were never deleted.
+///
+/// Files starting with the name of a current codegen unit are kept, since codegen also stores
+/// artifacts like pre-LTO bitcode next to the work products. Returns the number of files
+/// removed and their total size in bytes.
+pub fn delete_orphaned_workproduct_files(
+    sess: &Session,
+    work_products: &FxHashMap<WorkProductId, WorkProduct>,
+) -> (usize, u64) {
+    let Ok(dir_entries) = sess.incr_comp_session_dir().read_dir() else { return (0, 0) };
+
+    let saved_files: FxHashSet<&str> =
+        work_products.values().flat_map(|wp| wp.saved_files.values()).map(|s| &s[..]).collect();
+    let cgu_prefixes: Vec<String> =
+        work_products.values().map(|wp| format!("{}.", wp.cgu_name)).collect();
+
+    let mut removed = (0, 0);
+    for entry in dir_entries.flatten() {
+        let file_name = entry.file_name();
+        let Some(file_name) = file_name.to_str() else { continue };
+        let is_object_file = Path::new(file_name)
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map_or(false, |ext| OBJECT_FILE_EXTENSIONS.contains(&ext));
+        if !is_object_file
+            || saved_files.contains(file_name)
+            || cgu_prefixes.iter().any(|prefix| file_name.starts_with(&prefix[..]))
+        {
+            continue;
+        }
+
+        let size = entry.metadata().map_or(0, |metadata| metadata.len());
+        let path = entry.path();
+        debug!("delete_orphaned_workproduct_files() - deleting `{}`", path.display());
+        match std_fs::remove_file(&path) {
+            Ok(()) => {
+                removed.0 += 1;
+                removed.1 += size;
+            }
+            Err(err) => sess.warn(&format!(
+                "file-system error deleting orphaned file `{}`: {}",
+                path.display(),
+                err
+            )),
+        }
+    }
+    removed
+}
+
+/// Returns the total size in bytes of the files saved for the given work products.
+pub fn workproduct_files_size(
+    sess: &Session,
+    work_products: &FxHashMap<WorkProductId, WorkProduct>,
+) -> u64 {
+    work_products
+        .values()
+        .flat_map(|wp| wp.saved_files.values())
+        .filter_map(|path| std_fs::metadata(in_incr_comp_dir_sess(sess, path)).ok())
+        .map(|metadata| metadata.len())
+        .sum()
+}
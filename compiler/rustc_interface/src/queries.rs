@@ -187,7 +187,7 @@ fn dep_graph(&self) -> Result<&Query<DepGraph>> {
         self.dep_graph.compute(|| {
             let sess = self.session();
             let future_opt = self.dep_graph_future()?.take();
-            let dep_graph = future_opt
+            let mut dep_graph = future_opt
                 .and_then(|future| {
                     let (prev_graph, prev_work_products) =
                         sess.time("blocked_on_dep_graph_loading", || future.open().open(sess));
@@ -195,6 +195,9 @@ fn dep_graph(&self) -> Result<&Query<DepGraph>> {
                     rustc_incremental::build_dep_graph(sess, prev_graph, prev_work_products)
                 })
                 .unwrap_or_else(DepGraph::new_disabled);
+            if sess.opts.unstable_opts.time_queries.is_some() {
+                dep_graph.enable_query_timings();
+            }
             Ok(dep_graph)
         })
     }
@@ -392,6 +395,15 @@ pub fn enter<F, T>(&self, f: F) -> T
 
             self.session()
                 .time("serialize_dep_graph", || gcx.enter(rustc_incremental::save_dep_graph));
+
+            // Printed last, so that the queries run while serializing the dep graph are included.
+            if let Some(format) = self.session().opts.unstable_opts.time_queries {
+                gcx.enter(|tcx| {
+                    if let Some(timings) = tcx.dep_graph.query_timings() {
+                        timings.print(format);
+                    }
+                });
+            }
         }
 
         _timer = Some(self.session().timer("free_global_ctxt"));
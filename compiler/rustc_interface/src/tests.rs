@@ -11,7 +11,7 @@
 };
 use rustc_session::config::{
     BranchProtection, Externs, OomStrategy, OutputType, OutputTypes, PAuthKey, PacRet,
-    ProcMacroExecutionStrategy, SymbolManglingVersion, WasiExecModel,
+    ProcMacroExecutionStrategy, SymbolManglingVersion, TimeQueries, WasiExecModel,
 };
 use rustc_session::config::{CFGuard, ExternEntry, LinkerPluginLto, LtoCli, SwitchWithOptPath};
 use rustc_session::lint::Level;
@@ -610,7 +610,8 @@ macro_rules! tracked {
         };
     }
 
-    // Make sure that changing a [TRACKED_NO_CRATE_HASH] option leaves the crate hash unchanged but changes the incremental hash.
+    // Make sure that changing a [TRACKED_NO_CRATE_HASH] or [TRACKED_RELOCATABLE] option leaves the
+    // crate hash unchanged but changes the incremental hash.
     // tidy-alphabetical-start
     tracked!(
         real_rust_source_base_dir,
@@ -620,6 +621,25 @@ macro_rules! tracked {
     // tidy-alphabetical-end
 }
 
+#[test]
+fn test_top_level_options_relocatable() {
+    let mut reference = Options::default();
+    reference.remap_path_prefix = vec![("/home/bors/rust".into(), "/checkout".into())];
+    reference.real_rust_source_base_dir =
+        Some("/home/bors/.rustup/toolchains/nightly/lib/rustlib/src/rust".into());
+
+    // Moving the sources or the toolchain must not invalidate the incremental cache.
+    let mut opts = reference.clone();
+    opts.remap_path_prefix = vec![("/home/alice/rust".into(), "/checkout".into())];
+    opts.real_rust_source_base_dir =
+        Some("/home/alice/.rustup/toolchains/nightly/lib/rustlib/src/rust".into());
+    assert_eq!(reference.dep_tracking_hash(false), opts.dep_tracking_hash(false));
+
+    // But remapping to a different path must.
+    opts.remap_path_prefix = vec![("/home/alice/rust".into(), "/src".into())];
+    assert_ne!(reference.dep_tracking_hash(false), opts.dep_tracking_hash(false));
+}
+
 #[test]
 fn test_unstable_options_tracking_hash() {
     let reference = Options::default();
@@ -652,6 +672,7 @@ macro_rules! untracked {
     untracked!(future_incompat_test, true);
     untracked!(hir_stats, true);
     untracked!(identify_regions, true);
+    untracked!(incremental_cache_limit, Some(1024));
     untracked!(incremental_ignore_spans, true);
     untracked!(incremental_info, true);
     untracked!(incremental_verify_ich, true);
@@ -687,6 +708,7 @@ macro_rules! untracked {
     untracked!(threads, 99);
     untracked!(time_llvm_passes, true);
     untracked!(time_passes, true);
+    untracked!(time_queries, Some(TimeQueries::Summary));
     untracked!(trace_macros, true);
     untracked!(track_diagnostics, true);
     untracked!(trim_diagnostic_paths, false);
@@ -736,6 +758,7 @@ macro_rules! tracked {
     tracked!(force_unstable_if_unmarked, true);
     tracked!(fuel, Some(("abc".to_string(), 99)));
     tracked!(function_sections, Some(false));
+    tracked!(future_size_limit, Some(4096));
     tracked!(human_readable_cgu_names, true);
     tracked!(inline_in_all_cgus, Some(true));
     tracked!(inline_mir, Some(true));
@@ -2989,6 +2989,49 @@
     "detects large moves or copies",
 }
 
+declare_lint! {
+    /// The `large_futures` lint detects async functions, async blocks and
+    /// generators whose state machine is larger than a limit, which defaults
+    /// to 16 KiB and can be set with `-Z future-size-limit`.
+    ///
+    /// ### Example
+    ///
+    /// ```rust,compile_fail
+    /// #![deny(large_futures)]
+    /// async fn wait() {}
+    ///
+    /// async fn big() {
+    ///     let buf = [0u8; 20_000];
+    ///     wait().await;
+    ///     drop(buf);
+    /// }
+    ///
+    /// fn main() {
+    ///     let _ = big();
+    /// }
+    /// ```
+    ///
+    /// {{produces}}
+    ///
+    /// ### Explanation
+    ///
+    /// Every local that is alive across an `.await` or `yield` is stored in
+    /// the state machine, so a future is at least as large as the largest
+    /// set of locals held at the same suspension point. Large futures are
+    /// expensive to move and can overflow the stack when they are created
+    /// or polled. The lint points at the saved locals that contribute most
+    /// to the size, which can then be boxed, or dropped before the
+    /// suspension point.
+    ///
+    /// The size of a generic future depends on its type parameters, so the
+    /// lint is checked during monomorphization, once for every set of types
+    /// that a future is created with. This means that it is only emitted when
+    /// the crate is built, and not by `cargo check`.
+    pub LARGE_FUTURES,
+    Allow,
+    "detects futures and generators with a large state machine",
+}
+
 declare_lint! {
     /// The `deprecated_cfg_attr_crate_type_name` lint detects uses of the
     /// `#![cfg_attr(..., crate_type = "...")]` and
@@ -3254,6 +3297,7 @@
         PROC_MACRO_BACK_COMPAT,
         RUST_2021_INCOMPATIBLE_OR_PATTERNS,
         LARGE_ASSIGNMENTS,
+        LARGE_FUTURES,
         RUST_2021_PRELUDE_COLLISIONS,
         RUST_2021_PREFIXES_INCOMPATIBLE_SYNTAX,
         UNSUPPORTED_CALLING_CONVENTIONS,
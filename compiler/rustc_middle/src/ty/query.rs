@@ -224,7 +224,13 @@ pub fn $name(self, key: query_helper_param_ty!($($K)*)) {
                 let key = key.into_query_param();
                 opt_remap_env_constness!([$($modifiers)*][key]);
 
-                let cached = try_get_cached(self.tcx, &self.tcx.query_caches.$name, &key, noop);
+                let cached = try_get_cached(
+                    self.tcx,
+                    &self.tcx.query_caches.$name,
+                    &key,
+                    dep_graph::DepKind::$name,
+                    noop,
+                );
 
                 match cached {
                     Ok(()) => return,
@@ -253,7 +259,13 @@ pub fn $name(self, key: query_helper_param_ty!($($K)*)) -> query_stored::$name<'
                 let key = key.into_query_param();
                 opt_remap_env_constness!([$($modifiers)*][key]);
 
-                let cached = try_get_cached(self.tcx, &self.tcx.query_caches.$name, &key, copy);
+                let cached = try_get_cached(
+                    self.tcx,
+                    &self.tcx.query_caches.$name,
+                    &key,
+                    dep_graph::DepKind::$name,
+                    copy,
+                );
 
                 match cached {
                     Ok(value) => return value,
@@ -352,7 +364,7 @@ pub fn $name(self, value: $V) -> query_stored::$name<'tcx> {
                 let tcx = self.tcx;
                 let cache = &tcx.query_caches.$name;
 
-                let cached = try_get_cached(tcx, cache, &key, copy);
+                let cached = try_get_cached(tcx, cache, &key, dep_graph::DepKind::$name, copy);
 
                 match cached {
                     Ok(old) => {
@@ -816,9 +816,11 @@ fn compute_layout<'tcx>(
     // Gather live local types and their indices.
     let mut locals = IndexVec::<GeneratorSavedLocal, _>::new();
     let mut tys = IndexVec::<GeneratorSavedLocal, _>::new();
+    let mut field_source_info = IndexVec::<GeneratorSavedLocal, _>::new();
     for (saved_local, local) in saved_locals.iter_enumerated() {
         locals.push(local);
         tys.push(body.local_decls[local].ty);
+        field_source_info.push(body.local_decls[local].source_info);
         debug!("generator saved local {:?} => {:?}", saved_local, local);
     }
 
@@ -857,8 +859,13 @@ fn compute_layout<'tcx>(
     debug!("generator variant_fields = {:?}", variant_fields);
     debug!("generator storage_conflicts = {:#?}", storage_conflicts);
 
-    let layout =
-        GeneratorLayout { field_tys: tys, variant_fields, variant_source_info, storage_conflicts };
+    let layout = GeneratorLayout {
+        field_tys: tys,
+        field_source_info,
+        variant_fields,
+        variant_source_info,
+        storage_conflicts,
+    };
 
     (remap, layout, storage_liveness)
 }
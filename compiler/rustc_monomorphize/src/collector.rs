@@ -194,14 +194,17 @@
 };
 use rustc_middle::{middle::codegen_fn_attrs::CodegenFnAttrFlags, mir::visit::TyContext};
 use rustc_session::config::EntryFnType;
-use rustc_session::lint::builtin::LARGE_ASSIGNMENTS;
+use rustc_session::lint::builtin::{LARGE_ASSIGNMENTS, LARGE_FUTURES};
+use rustc_session::lint::Level;
 use rustc_session::Limit;
 use rustc_span::source_map::{dummy_spanned, respan, Span, Spanned, DUMMY_SP};
 use rustc_target::abi::Size;
 use std::ops::Range;
 use std::path::PathBuf;
 
-use crate::errors::{LargeAssignmentsLint, RecursionLimit, TypeLengthLimit};
+use crate::errors::{
+    LargeAssignmentsLint, LargeFuturesLint, LargeFuturesSavedLocal, RecursionLimit, TypeLengthLimit,
+};
 
 #[derive(PartialEq)]
 pub enum MonoItemCollectionMode {
@@ -667,9 +670,86 @@ pub fn monomorphize<T>(&self, value: T) -> T
             value,
         )
     }
+
+    /// Emits the `large_futures` lint if the given generator, which is created at `source_info`,
+    /// is larger than `-Zfuture-size-limit`.
+    ///
+    /// This is checked here rather than in a MIR pass so that generic async functions and
+    /// generators are linted once their size is known, for each instantiation of them.
+    fn check_generator_size(&self, generator_ty: Ty<'tcx>, source_info: &mir::SourceInfo) {
+        let ty::Generator(def_id, substs, _) = *generator_ty.kind() else { return };
+        let Some(lint_root) = source_info.scope.lint_root(&self.body.source_scopes) else {
+            // Like for `large_assignments`, the generator is created in a function from a
+            // foreign crate, which has no `HirId`.
+            return;
+        };
+        // Computing the layout of every generator is wasted if the lint cannot fire anyway.
+        if self.tcx.lint_level_at_node(LARGE_FUTURES, lint_root).0 == Level::Allow {
+            return;
+        }
+
+        let param_env = ty::ParamEnv::reveal_all();
+        let Ok(layout) = self.tcx.layout_of(param_env.and(generator_ty)) else { return };
+        let limit = self.tcx.sess.opts.unstable_opts.future_size_limit.unwrap_or(16 * 1024);
+        let size = layout.size.bytes();
+        if size <= limit as u64 {
+            return;
+        }
+
+        let Some(generator_layout) = self.tcx.generator_layout(def_id) else { return };
+        let mut saved_locals: Vec<_> = generator_layout
+            .field_tys
+            .iter_enumerated()
+            .filter_map(|(saved_local, &ty)| {
+                let ty = ty::EarlyBinder(ty).subst(self.tcx, substs);
+                let size = self.tcx.layout_of(param_env.and(ty)).ok()?.size.bytes();
+                let suspension_points = generator_layout
+                    .variant_fields
+                    .iter()
+                    .filter(|fields| fields.contains(&saved_local))
+                    .count();
+                let span = generator_layout.field_source_info[saved_local].span;
+                (size > 0).then_some(LargeFuturesSavedLocal {
+                    span,
+                    ty,
+                    local_size: size,
+                    suspension_points,
+                })
+            })
+            .collect();
+        // Largest first, keeping source order between locals of the same size.
+        saved_locals.sort_by(|a, b| b.local_size.cmp(&a.local_size));
+
+        let is_future =
+            matches!(self.tcx.generator_kind(def_id), Some(hir::GeneratorKind::Async(_)));
+        self.tcx.emit_spanned_lint(
+            LARGE_FUTURES,
+            lint_root,
+            self.tcx.def_span(def_id),
+            LargeFuturesLint {
+                handler: self.tcx.sess.diagnostic(),
+                is_future,
+                size,
+                limit: limit as u64,
+                saved_locals,
+            },
+        );
+    }
 }
 
 impl<'a, 'tcx> MirVisitor<'tcx> for MirNeighborCollector<'a, 'tcx> {
+    fn visit_statement(&mut self, statement: &mir::Statement<'tcx>, location: Location) {
+        // Generators are created by setting their discriminant to the unresumed state once their
+        // upvars are initialized, since the `Deaggregator` has removed their aggregates.
+        if let mir::StatementKind::SetDiscriminant { ref place, variant_index } = statement.kind {
+            if variant_index.as_usize() == ty::GeneratorSubsts::UNRESUMED {
+                let ty = self.monomorphize(place.ty(self.body, self.tcx).ty);
+                self.check_generator_size(ty, &statement.source_info);
+            }
+        }
+        self.super_statement(statement, location);
+    }
+
     fn visit_rvalue(&mut self, rvalue: &mir::Rvalue<'tcx>, location: Location) {
         debug!("visiting rvalue {:?}", *rvalue);
 
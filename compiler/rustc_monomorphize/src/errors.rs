@@ -2,7 +2,9 @@
 
 use rustc_errors::ErrorGuaranteed;
 use rustc_errors::IntoDiagnostic;
-use rustc_macros::{Diagnostic, LintDiagnostic};
+use rustc_errors::{DecorateLint, DiagnosticBuilder, DiagnosticMessage, Handler};
+use rustc_macros::{Diagnostic, LintDiagnostic, Subdiagnostic};
+use rustc_middle::ty::Ty;
 use rustc_span::Span;
 
 #[derive(Diagnostic)]
@@ -66,6 +68,57 @@ pub struct LargeAssignmentsLint {
     pub limit: u64,
 }
 
+pub struct LargeFuturesLint<'tcx> {
+    /// Translates the labels of the saved locals, which each need their own values.
+    pub handler: &'tcx Handler,
+    pub is_future: bool,
+    pub size: u64,
+    pub limit: u64,
+    /// The saved locals of the generator, largest first.
+    pub saved_locals: Vec<LargeFuturesSavedLocal<'tcx>>,
+}
+
+#[derive(Subdiagnostic)]
+#[label(monomorphize_large_futures_saved_local)]
+pub struct LargeFuturesSavedLocal<'tcx> {
+    #[primary_span]
+    pub span: Span,
+    pub ty: Ty<'tcx>,
+    pub local_size: u64,
+    pub suspension_points: usize,
+}
+
+/// The number of saved locals that `large_futures` points at.
+const MAX_REPORTED_LOCALS: usize = 5;
+
+impl<'a> DecorateLint<'a, ()> for LargeFuturesLint<'_> {
+    fn decorate_lint<'b>(
+        self,
+        diag: &'b mut DiagnosticBuilder<'a, ()>,
+    ) -> &'b mut DiagnosticBuilder<'a, ()> {
+        let (kind, suspension) =
+            if self.is_future { ("future", "await") } else { ("generator", "yield") };
+        diag.set_arg("kind", kind);
+        diag.set_arg("size", self.size);
+        diag.set_arg("limit", self.limit);
+        diag.set_arg("suspension", suspension);
+        let more_locals = self.saved_locals.len().saturating_sub(MAX_REPORTED_LOCALS);
+        for saved_local in self.saved_locals.into_iter().take(MAX_REPORTED_LOCALS) {
+            diag.eager_subdiagnostic(self.handler, saved_local);
+        }
+        if more_locals > 0 {
+            diag.set_arg("more", more_locals);
+            diag.note(rustc_errors::fluent::monomorphize_large_futures_more_locals);
+        }
+        diag.note(rustc_errors::fluent::_subdiag::note);
+        diag.help(rustc_errors::fluent::_subdiag::help)
+    }
+
+    fn msg(&self) -> DiagnosticMessage {
+        rustc_errors::fluent::monomorphize_large_futures
+    }
+}
+
 #[derive(Diagnostic)]
 #[diag(monomorphize_unknown_partition_strategy)]
 pub struct UnknownPartitionStrategy;
@@ -20,7 +20,7 @@
 use super::serialized::{GraphEncoder, SerializedDepGraph, SerializedDepNodeIndex};
 use super::{DepContext, DepKind, DepNode, HasDepContext, WorkProductId};
 use crate::ich::StableHashingContext;
-use crate::query::{QueryContext, QuerySideEffects};
+use crate::query::{QueryContext, QuerySideEffects, QueryTimings};
 
 #[cfg(debug_assertions)]
 use {super::debug::EdgeFilter, std::env};
@@ -34,6 +34,9 @@ pub struct DepGraph<K: DepKind> {
     /// each task has a `DepNodeIndex` that uniquely identifies it. This unique
     /// ID is used for self-profiling.
     virtual_dep_node_index: Lrc<AtomicU32>,
+
+    /// The statistics collected for `-Z time-queries`, if enabled.
+    query_timings: Option<Lrc<QueryTimings<K>>>,
 }
 
 rustc_index::newtype_index! {
@@ -160,11 +163,26 @@ pub fn new(
                 debug_loaded_from_disk: Default::default(),
             })),
             virtual_dep_node_index: Lrc::new(AtomicU32::new(0)),
+            query_timings: None,
         }
     }
 
     pub fn new_disabled() -> DepGraph<K> {
-        DepGraph { data: None, virtual_dep_node_index: Lrc::new(AtomicU32::new(0)) }
+        DepGraph {
+            data: None,
+            virtual_dep_node_index: Lrc::new(AtomicU32::new(0)),
+            query_timings: None,
+        }
+    }
+
+    /// Starts collecting the statistics printed by `-Z time-queries`.
+    pub fn enable_query_timings(&mut self) {
+        self.query_timings = Some(Lrc::new(QueryTimings::new()));
+    }
+
+    #[inline(always)]
+    pub fn query_timings(&self) -> Option<&QueryTimings<K>> {
+        self.query_timings.as_deref()
     }
 
     /// Returns `true` if we are actually building the full dep-graph, and `false` otherwise.
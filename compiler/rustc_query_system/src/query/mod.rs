@@ -14,6 +14,9 @@
 mod config;
 pub use self::config::{QueryConfig, QueryVTable};
 
+mod timings;
+pub use self::timings::QueryTimings;
+
 use crate::dep_graph::{DepNodeIndex, HasDepContext, SerializedDepNodeIndex};
 use rustc_data_structures::sync::Lock;
 use rustc_errors::Diagnostic;
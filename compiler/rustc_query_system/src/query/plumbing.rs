@@ -27,6 +27,8 @@
 use std::hash::Hash;
 use std::mem;
 use std::ptr;
+#[cfg(parallel_compiler)]
+use std::time::Instant;
 use thin_vec::ThinVec;
 
 use super::QueryConfig;
@@ -226,6 +228,7 @@ fn try_start<'b, Qcx>(
                         // in another thread has completed. Record how long we wait in the
                         // self-profiler.
                         let query_blocked_prof_timer = qcx.dep_context().profiler().query_blocked();
+                        let blocked_since = Instant::now();
 
                         // Get the latch out
                         let latch = job.latch();
@@ -237,7 +240,9 @@ fn try_start<'b, Qcx>(
                         let result = latch.wait_on(qcx.current_query_job(), span);
 
                         match result {
-                            Ok(()) => TryGetJob::JobCompleted(query_blocked_prof_timer),
+                            Ok(()) => {
+                                TryGetJob::JobCompleted(query_blocked_prof_timer, blocked_since)
+                            }
                             Err(cycle) => TryGetJob::Cycle(cycle),
                         }
                     }
@@ -326,7 +331,7 @@ enum TryGetJob<'tcx, K>
     /// Returns the result of the query and its dep-node index
     /// if it succeeded or a cycle error if it failed.
     #[cfg(parallel_compiler)]
-    JobCompleted(TimingGuard<'tcx>),
+    JobCompleted(TimingGuard<'tcx>, Instant),
 
     /// Trying to execute the query resulted in a cycle.
     Cycle(CycleError),
@@ -341,6 +346,7 @@ pub fn try_get_cached<'a, Tcx, C, R, OnHit>(
     tcx: Tcx,
     cache: &'a C,
     key: &C::Key,
+    dep_kind: Tcx::DepKind,
     // `on_hit` can be called while holding a lock to the query cache
     on_hit: OnHit,
 ) -> Result<R, ()>
@@ -353,11 +359,24 @@ pub fn try_get_cached<'a, Tcx, C, R, OnHit>(
         if std::intrinsics::unlikely(tcx.profiler().enabled()) {
             tcx.profiler().query_cache_hit(index.into());
         }
+        if std::intrinsics::unlikely(tcx.dep_graph().query_timings().is_some()) {
+            record_cache_hit(tcx, dep_kind);
+        }
         tcx.dep_graph().read_index(index);
         on_hit(value)
     })
 }
 
+/// Kept out of `try_get_cached`, like the self-profiler's events, so that cache hits only pay for
+/// checking whether `-Z time-queries` is enabled.
+#[cold]
+#[inline(never)]
+fn record_cache_hit<Tcx: DepContext>(tcx: Tcx, dep_kind: Tcx::DepKind) {
+    if let Some(timings) = tcx.dep_graph().query_timings() {
+        timings.record_cache_hit(dep_kind);
+    }
+}
+
 fn try_execute_query<Qcx, C>(
     qcx: Qcx,
     state: &QueryState<C::Key>,
@@ -376,7 +395,10 @@ fn try_execute_query<Qcx, C>(
 {
     match JobOwner::<'_, C::Key>::try_start(&qcx, state, span, key.clone()) {
         TryGetJob::NotYetStarted(job) => {
+            let timer =
+                qcx.dep_context().dep_graph().query_timings().map(|t| t.start(query.dep_kind));
             let (result, dep_node_index) = execute_job(qcx, key.clone(), dep_node, query, job.id);
+            drop(timer);
             if query.feedable {
                 // We may have put a value inside the cache from inside the execution.
                 // Verify that it has the same hash as what we have now, to ensure consistency.
@@ -399,7 +421,7 @@ fn try_execute_query<Qcx, C>(
             (result, None)
         }
         #[cfg(parallel_compiler)]
-        TryGetJob::JobCompleted(query_blocked_prof_timer) => {
+        TryGetJob::JobCompleted(query_blocked_prof_timer, blocked_since) => {
             let (v, index) = cache
                 .lookup(&key, |value, index| (value.clone(), index))
                 .unwrap_or_else(|_| panic!("value must be in cache after waiting"));
@@ -408,6 +430,10 @@ fn try_execute_query<Qcx, C>(
                 qcx.dep_context().profiler().query_cache_hit(index.into());
             }
             query_blocked_prof_timer.finish_with_query_invocation_id(index.into());
+            if let Some(timings) = qcx.dep_context().dep_graph().query_timings() {
+                timings.record_blocked(query.dep_kind, blocked_since);
+                timings.record_cache_hit(query.dep_kind);
+            }
 
             (v, Some(index))
         }
@@ -446,9 +472,18 @@ fn execute_job<Qcx, K, V>(
 
         // The diagnostics for this query will be promoted to the current session during
         // `try_mark_green()`, so we can ignore them here.
-        if let Some(ret) = qcx.start_query(job_id, false, None, || {
+        let loaded = qcx.start_query(job_id, false, None, || {
             try_load_from_disk_and_cache_in_memory(qcx, &key, &dep_node, query)
-        }) {
+        });
+        if let Some(timings) = dep_graph.query_timings() {
+            if loaded.is_some() {
+                timings.record_green(query.dep_kind);
+            } else if dep_graph.prev_fingerprint_of(dep_node).is_some() {
+                // Nodes which are not in the previous dep graph are new rather than red.
+                timings.record_red(query.dep_kind);
+            }
+        }
+        if let Some(ret) = loaded {
             return ret;
         }
     }
@@ -728,6 +763,9 @@ fn ensure_must_run<Qcx, K, V>(
         Some((_, dep_node_index)) => {
             dep_graph.read_index(dep_node_index);
             qcx.dep_context().profiler().query_cache_hit(dep_node_index.into());
+            if let Some(timings) = dep_graph.query_timings() {
+                timings.record_green(query.dep_kind);
+            }
             (false, None)
         }
     }
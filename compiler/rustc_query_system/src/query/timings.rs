@@ -0,0 +1,188 @@
+//! Collection of the per-query statistics printed by `-Z time-queries`.
+
+use crate::dep_graph::DepKind;
+use rustc_data_structures::fx::FxHashMap;
+use rustc_data_structures::sync::Lock;
+use rustc_session::config::TimeQueries;
+use std::cell::RefCell;
+use std::fmt::Write;
+use std::time::{Duration, Instant};
+
+/// Statistics about all invocations of one query.
+#[derive(Clone, Copy, Default)]
+struct QueryStats {
+    /// Time spent executing the query, not including the time spent in the queries it invoked.
+    self_time: Duration,
+    /// Number of invocations that were answered from the in-memory cache.
+    cache_hits: u64,
+    /// Number of invocations that had to execute the query or load its result from disk.
+    cache_misses: u64,
+    /// Number of invocations whose result from the previous session could be reused.
+    green: u64,
+    /// Number of invocations whose result from the previous session was out of date.
+    red: u64,
+    /// Time spent waiting for the query to complete on another thread.
+    blocked_time: Duration,
+}
+
+/// The statistics printed by `-Z time-queries`, collected while queries are executed.
+pub struct QueryTimings<K: DepKind> {
+    stats: Lock<FxHashMap<K, QueryStats>>,
+}
+
+thread_local! {
+    /// For each query being executed on this thread, the time spent in the queries it invoked.
+    static NESTED_TIME: RefCell<Vec<Duration>> = RefCell::new(Vec::new());
+}
+
+impl<K: DepKind> QueryTimings<K> {
+    pub fn new() -> Self {
+        QueryTimings { stats: Default::default() }
+    }
+
+    pub(crate) fn record_cache_hit(&self, kind: K) {
+        self.stats.lock().entry(kind).or_default().cache_hits += 1;
+    }
+
+    #[cold]
+    #[inline(never)]
+    pub(crate) fn record_green(&self, kind: K) {
+        self.stats.lock().entry(kind).or_default().green += 1;
+    }
+
+    #[cold]
+    #[inline(never)]
+    pub(crate) fn record_red(&self, kind: K) {
+        self.stats.lock().entry(kind).or_default().red += 1;
+    }
+
+    #[cold]
+    #[inline(never)]
+    pub(crate) fn record_blocked(&self, kind: K, blocked_since: Instant) {
+        let blocked_time = blocked_since.elapsed();
+        // Waiting is not part of the self time of the query that is waiting.
+        NESTED_TIME.with(|nested_time| {
+            if let Some(nested_time) = nested_time.borrow_mut().last_mut() {
+                *nested_time += blocked_time;
+            }
+        });
+        self.stats.lock().entry(kind).or_default().blocked_time += blocked_time;
+    }
+
+    /// Starts timing the execution of a query, which ends when the returned guard is dropped.
+    #[cold]
+    #[inline(never)]
+    pub(crate) fn start(&self, kind: K) -> QueryTimer<'_, K> {
+        NESTED_TIME.with(|nested_time| nested_time.borrow_mut().push(Duration::ZERO));
+        QueryTimer { timings: self, kind, start: Instant::now() }
+    }
+
+    /// Prints the collected statistics to stderr, sorted by self time.
+    pub fn print(&self, format: TimeQueries) {
+        let stats = self.stats.lock();
+        let mut stats: Vec<_> =
+            stats.iter().map(|(kind, stats)| (format!("{kind:?}"), *stats)).collect();
+        stats.sort_by(|(a_name, a), (b_name, b)| {
+            b.self_time.cmp(&a.self_time).then_with(|| a_name.cmp(b_name))
+        });
+
+        match format {
+            TimeQueries::Summary => print_summary(&stats),
+            TimeQueries::Json => print_json(&stats),
+        }
+    }
+}
+
+fn print_summary(stats: &[(String, QueryStats)]) {
+    let total_self_time: Duration = stats.iter().map(|(_, stats)| stats.self_time).sum();
+
+    const SEPARATOR: &str = "[time-queries] --------------------------------------------\
+                             --------------------------------------------------------\
+                             ---------------";
+
+    eprintln!("[time-queries]");
+    eprintln!("[time-queries] Query Statistics");
+    eprintln!("{}", SEPARATOR);
+    eprintln!("[time-queries]");
+    eprintln!("[time-queries] Total Self Time: {:.3}s", total_self_time.as_secs_f64());
+    eprintln!("[time-queries]");
+    eprintln!(
+        "[time-queries]  {:<36}| {:<12}| {:<7}| {:<11}| {:<11}| {:<8}| {:<8}| {:<10}|",
+        "Query", "Self Time", "%", "Hits", "Misses", "Green", "Red", "Blocked"
+    );
+    eprintln!("{}", SEPARATOR);
+
+    for (name, stats) in stats {
+        let self_time_ratio = if total_self_time.is_zero() {
+            0.0
+        } else {
+            100.0 * stats.self_time.as_secs_f64() / total_self_time.as_secs_f64()
+        };
+
+        eprintln!(
+            "[time-queries]  {:<36}|{:>11.3}s |{:>6.1}% |{:>11} |{:>11} |{:>8} |{:>8} |{:>9.3}s |",
+            name,
+            stats.self_time.as_secs_f64(),
+            self_time_ratio,
+            stats.cache_hits,
+            stats.cache_misses,
+            stats.green,
+            stats.red,
+            stats.blocked_time.as_secs_f64(),
+        );
+    }
+
+    eprintln!("{}", SEPARATOR);
+    eprintln!("[time-queries]");
+}
+
+fn print_json(stats: &[(String, QueryStats)]) {
+    // Query names are plain identifiers, so they never need to be escaped.
+    let mut json = String::from("[");
+    for (i, (name, stats)) in stats.iter().enumerate() {
+        if i > 0 {
+            json.push(',');
+        }
+        write!(
+            json,
+            "{{\"query\":\"{}\",\"self_time_ns\":{},\"cache_hits\":{},\"cache_misses\":{},\
+             \"green\":{},\"red\":{},\"blocked_time_ns\":{}}}",
+            name,
+            stats.self_time.as_nanos(),
+            stats.cache_hits,
+            stats.cache_misses,
+            stats.green,
+            stats.red,
+            stats.blocked_time.as_nanos(),
+        )
+        .unwrap();
+    }
+    json.push(']');
+    eprintln!("{}", json);
+}
+
+/// Measures the execution of a query, see [`QueryTimings::start`].
+pub(crate) struct QueryTimer<'a, K: DepKind> {
+    timings: &'a QueryTimings<K>,
+    kind: K,
+    start: Instant,
+}
+
+impl<K: DepKind> Drop for QueryTimer<'_, K> {
+    fn drop(&mut self) {
+        let elapsed = self.start.elapsed();
+        let nested_time = NESTED_TIME.with(|nested_time| {
+            let mut nested_time = nested_time.borrow_mut();
+            let own_nested_time = nested_time.pop().unwrap();
+            if let Some(parent_nested_time) = nested_time.last_mut() {
+                *parent_nested_time += elapsed;
+            }
+            own_nested_time
+        });
+
+        let mut stats = self.timings.stats.lock();
+        let stats = stats.entry(self.kind).or_default();
+        stats.cache_misses += 1;
+        stats.self_time += elapsed.saturating_sub(nested_time);
+    }
+}
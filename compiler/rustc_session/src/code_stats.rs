@@ -2,7 +2,9 @@
 use rustc_data_structures::sync::Lock;
 use rustc_span::Symbol;
 use rustc_target::abi::{Align, Size};
+use serde::Serialize;
 use std::cmp::{self, Ordering};
+use std::io::{self, Write};
 
 #[derive(Clone, PartialEq, Eq, Hash, Debug)]
 pub struct VariantInfo {
@@ -36,6 +38,42 @@ pub enum DataTypeKind {
     Generator,
 }
 
+/// The largest niche of a type, i.e. the range of invalid values of one of its scalars that can
+/// be used to encode the discriminant of an enclosing enum.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct NicheInfo {
+    pub offset: u64,
+    pub size: u64,
+    pub valid_range_start: u128,
+    pub valid_range_end: u128,
+    /// The number of invalid values, i.e. how many more variants could be stored in the niche.
+    pub available: u128,
+}
+
+/// How the active variant of an enum or generator is encoded.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum TagInfo {
+    /// The tag directly stores the discriminant.
+    Direct { offset: u64, size: u64 },
+    /// The tag is stored in a niche of `untagged_variant`, and the variants in the inclusive
+    /// range `niche_variants` are encoded starting at the value `niche_start`.
+    Niche {
+        offset: u64,
+        size: u64,
+        untagged_variant: Symbol,
+        niche_variants: (Symbol, Symbol),
+        niche_start: u128,
+    },
+}
+
+/// Layout details beyond sizes, which are only reported by `--print type-layouts=json`.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct LayoutInfo {
+    pub abi: &'static str,
+    pub largest_niche: Option<NicheInfo>,
+    pub tag: Option<TagInfo>,
+}
+
 #[derive(PartialEq, Eq, Hash, Debug)]
 pub struct TypeSizeInfo {
     pub kind: DataTypeKind,
@@ -45,6 +83,7 @@ pub struct TypeSizeInfo {
     pub packed: bool,
     pub opt_discr_size: Option<u64>,
     pub variants: Vec<VariantInfo>,
+    pub layout: LayoutInfo,
 }
 
 #[derive(Default)]
@@ -61,12 +100,9 @@ pub fn record_type_size<S: ToString>(
         overall_size: Size,
         packed: bool,
         opt_discr_size: Option<Size>,
-        mut variants: Vec<VariantInfo>,
+        variants: Vec<VariantInfo>,
+        layout: LayoutInfo,
     ) {
-        // Sort variants so the largest ones are shown first. A stable sort is
-        // used here so that source code order is preserved for all variants
-        // that have the same size.
-        variants.sort_by(|info1, info2| info2.size.cmp(&info1.size));
         let info = TypeSizeInfo {
             kind,
             type_description: type_desc.to_string(),
@@ -75,6 +111,7 @@ pub fn record_type_size<S: ToString>(
             packed,
             opt_discr_size: opt_discr_size.map(|s| s.bytes()),
             variants,
+            layout,
         };
         self.type_sizes.borrow_mut().insert(info);
     }
@@ -117,6 +154,12 @@ pub fn print_type_sizes(&self) {
                 DataTypeKind::Struct | DataTypeKind::Closure => true,
                 DataTypeKind::Enum | DataTypeKind::Union | DataTypeKind::Generator => false,
             };
+
+            // Sort variants so the largest ones are shown first. A stable sort is
+            // used here so that source code order is preserved for all variants
+            // that have the same size.
+            let mut variants: Vec<_> = variants.iter().collect();
+            variants.sort_by(|info1, info2| info2.size.cmp(&info1.size));
             for (i, variant_info) in variants.into_iter().enumerate() {
                 let VariantInfo { ref name, kind: _, align: _, size, ref fields } = *variant_info;
                 let indent = if !struct_like {
@@ -180,4 +223,183 @@ pub fn print_type_sizes(&self) {
             }
         }
     }
+
+    /// Prints one JSON object per recorded type, sorted by type description so that the output
+    /// of two compilations can be diffed.
+    pub fn print_type_layouts_json(&self) {
+        let type_sizes = self.type_sizes.borrow();
+        let mut sorted: Vec<_> = type_sizes.iter().collect();
+        sorted.sort_by(|info1, info2| {
+            info1
+                .type_description
+                .cmp(&info2.type_description)
+                .then(info1.overall_size.cmp(&info2.overall_size))
+        });
+
+        let stdout = io::stdout();
+        let mut stdout = stdout.lock();
+        for info in sorted {
+            serde_json::to_writer(&mut stdout, &JsonTypeLayout::new(info))
+                .and_then(|()| writeln!(stdout).map_err(serde_json::Error::io))
+                .unwrap_or_else(|e| panic!("failed to print type layouts: {e}"));
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct JsonTypeLayout<'a> {
+    #[serde(rename = "type")]
+    type_description: &'a str,
+    kind: &'static str,
+    size: u64,
+    align: u64,
+    packed: bool,
+    abi: &'static str,
+    largest_niche: Option<JsonNiche>,
+    tag: Option<JsonTag>,
+    variants: Vec<JsonVariant>,
+}
+
+#[derive(Serialize)]
+struct JsonNiche {
+    offset: u64,
+    size: u64,
+    valid_range_start: u128,
+    valid_range_end: u128,
+    available: u128,
+}
+
+#[derive(Serialize)]
+struct JsonTag {
+    encoding: &'static str,
+    offset: u64,
+    size: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    untagged_variant: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    niche_variants: Option<(String, String)>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    niche_start: Option<u128>,
+}
+
+#[derive(Serialize)]
+struct JsonVariant {
+    name: Option<String>,
+    size_kind: &'static str,
+    size: u64,
+    align: u64,
+    /// Bytes of the type that are neither covered by a field of this variant nor by the tag.
+    padding_bytes: u64,
+    fields: Vec<JsonField>,
+}
+
+#[derive(Serialize)]
+struct JsonField {
+    name: String,
+    offset: u64,
+    size: u64,
+    align: u64,
+}
+
+impl<'a> JsonTypeLayout<'a> {
+    fn new(info: &'a TypeSizeInfo) -> Self {
+        let LayoutInfo { abi, largest_niche, tag } = info.layout;
+        let tag_range = tag.map(|tag| match tag {
+            TagInfo::Direct { offset, size } | TagInfo::Niche { offset, size, .. } => {
+                (offset, offset + size)
+            }
+        });
+
+        JsonTypeLayout {
+            type_description: &info.type_description,
+            kind: match info.kind {
+                DataTypeKind::Struct => "struct",
+                DataTypeKind::Union => "union",
+                DataTypeKind::Enum => "enum",
+                DataTypeKind::Closure => "closure",
+                DataTypeKind::Generator => "generator",
+            },
+            size: info.overall_size,
+            align: info.align,
+            packed: info.packed,
+            abi,
+            largest_niche: largest_niche.map(|niche| JsonNiche {
+                offset: niche.offset,
+                size: niche.size,
+                valid_range_start: niche.valid_range_start,
+                valid_range_end: niche.valid_range_end,
+                available: niche.available,
+            }),
+            tag: tag.map(|tag| match tag {
+                TagInfo::Direct { offset, size } => JsonTag {
+                    encoding: "direct",
+                    offset,
+                    size,
+                    untagged_variant: None,
+                    niche_variants: None,
+                    niche_start: None,
+                },
+                TagInfo::Niche { offset, size, untagged_variant, niche_variants, niche_start } => {
+                    JsonTag {
+                        encoding: "niche",
+                        offset,
+                        size,
+                        untagged_variant: Some(untagged_variant.to_string()),
+                        niche_variants: Some((
+                            niche_variants.0.to_string(),
+                            niche_variants.1.to_string(),
+                        )),
+                        niche_start: Some(niche_start),
+                    }
+                }
+            }),
+            variants: info
+                .variants
+                .iter()
+                .map(|variant| JsonVariant {
+                    name: variant.name.map(|name| name.to_string()),
+                    size_kind: match variant.kind {
+                        SizeKind::Exact => "exact",
+                        SizeKind::Min => "min",
+                    },
+                    size: variant.size,
+                    align: variant.align,
+                    padding_bytes: padding_bytes(info.overall_size, &variant.fields, tag_range),
+                    fields: variant
+                        .fields
+                        .iter()
+                        .map(|field| JsonField {
+                            name: field.name.to_string(),
+                            offset: field.offset,
+                            size: field.size,
+                            align: field.align,
+                        })
+                        .collect(),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Counts the bytes in `0..overall_size` that are not covered by any of `fields` or by the tag.
+/// Fields may overlap, as they do in unions.
+fn padding_bytes(overall_size: u64, fields: &[FieldInfo], tag: Option<(u64, u64)>) -> u64 {
+    let mut ranges: Vec<_> = fields
+        .iter()
+        .map(|field| (field.offset, field.offset + field.size))
+        .chain(tag)
+        .filter(|&(start, end)| start < end)
+        .collect();
+    ranges.sort();
+
+    let mut covered = 0;
+    let mut covered_until = 0;
+    for (start, end) in ranges {
+        let start = start.max(covered_until);
+        if end > start {
+            covered += end - start;
+            covered_until = end;
+        }
+    }
+    overall_size.saturating_sub(covered)
 }
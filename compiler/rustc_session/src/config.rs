@@ -379,6 +379,9 @@ pub enum ErrorOutputType {
         /// human output.
         json_rendered: HumanReadableErrorType,
     },
+    /// A single SARIF 2.1.0 log covering all the diagnostics of the session, for code scanning
+    /// tools.
+    Sarif,
 }
 
 impl Default for ErrorOutputType {
@@ -552,6 +555,7 @@ pub enum PrintRequest {
     StackProtectorStrategies,
     LinkArgs,
     SplitDebuginfo,
+    TypeLayouts,
 }
 
 pub enum Input {
@@ -1421,7 +1425,7 @@ pub fn rustc_optgroups() -> Vec<RustcOptGroup> {
             "",
             "error-format",
             "How errors and other messages are produced",
-            "human|json|short",
+            "human|json|short|sarif",
         ),
         opt::multi_s("", "json", "Configure the JSON output of the compiler", "CONFIG"),
         opt::opt_s(
@@ -1602,12 +1606,13 @@ pub fn parse_error_format(
             Some("json") => ErrorOutputType::Json { pretty: false, json_rendered },
             Some("pretty-json") => ErrorOutputType::Json { pretty: true, json_rendered },
             Some("short") => ErrorOutputType::HumanReadable(HumanReadableErrorType::Short(color)),
+            Some("sarif") => ErrorOutputType::Sarif,
 
             Some(arg) => early_error(
                 ErrorOutputType::HumanReadable(HumanReadableErrorType::Default(color)),
                 &format!(
-                    "argument for `--error-format` must be `human`, `json` or \
-                     `short` (instead was `{arg}`)"
+                    "argument for `--error-format` must be `human`, `json`, `short` or \
+                     `sarif` (instead was `{arg}`)"
                 ),
             ),
         }
@@ -1684,6 +1689,12 @@ fn check_error_format_stability(
                 "`--error-format=human-annotate-rs` is unstable",
             );
         }
+        if let ErrorOutputType::Sarif = error_format {
+            early_error(
+                ErrorOutputType::Json { pretty: false, json_rendered },
+                "`--error-format=sarif` is unstable",
+            );
+        }
     }
 }
 
@@ -1811,6 +1822,7 @@ fn collect_print_requests(
         ("target-spec-json", PrintRequest::TargetSpec),
         ("link-args", PrintRequest::LinkArgs),
         ("split-debuginfo", PrintRequest::SplitDebuginfo),
+        ("type-layouts=json", PrintRequest::TypeLayouts),
     ];
 
     prints.extend(matches.opt_strs("print").into_iter().map(|req| {
@@ -1826,6 +1838,17 @@ fn collect_print_requests(
                     );
                 }
             }
+            Some((_, PrintRequest::TypeLayouts)) => {
+                if unstable_opts.unstable_options {
+                    PrintRequest::TypeLayouts
+                } else {
+                    early_error(
+                        error_format,
+                        "the `-Z unstable-options` flag must also be passed to \
+                     enable the type-layouts=json print option",
+                    );
+                }
+            }
             Some(&(_, print_request)) => print_request,
             None => {
                 let prints =
@@ -2934,6 +2957,33 @@ fn hash(
         }
     }
 
+    /// Hashes the `[TRACKED_RELOCATABLE]` options, which contain local paths, in a way that does
+    /// not depend on where the sources or the toolchain are located.
+    pub trait RelocatableDepTrackingHash {
+        fn hash(&self, hasher: &mut DefaultHasher, error_format: ErrorOutputType);
+    }
+
+    /// Only the replacements of `--remap-path-prefix` are hashed. The prefixes being remapped are
+    /// local paths, and which paths they match is already reflected in the (remapped) names of
+    /// the source files and of the working directory.
+    impl RelocatableDepTrackingHash for Vec<(PathBuf, PathBuf)> {
+        fn hash(&self, hasher: &mut DefaultHasher, error_format: ErrorOutputType) {
+            Hash::hash(&self.len(), hasher);
+            for (index, (_from, to)) in self.iter().enumerate() {
+                Hash::hash(&index, hasher);
+                DepTrackingHash::hash(to, hasher, error_format, false);
+            }
+        }
+    }
+
+    /// Only the presence of the `rust-src` component matters, not where the toolchain is
+    /// installed.
+    impl RelocatableDepTrackingHash for Option<PathBuf> {
+        fn hash(&self, hasher: &mut DefaultHasher, error_format: ErrorOutputType) {
+            DepTrackingHash::hash(&self.is_some(), hasher, error_format, false);
+        }
+    }
+
     // This is a stable hash because BTreeMap is a sorted container
     pub(crate) fn stable_hash(
         sub_hashes: BTreeMap<&'static str, &dyn DepTrackingHash>,
@@ -2972,6 +3022,15 @@ pub fn should_panic(self) -> u8 {
     }
 }
 
+/// The different settings that the `-Z time-queries` flag can have.
+#[derive(Clone, Copy, PartialEq, Hash, Debug)]
+pub enum TimeQueries {
+    /// `-Z time-queries=summary`: print a table of the queries, sorted by self time.
+    Summary,
+    /// `-Z time-queries=json`: print the same data as JSON.
+    Json,
+}
+
 /// How to run proc-macro code when building this crate
 #[derive(Clone, Copy, PartialEq, Hash, Debug)]
 pub enum ProcMacroExecutionStrategy {
@@ -42,6 +42,7 @@ macro_rules! hash_opt {
             insert!($opt_name, $opt_expr, $sub_hashes)
         }
     }};
+    ($opt_name:ident, $opt_expr:expr, $sub_hashes:expr, $_for_crate_hash: ident, [TRACKED_RELOCATABLE]) => {{}};
     ($opt_name:ident, $opt_expr:expr, $sub_hashes:expr, $_for_crate_hash: ident, [SUBSTRUCT]) => {{}};
 }
 
@@ -49,6 +50,13 @@ macro_rules! hash_substruct {
     ($opt_name:ident, $opt_expr:expr, $error_format:expr, $for_crate_hash:expr, $hasher:expr, [UNTRACKED]) => {{}};
     ($opt_name:ident, $opt_expr:expr, $error_format:expr, $for_crate_hash:expr, $hasher:expr, [TRACKED]) => {{}};
     ($opt_name:ident, $opt_expr:expr, $error_format:expr, $for_crate_hash:expr, $hasher:expr, [TRACKED_NO_CRATE_HASH]) => {{}};
+    ($opt_name:ident, $opt_expr:expr, $error_format:expr, $for_crate_hash:expr, $hasher:expr, [TRACKED_RELOCATABLE]) => {{
+        if !$for_crate_hash {
+            use crate::config::dep_tracking::RelocatableDepTrackingHash;
+            std::hash::Hash::hash(stringify!($opt_name), $hasher);
+            RelocatableDepTrackingHash::hash($opt_expr, $hasher, $error_format);
+        }
+    }};
     ($opt_name:ident, $opt_expr:expr, $error_format:expr, $for_crate_hash:expr, $hasher:expr, [SUBSTRUCT]) => {
         use crate::config::dep_tracking::DepTrackingHash;
         $opt_expr.dep_tracking_hash($for_crate_hash, $error_format).hash(
@@ -117,6 +125,12 @@ pub fn dep_tracking_hash(&self, for_crate_hash: bool) -> u64 {
     /// Same as `[TRACKED]`, but will not affect the crate hash. This is useful for options that only
     /// affect the incremental cache.
     ///
+    /// - `[TRACKED_RELOCATABLE]`
+    /// Same as `[TRACKED_NO_CRATE_HASH]`, but for options containing local paths. These are
+    /// hashed through `RelocatableDepTrackingHash` in a way that does not depend on where the
+    /// sources or the toolchain are located, so that incremental compilation caches can be
+    /// moved to a different checkout.
+    ///
     /// - `[UNTRACKED]`
     /// Incremental compilation is not influenced by this option.
     ///
@@ -184,7 +198,7 @@ pub struct Options {
         cli_forced_local_thinlto_off: bool [UNTRACKED],
 
         /// Remap source path prefixes in all output (messages, object files, debug, etc.).
-        remap_path_prefix: Vec<(PathBuf, PathBuf)> [TRACKED_NO_CRATE_HASH],
+        remap_path_prefix: Vec<(PathBuf, PathBuf)> [TRACKED_RELOCATABLE],
         /// Base directory containing the `src/` for the Rust standard library, and
         /// potentially `rustc` as well, if we can find it. Right now it's always
         /// `$sysroot/lib/rustlib/src/rust` (i.e. the `rustup` `rust-src` component).
@@ -192,7 +206,7 @@ pub struct Options {
         /// This directory is what the virtual `/rustc/$hash` is translated back to,
         /// if Rust was built with path remapping to `/rustc/$hash` enabled
         /// (the `rust.remap-debuginfo` option in `config.toml`).
-        real_rust_source_base_dir: Option<PathBuf> [TRACKED_NO_CRATE_HASH],
+        real_rust_source_base_dir: Option<PathBuf> [TRACKED_RELOCATABLE],
 
         edition: Edition [TRACKED],
 
@@ -408,6 +422,7 @@ mod desc {
         "a `,` separated combination of `bti`, `b-key`, `pac-ret`, or `leaf`";
     pub const parse_proc_macro_execution_strategy: &str =
         "one of supported execution strategies (`same-thread`, or `cross-thread`)";
+    pub const parse_time_queries: &str = "either `summary` or `json`";
 }
 
 mod parse {
@@ -1071,6 +1086,15 @@ pub(crate) fn parse_proc_macro_execution_strategy(
         };
         true
     }
+
+    pub(crate) fn parse_time_queries(slot: &mut Option<TimeQueries>, v: Option<&str>) -> bool {
+        *slot = match v {
+            Some("summary") => Some(TimeQueries::Summary),
+            Some("json") => Some(TimeQueries::Json),
+            _ => return false,
+        };
+        true
+    }
 }
 
 options! {
@@ -1318,6 +1342,8 @@ pub(crate) fn parse_proc_macro_execution_strategy(
         "whether each function should go in its own section"),
     future_incompat_test: bool = (false, parse_bool, [UNTRACKED],
         "forces all lints to be future incompatible, used for internal testing (default: no)"),
+    future_size_limit: Option<usize> = (None, parse_opt_number, [TRACKED],
+        "the size at which the `large_futures` lint starts to be emitted (default: 16384)"),
     gcc_ld: Option<LdImpl> = (None, parse_gcc_ld, [TRACKED], "implementation of ld used by cc"),
     graphviz_dark_mode: bool = (false, parse_bool, [UNTRACKED],
         "use dark-themed colors in graphviz output (default: no)"),
@@ -1330,6 +1356,10 @@ pub(crate) fn parse_proc_macro_execution_strategy(
         "generate human-readable, predictable names for codegen units (default: no)"),
     identify_regions: bool = (false, parse_bool, [UNTRACKED],
         "display unnamed regions as `'<id>`, using a non-ident unique id (default: no)"),
+    incremental_cache_limit: Option<u64> = (None, parse_opt_number, [UNTRACKED],
+        "once the incremental compilation directory is larger than this many MiB, delete \
+        the least recently used session directories until it fits, and delete object files that \
+        are no longer used by the current session (default: no limit)"),
     incremental_ignore_spans: bool = (false, parse_bool, [UNTRACKED],
         "ignore spans during ICH computation -- used for testing (default: no)"),
     incremental_info: bool = (false, parse_bool, [UNTRACKED],
@@ -1584,6 +1614,9 @@ pub(crate) fn parse_proc_macro_execution_strategy(
     #[rustc_lint_opt_deny_field_access("use `Session::time_passes` instead of this field")]
     time_passes: bool = (false, parse_bool, [UNTRACKED],
         "measure time of each rustc pass (default: no)"),
+    time_queries: Option<TimeQueries> = (None, parse_time_queries, [UNTRACKED],
+        "print the self time, cache hits and misses, incremental reuse and blocked time of each \
+        query at the end of the session, either as a table (`summary`) or as JSON (`json`)"),
     #[rustc_lint_opt_deny_field_access("use `Session::tls_model` instead of this field")]
     tls_model: Option<TlsModel> = (None, parse_tls_model, [TRACKED],
         "choose the TLS model to use (`rustc --print tls-models` for details)"),
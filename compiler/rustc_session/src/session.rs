@@ -1,7 +1,11 @@
 use crate::cgu_reuse_tracker::CguReuseTracker;
 use crate::code_stats::CodeStats;
-pub use crate::code_stats::{DataTypeKind, FieldInfo, SizeKind, VariantInfo};
-use crate::config::{self, CrateType, InstrumentCoverage, OptLevel, OutputType, SwitchWithOptPath};
+pub use crate::code_stats::{
+    DataTypeKind, FieldInfo, LayoutInfo, NicheInfo, SizeKind, TagInfo, VariantInfo,
+};
+use crate::config::{
+    self, CrateType, InstrumentCoverage, OptLevel, OutputType, PrintRequest, SwitchWithOptPath,
+};
 use crate::errors::{
     CannotEnableCrtStaticLinux, CannotMixAndMatchSanitizers, LinkerPluginToWindowsNotSupported,
     NotCircumventFeature, ProfileSampleUseFileDoesNotExist, ProfileUseFileDoesNotExist,
@@ -27,6 +31,7 @@
 use rustc_errors::emitter::{Emitter, EmitterWriter, HumanReadableErrorType};
 use rustc_errors::json::JsonEmitter;
 use rustc_errors::registry::Registry;
+use rustc_errors::sarif::SarifEmitter;
 use rustc_errors::{
     error_code, fallback_fluent_bundle, DiagnosticBuilder, DiagnosticId, DiagnosticMessage,
     ErrorGuaranteed, FluentBundle, IntoDiagnostic, LazyFallbackBundle, MultiSpan, Noted,
@@ -34,7 +39,7 @@
 use rustc_macros::HashStable_Generic;
 pub use rustc_span::def_id::StableCrateId;
 use rustc_span::edition::Edition;
-use rustc_span::source_map::{FileLoader, RealFileLoader, SourceMap, Span};
+use rustc_span::source_map::{FileLoader, FilePathMapping, RealFileLoader, SourceMap, Span};
 use rustc_span::{sym, SourceFileHashAlgorithm, Symbol};
 use rustc_target::asm::InlineAsmArch;
 use rustc_target::spec::{CodeModel, PanicStrategy, RelocModel, RelroLevel};
@@ -605,6 +610,7 @@ pub fn delay_span_bug<S: Into<MultiSpan>>(
     /// it's likely a bug.
     pub fn delay_good_path_bug(&self, msg: impl Into<DiagnosticMessage>) {
         if self.opts.unstable_opts.print_type_sizes
+            || self.opts.prints.contains(&PrintRequest::TypeLayouts)
             || self.opts.unstable_opts.query_dep_graph
             || self.opts.unstable_opts.dump_mir.is_some()
             || self.opts.unstable_opts.unpretty.is_some()
@@ -1303,6 +1309,13 @@ fn default_emitter(
             )
             .ui_testing(sopts.unstable_opts.ui_testing),
         ),
+        config::ErrorOutputType::Sarif => Box::new(SarifEmitter::stderr(
+            Some(registry),
+            source_map,
+            bundle,
+            fallback_bundle,
+            option_env!("CFG_RELEASE"),
+        )),
     }
 }
 
@@ -1622,6 +1635,13 @@ fn early_error_handler(output: config::ErrorOutputType) -> rustc_errors::Handler
             false,
             false,
         )),
+        config::ErrorOutputType::Sarif => Box::new(SarifEmitter::stderr(
+            None,
+            Lrc::new(SourceMap::new(FilePathMapping::empty())),
+            None,
+            fallback_bundle,
+            option_env!("CFG_RELEASE"),
+        )),
     };
     rustc_errors::Handler::with_emitter(true, None, emitter)
 }
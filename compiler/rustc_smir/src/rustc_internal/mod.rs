@@ -0,0 +1,99 @@
+//! Module that implements the bridge between Stable MIR and internal compiler MIR.
+//!
+//! For that, we define APIs that will temporarily be public to 3P that exposes rustc internal APIs
+//! until stable MIR is complete.
+
+use std::ops::ControlFlow;
+
+use crate::rustc_smir::Tables;
+use crate::stable_mir;
+use crate::very_unstable::driver::{Callbacks, Compilation, RunCompiler};
+use crate::very_unstable::hir::def_id::{CrateNum, DefId};
+use crate::very_unstable::interface::{interface, Queries};
+use crate::very_unstable::middle::ty::TyCtxt;
+
+fn with_tables<R>(mut f: impl FnMut(&mut Tables<'_>) -> R) -> R {
+    let mut ret = None;
+    stable_mir::with(|tables| tables.rustc_tables(&mut |t| ret = Some(f(t))));
+    ret.unwrap()
+}
+
+pub fn item_def_id(item: &stable_mir::CrateItem) -> DefId {
+    with_tables(|t| t.item_def_id(item))
+}
+
+pub fn crate_item(did: DefId) -> stable_mir::CrateItem {
+    with_tables(|t| t.crate_item(did))
+}
+
+pub fn crate_num(item: &stable_mir::Crate) -> CrateNum {
+    CrateNum::from_usize(item.id)
+}
+
+/// Makes the stable MIR of the crate being compiled by `tcx` available to `f`.
+pub fn run(tcx: TyCtxt<'_>, f: impl FnOnce()) {
+    stable_mir::run(Tables::new(tcx), f);
+}
+
+/// Driver entry point for tools built on top of stable MIR.
+///
+/// Runs the compiler with the given command line arguments, and once analysis has finished
+/// invokes the callback, from which all the functions in [`stable_mir`] can be used. If the
+/// callback returns [`ControlFlow::Break`], compilation stops right after it, otherwise the
+/// compiler goes on to generate code as usual.
+///
+/// ```ignore (needs a sysroot and an input file)
+/// let args = vec!["rustc".to_string(), "input.rs".to_string()];
+/// StableMir::new(args, || {
+///     for item in stable_mir::all_local_items() {
+///         if let Some(body) = item.body() {
+///             println!("{}: {} blocks", item.name(), body.blocks.len());
+///         }
+///     }
+///     ControlFlow::Break(())
+/// })
+/// .run()
+/// .unwrap();
+/// ```
+pub struct StableMir<F>
+where
+    F: FnOnce() -> ControlFlow<()> + Send,
+{
+    args: Vec<String>,
+    callback: Option<F>,
+}
+
+impl<F> StableMir<F>
+where
+    F: FnOnce() -> ControlFlow<()> + Send,
+{
+    /// Creates a new `StableMir` instance from the compiler arguments and the callback to invoke.
+    pub fn new(args: Vec<String>, callback: F) -> Self {
+        StableMir { args, callback: Some(callback) }
+    }
+
+    /// Runs the compiler, invoking the callback once analysis has finished.
+    pub fn run(mut self) -> interface::Result<()> {
+        let args = std::mem::take(&mut self.args);
+        RunCompiler::new(&args, &mut self).run()
+    }
+}
+
+impl<F> Callbacks for StableMir<F>
+where
+    F: FnOnce() -> ControlFlow<()> + Send,
+{
+    fn after_analysis<'tcx>(
+        &mut self,
+        compiler: &interface::Compiler,
+        queries: &'tcx Queries<'tcx>,
+    ) -> Compilation {
+        compiler.session().abort_if_errors();
+        let callback = self.callback.take().expect("the callback is only invoked once");
+        let mut flow = ControlFlow::Continue(());
+        queries.global_ctxt().unwrap().peek_mut().enter(|tcx| {
+            run(tcx, || flow = callback());
+        });
+        if flow.is_break() { Compilation::Stop } else { Compilation::Continue }
+    }
+}
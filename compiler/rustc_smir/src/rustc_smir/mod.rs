@@ -0,0 +1,825 @@
+//! Module that implements what will become the rustc side of Stable MIR.
+//!
+//! This module is responsible for building Stable MIR components from internal components.
+//!
+//! This module is not intended to be invoked directly by users. It will eventually
+//! become the public API of rustc that will be invoked by the `stable_mir` crate.
+//!
+//! For now, we are developing everything inside `rustc`, thus, we keep this module private.
+
+use crate::stable_mir::mir::{self as stable_mir_body};
+use crate::stable_mir::ty::{self as stable_ty};
+use crate::stable_mir::{self, opaque, Context};
+use crate::very_unstable::data_structures::fx::FxIndexSet;
+use crate::very_unstable::hir::def::DefKind;
+use crate::very_unstable::hir::{self, def_id::DefId, def_id::LOCAL_CRATE};
+use crate::very_unstable::middle::mir;
+use crate::very_unstable::middle::ty::{self, adjustment::PointerCast, Ty, TyCtxt};
+use crate::very_unstable::span::Span;
+
+impl<'tcx> Context for Tables<'tcx> {
+    fn entry_fn(&mut self) -> Option<stable_mir::CrateItem> {
+        Some(self.crate_item(self.tcx.entry_fn(())?.0))
+    }
+
+    fn all_local_items(&mut self) -> stable_mir::CrateItems {
+        self.tcx.mir_keys(()).iter().map(|item| self.crate_item(item.to_def_id())).collect()
+    }
+
+    fn mir_body(&mut self, item: &stable_mir::CrateItem) -> Option<stable_mir::mir::Body> {
+        let def_id = self.item_def_id(item);
+        let kind = self.item_kind(item)?;
+        if !self.tcx.is_mir_available(def_id) {
+            return None;
+        }
+        let mir = match kind {
+            stable_mir::ItemKind::Const | stable_mir::ItemKind::Static => {
+                self.tcx.mir_for_ctfe(def_id)
+            }
+            _ => self.tcx.optimized_mir(def_id),
+        };
+        Some(stable_mir::mir::Body {
+            blocks: mir
+                .basic_blocks
+                .iter()
+                .map(|block| stable_mir::mir::BasicBlock {
+                    terminator: block.terminator().stable(self),
+                    statements: block.statements.iter().map(|stmt| stmt.stable(self)).collect(),
+                    is_cleanup: block.is_cleanup,
+                })
+                .collect(),
+            locals: mir
+                .local_decls
+                .iter()
+                .map(|decl| stable_mir::mir::LocalDecl {
+                    ty: self.intern_ty(decl.ty),
+                    mutability: decl.mutability.stable(self),
+                    span: self.create_span(decl.source_info.span),
+                })
+                .collect(),
+            arg_count: mir.arg_count,
+            span: self.create_span(mir.span),
+        })
+    }
+
+    fn item_kind(&mut self, item: &stable_mir::CrateItem) -> Option<stable_mir::ItemKind> {
+        let def_id = self.item_def_id(item);
+        let kind = match self.tcx.def_kind(def_id) {
+            DefKind::Fn | DefKind::AssocFn => stable_mir::ItemKind::Fn,
+            DefKind::Closure => stable_mir::ItemKind::Closure,
+            DefKind::Generator => stable_mir::ItemKind::Generator,
+            DefKind::Ctor(..) => stable_mir::ItemKind::Ctor,
+            DefKind::Const | DefKind::AssocConst | DefKind::AnonConst | DefKind::InlineConst => {
+                stable_mir::ItemKind::Const
+            }
+            DefKind::Static(_) => stable_mir::ItemKind::Static,
+            _ => return None,
+        };
+        Some(kind)
+    }
+
+    fn name_of_def_id(&mut self, def_id: stable_mir::DefId) -> stable_mir::Symbol {
+        self.tcx.def_path_str(self.def_ids[def_id.0])
+    }
+
+    fn span_of_an_item(&mut self, def_id: stable_mir::DefId) -> stable_mir::Span {
+        let span = self.tcx.def_span(self.def_ids[def_id.0]);
+        self.create_span(span)
+    }
+
+    fn get_filename(&mut self, span: &stable_mir::Span) -> stable_mir::Filename {
+        let span = self.spans[span.0];
+        self.tcx.sess.source_map().span_to_filename(span).prefer_remapped().to_string()
+    }
+
+    fn get_lines(&mut self, span: &stable_mir::Span) -> stable_mir::LineInfo {
+        let span = self.spans[span.0];
+        let source_map = self.tcx.sess.source_map();
+        let lo = source_map.lookup_char_pos(span.lo());
+        let hi = source_map.lookup_char_pos(span.hi());
+        stable_mir::LineInfo {
+            start_line: lo.line,
+            start_col: lo.col.0 + 1,
+            end_line: hi.line,
+            end_col: hi.col.0 + 1,
+        }
+    }
+
+    fn local_crate(&self) -> stable_mir::Crate {
+        smir_crate(self.tcx, LOCAL_CRATE)
+    }
+
+    fn external_crates(&self) -> Vec<stable_mir::Crate> {
+        self.tcx.crates(()).iter().map(|crate_num| smir_crate(self.tcx, *crate_num)).collect()
+    }
+
+    fn find_crate(&self, name: &str) -> Option<stable_mir::Crate> {
+        [LOCAL_CRATE].iter().chain(self.tcx.crates(()).iter()).find_map(|crate_num| {
+            let crate_name = self.tcx.crate_name(*crate_num).to_string();
+            (name == crate_name).then(|| smir_crate(self.tcx, *crate_num))
+        })
+    }
+
+    fn ty_kind(&mut self, ty: stable_ty::Ty) -> stable_ty::TyKind {
+        let ty = self.types[ty.0];
+        self.rustc_ty_to_ty(ty)
+    }
+
+    fn rustc_tables(&mut self, f: &mut dyn FnMut(&mut Tables<'_>)) {
+        f(self)
+    }
+}
+
+/// The tables mapping between rustc's internal identifiers and the stable MIR ones.
+///
+/// The stable identifiers are indices into these sets.
+pub struct Tables<'tcx> {
+    pub tcx: TyCtxt<'tcx>,
+    pub def_ids: FxIndexSet<DefId>,
+    pub spans: FxIndexSet<Span>,
+    pub types: FxIndexSet<Ty<'tcx>>,
+}
+
+impl<'tcx> Tables<'tcx> {
+    pub fn new(tcx: TyCtxt<'tcx>) -> Self {
+        Tables {
+            tcx,
+            def_ids: FxIndexSet::default(),
+            spans: FxIndexSet::default(),
+            types: FxIndexSet::default(),
+        }
+    }
+
+    pub fn item_def_id(&self, item: &stable_mir::CrateItem) -> DefId {
+        self.def_ids[item.0.0]
+    }
+
+    pub fn crate_item(&mut self, did: DefId) -> stable_mir::CrateItem {
+        stable_mir::CrateItem(self.create_def_id(did))
+    }
+
+    pub fn create_def_id(&mut self, did: DefId) -> stable_mir::DefId {
+        stable_mir::DefId(self.def_ids.insert_full(did).0)
+    }
+
+    pub fn create_span(&mut self, span: Span) -> stable_mir::Span {
+        stable_mir::Span(self.spans.insert_full(span).0)
+    }
+
+    pub fn intern_ty(&mut self, ty: Ty<'tcx>) -> stable_ty::Ty {
+        stable_ty::Ty(self.types.insert_full(ty).0)
+    }
+
+    fn rustc_ty_to_ty(&mut self, ty: Ty<'tcx>) -> stable_ty::TyKind {
+        use stable_ty::RigidTy;
+        match *ty.kind() {
+            ty::Bool => stable_ty::TyKind::RigidTy(RigidTy::Bool),
+            ty::Char => stable_ty::TyKind::RigidTy(RigidTy::Char),
+            ty::Int(int_ty) => stable_ty::TyKind::RigidTy(RigidTy::Int(int_ty.stable(self))),
+            ty::Uint(uint_ty) => stable_ty::TyKind::RigidTy(RigidTy::Uint(uint_ty.stable(self))),
+            ty::Float(float_ty) => {
+                stable_ty::TyKind::RigidTy(RigidTy::Float(float_ty.stable(self)))
+            }
+            ty::Adt(adt_def, substs) => stable_ty::TyKind::RigidTy(RigidTy::Adt(
+                stable_ty::AdtDef(self.create_def_id(adt_def.did())),
+                substs.stable(self),
+            )),
+            ty::Foreign(def_id) => stable_ty::TyKind::RigidTy(RigidTy::Foreign(
+                stable_ty::ForeignDef(self.create_def_id(def_id)),
+            )),
+            ty::Str => stable_ty::TyKind::RigidTy(RigidTy::Str),
+            ty::Array(elem_ty, len) => stable_ty::TyKind::RigidTy(RigidTy::Array(
+                self.intern_ty(elem_ty),
+                len.try_eval_usize(self.tcx, ty::ParamEnv::reveal_all()),
+            )),
+            ty::Slice(elem_ty) => {
+                stable_ty::TyKind::RigidTy(RigidTy::Slice(self.intern_ty(elem_ty)))
+            }
+            ty::RawPtr(ty::TypeAndMut { ty, mutbl }) => {
+                stable_ty::TyKind::RigidTy(RigidTy::RawPtr(self.intern_ty(ty), mutbl.stable(self)))
+            }
+            ty::Ref(region, ty, mutbl) => stable_ty::TyKind::RigidTy(RigidTy::Ref(
+                opaque(&region),
+                self.intern_ty(ty),
+                mutbl.stable(self),
+            )),
+            ty::FnDef(def_id, substs) => stable_ty::TyKind::RigidTy(RigidTy::FnDef(
+                stable_ty::FnDef(self.create_def_id(def_id)),
+                substs.stable(self),
+            )),
+            ty::FnPtr(sig) => {
+                let sig = sig.skip_binder();
+                stable_ty::TyKind::RigidTy(RigidTy::FnPtr(stable_ty::FnSig {
+                    inputs_and_output: sig
+                        .inputs_and_output
+                        .iter()
+                        .map(|ty| self.intern_ty(ty))
+                        .collect(),
+                    c_variadic: sig.c_variadic,
+                    unsafety: sig.unsafety.stable(self),
+                    abi: opaque(&sig.abi),
+                }))
+            }
+            ty::Dynamic(predicates, region, _) => stable_ty::TyKind::RigidTy(RigidTy::Dynamic(
+                predicates
+                    .principal_def_id()
+                    .into_iter()
+                    .chain(predicates.auto_traits())
+                    .map(|def_id| stable_ty::TraitDef(self.create_def_id(def_id)))
+                    .collect(),
+                opaque(&region),
+            )),
+            ty::Closure(def_id, substs) => stable_ty::TyKind::RigidTy(RigidTy::Closure(
+                stable_ty::ClosureDef(self.create_def_id(def_id)),
+                substs.stable(self),
+            )),
+            ty::Generator(def_id, substs, movability) => {
+                stable_ty::TyKind::RigidTy(RigidTy::Generator(
+                    stable_ty::GeneratorDef(self.create_def_id(def_id)),
+                    substs.stable(self),
+                    movability.stable(self),
+                ))
+            }
+            ty::GeneratorWitness(witness) => {
+                stable_ty::TyKind::RigidTy(RigidTy::GeneratorWitness(opaque(&witness)))
+            }
+            ty::Never => stable_ty::TyKind::RigidTy(RigidTy::Never),
+            ty::Tuple(fields) => stable_ty::TyKind::RigidTy(RigidTy::Tuple(
+                fields.iter().map(|ty| self.intern_ty(ty)).collect(),
+            )),
+            ty::Projection(projection) => stable_ty::TyKind::Alias(
+                stable_ty::AliasKind::Projection,
+                stable_ty::AliasTy {
+                    def_id: stable_ty::AliasDef(self.create_def_id(projection.item_def_id)),
+                    args: projection.substs.stable(self),
+                },
+            ),
+            ty::Opaque(def_id, substs) => stable_ty::TyKind::Alias(
+                stable_ty::AliasKind::Opaque,
+                stable_ty::AliasTy {
+                    def_id: stable_ty::AliasDef(self.create_def_id(def_id)),
+                    args: substs.stable(self),
+                },
+            ),
+            ty::Param(param_ty) => stable_ty::TyKind::Param(stable_ty::ParamTy {
+                index: param_ty.index,
+                name: param_ty.name.to_string(),
+            }),
+            ty::Bound(debruijn, bound_ty) => stable_ty::TyKind::Bound(
+                debruijn.as_usize(),
+                stable_ty::BoundTy {
+                    var: bound_ty.var.as_usize(),
+                    name: match bound_ty.kind {
+                        ty::BoundTyKind::Anon => None,
+                        ty::BoundTyKind::Param(name) => Some(name.to_string()),
+                    },
+                },
+            ),
+            ty::Placeholder(..) | ty::Infer(_) | ty::Error(_) => {
+                unreachable!("type `{ty:?}` cannot appear in MIR after type checking")
+            }
+        }
+    }
+}
+
+/// Build a stable mir crate from a given crate number.
+fn smir_crate(tcx: TyCtxt<'_>, crate_num: hir::def_id::CrateNum) -> stable_mir::Crate {
+    let crate_name = tcx.crate_name(crate_num).to_string();
+    let is_local = crate_num == LOCAL_CRATE;
+    stable_mir::Crate { id: crate_num.as_usize(), name: crate_name, is_local }
+}
+
+/// Trait used to convert between an internal MIR type to a Stable MIR type.
+pub(crate) trait Stable<'tcx> {
+    /// The stable representation of the type implementing Stable.
+    type T;
+    /// Converts an object to the equivalent Stable MIR representation.
+    fn stable(&self, tables: &mut Tables<'tcx>) -> Self::T;
+}
+
+impl<'tcx> Stable<'tcx> for mir::Statement<'tcx> {
+    type T = stable_mir_body::Statement;
+    fn stable(&self, tables: &mut Tables<'tcx>) -> Self::T {
+        use mir::StatementKind::*;
+        use stable_mir_body::StatementKind as S;
+        let kind = match &self.kind {
+            Assign(assign) => S::Assign(assign.0.stable(tables), assign.1.stable(tables)),
+            FakeRead(fake_read_place) => {
+                S::FakeRead(fake_read_place.0.stable(tables), fake_read_place.1.stable(tables))
+            }
+            SetDiscriminant { place, variant_index } => S::SetDiscriminant {
+                place: place.as_ref().stable(tables),
+                variant_index: variant_index.as_usize(),
+            },
+            Deinit(place) => S::Deinit(place.as_ref().stable(tables)),
+            StorageLive(place) => S::StorageLive(place.as_usize()),
+            StorageDead(place) => S::StorageDead(place.as_usize()),
+            Retag(retag, place) => S::Retag(retag.stable(tables), place.as_ref().stable(tables)),
+            AscribeUserType(place_projection, variance) => S::AscribeUserType {
+                place: place_projection.0.stable(tables),
+                projections: opaque(&place_projection.1),
+                variance: variance.stable(tables),
+            },
+            Coverage(coverage) => S::Coverage(opaque(coverage)),
+            Intrinsic(intrinsic) => S::Intrinsic(intrinsic.stable(tables)),
+            Nop => S::Nop,
+        };
+        stable_mir_body::Statement { kind, span: tables.create_span(self.source_info.span) }
+    }
+}
+
+impl<'tcx> Stable<'tcx> for mir::Rvalue<'tcx> {
+    type T = stable_mir_body::Rvalue;
+    fn stable(&self, tables: &mut Tables<'tcx>) -> Self::T {
+        use mir::Rvalue::*;
+        use stable_mir_body::Rvalue as R;
+        match self {
+            Use(op) => R::Use(op.stable(tables)),
+            Repeat(op, len) => R::Repeat(op.stable(tables), opaque(len)),
+            Ref(region, kind, place) => {
+                R::Ref(opaque(region), kind.stable(tables), place.stable(tables))
+            }
+            ThreadLocalRef(def_id) => R::ThreadLocalRef(tables.crate_item(*def_id)),
+            AddressOf(mutability, place) => {
+                R::AddressOf(mutability.stable(tables), place.stable(tables))
+            }
+            Len(place) => R::Len(place.stable(tables)),
+            Cast(cast_kind, op, ty) => {
+                R::Cast(cast_kind.stable(tables), op.stable(tables), tables.intern_ty(*ty))
+            }
+            BinaryOp(bin_op, ops) => {
+                R::BinaryOp(bin_op.stable(tables), ops.0.stable(tables), ops.1.stable(tables))
+            }
+            CheckedBinaryOp(bin_op, ops) => R::CheckedBinaryOp(
+                bin_op.stable(tables),
+                ops.0.stable(tables),
+                ops.1.stable(tables),
+            ),
+            NullaryOp(null_op, ty) => R::NullaryOp(null_op.stable(tables), tables.intern_ty(*ty)),
+            UnaryOp(un_op, op) => R::UnaryOp(un_op.stable(tables), op.stable(tables)),
+            Discriminant(place) => R::Discriminant(place.stable(tables)),
+            Aggregate(agg_kind, operands) => R::Aggregate(
+                agg_kind.stable(tables),
+                operands.iter().map(|op| op.stable(tables)).collect(),
+            ),
+            ShallowInitBox(op, ty) => R::ShallowInitBox(op.stable(tables), tables.intern_ty(*ty)),
+            CopyForDeref(place) => R::CopyForDeref(place.stable(tables)),
+        }
+    }
+}
+
+impl<'tcx> Stable<'tcx> for mir::AggregateKind<'tcx> {
+    type T = stable_mir_body::AggregateKind;
+    fn stable(&self, tables: &mut Tables<'tcx>) -> Self::T {
+        use mir::AggregateKind::*;
+        use stable_mir_body::AggregateKind as A;
+        match self {
+            Array(ty) => A::Array(tables.intern_ty(*ty)),
+            Tuple => A::Tuple,
+            Adt(def_id, variant_index, substs, _, field_idx) => A::Adt(
+                stable_ty::AdtDef(tables.create_def_id(*def_id)),
+                variant_index.as_usize(),
+                substs.stable(tables),
+                *field_idx,
+            ),
+            Closure(def_id, substs) => A::Closure(
+                stable_ty::ClosureDef(tables.create_def_id(def_id.to_def_id())),
+                substs.stable(tables),
+            ),
+            Generator(def_id, substs, movability) => A::Generator(
+                stable_ty::GeneratorDef(tables.create_def_id(def_id.to_def_id())),
+                substs.stable(tables),
+                movability.stable(tables),
+            ),
+        }
+    }
+}
+
+impl<'tcx> Stable<'tcx> for mir::Operand<'tcx> {
+    type T = stable_mir_body::Operand;
+    fn stable(&self, tables: &mut Tables<'tcx>) -> Self::T {
+        use mir::Operand::*;
+        match self {
+            Copy(place) => stable_mir_body::Operand::Copy(place.stable(tables)),
+            Move(place) => stable_mir_body::Operand::Move(place.stable(tables)),
+            Constant(c) => stable_mir_body::Operand::Constant(c.stable(tables)),
+        }
+    }
+}
+
+impl<'tcx> Stable<'tcx> for mir::Constant<'tcx> {
+    type T = stable_mir_body::Constant;
+    fn stable(&self, tables: &mut Tables<'tcx>) -> Self::T {
+        stable_mir_body::Constant {
+            span: tables.create_span(self.span),
+            ty: tables.intern_ty(self.literal.ty()),
+            literal: opaque(&self.literal),
+        }
+    }
+}
+
+impl<'tcx> Stable<'tcx> for mir::Place<'tcx> {
+    type T = stable_mir_body::Place;
+    fn stable(&self, tables: &mut Tables<'tcx>) -> Self::T {
+        stable_mir_body::Place {
+            local: self.local.as_usize(),
+            projection: self.projection.iter().map(|elem| elem.stable(tables)).collect(),
+        }
+    }
+}
+
+impl<'tcx> Stable<'tcx> for mir::PlaceElem<'tcx> {
+    type T = stable_mir_body::ProjectionElem;
+    fn stable(&self, tables: &mut Tables<'tcx>) -> Self::T {
+        use mir::ProjectionElem::*;
+        use stable_mir_body::ProjectionElem as P;
+        match *self {
+            Deref => P::Deref,
+            Field(field, ty) => P::Field(field.as_usize(), tables.intern_ty(ty)),
+            Index(local) => P::Index(local.as_usize()),
+            ConstantIndex { offset, min_length, from_end } => {
+                P::ConstantIndex { offset, min_length, from_end }
+            }
+            Subslice { from, to, from_end } => P::Subslice { from, to, from_end },
+            Downcast(_, variant_index) => P::Downcast(variant_index.as_usize()),
+            OpaqueCast(ty) => P::OpaqueCast(tables.intern_ty(ty)),
+        }
+    }
+}
+
+impl<'tcx> Stable<'tcx> for mir::Terminator<'tcx> {
+    type T = stable_mir_body::Terminator;
+    fn stable(&self, tables: &mut Tables<'tcx>) -> Self::T {
+        use mir::TerminatorKind::*;
+        use stable_mir_body::TerminatorKind as T;
+        let kind = match &self.kind {
+            Goto { target } => T::Goto { target: target.as_usize() },
+            SwitchInt { discr, targets } => T::SwitchInt {
+                discr: discr.stable(tables),
+                targets: targets
+                    .iter()
+                    .map(|(value, target)| stable_mir_body::SwitchTarget {
+                        value,
+                        target: target.as_usize(),
+                    })
+                    .collect(),
+                otherwise: targets.otherwise().as_usize(),
+            },
+            Resume => T::Resume,
+            Abort => T::Abort,
+            Return => T::Return,
+            Unreachable => T::Unreachable,
+            Drop { place, target, unwind } => T::Drop {
+                place: place.stable(tables),
+                target: target.as_usize(),
+                unwind: unwind.map(|cleanup| cleanup.as_usize()),
+            },
+            DropAndReplace { place, value, target, unwind } => T::DropAndReplace {
+                place: place.stable(tables),
+                value: value.stable(tables),
+                target: target.as_usize(),
+                unwind: unwind.map(|cleanup| cleanup.as_usize()),
+            },
+            Call { func, args, destination, target, cleanup, from_hir_call: _, fn_span: _ } => {
+                T::Call {
+                    func: func.stable(tables),
+                    args: args.iter().map(|arg| arg.stable(tables)).collect(),
+                    destination: destination.stable(tables),
+                    target: target.map(|t| t.as_usize()),
+                    cleanup: cleanup.map(|t| t.as_usize()),
+                }
+            }
+            Assert { cond, expected, msg, target, cleanup } => T::Assert {
+                cond: cond.stable(tables),
+                expected: *expected,
+                msg: msg.stable(tables),
+                target: target.as_usize(),
+                cleanup: cleanup.map(|t| t.as_usize()),
+            },
+            InlineAsm { template, operands, options, line_spans, destination, cleanup } => {
+                T::InlineAsm {
+                    template: format!("{template:?}"),
+                    operands: operands.iter().map(|operand| operand.stable(tables)).collect(),
+                    options: format!("{options:?}"),
+                    line_spans: format!("{line_spans:?}"),
+                    destination: destination.map(|d| d.as_usize()),
+                    cleanup: cleanup.map(|c| c.as_usize()),
+                }
+            }
+            // These are all removed by the MIR passes that run before `optimized_mir` and
+            // `mir_for_ctfe`, which are the only bodies we hand out.
+            Yield { .. } | GeneratorDrop | FalseEdge { .. } | FalseUnwind { .. } => {
+                unreachable!("terminator {:?} in optimized MIR", self.kind)
+            }
+        };
+        stable_mir_body::Terminator { kind, span: tables.create_span(self.source_info.span) }
+    }
+}
+
+impl<'tcx> Stable<'tcx> for mir::InlineAsmOperand<'tcx> {
+    type T = stable_mir_body::InlineAsmOperand;
+    fn stable(&self, tables: &mut Tables<'tcx>) -> Self::T {
+        use mir::InlineAsmOperand;
+
+        let (in_value, out_place) = match self {
+            InlineAsmOperand::In { value, .. } => (Some(value.stable(tables)), None),
+            InlineAsmOperand::Out { place, .. } => {
+                (None, place.as_ref().map(|place| place.stable(tables)))
+            }
+            InlineAsmOperand::InOut { in_value, out_place, .. } => (
+                Some(in_value.stable(tables)),
+                out_place.as_ref().map(|place| place.stable(tables)),
+            ),
+            InlineAsmOperand::Const { .. }
+            | InlineAsmOperand::SymFn { .. }
+            | InlineAsmOperand::SymStatic { .. } => (None, None),
+        };
+
+        stable_mir_body::InlineAsmOperand { in_value, out_place, raw_rpr: format!("{self:?}") }
+    }
+}
+
+impl<'tcx> Stable<'tcx> for mir::AssertMessage<'tcx> {
+    type T = stable_mir_body::AssertMessage;
+    fn stable(&self, tables: &mut Tables<'tcx>) -> Self::T {
+        use mir::AssertKind;
+        use stable_mir_body::AssertMessage as A;
+        match self {
+            AssertKind::BoundsCheck { len, index } => {
+                A::BoundsCheck { len: len.stable(tables), index: index.stable(tables) }
+            }
+            AssertKind::Overflow(bin_op, op1, op2) => {
+                A::Overflow(bin_op.stable(tables), op1.stable(tables), op2.stable(tables))
+            }
+            AssertKind::OverflowNeg(op) => A::OverflowNeg(op.stable(tables)),
+            AssertKind::DivisionByZero(op) => A::DivisionByZero(op.stable(tables)),
+            AssertKind::RemainderByZero(op) => A::RemainderByZero(op.stable(tables)),
+            AssertKind::ResumedAfterReturn(generator) => {
+                A::ResumedAfterReturn(generator.stable(tables))
+            }
+            AssertKind::ResumedAfterPanic(generator) => {
+                A::ResumedAfterPanic(generator.stable(tables))
+            }
+        }
+    }
+}
+
+impl<'tcx> Stable<'tcx> for mir::NonDivergingIntrinsic<'tcx> {
+    type T = stable_mir_body::NonDivergingIntrinsic;
+    fn stable(&self, tables: &mut Tables<'tcx>) -> Self::T {
+        use mir::NonDivergingIntrinsic::*;
+        use stable_mir_body::NonDivergingIntrinsic as N;
+        match self {
+            Assume(op) => N::Assume(op.stable(tables)),
+            CopyNonOverlapping(copy) => N::CopyNonOverlapping {
+                src: copy.src.stable(tables),
+                dst: copy.dst.stable(tables),
+                count: copy.count.stable(tables),
+            },
+        }
+    }
+}
+
+impl<'tcx> Stable<'tcx> for ty::subst::SubstsRef<'tcx> {
+    type T = stable_ty::GenericArgs;
+    fn stable(&self, tables: &mut Tables<'tcx>) -> Self::T {
+        use ty::subst::GenericArgKind;
+        stable_ty::GenericArgs(
+            self.iter()
+                .map(|arg| match arg.unpack() {
+                    GenericArgKind::Lifetime(region) => {
+                        stable_ty::GenericArgKind::Lifetime(opaque(&region))
+                    }
+                    GenericArgKind::Type(ty) => {
+                        stable_ty::GenericArgKind::Type(tables.intern_ty(ty))
+                    }
+                    GenericArgKind::Const(c) => stable_ty::GenericArgKind::Const(opaque(&c)),
+                })
+                .collect(),
+        )
+    }
+}
+
+impl<'tcx> Stable<'tcx> for mir::BinOp {
+    type T = stable_mir_body::BinOp;
+    fn stable(&self, _: &mut Tables<'tcx>) -> Self::T {
+        use mir::BinOp;
+        match self {
+            BinOp::Add => stable_mir_body::BinOp::Add,
+            BinOp::Sub => stable_mir_body::BinOp::Sub,
+            BinOp::Mul => stable_mir_body::BinOp::Mul,
+            BinOp::Div => stable_mir_body::BinOp::Div,
+            BinOp::Rem => stable_mir_body::BinOp::Rem,
+            BinOp::BitXor => stable_mir_body::BinOp::BitXor,
+            BinOp::BitAnd => stable_mir_body::BinOp::BitAnd,
+            BinOp::BitOr => stable_mir_body::BinOp::BitOr,
+            BinOp::Shl => stable_mir_body::BinOp::Shl,
+            BinOp::Shr => stable_mir_body::BinOp::Shr,
+            BinOp::Eq => stable_mir_body::BinOp::Eq,
+            BinOp::Lt => stable_mir_body::BinOp::Lt,
+            BinOp::Le => stable_mir_body::BinOp::Le,
+            BinOp::Ne => stable_mir_body::BinOp::Ne,
+            BinOp::Ge => stable_mir_body::BinOp::Ge,
+            BinOp::Gt => stable_mir_body::BinOp::Gt,
+            BinOp::Offset => stable_mir_body::BinOp::Offset,
+        }
+    }
+}
+
+impl<'tcx> Stable<'tcx> for mir::UnOp {
+    type T = stable_mir_body::UnOp;
+    fn stable(&self, _: &mut Tables<'tcx>) -> Self::T {
+        use mir::UnOp;
+        match self {
+            UnOp::Not => stable_mir_body::UnOp::Not,
+            UnOp::Neg => stable_mir_body::UnOp::Neg,
+        }
+    }
+}
+
+impl<'tcx> Stable<'tcx> for mir::NullOp {
+    type T = stable_mir_body::NullOp;
+    fn stable(&self, _: &mut Tables<'tcx>) -> Self::T {
+        use mir::NullOp::*;
+        match self {
+            SizeOf => stable_mir_body::NullOp::SizeOf,
+            AlignOf => stable_mir_body::NullOp::AlignOf,
+        }
+    }
+}
+
+impl<'tcx> Stable<'tcx> for mir::CastKind {
+    type T = stable_mir_body::CastKind;
+    fn stable(&self, tables: &mut Tables<'tcx>) -> Self::T {
+        use mir::CastKind::*;
+        match self {
+            PointerExposeAddress => stable_mir_body::CastKind::PointerExposeAddress,
+            PointerFromExposedAddress => stable_mir_body::CastKind::PointerFromExposedAddress,
+            Pointer(cast) => stable_mir_body::CastKind::Pointer(cast.stable(tables)),
+            DynStar => stable_mir_body::CastKind::DynStar,
+            IntToInt => stable_mir_body::CastKind::IntToInt,
+            FloatToInt => stable_mir_body::CastKind::FloatToInt,
+            FloatToFloat => stable_mir_body::CastKind::FloatToFloat,
+            IntToFloat => stable_mir_body::CastKind::IntToFloat,
+            PtrToPtr => stable_mir_body::CastKind::PtrToPtr,
+            FnPtrToPtr => stable_mir_body::CastKind::FnPtrToPtr,
+        }
+    }
+}
+
+impl<'tcx> Stable<'tcx> for PointerCast {
+    type T = stable_mir_body::PointerCast;
+    fn stable(&self, tables: &mut Tables<'tcx>) -> Self::T {
+        use PointerCast::*;
+        match self {
+            ReifyFnPointer => stable_mir_body::PointerCast::ReifyFnPointer,
+            UnsafeFnPointer => stable_mir_body::PointerCast::UnsafeFnPointer,
+            ClosureFnPointer(unsafety) => {
+                stable_mir_body::PointerCast::ClosureFnPointer(unsafety.stable(tables))
+            }
+            MutToConstPointer => stable_mir_body::PointerCast::MutToConstPointer,
+            ArrayToPointer => stable_mir_body::PointerCast::ArrayToPointer,
+            Unsize => stable_mir_body::PointerCast::Unsize,
+        }
+    }
+}
+
+impl<'tcx> Stable<'tcx> for mir::BorrowKind {
+    type T = stable_mir_body::BorrowKind;
+    fn stable(&self, _: &mut Tables<'tcx>) -> Self::T {
+        use mir::BorrowKind::*;
+        match *self {
+            Shared => stable_mir_body::BorrowKind::Shared,
+            Shallow => stable_mir_body::BorrowKind::Shallow,
+            Unique => stable_mir_body::BorrowKind::Unique,
+            Mut { allow_two_phase_borrow } => {
+                stable_mir_body::BorrowKind::Mut { allow_two_phase_borrow }
+            }
+        }
+    }
+}
+
+impl<'tcx> Stable<'tcx> for mir::FakeReadCause {
+    type T = stable_mir_body::FakeReadCause;
+    fn stable(&self, _: &mut Tables<'tcx>) -> Self::T {
+        use mir::FakeReadCause::*;
+        match self {
+            ForMatchGuard => stable_mir_body::FakeReadCause::ForMatchGuard,
+            ForMatchedPlace(_) => stable_mir_body::FakeReadCause::ForMatchedPlace,
+            ForGuardBinding => stable_mir_body::FakeReadCause::ForGuardBinding,
+            ForLet(_) => stable_mir_body::FakeReadCause::ForLet,
+            ForIndex => stable_mir_body::FakeReadCause::ForIndex,
+        }
+    }
+}
+
+impl<'tcx> Stable<'tcx> for mir::RetagKind {
+    type T = stable_mir_body::RetagKind;
+    fn stable(&self, _: &mut Tables<'tcx>) -> Self::T {
+        use mir::RetagKind;
+        match self {
+            RetagKind::FnEntry => stable_mir_body::RetagKind::FnEntry,
+            RetagKind::TwoPhase => stable_mir_body::RetagKind::TwoPhase,
+            RetagKind::Raw => stable_mir_body::RetagKind::Raw,
+            RetagKind::Default => stable_mir_body::RetagKind::Default,
+        }
+    }
+}
+
+impl<'tcx> Stable<'tcx> for ty::Variance {
+    type T = stable_mir_body::Variance;
+    fn stable(&self, _: &mut Tables<'tcx>) -> Self::T {
+        match self {
+            ty::Variance::Covariant => stable_mir_body::Variance::Covariant,
+            ty::Variance::Invariant => stable_mir_body::Variance::Invariant,
+            ty::Variance::Contravariant => stable_mir_body::Variance::Contravariant,
+            ty::Variance::Bivariant => stable_mir_body::Variance::Bivariant,
+        }
+    }
+}
+
+impl<'tcx> Stable<'tcx> for hir::GeneratorKind {
+    type T = stable_mir_body::GeneratorKind;
+    fn stable(&self, _: &mut Tables<'tcx>) -> Self::T {
+        use hir::{AsyncGeneratorKind, GeneratorKind};
+        match self {
+            GeneratorKind::Async(async_gen) => {
+                let async_gen = match async_gen {
+                    AsyncGeneratorKind::Block => stable_mir_body::AsyncGeneratorKind::Block,
+                    AsyncGeneratorKind::Closure => stable_mir_body::AsyncGeneratorKind::Closure,
+                    AsyncGeneratorKind::Fn => stable_mir_body::AsyncGeneratorKind::Fn,
+                };
+                stable_mir_body::GeneratorKind::Async(async_gen)
+            }
+            GeneratorKind::Gen => stable_mir_body::GeneratorKind::Gen,
+        }
+    }
+}
+
+impl<'tcx> Stable<'tcx> for hir::Mutability {
+    type T = stable_ty::Mutability;
+    fn stable(&self, _: &mut Tables<'tcx>) -> Self::T {
+        match self {
+            hir::Mutability::Not => stable_ty::Mutability::Not,
+            hir::Mutability::Mut => stable_ty::Mutability::Mut,
+        }
+    }
+}
+
+impl<'tcx> Stable<'tcx> for hir::Movability {
+    type T = stable_ty::Movability;
+    fn stable(&self, _: &mut Tables<'tcx>) -> Self::T {
+        match self {
+            hir::Movability::Static => stable_ty::Movability::Static,
+            hir::Movability::Movable => stable_ty::Movability::Movable,
+        }
+    }
+}
+
+impl<'tcx> Stable<'tcx> for hir::Unsafety {
+    type T = stable_ty::Unsafety;
+    fn stable(&self, _: &mut Tables<'tcx>) -> Self::T {
+        match self {
+            hir::Unsafety::Unsafe => stable_ty::Unsafety::Unsafe,
+            hir::Unsafety::Normal => stable_ty::Unsafety::Normal,
+        }
+    }
+}
+
+impl<'tcx> Stable<'tcx> for ty::IntTy {
+    type T = stable_ty::IntTy;
+    fn stable(&self, _: &mut Tables<'tcx>) -> Self::T {
+        match self {
+            ty::IntTy::Isize => stable_ty::IntTy::Isize,
+            ty::IntTy::I8 => stable_ty::IntTy::I8,
+            ty::IntTy::I16 => stable_ty::IntTy::I16,
+            ty::IntTy::I32 => stable_ty::IntTy::I32,
+            ty::IntTy::I64 => stable_ty::IntTy::I64,
+            ty::IntTy::I128 => stable_ty::IntTy::I128,
+        }
+    }
+}
+
+impl<'tcx> Stable<'tcx> for ty::UintTy {
+    type T = stable_ty::UintTy;
+    fn stable(&self, _: &mut Tables<'tcx>) -> Self::T {
+        match self {
+            ty::UintTy::Usize => stable_ty::UintTy::Usize,
+            ty::UintTy::U8 => stable_ty::UintTy::U8,
+            ty::UintTy::U16 => stable_ty::UintTy::U16,
+            ty::UintTy::U32 => stable_ty::UintTy::U32,
+            ty::UintTy::U64 => stable_ty::UintTy::U64,
+            ty::UintTy::U128 => stable_ty::UintTy::U128,
+        }
+    }
+}
+
+impl<'tcx> Stable<'tcx> for ty::FloatTy {
+    type T = stable_ty::FloatTy;
+    fn stable(&self, _: &mut Tables<'tcx>) -> Self::T {
+        match self {
+            ty::FloatTy::F32 => stable_ty::FloatTy::F32,
+            ty::FloatTy::F64 => stable_ty::FloatTy::F64,
+        }
+    }
+}
@@ -0,0 +1,225 @@
+//! Module that implements the public interface to the Stable MIR.
+//!
+//! This module shall contain all type definitions and APIs that we expect 3P tools to invoke to
+//! interact with the compiler.
+//!
+//! The goal is to eventually move this module to its own crate which shall be published on
+//! [crates.io](https://crates.io).
+//!
+//! ## Note:
+//!
+//! There shouldn't be any direct references to internal compiler constructs in this module.
+//! If you need an internal construct, consider using `rustc_internal` or `rustc_smir`.
+
+use std::cell::Cell;
+use std::fmt;
+use std::fmt::Debug;
+
+use crate::rustc_smir::Tables;
+
+use self::ty::{Ty, TyKind};
+
+pub mod mir;
+pub mod ty;
+
+/// The version of the stable MIR interface.
+///
+/// This is bumped every time a breaking change is made to the types or functions in this module,
+/// so tools can detect which shape of the API they were built against.
+pub const API_VERSION: u32 = 1;
+
+/// Use String for now but we should replace it.
+pub type Symbol = String;
+
+/// The number that identifies a crate.
+pub type CrateNum = usize;
+
+/// A unique identification number for each item accessible for the current compilation unit.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub struct DefId(pub(crate) usize);
+
+/// A list of crate items.
+pub type CrateItems = Vec<CrateItem>;
+
+/// Holds information about a crate.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Crate {
+    pub(crate) id: CrateNum,
+    pub name: Symbol,
+    pub is_local: bool,
+}
+
+/// Holds information about an item in the crate.
+/// For now, it only stores the item DefId. Use functions inside `rustc_internal` module to
+/// use this item.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct CrateItem(pub(crate) DefId);
+
+impl CrateItem {
+    /// Retrieve the MIR body of this item. For functions this is the optimized MIR, for
+    /// constants and statics it is the MIR used for const evaluation.
+    ///
+    /// Returns `None` if the item has no MIR, e.g. because it is a type or a module, or a
+    /// function of another crate whose MIR was not encoded.
+    pub fn body(&self) -> Option<mir::Body> {
+        with(|cx| cx.mir_body(self))
+    }
+
+    /// The path of this item, as it would be printed in diagnostics.
+    pub fn name(&self) -> Symbol {
+        with(|cx| cx.name_of_def_id(self.0))
+    }
+
+    /// What kind of item this is, or `None` if it is not an item that can have MIR.
+    pub fn kind(&self) -> Option<ItemKind> {
+        with(|cx| cx.item_kind(self))
+    }
+
+    /// The span of this item's definition.
+    pub fn span(&self) -> Span {
+        with(|cx| cx.span_of_an_item(self.0))
+    }
+}
+
+/// The kinds of items that have a MIR body.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
+pub enum ItemKind {
+    Fn,
+    Closure,
+    Generator,
+    Ctor,
+    Const,
+    Static,
+}
+
+/// A reference to a region of source code.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
+pub struct Span(pub(crate) usize);
+
+impl Span {
+    /// Return the name of the file this span points into.
+    pub fn get_filename(&self) -> Filename {
+        with(|cx| cx.get_filename(self))
+    }
+
+    /// Return the start and end lines and columns of this span. Lines and columns are 1-based.
+    pub fn get_lines(&self) -> LineInfo {
+        with(|cx| cx.get_lines(self))
+    }
+}
+
+/// The name of a source file, with path remapping already applied.
+pub type Filename = String;
+
+/// Information about the lines and columns a [`Span`] covers.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
+pub struct LineInfo {
+    pub start_line: usize,
+    pub start_col: usize,
+    pub end_line: usize,
+    pub end_col: usize,
+}
+
+/// An opaque, debug-printed representation of a rustc internal value that has no stable
+/// counterpart yet.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct Opaque(String);
+
+impl fmt::Display for Opaque {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Debug for Opaque {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self.0)
+    }
+}
+
+pub(crate) fn opaque<T: Debug>(value: &T) -> Opaque {
+    Opaque(format!("{value:?}"))
+}
+
+/// Access to the local crate.
+pub fn local_crate() -> Crate {
+    with(|cx| cx.local_crate())
+}
+
+/// Try to find a crate with the given name.
+pub fn find_crate(name: &str) -> Option<Crate> {
+    with(|cx| cx.find_crate(name))
+}
+
+/// Retrieve all the crates the local crate depends on.
+pub fn external_crates() -> Vec<Crate> {
+    with(|cx| cx.external_crates())
+}
+
+/// Retrieve all items in the local crate that have a MIR associated with them.
+pub fn all_local_items() -> CrateItems {
+    with(|cx| cx.all_local_items())
+}
+
+/// Retrieve the entry function of the local crate, if there is one.
+pub fn entry_fn() -> Option<CrateItem> {
+    with(|cx| cx.entry_fn())
+}
+
+/// The interface between the stable MIR and the compiler. Tools should not need to implement or
+/// call this directly; use the free functions in this module instead.
+pub trait Context {
+    /// Retrieve the entry function of the local crate, if there is one.
+    fn entry_fn(&mut self) -> Option<CrateItem>;
+    /// Retrieve all items of the local crate that have a MIR associated with them.
+    fn all_local_items(&mut self) -> CrateItems;
+    /// Convert the MIR body of an item to its stable representation.
+    fn mir_body(&mut self, item: &CrateItem) -> Option<mir::Body>;
+    fn item_kind(&mut self, item: &CrateItem) -> Option<ItemKind>;
+    /// Get the path of an item, as it would be printed in diagnostics.
+    fn name_of_def_id(&mut self, def_id: DefId) -> Symbol;
+    /// Get the span of the definition of an item.
+    fn span_of_an_item(&mut self, def_id: DefId) -> Span;
+    fn get_filename(&mut self, span: &Span) -> Filename;
+    fn get_lines(&mut self, span: &Span) -> LineInfo;
+    /// Get information about the local crate.
+    fn local_crate(&self) -> Crate;
+    /// Retrieve a list of all external crates.
+    fn external_crates(&self) -> Vec<Crate>;
+
+    /// Find a crate with the given name.
+    fn find_crate(&self, name: &str) -> Option<Crate>;
+
+    /// Obtain the representation of a type.
+    fn ty_kind(&mut self, ty: Ty) -> TyKind;
+
+    /// Gives access to the underlying rustc tables, for use by `rustc_internal`.
+    fn rustc_tables(&mut self, f: &mut dyn FnMut(&mut Tables<'_>));
+}
+
+// A thread local variable that stores a pointer to the tables mapping between TyCtxt
+// datastructures and stable MIR datastructures.
+scoped_tls::scoped_thread_local! (static TLV: Cell<*mut ()>);
+
+/// Makes `context` available to the functions of this module for the duration of `f`.
+pub fn run(mut context: impl Context, f: impl FnOnce()) {
+    assert!(!TLV.is_set());
+    fn g<'a>(mut context: &mut (dyn Context + 'a), f: impl FnOnce()) {
+        let ptr: *mut () = &mut context as *mut &mut _ as _;
+        TLV.set(&Cell::new(ptr), || {
+            f();
+        });
+    }
+    g(&mut context, f);
+}
+
+/// Loads the current context and calls a function with it.
+/// Do not nest these, as that will ICE.
+pub(crate) fn with<R>(f: impl FnOnce(&mut dyn Context) -> R) -> R {
+    assert!(TLV.is_set());
+    TLV.with(|tlv| {
+        let ptr = tlv.get();
+        assert!(!ptr.is_null());
+        f(unsafe { *(ptr as *mut &mut dyn Context) })
+    })
+}
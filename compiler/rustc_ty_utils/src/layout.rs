@@ -9,7 +9,8 @@
 use rustc_middle::ty::{
     self, subst::SubstsRef, AdtDef, EarlyBinder, ReprOptions, Ty, TyCtxt, TypeVisitable,
 };
-use rustc_session::{DataTypeKind, FieldInfo, SizeKind, VariantInfo};
+use rustc_session::config::PrintRequest;
+use rustc_session::{DataTypeKind, FieldInfo, LayoutInfo, NicheInfo, SizeKind, TagInfo, VariantInfo};
 use rustc_span::symbol::Symbol;
 use rustc_span::DUMMY_SP;
 use rustc_target::abi::*;
@@ -783,9 +784,10 @@ fn generator_layout<'tcx>(
 /// layout of each type.
 #[inline(always)]
 fn record_layout_for_printing<'tcx>(cx: &LayoutCx<'tcx, TyCtxt<'tcx>>, layout: TyAndLayout<'tcx>) {
-    // If we are running with `-Zprint-type-sizes`, maybe record layouts
-    // for dumping later.
-    if cx.tcx.sess.opts.unstable_opts.print_type_sizes {
+    // If we are running with `-Zprint-type-sizes` or `--print type-layouts=json`,
+    // maybe record layouts for dumping later.
+    let opts = &cx.tcx.sess.opts;
+    if opts.unstable_opts.print_type_sizes || opts.prints.contains(&PrintRequest::TypeLayouts) {
         record_layout_for_printing_outlined(cx, layout)
     }
 }
@@ -802,7 +804,7 @@ fn record_layout_for_printing_outlined<'tcx>(
     }
 
     // (delay format until we actually need it)
-    let record = |kind, packed, opt_discr_size, variants| {
+    let record = |kind, packed, opt_discr_size, variants, layout_info| {
         let type_desc = format!("{:?}", layout.ty);
         cx.tcx.sess.code_stats.record_type_size(
             kind,
@@ -812,6 +814,7 @@ fn record_layout_for_printing_outlined<'tcx>(
             packed,
             opt_discr_size,
             variants,
+            layout_info,
         );
     };
 
@@ -821,7 +824,8 @@ fn record_layout_for_printing_outlined<'tcx>(
             let adt_kind = adt_def.adt_kind();
             let adt_packed = adt_def.repr().pack.is_some();
             let (variant_infos, opt_discr_size) = variant_info_for_adt(cx, layout, adt_def);
-            record(adt_kind.into(), adt_packed, opt_discr_size, variant_infos);
+            let layout_info = layout_info_for_printing(cx, layout, |i| adt_def.variant(i).name);
+            record(adt_kind.into(), adt_packed, opt_discr_size, variant_infos, layout_info);
         }
 
         ty::Generator(def_id, substs, _) => {
@@ -829,12 +833,16 @@ fn record_layout_for_printing_outlined<'tcx>(
             // Generators always have a begin/poisoned/end state with additional suspend points
             let (variant_infos, opt_discr_size) =
                 variant_info_for_generator(cx, layout, def_id, substs);
-            record(DataTypeKind::Generator, false, opt_discr_size, variant_infos);
+            let layout_info = layout_info_for_printing(cx, layout, |i| {
+                Symbol::intern(&ty::GeneratorSubsts::variant_name(i))
+            });
+            record(DataTypeKind::Generator, false, opt_discr_size, variant_infos, layout_info);
         }
 
         ty::Closure(..) => {
             debug!("print-type-size t: `{:?}` record closure", layout.ty);
-            record(DataTypeKind::Closure, false, None, vec![]);
+            let layout_info = layout_info_for_printing(cx, layout, |_| unreachable!());
+            record(DataTypeKind::Closure, false, None, vec![], layout_info);
         }
 
         _ => {
@@ -843,6 +851,57 @@ fn record_layout_for_printing_outlined<'tcx>(
     };
 }
 
+/// Collects the ABI, niche and tag encoding of `layout`, which are only reported by
+/// `--print type-layouts=json`. `variant_name` is used to name the variants involved in a niche
+/// encoding.
+fn layout_info_for_printing<'tcx>(
+    cx: &LayoutCx<'tcx, TyCtxt<'tcx>>,
+    layout: TyAndLayout<'tcx>,
+    variant_name: impl Fn(VariantIdx) -> Symbol,
+) -> LayoutInfo {
+    let abi = match layout.abi {
+        Abi::Uninhabited => "uninhabited",
+        Abi::Scalar(_) => "scalar",
+        Abi::ScalarPair(..) => "scalar_pair",
+        Abi::Vector { .. } => "vector",
+        Abi::Aggregate { sized: true } => "aggregate",
+        Abi::Aggregate { sized: false } => "unsized_aggregate",
+    };
+
+    let largest_niche = layout.largest_niche.map(|niche| NicheInfo {
+        offset: niche.offset.bytes(),
+        size: niche.value.size(cx).bytes(),
+        valid_range_start: niche.valid_range.start,
+        valid_range_end: niche.valid_range.end,
+        available: niche.available(cx),
+    });
+
+    let tag = match layout.variants {
+        Variants::Single { .. } => None,
+        Variants::Multiple { tag, ref tag_encoding, tag_field, .. } => {
+            let offset = layout.fields.offset(tag_field).bytes();
+            let size = tag.size(cx).bytes();
+            Some(match *tag_encoding {
+                TagEncoding::Direct => TagInfo::Direct { offset, size },
+                TagEncoding::Niche { untagged_variant, ref niche_variants, niche_start } => {
+                    TagInfo::Niche {
+                        offset,
+                        size,
+                        untagged_variant: variant_name(untagged_variant),
+                        niche_variants: (
+                            variant_name(*niche_variants.start()),
+                            variant_name(*niche_variants.end()),
+                        ),
+                        niche_start,
+                    }
+                }
+            })
+        }
+    };
+
+    LayoutInfo { abi, largest_niche, tag }
+}
+
 fn variant_info_for_adt<'tcx>(
     cx: &LayoutCx<'tcx, TyCtxt<'tcx>>,
     layout: TyAndLayout<'tcx>,
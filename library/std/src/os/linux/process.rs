@@ -6,6 +6,7 @@
 
 use crate::io::Result;
 use crate::os::unix::io::{AsFd, AsRawFd, BorrowedFd, FromRawFd, IntoRawFd, OwnedFd, RawFd};
+use crate::path::Path;
 use crate::process;
 use crate::sealed::Sealed;
 #[cfg(not(doc))]
@@ -105,6 +106,75 @@ fn from(pid_fd: PidFd) -> Self {
     }
 }
 
+/// A resource whose usage can be limited with [`CommandExt::setrlimit`].
+///
+/// Refer to the man page of [`setrlimit(2)`] for the details of every resource.
+///
+/// [`setrlimit(2)`]: https://man7.org/linux/man-pages/man2/setrlimit.2.html
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+#[unstable(feature = "linux_process_setup", issue = "none")]
+pub enum Resource {
+    /// `RLIMIT_AS`, the size of the virtual memory of the process in bytes.
+    AddressSpace,
+    /// `RLIMIT_CORE`, the size of core dumps in bytes.
+    Core,
+    /// `RLIMIT_CPU`, the CPU time of the process in seconds.
+    Cpu,
+    /// `RLIMIT_DATA`, the size of the data segment of the process in bytes.
+    Data,
+    /// `RLIMIT_FSIZE`, the size of the files created by the process in bytes.
+    FileSize,
+    /// `RLIMIT_MEMLOCK`, the number of bytes of memory that can be locked.
+    MemLock,
+    /// `RLIMIT_MSGQUEUE`, the number of bytes that can be allocated for POSIX message queues.
+    MsgQueue,
+    /// `RLIMIT_NICE`, the ceiling of the nice value of the process.
+    Nice,
+    /// `RLIMIT_NOFILE`, one more than the largest file descriptor the process can open.
+    NoFile,
+    /// `RLIMIT_NPROC`, the number of threads of the real user ID of the process.
+    NProc,
+    /// `RLIMIT_RTPRIO`, the ceiling of the real-time priority of the process.
+    RtPrio,
+    /// `RLIMIT_RTTIME`, the CPU time in microseconds that a real-time thread can use without
+    /// blocking.
+    RtTime,
+    /// `RLIMIT_SIGPENDING`, the number of signals that can be queued for the real user ID of the
+    /// process.
+    SigPending,
+    /// `RLIMIT_STACK`, the size of the stack of the main thread in bytes.
+    Stack,
+}
+
+/// A kind of namespace that the child process can be moved into with [`CommandExt::unshare`].
+///
+/// Refer to the man page of [`namespaces(7)`] for the details of every kind.
+///
+/// [`namespaces(7)`]: https://man7.org/linux/man-pages/man7/namespaces.7.html
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+#[unstable(feature = "linux_process_setup", issue = "none")]
+pub enum Namespace {
+    /// `CLONE_NEWCGROUP`, the cgroup root directory.
+    Cgroup,
+    /// `CLONE_NEWIPC`, System V IPC objects and POSIX message queues.
+    Ipc,
+    /// `CLONE_NEWNS`, the mount points.
+    Mount,
+    /// `CLONE_NEWNET`, the network devices, stacks and ports.
+    Network,
+    /// `CLONE_NEWPID`, the process IDs.
+    ///
+    /// Note that the child process itself stays in its PID namespace: only the processes it
+    /// creates are in the new one, the first of which gets the process ID 1.
+    Pid,
+    /// `CLONE_NEWUSER`, the user and group IDs.
+    User,
+    /// `CLONE_NEWUTS`, the hostname and NIS domain name.
+    Uts,
+}
+
 /// Os-specific extensions for [`Child`]
 ///
 /// [`Child`]: process::Child
@@ -155,6 +225,54 @@ pub trait CommandExt: Sealed {
     /// [`pidfd`]: fn@ChildExt::pidfd
     /// [`take_pidfd`]: ChildExt::take_pidfd
     fn create_pidfd(&mut self, val: bool) -> &mut process::Command;
+
+    /// Sets a limit on the usage of a resource by the child process.
+    ///
+    /// `soft` is the limit that the kernel enforces, which the child process can raise up to
+    /// `hard`. A limit of `u64::MAX` means no limit (`RLIM_INFINITY`). Raising a hard limit needs
+    /// the `CAP_SYS_RESOURCE` capability.
+    ///
+    /// Like the other options of this trait, the limit is set in the child process between
+    /// `fork` and `exec`, and an error to set it is returned by [`spawn`].
+    ///
+    /// [`spawn`]: process::Command::spawn
+    #[unstable(feature = "linux_process_setup", issue = "none")]
+    fn setrlimit(&mut self, resource: Resource, soft: u64, hard: u64) -> &mut process::Command;
+
+    /// Moves the child process into a cgroup, given the path of its directory in the cgroup v2
+    /// hierarchy, such as `/sys/fs/cgroup/sandbox`.
+    ///
+    /// The child process is moved by writing to the `cgroup.procs` file of the directory, before
+    /// it unshares any namespaces. A relative path is relative to the working directory of the
+    /// parent process.
+    #[unstable(feature = "linux_process_setup", issue = "none")]
+    fn cgroup<P: AsRef<Path>>(&mut self, dir: P) -> &mut process::Command;
+
+    /// Moves the child process into a new namespace of the given kind.
+    ///
+    /// This can be called several times to unshare several kinds of namespaces, which happens
+    /// with a single call to [`unshare(2)`]. Unsharing anything but a user namespace needs the
+    /// `CAP_SYS_ADMIN` capability, which the child process has in a new user namespace.
+    ///
+    /// Namespaces are unshared after [`setrlimit`] and [`cgroup`], but before the child process
+    /// changes its user and group IDs and its working directory.
+    ///
+    /// [`unshare(2)`]: https://man7.org/linux/man-pages/man2/unshare.2.html
+    /// [`setrlimit`]: CommandExt::setrlimit
+    /// [`cgroup`]: CommandExt::cgroup
+    #[unstable(feature = "linux_process_setup", issue = "none")]
+    fn unshare(&mut self, namespace: Namespace) -> &mut process::Command;
+
+    /// Sets whether the child process should inherit only its standard input, output and error,
+    /// and none of the other file descriptors of the parent process.
+    ///
+    /// By default, the child process inherits every file descriptor that doesn't have the
+    /// close-on-exec flag, which is set on all of those that the standard library opens. File
+    /// descriptors opened by [`pre_exec`] closures are still inherited.
+    ///
+    /// [`pre_exec`]: crate::os::unix::process::CommandExt::pre_exec
+    #[unstable(feature = "linux_process_setup", issue = "none")]
+    fn close_other_fds(&mut self, close: bool) -> &mut process::Command;
 }
 
 impl CommandExt for process::Command {
@@ -162,4 +280,24 @@ fn create_pidfd(&mut self, val: bool) -> &mut process::Command {
         self.as_inner_mut().create_pidfd(val);
         self
     }
+
+    fn setrlimit(&mut self, resource: Resource, soft: u64, hard: u64) -> &mut process::Command {
+        self.as_inner_mut().setrlimit(resource, soft, hard);
+        self
+    }
+
+    fn cgroup<P: AsRef<Path>>(&mut self, dir: P) -> &mut process::Command {
+        self.as_inner_mut().cgroup(dir.as_ref());
+        self
+    }
+
+    fn unshare(&mut self, namespace: Namespace) -> &mut process::Command {
+        self.as_inner_mut().unshare(namespace);
+        self
+    }
+
+    fn close_other_fds(&mut self, close: bool) -> &mut process::Command {
+        self.as_inner_mut().close_other_fds(close);
+        self
+    }
 }
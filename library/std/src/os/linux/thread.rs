@@ -0,0 +1,121 @@
+//! Linux-specific extensions to primitives in the [`std::thread`] module.
+//!
+//! [`std::thread`]: crate::thread
+
+#![unstable(feature = "linux_thread_options", issue = "none")]
+
+use crate::sealed::Sealed;
+use crate::sys_common::AsInnerMut;
+use crate::thread;
+
+/// A scheduling policy of a thread.
+///
+/// Refer to the man page of [`sched(7)`] for the details of every policy.
+///
+/// [`sched(7)`]: https://man7.org/linux/man-pages/man7/sched.7.html
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum SchedPolicy {
+    /// `SCHED_OTHER`, the default time-sharing policy.
+    Other,
+    /// `SCHED_FIFO`, a real-time first-in, first-out policy.
+    Fifo,
+    /// `SCHED_RR`, a real-time round-robin policy.
+    RoundRobin,
+    /// `SCHED_BATCH`, for CPU-intensive threads that don't need to react quickly.
+    Batch,
+    /// `SCHED_IDLE`, for threads that should only run when nothing else needs to.
+    Idle,
+}
+
+/// Os-specific extensions for [`Builder`]
+///
+/// The options set with these methods are applied by the new thread itself, before it runs the
+/// closure it was spawned with. If one of them can't be applied, the closure isn't run, and
+/// [`spawn`] returns the error once the new thread has exited.
+///
+/// # Examples
+///
+/// ```no_run
+/// #![feature(linux_thread_options)]
+/// use std::os::linux::thread::{BuilderExt, SchedPolicy};
+/// use std::thread;
+///
+/// let handle = thread::Builder::new()
+///     .cpu_affinity(&[2, 3])
+///     .sched_policy(SchedPolicy::Fifo)
+///     .sched_priority(10)
+///     .spawn(|| {
+///         // latency-sensitive work
+///     })
+///     .expect("failed to spawn a real-time thread");
+/// handle.join().unwrap();
+/// ```
+///
+/// [`Builder`]: thread::Builder
+/// [`spawn`]: thread::Builder::spawn
+pub trait BuilderExt: Sealed {
+    /// Sets the CPUs that the new thread may run on, by their index.
+    ///
+    /// Refer to the man page of [`sched_setaffinity(2)`] for further details.
+    ///
+    /// [`sched_setaffinity(2)`]: https://man7.org/linux/man-pages/man2/sched_setaffinity.2.html
+    fn cpu_affinity(self, cpus: &[usize]) -> thread::Builder;
+
+    /// Sets the scheduling policy of the new thread.
+    ///
+    /// By default, the new thread inherits the policy of the thread that spawns it. The real-time
+    /// policies [`Fifo`] and [`RoundRobin`] need a priority of at least 1, set with
+    /// [`sched_priority`], and usually need the `CAP_SYS_NICE` capability.
+    ///
+    /// [`Fifo`]: SchedPolicy::Fifo
+    /// [`RoundRobin`]: SchedPolicy::RoundRobin
+    /// [`sched_priority`]: BuilderExt::sched_priority
+    fn sched_policy(self, policy: SchedPolicy) -> thread::Builder;
+
+    /// Sets the static scheduling priority of the new thread.
+    ///
+    /// The priority ranges from 1 to 99 for the real-time policies, and is 0 for the others. By
+    /// default, the new thread inherits the priority of the thread that spawns it.
+    ///
+    /// Refer to the man page of [`pthread_setschedparam(3)`] for further details.
+    ///
+    /// [`pthread_setschedparam(3)`]: https://man7.org/linux/man-pages/man3/pthread_setschedparam.3.html
+    fn sched_priority(self, priority: i32) -> thread::Builder;
+
+    /// Sets the nice value of the new thread, from -20 for the most favorable scheduling to 19 for
+    /// the least favorable one.
+    ///
+    /// The nice value only matters for the policies [`Other`] and [`Batch`], where it is the way to
+    /// raise or lower the priority of a thread. By default, the new thread inherits the nice value
+    /// of the thread that spawns it. Lowering it usually needs the `CAP_SYS_NICE` capability.
+    ///
+    /// Refer to the man page of [`setpriority(2)`] for further details.
+    ///
+    /// [`Other`]: SchedPolicy::Other
+    /// [`Batch`]: SchedPolicy::Batch
+    /// [`setpriority(2)`]: https://man7.org/linux/man-pages/man2/setpriority.2.html
+    fn nice(self, nice: i32) -> thread::Builder;
+}
+
+impl BuilderExt for thread::Builder {
+    fn cpu_affinity(mut self, cpus: &[usize]) -> thread::Builder {
+        self.as_inner_mut().cpu_affinity(cpus);
+        self
+    }
+
+    fn sched_policy(mut self, policy: SchedPolicy) -> thread::Builder {
+        self.as_inner_mut().sched_policy(policy);
+        self
+    }
+
+    fn sched_priority(mut self, priority: i32) -> thread::Builder {
+        self.as_inner_mut().sched_priority(priority);
+        self
+    }
+
+    fn nice(mut self, nice: i32) -> thread::Builder {
+        self.as_inner_mut().nice(nice);
+        self
+    }
+}
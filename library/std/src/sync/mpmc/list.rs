@@ -597,6 +597,25 @@ pub(crate) fn is_empty(&self) -> bool {
     pub(crate) fn is_full(&self) -> bool {
         false
     }
+
+    /// Returns `true` if a receive operation would not block, because there is a message or the
+    /// channel is disconnected.
+    pub(crate) fn is_ready_to_recv(&self) -> bool {
+        !self.is_empty() || self.is_disconnected()
+    }
+
+    /// Registers an operation that waits for a receive operation to become ready.
+    ///
+    /// Returns `true` if it already is ready.
+    pub(crate) fn watch_recv(&self, oper: Operation, cx: &Context) -> bool {
+        self.receivers.watch(oper, cx);
+        self.is_ready_to_recv()
+    }
+
+    /// Unregisters an operation registered with `watch_recv`.
+    pub(crate) fn unwatch_recv(&self, oper: Operation) {
+        self.receivers.unwatch(oper);
+    }
 }
 
 impl<T> Drop for Channel<T> {
@@ -1,3 +1,10 @@
+use super::context::Context;
+use super::error::{ReadyTimeoutError, TryReadyError};
+use super::Receiver;
+
+use crate::fmt;
+use crate::time::{Duration, Instant};
+
 /// Temporary data that gets initialized during a blocking operation, and is consumed by
 /// `read` or `write`.
 ///
@@ -69,3 +76,231 @@ fn into(self) -> usize {
         }
     }
 }
+
+/// A receiver that can be waited on by a [`Select`].
+pub(crate) trait SelectHandle {
+    /// Returns `true` if a receive operation would not block.
+    fn is_ready(&self) -> bool;
+
+    /// Registers an operation that waits until a receive operation becomes ready.
+    ///
+    /// Returns `true` if it already is ready.
+    fn watch(&self, oper: Operation, cx: &Context) -> bool;
+
+    /// Unregisters an operation registered with `watch`.
+    fn unwatch(&self, oper: Operation);
+}
+
+/// Waits until one of several receivers is ready.
+///
+/// A receiver is ready when a receive operation on it would not block, because it has a message
+/// or its channel is disconnected. Receivers are added with [`recv`], which returns the index
+/// that the waiting methods return when that receiver is ready.
+///
+/// Readiness is only a hint: since receivers can be cloned, another thread may have received the
+/// message by the time the current thread tries to. Use [`Receiver::try_recv`] on the ready
+/// receiver, and wait again if it returns [`TryRecvError::Empty`].
+///
+/// [`recv`]: Select::recv
+/// [`TryRecvError::Empty`]: super::TryRecvError::Empty
+///
+/// # Examples
+///
+/// ```
+/// #![feature(mpmc_channel)]
+///
+/// use std::sync::mpmc::{channel, Select};
+/// use std::thread;
+///
+/// let (tx1, rx1) = channel::<i32>();
+/// let (tx2, rx2) = channel::<&str>();
+///
+/// thread::spawn(move || tx2.send("hello").unwrap());
+///
+/// let mut sel = Select::new();
+/// let oper1 = sel.recv(&rx1);
+/// let oper2 = sel.recv(&rx2);
+///
+/// // `tx1` is neither used nor dropped, so only `rx2` can become ready.
+/// let index = sel.ready();
+/// assert_eq!(index, oper2);
+/// assert_eq!(rx2.try_recv(), Ok("hello"));
+/// # drop((tx1, oper1));
+/// ```
+#[unstable(feature = "mpmc_channel", issue = "none")]
+pub struct Select<'a> {
+    /// The receivers that were added, with their indices.
+    handles: Vec<(&'a dyn SelectHandle, usize)>,
+
+    /// The index of the next receiver to be added.
+    next_index: usize,
+}
+
+impl<'a> Select<'a> {
+    /// Creates an empty list of receivers to wait on.
+    #[unstable(feature = "mpmc_channel", issue = "none")]
+    pub fn new() -> Select<'a> {
+        Select { handles: Vec::new(), next_index: 0 }
+    }
+
+    /// Adds a receiver to wait on, and returns its index.
+    #[unstable(feature = "mpmc_channel", issue = "none")]
+    pub fn recv<T>(&mut self, r: &'a Receiver<T>) -> usize {
+        let i = self.next_index;
+        self.handles.push((r, i));
+        self.next_index += 1;
+        i
+    }
+
+    /// Removes the receiver with the given index.
+    ///
+    /// The indices of the other receivers don't change.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no receiver with the given index.
+    #[unstable(feature = "mpmc_channel", issue = "none")]
+    pub fn remove(&mut self, index: usize) {
+        let i = self
+            .handles
+            .iter()
+            .position(|&(_, i)| i == index)
+            .expect("no receiver with this index in `Select`");
+        self.handles.swap_remove(i);
+    }
+
+    /// Returns the index of a ready receiver, if there is one, without blocking.
+    #[unstable(feature = "mpmc_channel", issue = "none")]
+    pub fn try_ready(&mut self) -> Result<usize, TryReadyError> {
+        self.rotate();
+        let ready = self.handles.iter().find(|(handle, _)| handle.is_ready());
+        ready.map(|&(_, i)| i).ok_or(TryReadyError)
+    }
+
+    /// Blocks the current thread until one of the receivers is ready, and returns its index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no receivers have been added, since this would block forever.
+    #[unstable(feature = "mpmc_channel", issue = "none")]
+    pub fn ready(&mut self) -> usize {
+        if self.handles.is_empty() {
+            panic!("no receivers have been added to `Select`");
+        }
+        self.run_ready(None).unwrap()
+    }
+
+    /// Waits for one of the receivers to be ready, but only for a limited time.
+    ///
+    /// Returns the index of the ready receiver.
+    #[unstable(feature = "mpmc_channel", issue = "none")]
+    pub fn ready_timeout(&mut self, timeout: Duration) -> Result<usize, ReadyTimeoutError> {
+        match Instant::now().checked_add(timeout) {
+            Some(deadline) => self.ready_deadline(deadline),
+            // So far in the future that it's practically the same as waiting indefinitely.
+            None => Ok(self.ready()),
+        }
+    }
+
+    /// Waits for one of the receivers to be ready, but only until a given deadline.
+    ///
+    /// Returns the index of the ready receiver.
+    #[unstable(feature = "mpmc_channel", issue = "none")]
+    pub fn ready_deadline(&mut self, deadline: Instant) -> Result<usize, ReadyTimeoutError> {
+        self.run_ready(Some(deadline)).ok_or(ReadyTimeoutError)
+    }
+
+    /// Changes the order in which the receivers are checked, so that a receiver that is always
+    /// ready doesn't starve the others.
+    fn rotate(&mut self) {
+        if !self.handles.is_empty() {
+            self.handles.rotate_left(1);
+        }
+    }
+
+    /// Blocks until one of the receivers is ready or the deadline is reached.
+    fn run_ready(&mut self, deadline: Option<Instant>) -> Option<usize> {
+        self.rotate();
+        let handles = &mut self.handles[..];
+
+        loop {
+            // Check whether any of the receivers is already ready.
+            if let Some(&(_, i)) = handles.iter().find(|(handle, _)| handle.is_ready()) {
+                return Some(i);
+            }
+
+            let res = Context::with(|cx| {
+                let mut sel = Selected::Waiting;
+                let mut registered_count = 0;
+
+                // Register all receivers, so that the current thread is woken up once one of them
+                // becomes ready.
+                for (handle, _) in handles.iter_mut() {
+                    registered_count += 1;
+                    let oper = Operation::hook::<&dyn SelectHandle>(handle);
+
+                    // If the receiver became ready in the meantime, try selecting it.
+                    if handle.watch(oper, cx) {
+                        sel = match cx.try_select(Selected::Operation(oper)) {
+                            Ok(()) => Selected::Operation(oper),
+                            Err(s) => s,
+                        };
+                        break;
+                    }
+
+                    // If another thread has already selected one of the receivers, stop
+                    // registering.
+                    sel = cx.selected();
+                    if sel != Selected::Waiting {
+                        break;
+                    }
+                }
+
+                if sel == Selected::Waiting {
+                    sel = cx.wait_until(deadline);
+                }
+
+                for (handle, _) in handles.iter_mut().take(registered_count) {
+                    handle.unwatch(Operation::hook::<&dyn SelectHandle>(handle));
+                }
+
+                match sel {
+                    Selected::Waiting => unreachable!(),
+                    Selected::Aborted | Selected::Disconnected => {}
+                    Selected::Operation(_) => {
+                        for (handle, i) in handles.iter_mut() {
+                            let oper = Operation::hook::<&dyn SelectHandle>(handle);
+                            if sel == Selected::Operation(oper) {
+                                return Some(*i);
+                            }
+                        }
+                    }
+                }
+                None
+            });
+
+            if res.is_some() {
+                return res;
+            }
+            if let Some(deadline) = deadline {
+                if Instant::now() >= deadline {
+                    return None;
+                }
+            }
+        }
+    }
+}
+
+#[unstable(feature = "mpmc_channel", issue = "none")]
+impl Default for Select<'_> {
+    fn default() -> Self {
+        Select::new()
+    }
+}
+
+#[unstable(feature = "mpmc_channel", issue = "none")]
+impl fmt::Debug for Select<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("Select { .. }")
+    }
+}
@@ -0,0 +1,143 @@
+use super::*;
+use crate::sync::atomic::{AtomicUsize, Ordering};
+use crate::sync::Arc;
+use crate::thread;
+use crate::time::{Duration, Instant};
+
+#[test]
+fn smoke() {
+    let (tx, rx) = channel::<i32>();
+    tx.send(1).unwrap();
+    assert_eq!(rx.recv().unwrap(), 1);
+}
+
+#[test]
+fn cloned_receivers() {
+    let (tx, rx1) = channel::<i32>();
+    let rx2 = rx1.clone();
+    assert!(rx1.same_channel(&rx2));
+    tx.send(1).unwrap();
+    tx.send(2).unwrap();
+    assert_eq!(rx2.recv().unwrap(), 1);
+    assert_eq!(rx1.recv().unwrap(), 2);
+    assert_eq!(rx1.try_recv(), Err(TryRecvError::Empty));
+    drop(tx);
+    assert_eq!(rx2.recv(), Err(RecvError));
+}
+
+#[test]
+fn every_message_received_once() {
+    const MESSAGES: usize = 1000;
+
+    for cap in [0, 1, 16] {
+        let (tx, rx) = sync_channel::<usize>(cap);
+        let received = Arc::new(AtomicUsize::new(0));
+        let workers: Vec<_> = (0..4)
+            .map(|_| {
+                let rx = rx.clone();
+                let received = received.clone();
+                thread::spawn(move || {
+                    let mut sum = 0;
+                    while let Ok(msg) = rx.recv() {
+                        received.fetch_add(1, Ordering::SeqCst);
+                        sum += msg;
+                    }
+                    sum
+                })
+            })
+            .collect();
+        drop(rx);
+
+        for i in 0..MESSAGES {
+            tx.send(i).unwrap();
+        }
+        drop(tx);
+
+        let sum: usize = workers.into_iter().map(|worker| worker.join().unwrap()).sum();
+        assert_eq!(received.load(Ordering::SeqCst), MESSAGES);
+        assert_eq!(sum, MESSAGES * (MESSAGES - 1) / 2);
+    }
+}
+
+#[test]
+fn recv_timeout() {
+    let (tx, rx) = sync_channel::<i32>(1);
+    assert_eq!(rx.recv_timeout(Duration::from_millis(1)), Err(RecvTimeoutError::Timeout));
+    tx.send(1).unwrap();
+    assert_eq!(rx.recv_timeout(Duration::from_millis(1)), Ok(1));
+    drop(tx);
+    assert_eq!(rx.recv_timeout(Duration::from_millis(1)), Err(RecvTimeoutError::Disconnected));
+}
+
+#[test]
+fn send_timeout() {
+    let (tx, rx) = sync_channel::<i32>(1);
+    tx.send(1).unwrap();
+    assert_eq!(tx.send_timeout(2, Duration::from_millis(1)), Err(SendTimeoutError::Timeout(2)));
+    assert_eq!(rx.recv().unwrap(), 1);
+    drop(rx);
+    assert_eq!(
+        tx.send_timeout(3, Duration::from_millis(1)),
+        Err(SendTimeoutError::Disconnected(3))
+    );
+}
+
+#[test]
+fn select_try_ready() {
+    let (tx1, rx1) = channel::<i32>();
+    let (tx2, rx2) = sync_channel::<i32>(0);
+
+    let mut sel = Select::new();
+    let oper1 = sel.recv(&rx1);
+    let oper2 = sel.recv(&rx2);
+    assert_eq!(sel.try_ready(), Err(TryReadyError));
+
+    tx1.send(1).unwrap();
+    assert_eq!(sel.try_ready(), Ok(oper1));
+    assert_eq!(rx1.try_recv(), Ok(1));
+    assert_eq!(sel.try_ready(), Err(TryReadyError));
+
+    // A disconnected receiver is ready, since receiving from it doesn't block.
+    drop(tx2);
+    assert_eq!(sel.try_ready(), Ok(oper2));
+
+    sel.remove(oper2);
+    assert_eq!(sel.try_ready(), Err(TryReadyError));
+}
+
+#[test]
+fn select_ready() {
+    for cap in [0, 1] {
+        let (_tx1, rx1) = sync_channel::<i32>(cap);
+        let (tx2, rx2) = sync_channel::<i32>(cap);
+
+        let t = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(10));
+            tx2.send(2).unwrap();
+        });
+
+        let mut sel = Select::new();
+        let _oper1 = sel.recv(&rx1);
+        let oper2 = sel.recv(&rx2);
+        assert_eq!(sel.ready(), oper2);
+        assert_eq!(rx2.recv(), Ok(2));
+        t.join().unwrap();
+    }
+}
+
+#[test]
+fn select_ready_timeout() {
+    let (_tx1, rx1) = channel::<i32>();
+    let (tx2, rx2) = channel::<i32>();
+
+    let mut sel = Select::new();
+    sel.recv(&rx1);
+    let oper2 = sel.recv(&rx2);
+
+    let start = Instant::now();
+    assert_eq!(sel.ready_timeout(Duration::from_millis(10)), Err(ReadyTimeoutError));
+    assert!(start.elapsed() >= Duration::from_millis(10));
+
+    drop(tx2);
+    assert_eq!(sel.ready_timeout(Duration::from_millis(10)), Ok(oper2));
+}
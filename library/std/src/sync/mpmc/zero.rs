@@ -315,4 +315,25 @@ pub(crate) fn is_empty(&self) -> bool {
     pub(crate) fn is_full(&self) -> bool {
         true
     }
+
+    /// Returns `true` if a receive operation would not block, because there is a waiting sender
+    /// or the channel is disconnected.
+    pub(crate) fn is_ready_to_recv(&self) -> bool {
+        let inner = self.inner.lock().unwrap();
+        inner.senders.can_select() || inner.is_disconnected
+    }
+
+    /// Registers an operation that waits for a receive operation to become ready.
+    ///
+    /// Returns `true` if it already is ready.
+    pub(crate) fn watch_recv(&self, oper: Operation, cx: &Context) -> bool {
+        let mut inner = self.inner.lock().unwrap();
+        inner.receivers.watch(oper, cx);
+        inner.senders.can_select() || inner.is_disconnected
+    }
+
+    /// Unregisters an operation registered with `watch_recv`.
+    pub(crate) fn unwatch_recv(&self, oper: Operation) {
+        self.inner.lock().unwrap().receivers.unwatch(oper);
+    }
 }
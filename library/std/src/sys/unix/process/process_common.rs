@@ -15,6 +15,8 @@
 use crate::sys_common::process::{CommandEnv, CommandEnvs};
 use crate::sys_common::IntoInner;
 
+#[cfg(target_os = "linux")]
+use crate::os::linux::process::{Namespace, Resource};
 #[cfg(not(target_os = "fuchsia"))]
 use crate::sys::fs::OpenOptions;
 
@@ -106,6 +108,16 @@ pub struct Command {
     stderr: Option<Stdio>,
     #[cfg(target_os = "linux")]
     create_pidfd: bool,
+    #[cfg(target_os = "linux")]
+    rlimits: Vec<(Resource, libc::rlimit)>,
+    /// The path of the `cgroup.procs` file of the cgroup to move the child process into.
+    #[cfg(target_os = "linux")]
+    cgroup: Option<CString>,
+    /// The `CLONE_NEW*` flags to pass to `unshare`.
+    #[cfg(target_os = "linux")]
+    unshare: c_int,
+    #[cfg(target_os = "linux")]
+    close_other_fds: bool,
     pgroup: Option<pid_t>,
 }
 
@@ -220,6 +232,10 @@ pub fn new(program: &OsStr) -> Command {
             stdout: None,
             stderr: None,
             create_pidfd: false,
+            rlimits: Vec::new(),
+            cgroup: None,
+            unshare: 0,
+            close_other_fds: false,
             pgroup: None,
         }
     }
@@ -276,6 +292,75 @@ pub fn get_create_pidfd(&self) -> bool {
         self.create_pidfd
     }
 
+    #[cfg(target_os = "linux")]
+    pub fn setrlimit(&mut self, resource: Resource, soft: u64, hard: u64) {
+        // Limits that don't fit in `rlim_t` are as good as no limit.
+        let limit = |value: u64| value.try_into().unwrap_or(libc::RLIM_INFINITY);
+        let rlimit = libc::rlimit { rlim_cur: limit(soft), rlim_max: limit(hard) };
+        self.rlimits.push((resource, rlimit));
+    }
+
+    #[cfg(target_os = "linux")]
+    pub fn cgroup(&mut self, dir: &Path) {
+        self.cgroup = Some(os2c(dir.join("cgroup.procs").as_os_str(), &mut self.saw_nul));
+    }
+
+    #[cfg(target_os = "linux")]
+    pub fn unshare(&mut self, namespace: Namespace) {
+        self.unshare |= match namespace {
+            Namespace::Cgroup => libc::CLONE_NEWCGROUP,
+            Namespace::Ipc => libc::CLONE_NEWIPC,
+            Namespace::Mount => libc::CLONE_NEWNS,
+            Namespace::Network => libc::CLONE_NEWNET,
+            Namespace::Pid => libc::CLONE_NEWPID,
+            Namespace::User => libc::CLONE_NEWUSER,
+            Namespace::Uts => libc::CLONE_NEWUTS,
+        };
+    }
+
+    #[cfg(target_os = "linux")]
+    pub fn close_other_fds(&mut self, close: bool) {
+        self.close_other_fds = close;
+    }
+
+    #[cfg(target_os = "linux")]
+    pub fn get_rlimits(&self) -> &[(Resource, libc::rlimit)] {
+        &self.rlimits
+    }
+
+    #[cfg(target_os = "linux")]
+    pub fn get_cgroup(&self) -> Option<&CStr> {
+        self.cgroup.as_deref()
+    }
+
+    #[cfg(target_os = "linux")]
+    pub fn get_unshare(&self) -> c_int {
+        self.unshare
+    }
+
+    #[cfg(target_os = "linux")]
+    pub fn get_close_other_fds(&self) -> bool {
+        self.close_other_fds
+    }
+
+    /// Returns `true` if the child process needs any of the Linux-specific setup between `fork`
+    /// and `exec`.
+    #[cfg(not(target_os = "linux"))]
+    #[allow(dead_code)]
+    pub fn needs_linux_setup(&self) -> bool {
+        false
+    }
+
+    /// Returns `true` if the child process needs any of the Linux-specific setup between `fork`
+    /// and `exec`.
+    #[cfg(target_os = "linux")]
+    pub fn needs_linux_setup(&self) -> bool {
+        !self.rlimits.is_empty()
+            || self.cgroup.is_some()
+            || self.unshare != 0
+            || self.close_other_fds
+    }
+
     pub fn saw_nul(&self) -> bool {
         self.saw_nul
     }
@@ -8,7 +8,7 @@
 use core::ffi::NonZero_c_int;
 
 #[cfg(target_os = "linux")]
-use crate::os::linux::process::PidFd;
+use crate::os::linux::process::{PidFd, Resource};
 
 #[cfg(target_os = "linux")]
 use crate::sys::weak::raw_syscall;
@@ -289,6 +289,9 @@ unsafe fn do_exec(
             cvt_r(|| libc::dup2(fd, libc::STDERR_FILENO))?;
         }
 
+        #[cfg(target_os = "linux")]
+        self.setup_linux_child()?;
+
         #[cfg(not(target_os = "l4re"))]
         {
             if let Some(_g) = self.get_groups() {
@@ -378,6 +381,56 @@ fn drop(&mut self) {
         Err(io::Error::last_os_error())
     }
 
+    // Applies the options of `std::os::linux::process::CommandExt` in the
+    // child process. Like the rest of `do_exec`, this must not allocate.
+    //
+    // The options are applied in an order in which they don't get in the way of
+    // each other: file descriptors are marked before the limit on their number
+    // can change, and the child process is moved into its cgroup and gets its
+    // limits raised while it still has the privileges of the parent, which it
+    // may lose by unsharing a user namespace.
+    #[cfg(target_os = "linux")]
+    unsafe fn setup_linux_child(&self) -> io::Result<()> {
+        use crate::sys::cvt_r;
+
+        if self.get_close_other_fds() {
+            cloexec_other_fds()?;
+        }
+
+        for (resource, limit) in self.get_rlimits() {
+            let resource = match resource {
+                Resource::AddressSpace => libc::RLIMIT_AS,
+                Resource::Core => libc::RLIMIT_CORE,
+                Resource::Cpu => libc::RLIMIT_CPU,
+                Resource::Data => libc::RLIMIT_DATA,
+                Resource::FileSize => libc::RLIMIT_FSIZE,
+                Resource::MemLock => libc::RLIMIT_MEMLOCK,
+                Resource::MsgQueue => libc::RLIMIT_MSGQUEUE,
+                Resource::Nice => libc::RLIMIT_NICE,
+                Resource::NoFile => libc::RLIMIT_NOFILE,
+                Resource::NProc => libc::RLIMIT_NPROC,
+                Resource::RtPrio => libc::RLIMIT_RTPRIO,
+                Resource::RtTime => libc::RLIMIT_RTTIME,
+                Resource::SigPending => libc::RLIMIT_SIGPENDING,
+                Resource::Stack => libc::RLIMIT_STACK,
+            };
+            cvt(libc::setrlimit(resource, limit))?;
+        }
+
+        if let Some(procs) = self.get_cgroup() {
+            let fd = cvt_r(|| libc::open(procs.as_ptr(), libc::O_WRONLY | libc::O_CLOEXEC))?;
+            // Writing 0 to `cgroup.procs` moves the process that writes it.
+            let res = cvt(libc::write(fd, b"0".as_ptr().cast(), 1));
+            libc::close(fd);
+            res?;
+        }
+
+        if self.get_unshare() != 0 {
+            cvt(libc::unshare(self.get_unshare()))?;
+        }
+        Ok(())
+    }
+
     #[cfg(not(any(
         target_os = "macos",
         target_os = "freebsd",
@@ -414,6 +467,7 @@ fn posix_spawn(
             || !self.get_closures().is_empty()
             || self.get_groups().is_some()
             || self.get_create_pidfd()
+            || self.needs_linux_setup()
         {
             return Ok(None);
         }
@@ -562,6 +616,91 @@ fn drop(&mut self) {
     }
 }
 
+/// Sets the close-on-exec flag on every file descriptor but the standard ones, so
+/// that the child process doesn't inherit them. The pipe that reports errors from
+/// `exec` to the parent already has the flag, so it stays open until `exec`.
+#[cfg(target_os = "linux")]
+unsafe fn cloexec_other_fds() -> io::Result<()> {
+    use crate::sys::cvt_r;
+
+    // `close_range` can set the flag on all of them at once since Linux 5.11.
+    const CLOSE_RANGE_CLOEXEC: libc::c_uint = 1 << 2;
+    let ret = libc::syscall(
+        libc::SYS_close_range,
+        3 as libc::c_uint,
+        libc::c_uint::MAX,
+        CLOSE_RANGE_CLOEXEC,
+    );
+    if ret == 0 {
+        return Ok(());
+    }
+    let err = io::Error::last_os_error();
+    if !matches!(err.raw_os_error(), Some(libc::ENOSYS | libc::EINVAL)) {
+        return Err(err);
+    }
+
+    // Otherwise, set it on every open file descriptor, as listed in `/proc/self/fd`. The entries
+    // of the directory are read with `getdents64` into a buffer on the stack, since `readdir`
+    // may allocate. Walking the directory rather than every possible file descriptor matters
+    // because the limit on their number can be very large, or even unlimited.
+    let dir = cvt_r(|| {
+        libc::open(
+            b"/proc/self/fd\0".as_ptr().cast(),
+            libc::O_RDONLY | libc::O_DIRECTORY | libc::O_CLOEXEC,
+        )
+    })?;
+    let res = cloexec_listed_fds(dir);
+    libc::close(dir);
+    res
+}
+
+#[cfg(target_os = "linux")]
+unsafe fn cloexec_listed_fds(dir: c_int) -> io::Result<()> {
+    // The offset of `d_reclen` and `d_name` in a `struct linux_dirent64`.
+    const RECLEN_OFFSET: usize = 16;
+    const NAME_OFFSET: usize = 19;
+
+    let mut buf = [0u8; 1024];
+    loop {
+        let len = cvt(libc::syscall(libc::SYS_getdents64, dir, buf.as_mut_ptr(), buf.len()))?;
+        if len == 0 {
+            return Ok(());
+        }
+
+        let mut entries = &buf[..len as usize];
+        while entries.len() > NAME_OFFSET {
+            let reclen = u16::from_ne_bytes([entries[RECLEN_OFFSET], entries[RECLEN_OFFSET + 1]]);
+            let (entry, rest) = entries.split_at(usize::from(reclen).clamp(1, entries.len()));
+            entries = rest;
+
+            // Parses the name of the entry, which ends with a nul byte. Entries which aren't
+            // file descriptors, such as `.` and `..`, are skipped.
+            let mut fd: c_int = 0;
+            let mut is_fd = false;
+            for &b in entry[NAME_OFFSET..].iter().take_while(|&&b| b != 0) {
+                match (b as char).to_digit(10) {
+                    Some(d) => {
+                        fd = fd.saturating_mul(10).saturating_add(d as c_int);
+                        is_fd = true;
+                    }
+                    None => {
+                        is_fd = false;
+                        break;
+                    }
+                }
+            }
+            if !is_fd || fd < 3 || fd == dir {
+                continue;
+            }
+
+            let flags = libc::fcntl(fd, libc::F_GETFD);
+            if flags >= 0 && flags & libc::FD_CLOEXEC == 0 {
+                cvt(libc::fcntl(fd, libc::F_SETFD, flags | libc::FD_CLOEXEC))?;
+            }
+        }
+    }
+}
+
 ////////////////////////////////////////////////////////////////////////////////
 // Processes
 ////////////////////////////////////////////////////////////////////////////////
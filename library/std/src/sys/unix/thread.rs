@@ -4,6 +4,12 @@
 use crate::mem;
 use crate::num::NonZeroUsize;
 use crate::ptr;
+#[cfg(target_os = "linux")]
+use crate::os::linux::thread::SchedPolicy;
+#[cfg(target_os = "linux")]
+use crate::sync::mpsc;
+#[cfg(target_os = "linux")]
+use crate::sys::{cvt, cvt_nz};
 use crate::sys::{os, stack_overflow};
 use crate::time::Duration;
 
@@ -41,6 +47,89 @@ pub struct Thread {
     id: libc::pthread_t,
 }
 
+/// Options of `thread::Builder` that are specific to Linux, set through
+/// `std::os::linux::thread::BuilderExt`.
+#[cfg(target_os = "linux")]
+#[derive(Debug, Default)]
+pub struct Options {
+    cpu_affinity: Option<Vec<usize>>,
+    sched_policy: Option<libc::c_int>,
+    sched_priority: Option<libc::c_int>,
+    nice: Option<libc::c_int>,
+}
+
+#[cfg(target_os = "linux")]
+impl Options {
+    pub fn cpu_affinity(&mut self, cpus: &[usize]) {
+        self.cpu_affinity = Some(cpus.to_vec());
+    }
+
+    pub fn sched_policy(&mut self, policy: SchedPolicy) {
+        self.sched_policy = Some(match policy {
+            SchedPolicy::Other => libc::SCHED_OTHER,
+            SchedPolicy::Fifo => libc::SCHED_FIFO,
+            SchedPolicy::RoundRobin => libc::SCHED_RR,
+            SchedPolicy::Batch => libc::SCHED_BATCH,
+            SchedPolicy::Idle => libc::SCHED_IDLE,
+        });
+    }
+
+    pub fn sched_priority(&mut self, priority: libc::c_int) {
+        self.sched_priority = Some(priority);
+    }
+
+    pub fn nice(&mut self, nice: libc::c_int) {
+        self.nice = Some(nice);
+    }
+
+    fn is_empty(&self) -> bool {
+        self.cpu_affinity.is_none()
+            && self.sched_policy.is_none()
+            && self.sched_priority.is_none()
+            && self.nice.is_none()
+    }
+
+    /// Applies the options to the current thread.
+    fn apply(&self) -> io::Result<()> {
+        if let Some(cpus) = &self.cpu_affinity {
+            let mut set: libc::cpu_set_t = unsafe { mem::zeroed() };
+            for &cpu in cpus {
+                if cpu >= libc::CPU_SETSIZE as usize {
+                    return Err(io::const_io_error!(
+                        io::ErrorKind::InvalidInput,
+                        "CPU index is out of range for an affinity mask",
+                    ));
+                }
+                unsafe { libc::CPU_SET(cpu, &mut set) };
+            }
+            // A pid of 0 means the calling thread.
+            cvt(unsafe { libc::sched_setaffinity(0, mem::size_of_val(&set), &set) })?;
+        }
+
+        if self.sched_policy.is_some() || self.sched_priority.is_some() {
+            let thread = unsafe { libc::pthread_self() };
+            let mut policy = 0;
+            let mut param: libc::sched_param = unsafe { mem::zeroed() };
+            cvt_nz(unsafe { libc::pthread_getschedparam(thread, &mut policy, &mut param) })?;
+            if let Some(sched_policy) = self.sched_policy {
+                policy = sched_policy;
+            }
+            if let Some(sched_priority) = self.sched_priority {
+                param.sched_priority = sched_priority;
+            }
+            cvt_nz(unsafe { libc::pthread_setschedparam(thread, policy, &param) })?;
+        }
+
+        if let Some(nice) = self.nice {
+            // On Linux, the niceness is an attribute of every thread rather than of the process,
+            // so setting it for the thread ID only affects the calling thread.
+            let tid = unsafe { libc::syscall(libc::SYS_gettid) };
+            cvt(unsafe { libc::setpriority(libc::PRIO_PROCESS as _, tid as libc::id_t, nice) })?;
+        }
+        Ok(())
+    }
+}
+
 // Some platforms may have pthread_t as a pointer in which case we still want
 // a thread to be Send/Sync
 unsafe impl Send for Thread {}
@@ -111,6 +200,38 @@ extern "C" fn thread_start(main: *mut libc::c_void) -> *mut libc::c_void {
         }
     }
 
+    /// Like `new`, but the new thread first applies `options` to itself, and only runs `p` if
+    /// that succeeds. Otherwise, the error is returned once the new thread has exited.
+    #[cfg(target_os = "linux")]
+    pub unsafe fn new_with_options(
+        stack: usize,
+        options: Options,
+        p: Box<dyn FnOnce()>,
+    ) -> io::Result<Thread> {
+        if options.is_empty() {
+            return Thread::new(stack, p);
+        }
+
+        let (tx, rx) = mpsc::sync_channel(1);
+        let main = move || {
+            let res = options.apply();
+            let applied = res.is_ok();
+            // The receiver is only dropped once it has received the result.
+            tx.send(res).unwrap();
+            if applied {
+                p();
+            }
+        };
+        let thread = Thread::new(stack, Box::new(main))?;
+        match rx.recv().unwrap() {
+            Ok(()) => Ok(thread),
+            Err(err) => {
+                thread.join();
+                Err(err)
+            }
+        }
+    }
+
     pub fn yield_now() {
         let ret = unsafe { libc::sched_yield() };
         debug_assert_eq!(ret, 0);
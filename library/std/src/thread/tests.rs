@@ -68,6 +68,38 @@ fn test_named_thread_truncation() {
     result.unwrap().join().unwrap();
 }
 
+#[cfg(target_os = "linux")]
+#[test]
+fn test_linux_options() {
+    use crate::io;
+    use crate::os::linux::thread::{BuilderExt, SchedPolicy};
+
+    // `SCHED_OTHER` with a priority of 0 doesn't need any privileges.
+    let handle =
+        Builder::new().sched_policy(SchedPolicy::Other).sched_priority(0).spawn(|| 42).unwrap();
+    assert_eq!(handle.join().unwrap(), 42);
+
+    // Raising the nice value doesn't need any privileges either, and only affects the new thread.
+    let nice_of_current_thread = || {
+        let tid = unsafe { libc::syscall(libc::SYS_gettid) };
+        unsafe { libc::getpriority(libc::PRIO_PROCESS as _, tid as libc::id_t) }
+    };
+    let nice = nice_of_current_thread();
+    let handle = Builder::new().nice(19).spawn(nice_of_current_thread).unwrap();
+    assert_eq!(handle.join().unwrap(), 19);
+    assert_eq!(nice_of_current_thread(), nice);
+
+    // If the options can't be applied, the closure isn't run.
+    let ran = Arc::new(AtomicBool::new(false));
+    let ran2 = ran.clone();
+    let err = Builder::new()
+        .cpu_affinity(&[usize::MAX])
+        .spawn(move || ran2.store(true, Ordering::SeqCst))
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    assert!(!ran.load(Ordering::SeqCst));
+}
+
 #[test]
 #[should_panic]
 fn test_invalid_named_thread() {
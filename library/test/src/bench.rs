@@ -230,7 +230,7 @@ pub fn benchmark<F>(
     };
 
     let stdout = data.lock().unwrap().to_vec();
-    let message = CompletedTest::new(id, desc, test_result, None, stdout);
+    let message = CompletedTest::new(id, desc, test_result, None, stdout, None);
     monitor_ch.send(message).unwrap();
 }
 
@@ -210,16 +210,28 @@ fn handle_test_result(st: &mut ConsoleTestState, completed_test: CompletedTest)
         TestResult::TrFailedMsg(msg) => {
             st.failed += 1;
             let mut stdout = stdout;
-            stdout.extend_from_slice(format!("note: {msg}").as_bytes());
+            write_note(&mut stdout, &msg);
             st.failures.push((test, stdout));
         }
         TestResult::TrTimedFail => {
             st.failed += 1;
+            let mut stdout = stdout;
+            if let Some(msg) = completed_test.message {
+                write_note(&mut stdout, &msg);
+            }
             st.time_failures.push((test, stdout));
         }
     }
 }
 
+// Appends a note explaining a failure to the output of a test, on a line of its own.
+fn write_note(stdout: &mut Vec<u8>, msg: &str) {
+    if !matches!(stdout.last(), None | Some(b'\n')) {
+        stdout.push(b'\n');
+    }
+    stdout.extend_from_slice(format!("note: {msg}\n").as_bytes());
+}
+
 // Handler for events that occur during test execution.
 // It is provided as a callback to the `run_tests` function.
 fn on_test_event(
@@ -242,9 +254,10 @@ fn on_test_event(
             let result = &completed_test.result;
             let exec_time = &completed_test.exec_time;
             let stdout = &completed_test.stdout;
+            let message = completed_test.message.as_deref();
 
             st.write_log_result(test, result, exec_time.as_ref())?;
-            out.write_result(test, result, exec_time.as_ref(), &*stdout, st)?;
+            out.write_result(test, result, exec_time.as_ref(), &*stdout, message, st)?;
             handle_test_result(st, completed_test);
         }
     }
@@ -12,6 +12,8 @@ pub struct CompletedTest {
     pub result: TestResult,
     pub exec_time: Option<TestExecTime>,
     pub stdout: Vec<u8>,
+    /// Why the test failed, if known: the payload of its panic or the limit it exceeded.
+    pub message: Option<String>,
 }
 
 impl CompletedTest {
@@ -21,8 +23,9 @@ pub fn new(
         result: TestResult,
         exec_time: Option<TestExecTime>,
         stdout: Vec<u8>,
+        message: Option<String>,
     ) -> Self {
-        Self { id, desc, result, exec_time, stdout }
+        Self { id, desc, result, exec_time, stdout, message }
     }
 }
 
@@ -85,6 +85,7 @@ fn write_result(
         result: &TestResult,
         exec_time: Option<&time::TestExecTime>,
         stdout: &[u8],
+        message: Option<&str>,
         state: &ConsoleTestState,
     ) -> io::Result<()> {
         let display_stdout = state.options.display_output || *result != TestResult::TrOk;
@@ -98,9 +99,14 @@ fn write_result(
                 self.write_event("test", desc.name.as_slice(), "ok", exec_time, stdout, None)
             }
 
-            TestResult::TrFailed => {
-                self.write_event("test", desc.name.as_slice(), "failed", exec_time, stdout, None)
-            }
+            TestResult::TrFailed => self.write_event(
+                "test",
+                desc.name.as_slice(),
+                "failed",
+                exec_time,
+                stdout,
+                message.map(|msg| format!(r#""message": "{}""#, EscapedString(msg))).as_deref(),
+            ),
 
             TestResult::TrTimedFail => self.write_event(
                 "test",
@@ -108,7 +114,13 @@ fn write_result(
                 "failed",
                 exec_time,
                 stdout,
-                Some(r#""reason": "time limit exceeded""#),
+                Some(&*match message {
+                    Some(msg) => format!(
+                        r#""reason": "time limit exceeded", "message": "{}""#,
+                        EscapedString(msg)
+                    ),
+                    None => r#""reason": "time limit exceeded""#.to_owned(),
+                }),
             ),
 
             TestResult::TrFailedMsg(ref m) => self.write_event(
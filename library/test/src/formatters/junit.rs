@@ -11,7 +11,7 @@
 
 pub struct JunitFormatter<T> {
     out: OutputLocation<T>,
-    results: Vec<(TestDesc, TestResult, Duration)>,
+    results: Vec<(TestDesc, TestResult, Duration, Vec<u8>, Option<String>)>,
 }
 
 impl<T: Write> JunitFormatter<T> {
@@ -24,6 +24,42 @@ fn write_message(&mut self, s: &str) -> io::Result<()> {
 
         self.out.write_all(s.as_ref())
     }
+
+    fn write_testcase_start(
+        &mut self,
+        class_name: &str,
+        test_name: &str,
+        duration: Duration,
+    ) -> io::Result<()> {
+        self.write_message(&*format!(
+            "<testcase classname=\"{}\" \
+             name=\"{}\" time=\"{}\">",
+            EscapedString(class_name),
+            EscapedString(test_name),
+            duration.as_secs_f64()
+        ))
+    }
+
+    fn write_failure(&mut self, ty: &str, message: Option<&str>) -> io::Result<()> {
+        match message {
+            Some(message) => self.write_message(&*format!(
+                "<failure type=\"{}\" message=\"{}\"/>",
+                ty,
+                EscapedString(message)
+            )),
+            None => self.write_message(&*format!("<failure type=\"{ty}\"/>")),
+        }
+    }
+
+    fn write_system_out(&mut self, stdout: &[u8]) -> io::Result<()> {
+        if stdout.is_empty() {
+            return Ok(());
+        }
+        self.write_message(&*format!(
+            "<system-out>{}</system-out>",
+            EscapedString(String::from_utf8_lossy(stdout))
+        ))
+    }
 }
 
 impl<T: Write> OutputFormatter for JunitFormatter<T> {
@@ -51,65 +87,74 @@ fn write_result(
         desc: &TestDesc,
         result: &TestResult,
         exec_time: Option<&time::TestExecTime>,
-        _stdout: &[u8],
-        _state: &ConsoleTestState,
+        stdout: &[u8],
+        message: Option<&str>,
+        state: &ConsoleTestState,
     ) -> io::Result<()> {
         // Because the testsuite node holds some of the information as attributes, we can't write it
         // until all of the tests have finished. Instead of writing every result as they come in, we add
         // them to a Vec and write them all at once when run is complete.
         let duration = exec_time.map(|t| t.0).unwrap_or_default();
-        self.results.push((desc.clone(), result.clone(), duration));
+        // Like the json formatter, only keep the captured output of successful tests if asked to.
+        let display_stdout = state.options.display_output || *result != TestResult::TrOk;
+        let stdout = if display_stdout { stdout.to_vec() } else { Vec::new() };
+        let message = message.map(str::to_owned);
+        self.results.push((desc.clone(), result.clone(), duration, stdout, message));
         Ok(())
     }
     fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
         self.write_message("<testsuites>")?;
 
+        let suite_time = state.exec_time.as_ref().map(|t| t.0).unwrap_or_default();
         self.write_message(&*format!(
             "<testsuite name=\"test\" package=\"test\" id=\"0\" \
              errors=\"0\" \
              failures=\"{}\" \
              tests=\"{}\" \
              skipped=\"{}\" \
+             time=\"{}\" \
              >",
-            state.failed, state.total, state.ignored
+            state.failed,
+            state.total,
+            state.ignored,
+            suite_time.as_secs_f64()
         ))?;
-        for (desc, result, duration) in std::mem::replace(&mut self.results, Vec::new()) {
+        for (desc, result, duration, stdout, message) in
+            std::mem::replace(&mut self.results, Vec::new())
+        {
             let (class_name, test_name) = parse_class_name(&desc);
             match result {
-                TestResult::TrIgnored => { /* no-op */ }
+                TestResult::TrIgnored => {
+                    self.write_testcase_start(&class_name, &test_name, duration)?;
+                    match desc.ignore_message {
+                        Some(msg) => self.write_message(&*format!(
+                            "<skipped message=\"{}\"/>",
+                            EscapedString(msg)
+                        ))?,
+                        None => self.write_message("<skipped/>")?,
+                    }
+                    self.write_message("</testcase>")?;
+                }
+
                 TestResult::TrFailed => {
-                    self.write_message(&*format!(
-                        "<testcase classname=\"{}\" \
-                         name=\"{}\" time=\"{}\">",
-                        class_name,
-                        test_name,
-                        duration.as_secs_f64()
-                    ))?;
-                    self.write_message("<failure type=\"assert\"/>")?;
+                    self.write_testcase_start(&class_name, &test_name, duration)?;
+                    self.write_failure("assert", message.as_deref())?;
+                    self.write_system_out(&stdout)?;
                     self.write_message("</testcase>")?;
                 }
 
                 TestResult::TrFailedMsg(ref m) => {
-                    self.write_message(&*format!(
-                        "<testcase classname=\"{}\" \
-                         name=\"{}\" time=\"{}\">",
-                        class_name,
-                        test_name,
-                        duration.as_secs_f64()
-                    ))?;
-                    self.write_message(&*format!("<failure message=\"{m}\" type=\"assert\"/>"))?;
+                    self.write_testcase_start(&class_name, &test_name, duration)?;
+                    self.write_failure("assert", Some(m))?;
+                    self.write_system_out(&stdout)?;
                     self.write_message("</testcase>")?;
                 }
 
                 TestResult::TrTimedFail => {
-                    self.write_message(&*format!(
-                        "<testcase classname=\"{}\" \
-                         name=\"{}\" time=\"{}\">",
-                        class_name,
-                        test_name,
-                        duration.as_secs_f64()
-                    ))?;
-                    self.write_message("<failure type=\"timeout\"/>")?;
+                    self.write_testcase_start(&class_name, &test_name, duration)?;
+                    let message = message.as_deref().unwrap_or("time limit exceeded");
+                    self.write_failure("timeout", Some(message))?;
+                    self.write_system_out(&stdout)?;
                     self.write_message("</testcase>")?;
                 }
 
@@ -117,19 +162,27 @@ fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
                     self.write_message(&*format!(
                         "<testcase classname=\"benchmark::{}\" \
                          name=\"{}\" time=\"{}\" />",
-                        class_name, test_name, b.ns_iter_summ.sum
+                        EscapedString(&class_name),
+                        EscapedString(&test_name),
+                        b.ns_iter_summ.sum
                     ))?;
                 }
 
-                TestResult::TrOk => {
+                TestResult::TrOk if stdout.is_empty() => {
                     self.write_message(&*format!(
                         "<testcase classname=\"{}\" \
                          name=\"{}\" time=\"{}\"/>",
-                        class_name,
-                        test_name,
+                        EscapedString(&class_name),
+                        EscapedString(&test_name),
                         duration.as_secs_f64()
                     ))?;
                 }
+
+                TestResult::TrOk => {
+                    self.write_testcase_start(&class_name, &test_name, duration)?;
+                    self.write_system_out(&stdout)?;
+                    self.write_message("</testcase>")?;
+                }
             }
         }
         self.write_message("<system-out/>")?;
@@ -178,3 +231,45 @@ fn parse_class_name_doc(desc: &TestDesc) -> (String, String) {
 fn parse_class_name_integration(desc: &TestDesc) -> (String, String) {
     (String::from("integration"), String::from(desc.name.as_slice()))
 }
+
+/// A formatting utility used to print strings as XML attribute values or character data.
+///
+/// Newlines are written as character references, so that every report stays on a single line,
+/// and characters which are not allowed in XML 1.0 documents (such as the escape character of
+/// terminal color codes) are replaced by U+FFFD.
+struct EscapedString<S: AsRef<str>>(S);
+
+impl<S: AsRef<str>> std::fmt::Display for EscapedString<S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = self.0.as_ref();
+        let mut start = 0;
+
+        for (i, c) in s.char_indices() {
+            let escaped = match c {
+                '&' => "&amp;",
+                '<' => "&lt;",
+                '>' => "&gt;",
+                '"' => "&quot;",
+                '\t' => "&#x9;",
+                '\n' => "&#xA;",
+                '\r' => "&#xD;",
+                '\u{0}'..='\u{1f}' | '\u{fffe}' | '\u{ffff}' => "\u{fffd}",
+                _ => continue,
+            };
+
+            if start < i {
+                f.write_str(&s[start..i])?;
+            }
+
+            f.write_str(escaped)?;
+
+            start = i + c.len_utf8();
+        }
+
+        if start != s.len() {
+            f.write_str(&s[start..])?;
+        }
+
+        Ok(())
+    }
+}
@@ -209,6 +209,7 @@ fn write_result(
         result: &TestResult,
         exec_time: Option<&time::TestExecTime>,
         _: &[u8],
+        _: Option<&str>,
         _: &ConsoleTestState,
     ) -> io::Result<()> {
         if self.is_multithreaded {
@@ -196,6 +196,7 @@ fn write_result(
         result: &TestResult,
         _: Option<&time::TestExecTime>,
         _: &[u8],
+        _: Option<&str>,
         _: &ConsoleTestState,
     ) -> io::Result<()> {
         match *result {
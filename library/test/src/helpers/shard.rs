@@ -0,0 +1,22 @@
+//! Deterministic assignment of tests to shards, used to split a test suite
+//! across several machines.
+
+use crate::types::TestName;
+
+/// Returns `true` if the test called `name` belongs to the shard `index` out of `count`.
+///
+/// The assignment only depends on the name of the test, so every machine agrees on it
+/// regardless of the other tests it was given, of the order they run in (`--shuffle-seed`
+/// is applied to each shard independently), or of the version of the standard library.
+pub fn is_in_shard(name: &TestName, index: usize, count: usize) -> bool {
+    stable_hash(name.as_slice()) % count as u64 == index as u64
+}
+
+// 64-bit FNV-1a. `DefaultHasher` is not used because its algorithm is unspecified and may
+// change between releases, which would move tests between shards.
+fn stable_hash(s: &str) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0100_0000_01b3;
+
+    s.bytes().fold(OFFSET_BASIS, |hash, byte| (hash ^ byte as u64).wrapping_mul(PRIME))
+}
@@ -84,6 +84,7 @@ pub mod test {
 use event::{CompletedTest, TestEvent};
 use helpers::concurrency::get_concurrency;
 use helpers::exit_code::get_exit_code;
+use helpers::shard::is_in_shard;
 use helpers::shuffle::{get_shuffle_seed, shuffle_tests};
 use options::RunStrategy;
 use test_result::*;
@@ -500,6 +501,11 @@ pub fn filter_tests(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> Vec<TestDescA
         filtered.retain(|test| test.desc.should_panic == ShouldPanic::No);
     }
 
+    // Only keep the tests of the requested shard
+    if let Some((index, count)) = opts.shard {
+        filtered.retain(|test| is_in_shard(&test.desc.name, index, count));
+    }
+
     // maybe unignore tests
     match opts.run_ignored {
         RunIgnored::Yes => {
@@ -550,7 +556,7 @@ pub fn run_test(
         && !cfg!(target_os = "emscripten");
 
     if force_ignore || desc.ignore || ignore_because_no_process_support {
-        let message = CompletedTest::new(id, desc, TrIgnored, None, Vec::new());
+        let message = CompletedTest::new(id, desc, TrIgnored, None, Vec::new(), None);
         monitor_ch.send(message).unwrap();
         return None;
     }
@@ -558,7 +564,9 @@ pub fn run_test(
     struct TestRunOpts {
         pub strategy: RunStrategy,
         pub nocapture: bool,
+        pub report_time: bool,
         pub time: Option<time::TestTimeOptions>,
+        pub timeout: Option<Duration>,
     }
 
     fn run_test_inner(
@@ -575,18 +583,20 @@ fn run_test_inner(
                 id,
                 desc,
                 opts.nocapture,
-                opts.time.is_some(),
+                opts.report_time,
                 testfn,
                 monitor_ch,
                 opts.time,
+                opts.timeout,
             ),
             RunStrategy::SpawnPrimary => spawn_test_subprocess(
                 id,
                 desc,
                 opts.nocapture,
-                opts.time.is_some(),
+                opts.report_time,
                 monitor_ch,
                 opts.time,
+                opts.timeout,
             ),
         };
 
@@ -614,8 +624,19 @@ fn run_test_inner(
         }
     }
 
-    let test_run_opts =
-        TestRunOpts { strategy, nocapture: opts.nocapture, time: opts.time_options };
+    // Machine-readable formats always report per-test execution times, and timeouts are
+    // enforced, except on targets where `Instant` can't be used (see `run_tests_console`).
+    let is_instant_supported = !cfg!(target_family = "wasm") && !cfg!(miri);
+    let is_machine_readable = matches!(opts.format, OutputFormat::Json | OutputFormat::Junit);
+    let report_time = opts.time_options.is_some() || (is_instant_supported && is_machine_readable);
+
+    let test_run_opts = TestRunOpts {
+        strategy,
+        nocapture: opts.nocapture,
+        report_time,
+        time: opts.time_options,
+        timeout: time::get_test_timeout(&desc, opts.test_timeout).filter(|_| is_instant_supported),
+    };
 
     match testfn {
         DynBenchFn(benchfn) => {
@@ -668,6 +689,7 @@ fn run_test_in_process(
     testfn: Box<dyn FnOnce() -> Result<(), String> + Send>,
     monitor_ch: Sender<CompletedTest>,
     time_opts: Option<time::TestTimeOptions>,
+    timeout: Option<Duration>,
 ) {
     // Buffer for capturing standard I/O
     let data = Arc::new(Mutex::new(Vec::new()));
@@ -676,21 +698,35 @@ fn run_test_in_process(
         io::set_output_capture(Some(data.clone()));
     }
 
-    let start = report_time.then(Instant::now);
+    let start = (report_time || timeout.is_some()).then(Instant::now);
     let result = fold_err(catch_unwind(AssertUnwindSafe(testfn)));
-    let exec_time = start.map(|start| {
-        let duration = start.elapsed();
-        TestExecTime(duration)
-    });
+    let duration = start.map(|start| start.elapsed());
+    let exec_time = duration.filter(|_| report_time).map(TestExecTime);
 
     io::set_output_capture(None);
 
-    let test_result = match result {
-        Ok(()) => calc_result(&desc, Ok(()), &time_opts, &exec_time),
-        Err(e) => calc_result(&desc, Err(e.as_ref()), &time_opts, &exec_time),
+    let (mut test_result, mut message) = match result {
+        Ok(()) => (calc_result(&desc, Ok(()), &time_opts, &exec_time), None),
+        Err(e) => {
+            let test_result = calc_result(&desc, Err(e.as_ref()), &time_opts, &exec_time);
+            let message = match test_result {
+                TrFailed => panic_message(e.as_ref()).map(str::to_owned),
+                _ => None,
+            };
+            (test_result, message)
+        }
     };
     let stdout = data.lock().unwrap_or_else(|e| e.into_inner()).to_vec();
-    let message = CompletedTest::new(id, desc, test_result, exec_time, stdout);
+
+    // A thread can't be stopped from the outside, so tests running in-process are only
+    // reported as timed out once they are done.
+    if let (Some(duration), Some(timeout)) = (duration, timeout) {
+        if duration > timeout {
+            test_result = TrTimedFail;
+            message = Some(timeout_message(timeout, false));
+        }
+    }
+    let message = CompletedTest::new(id, desc, test_result, exec_time, stdout, message);
     monitor_ch.send(message).unwrap();
 }
 
@@ -714,8 +750,9 @@ fn spawn_test_subprocess(
     report_time: bool,
     monitor_ch: Sender<CompletedTest>,
     time_opts: Option<time::TestTimeOptions>,
+    timeout: Option<Duration>,
 ) {
-    let (result, test_output, exec_time) = (|| {
+    let (result, test_output, exec_time, message) = (|| {
         let args = env::args().collect::<Vec<_>>();
         let current_exe = &args[0];
 
@@ -727,11 +764,15 @@ fn spawn_test_subprocess(
         }
 
         let start = report_time.then(Instant::now);
-        let output = match command.output() {
+        let output = match timeout {
+            Some(timeout) => output_with_timeout(&mut command, nocapture, timeout),
+            None => command.output().map(|output| (output, false)),
+        };
+        let (output, timed_out) = match output {
             Ok(out) => out,
             Err(e) => {
                 let err = format!("Failed to spawn {} as child for test: {:?}", args[0], e);
-                return (TrFailed, err.into_bytes(), None);
+                return (TrFailed, err.into_bytes(), None, None);
             }
         };
         let exec_time = start.map(|start| {
@@ -744,6 +785,11 @@ fn spawn_test_subprocess(
         formatters::write_stderr_delimiter(&mut test_output, &desc.name);
         test_output.extend_from_slice(&stderr);
 
+        if let Some(timeout) = timeout.filter(|_| timed_out) {
+            let message = Some(timeout_message(timeout, true));
+            return (TrTimedFail, test_output, exec_time, message);
+        }
+
         let result = match (|| -> Result<TestResult, String> {
             let exit_code = get_exit_code(status)?;
             Ok(get_result_from_exit_code(&desc, exit_code, &time_opts, &exec_time))
@@ -755,13 +801,70 @@ fn spawn_test_subprocess(
             }
         };
 
-        (result, test_output, exec_time)
+        (result, test_output, exec_time, None)
     })();
 
-    let message = CompletedTest::new(id, desc, result, exec_time, test_output);
+    let message = CompletedTest::new(id, desc, result, exec_time, test_output, message);
     monitor_ch.send(message).unwrap();
 }
 
+/// Like `Command::output`, but kills the child process if it is still running after
+/// `timeout`. The returned flag tells whether that happened, in which case the output is
+/// whatever the child wrote before being killed.
+fn output_with_timeout(
+    command: &mut Command,
+    nocapture: bool,
+    timeout: Duration,
+) -> io::Result<(process::Output, bool)> {
+    // `Command::spawn` inherits all the standard streams, unlike `Command::output`.
+    command.stdin(process::Stdio::null());
+    if !nocapture {
+        command.stdout(process::Stdio::piped());
+        command.stderr(process::Stdio::piped());
+    }
+    let mut child = command.spawn()?;
+
+    // Drain the pipes on other threads, so that the child never blocks on a full pipe.
+    fn read_to_end(pipe: Option<impl io::Read + Send + 'static>) -> thread::JoinHandle<Vec<u8>> {
+        thread::spawn(move || {
+            let mut buf = Vec::new();
+            if let Some(mut pipe) = pipe {
+                // Whatever was read before an error is still worth reporting.
+                let _ = pipe.read_to_end(&mut buf);
+            }
+            buf
+        })
+    }
+    let stdout = read_to_end(child.stdout.take());
+    let stderr = read_to_end(child.stderr.take());
+
+    let deadline = Instant::now() + timeout;
+    let mut poll_interval = Duration::from_millis(1);
+    let (status, timed_out) = loop {
+        if let Some(status) = child.try_wait()? {
+            break (status, false);
+        }
+        let now = Instant::now();
+        if now >= deadline {
+            // Killing fails if the child exited in the meantime, which is fine.
+            let _ = child.kill();
+            break (child.wait()?, true);
+        }
+        thread::sleep(poll_interval.min(deadline - now));
+        poll_interval = (poll_interval * 2).min(Duration::from_millis(100));
+    };
+
+    let stdout = stdout.join().unwrap_or_default();
+    let stderr = stderr.join().unwrap_or_default();
+    Ok((process::Output { status, stdout, stderr }, timed_out))
+}
+
+/// Explains why a test failed after hitting its timeout.
+fn timeout_message(timeout: Duration, killed: bool) -> String {
+    let killed = if killed { " and was killed" } else { "" };
+    format!("test did not finish within {timeout:?}{killed}")
+}
+
 fn run_test_in_spawned_subprocess(
     desc: TestDesc,
     testfn: Box<dyn FnOnce() -> Result<(), String> + Send>,
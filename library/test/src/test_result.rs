@@ -23,6 +23,14 @@ pub enum TestResult {
     TrTimedFail,
 }
 
+/// Returns the message of a panic, if its payload is a string.
+pub fn panic_message<'a>(payload: &'a (dyn Any + 'static + Send)) -> Option<&'a str> {
+    payload
+        .downcast_ref::<String>()
+        .map(|e| &**e)
+        .or_else(|| payload.downcast_ref::<&'static str>().copied())
+}
+
 /// Creates a `TestResult` depending on the raw result of test execution
 /// and associated data.
 pub fn calc_result<'a>(
@@ -34,10 +42,7 @@ pub fn calc_result<'a>(
     let result = match (&desc.should_panic, task_result) {
         (&ShouldPanic::No, Ok(())) | (&ShouldPanic::Yes, Err(_)) => TestResult::TrOk,
         (&ShouldPanic::YesWithMessage(msg), Err(ref err)) => {
-            let maybe_panic_str = err
-                .downcast_ref::<String>()
-                .map(|e| &**e)
-                .or_else(|| err.downcast_ref::<&'static str>().copied());
+            let maybe_panic_str = panic_message(*err);
 
             if maybe_panic_str.map(|e| e.contains(msg)).unwrap_or(false) {
                 TestResult::TrOk
@@ -50,6 +50,8 @@ fn new() -> TestOpts {
             test_threads: None,
             skip: vec![],
             time_options: None,
+            shard: None,
+            test_timeout: None,
             options: Options::new(),
             fail_fast: false,
         }
@@ -67,6 +69,8 @@ fn one_ignored_one_unignored_test() -> Vec<TestDescAndFn> {
                 compile_fail: false,
                 no_run: false,
                 test_type: TestType::Unknown,
+                #[cfg(not(bootstrap))]
+                timeout: None,
             },
             testfn: DynTestFn(Box::new(move || Ok(()))),
         },
@@ -79,6 +83,8 @@ fn one_ignored_one_unignored_test() -> Vec<TestDescAndFn> {
                 compile_fail: false,
                 no_run: false,
                 test_type: TestType::Unknown,
+                #[cfg(not(bootstrap))]
+                timeout: None,
             },
             testfn: DynTestFn(Box::new(move || Ok(()))),
         },
@@ -99,6 +105,8 @@ fn f() -> Result<(), String> {
             compile_fail: false,
             no_run: false,
             test_type: TestType::Unknown,
+            #[cfg(not(bootstrap))]
+            timeout: None,
         },
         testfn: DynTestFn(Box::new(f)),
     };
@@ -122,6 +130,8 @@ fn f() -> Result<(), String> {
             compile_fail: false,
             no_run: false,
             test_type: TestType::Unknown,
+            #[cfg(not(bootstrap))]
+            timeout: None,
         },
         testfn: DynTestFn(Box::new(f)),
     };
@@ -147,6 +157,8 @@ fn f() -> Result<(), String> {
             compile_fail: false,
             no_run: false,
             test_type: TestType::Unknown,
+            #[cfg(not(bootstrap))]
+            timeout: None,
         },
         testfn: DynTestFn(Box::new(f)),
     };
@@ -172,6 +184,8 @@ fn f() -> Result<(), String> {
             compile_fail: false,
             no_run: false,
             test_type: TestType::Unknown,
+            #[cfg(not(bootstrap))]
+            timeout: None,
         },
         testfn: DynTestFn(Box::new(f)),
     };
@@ -202,6 +216,8 @@ fn f() -> Result<(), String> {
             compile_fail: false,
             no_run: false,
             test_type: TestType::Unknown,
+            #[cfg(not(bootstrap))]
+            timeout: None,
         },
         testfn: DynTestFn(Box::new(f)),
     };
@@ -236,6 +252,8 @@ fn f() -> Result<(), String> {
             compile_fail: false,
             no_run: false,
             test_type: TestType::Unknown,
+            #[cfg(not(bootstrap))]
+            timeout: None,
         },
         testfn: DynTestFn(Box::new(f)),
     };
@@ -264,6 +282,8 @@ fn f() -> Result<(), String> {
                 compile_fail: false,
                 no_run: false,
                 test_type: TestType::Unknown,
+                #[cfg(not(bootstrap))]
+                timeout: None,
             },
             testfn: DynTestFn(Box::new(f)),
         };
@@ -292,6 +312,8 @@ fn f() -> Result<(), String> {
             compile_fail: false,
             no_run: false,
             test_type: TestType::Unknown,
+            #[cfg(not(bootstrap))]
+            timeout: None,
         },
         testfn: DynTestFn(Box::new(f)),
     };
@@ -316,6 +338,49 @@ fn test_should_report_time() {
     assert!(exec_time.is_some());
 }
 
+#[test]
+fn test_in_process_timeout() {
+    fn f() -> Result<(), String> {
+        std::thread::sleep(Duration::from_millis(100));
+        Ok(())
+    }
+    let desc = TestDescAndFn {
+        desc: TestDesc {
+            name: StaticTestName("whatever"),
+            ignore: false,
+            ignore_message: None,
+            should_panic: ShouldPanic::No,
+            compile_fail: false,
+            no_run: false,
+            test_type: TestType::Unknown,
+            #[cfg(not(bootstrap))]
+            timeout: None,
+        },
+        testfn: DynTestFn(Box::new(f)),
+    };
+    let test_opts = TestOpts { test_timeout: Some(Duration::from_millis(10)), ..TestOpts::new() };
+    let (tx, rx) = channel();
+    run_test(&test_opts, false, TestId(0), desc, RunStrategy::InProcess, tx);
+    let completed_test = rx.recv().unwrap();
+    // The timeout is reported alongside the result, not in the output of the test.
+    assert_eq!(completed_test.result, TrTimedFail);
+    assert_eq!(completed_test.message.as_deref(), Some("test did not finish within 10ms"));
+    assert!(completed_test.stdout.is_empty());
+}
+
+#[test]
+#[cfg(not(bootstrap))]
+fn test_timeout_attribute_overrides_option() {
+    let mut desc = typed_test_desc(TestType::UnitTest);
+    let default_timeout = Some(Duration::from_secs(60));
+    assert_eq!(time::get_test_timeout(&desc, None), None);
+    assert_eq!(time::get_test_timeout(&desc, default_timeout), default_timeout);
+
+    desc.timeout = Some(5);
+    assert_eq!(time::get_test_timeout(&desc, None), Some(Duration::from_secs(5)));
+    assert_eq!(time::get_test_timeout(&desc, default_timeout), Some(Duration::from_secs(5)));
+}
+
 fn time_test_failure_template(test_type: TestType) -> TestResult {
     fn f() -> Result<(), String> {
         Ok(())
@@ -329,6 +394,8 @@ fn f() -> Result<(), String> {
             compile_fail: false,
             no_run: false,
             test_type,
+            #[cfg(not(bootstrap))]
+            timeout: None,
         },
         testfn: DynTestFn(Box::new(f)),
     };
@@ -368,6 +435,8 @@ fn typed_test_desc(test_type: TestType) -> TestDesc {
         compile_fail: false,
         no_run: false,
         test_type,
+        #[cfg(not(bootstrap))]
+        timeout: None,
     }
 }
 
@@ -430,6 +499,42 @@ fn parse_include_ignored_flag() {
     assert_eq!(opts.run_ignored, RunIgnored::Yes);
 }
 
+#[test]
+fn parse_shard_options() {
+    let args = |extra: &[&str]| {
+        let mut args = vec!["progname".to_string(), "-Zunstable-options".to_string()];
+        args.extend(extra.iter().map(|s| s.to_string()));
+        args
+    };
+
+    let opts = parse_opts(&args(&["--shard-index", "1", "--shard-count", "3"])).unwrap().unwrap();
+    assert_eq!(opts.shard, Some((1, 3)));
+
+    let opts = parse_opts(&args(&[])).unwrap().unwrap();
+    assert_eq!(opts.shard, None);
+
+    assert!(parse_opts(&args(&["--shard-index", "1"])).unwrap().is_err());
+    assert!(parse_opts(&args(&["--shard-count", "3"])).unwrap().is_err());
+    assert!(parse_opts(&args(&["--shard-index", "3", "--shard-count", "3"])).unwrap().is_err());
+    assert!(parse_opts(&args(&["--shard-index", "0", "--shard-count", "0"])).unwrap().is_err());
+    assert!(parse_opts(&args(&["--shard-index", "x", "--shard-count", "3"])).unwrap().is_err());
+}
+
+#[test]
+fn parse_test_timeout_option() {
+    let args = |extra: &[&str]| {
+        let mut args = vec!["progname".to_string(), "-Zunstable-options".to_string()];
+        args.extend(extra.iter().map(|s| s.to_string()));
+        args
+    };
+
+    let opts = parse_opts(&args(&["--test-timeout", "30"])).unwrap().unwrap();
+    assert_eq!(opts.test_timeout, Some(Duration::from_secs(30)));
+
+    assert!(parse_opts(&args(&["--test-timeout", "0"])).unwrap().is_err());
+    assert!(parse_opts(&args(&["--test-timeout", "1s"])).unwrap().is_err());
+}
+
 #[test]
 pub fn filter_for_ignored_option() {
     // When we run ignored tests the test filter should filter out all the
@@ -480,6 +585,8 @@ pub fn exclude_should_panic_option() {
             compile_fail: false,
             no_run: false,
             test_type: TestType::Unknown,
+            #[cfg(not(bootstrap))]
+            timeout: None,
         },
         testfn: DynTestFn(Box::new(move || Ok(()))),
     });
@@ -504,6 +611,8 @@ fn tests() -> Vec<TestDescAndFn> {
                     compile_fail: false,
                     no_run: false,
                     test_type: TestType::Unknown,
+                    #[cfg(not(bootstrap))]
+                    timeout: None,
                 },
                 testfn: DynTestFn(Box::new(move || Ok(()))),
             })
@@ -595,6 +704,8 @@ fn testfn() -> Result<(), String> {
                 compile_fail: false,
                 no_run: false,
                 test_type: TestType::Unknown,
+                #[cfg(not(bootstrap))]
+                timeout: None,
             },
             testfn: DynTestFn(Box::new(testfn)),
         };
@@ -668,6 +779,34 @@ pub fn order_depends_on_more_than_seed() {
     assert!(left.iter().zip(right).any(|(a, b)| a.0 != b.0));
 }
 
+#[test]
+pub fn shards_partition_tests() {
+    let mut all_names: Vec<String> =
+        sample_tests().iter().map(|test| test.desc.name.as_slice().to_string()).collect();
+
+    let shard_count = 3;
+    let mut sharded_names = Vec::new();
+    for shard_index in 0..shard_count {
+        let opts = TestOpts { shard: Some((shard_index, shard_count)), ..TestOpts::new() };
+        let shard = filter_tests(&opts, sample_tests());
+
+        // Sharding only depends on the test names, not on the order of the tests.
+        let mut reversed_tests = sample_tests();
+        reversed_tests.reverse();
+        let mut reversed_shard = filter_tests(&opts, reversed_tests);
+        reversed_shard.reverse();
+        assert!(shard.iter().zip(&reversed_shard).all(|(a, b)| a.desc.name == b.desc.name));
+        assert_eq!(shard.len(), reversed_shard.len());
+
+        sharded_names.extend(shard.iter().map(|test| test.desc.name.as_slice().to_string()));
+    }
+
+    // Every test ends up in exactly one shard.
+    sharded_names.sort();
+    all_names.sort();
+    assert_eq!(sharded_names, all_names);
+}
+
 #[test]
 pub fn test_metricmap_compare() {
     let mut m1 = MetricMap::new();
@@ -724,6 +863,8 @@ fn f(_: &mut Bencher) -> Result<(), String> {
         compile_fail: false,
         no_run: false,
         test_type: TestType::Unknown,
+        #[cfg(not(bootstrap))]
+        timeout: None,
     };
 
     crate::bench::benchmark(TestId(0), desc, tx, true, f);
@@ -747,6 +888,8 @@ fn f(b: &mut Bencher) -> Result<(), String> {
         compile_fail: false,
         no_run: false,
         test_type: TestType::Unknown,
+        #[cfg(not(bootstrap))]
+        timeout: None,
     };
 
     crate::bench::benchmark(TestId(0), desc, tx, true, f);
@@ -763,6 +906,8 @@ fn should_sort_failures_before_printing_them() {
         compile_fail: false,
         no_run: false,
         test_type: TestType::Unknown,
+        #[cfg(not(bootstrap))]
+        timeout: None,
     };
 
     let test_b = TestDesc {
@@ -773,6 +918,8 @@ fn should_sort_failures_before_printing_them() {
         compile_fail: false,
         no_run: false,
         test_type: TestType::Unknown,
+        #[cfg(not(bootstrap))]
+        timeout: None,
     };
 
     let mut out = PrettyFormatter::new(OutputLocation::Raw(Vec::new()), false, 10, false, None);
@@ -819,6 +966,8 @@ fn f(_: &mut Bencher) -> Result<(), String> {
             compile_fail: false,
             no_run: false,
             test_type: TestType::Unknown,
+            #[cfg(not(bootstrap))]
+            timeout: None,
         },
         testfn: DynBenchFn(Box::new(f)),
     };
@@ -833,3 +982,31 @@ fn f(_: &mut Bencher) -> Result<(), String> {
     let result = rx.recv().unwrap().result;
     assert_eq!(result, TrFailed);
 }
+
+#[test]
+fn test_failure_message_is_the_panic_payload() {
+    fn f() -> Result<(), String> {
+        panic!("assertion failed: {}", false);
+    }
+    let desc = TestDescAndFn {
+        desc: TestDesc {
+            name: StaticTestName("whatever"),
+            ignore: false,
+            ignore_message: None,
+            should_panic: ShouldPanic::No,
+            compile_fail: false,
+            no_run: false,
+            test_type: TestType::Unknown,
+            #[cfg(not(bootstrap))]
+            timeout: None,
+        },
+        testfn: DynTestFn(Box::new(f)),
+    };
+    // The message doesn't depend on the output of the test, which isn't even captured here.
+    let test_opts = TestOpts { nocapture: true, ..TestOpts::new() };
+    let (tx, rx) = channel();
+    run_test(&test_opts, false, TestId(0), desc, RunStrategy::InProcess, tx);
+    let completed_test = rx.recv().unwrap();
+    assert_eq!(completed_test.result, TrFailed);
+    assert_eq!(completed_test.message.as_deref(), Some("assertion failed: false"));
+}
@@ -61,6 +61,21 @@ pub fn get_default_test_timeout() -> Instant {
     Instant::now() + Duration::from_secs(TEST_WARN_TIMEOUT_S)
 }
 
+/// Returns the duration after which the test should be stopped and reported as timed out,
+/// if any. A timeout set on the test itself with `#[test_timeout]` takes precedence over
+/// the one passed with `--test-timeout`.
+pub fn get_test_timeout(desc: &TestDesc, default_timeout: Option<Duration>) -> Option<Duration> {
+    #[cfg(not(bootstrap))]
+    let test_timeout = desc.timeout.map(Duration::from_secs);
+    #[cfg(bootstrap)]
+    let test_timeout = {
+        let _ = desc;
+        None
+    };
+
+    test_timeout.or(default_timeout)
+}
+
 /// The measured execution time of a unit test.
 #[derive(Debug, Clone, PartialEq)]
 pub struct TestExecTime(pub Duration);
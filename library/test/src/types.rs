@@ -123,6 +123,9 @@ pub struct TestDesc {
     pub compile_fail: bool,
     pub no_run: bool,
     pub test_type: TestType,
+    /// The timeout set with `#[test_timeout = "seconds"]`, in seconds.
+    #[cfg(not(bootstrap))]
+    pub timeout: Option<u64>,
 }
 
 impl TestDesc {
@@ -22,6 +22,7 @@
 
 use crate::core::new_handler;
 use crate::externalfiles::ExternalHtml;
+use crate::formats::item_type::ItemType;
 use crate::html;
 use crate::html::markdown::IdMap;
 use crate::html::render::StylePath;
@@ -35,6 +36,8 @@
 pub(crate) enum OutputFormat {
     Json,
     Html,
+    Markdown,
+    Man,
 }
 
 impl Default for OutputFormat {
@@ -56,6 +59,8 @@ fn try_from(value: &str) -> Result<Self, Self::Error> {
         match value {
             "json" => Ok(OutputFormat::Json),
             "html" => Ok(OutputFormat::Html),
+            "markdown" => Ok(OutputFormat::Markdown),
+            "man" => Ok(OutputFormat::Man),
             _ => Err(format!("unknown output format `{}`", value)),
         }
     }
@@ -155,6 +160,8 @@ pub(crate) struct Options {
     pub(crate) json_unused_externs: JsonUnusedExterns,
     /// Whether to skip capturing stdout and stderr of tests.
     pub(crate) nocapture: bool,
+    /// Whether to compile the doctests that don't need a crate of their own into a single binary.
+    pub(crate) merge_doctests: bool,
 
     /// Configuration for scraping examples from the current crate. If this option is Some(..) then
     /// the compiler will scrape examples and not generate documentation.
@@ -205,6 +212,7 @@ fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             .field("run_check", &self.run_check)
             .field("no_run", &self.no_run)
             .field("nocapture", &self.nocapture)
+            .field("merge_doctests", &self.merge_doctests)
             .field("scrape_examples_options", &self.scrape_examples_options)
             .field("unstable_features", &self.unstable_features)
             .finish()
@@ -279,6 +287,45 @@ pub(crate) struct RenderOptions {
     pub(crate) call_locations: AllCallLocations,
     /// If `true`, Context::init will not emit shared files.
     pub(crate) no_emit_shared: bool,
+    /// The JSON output of another version of the crate to compare the public API against.
+    pub(crate) api_diff_against: Option<PathBuf>,
+    /// If `true`, the JSON output lists the items that each function body refers to.
+    pub(crate) json_call_graph: bool,
+    /// If `true`, the `--show-coverage` report is also broken down by item kind, and lists the
+    /// undocumented items.
+    pub(crate) coverage_details: bool,
+    /// The minimum percentages of documented items for `--show-coverage`, for an item kind or for
+    /// the whole crate.
+    pub(crate) coverage_thresholds: Vec<CoverageThreshold>,
+}
+
+/// A `--coverage-threshold` option.
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct CoverageThreshold {
+    /// The kind of the items the threshold applies to, or `None` for all items.
+    pub(crate) kind: Option<ItemType>,
+    pub(crate) percentage: f64,
+}
+
+impl FromStr for CoverageThreshold {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (kind, percentage) = match s.split_once('=') {
+            Some((kind, percentage)) => {
+                let kind = ItemType::from_str(kind)
+                    .map_err(|()| format!("unknown item kind `{}` in `{}`", kind, s))?;
+                (Some(kind), percentage)
+            }
+            None => (None, s),
+        };
+        match percentage.parse::<f64>() {
+            Ok(percentage) if (0.0..=100.0).contains(&percentage) => {
+                Ok(CoverageThreshold { kind, percentage })
+            }
+            _ => Err(format!("`{}` is not a percentage between 0 and 100", percentage)),
+        }
+    }
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
@@ -654,9 +701,10 @@ fn println_condition(condition: Condition) {
             Some(s) => match OutputFormat::try_from(s.as_str()) {
                 Ok(out_fmt) => {
                     if !out_fmt.is_json() && show_coverage {
-                        diag.struct_err(
-                            "html output format isn't supported for the --show-coverage option",
-                        )
+                        diag.struct_err(&format!(
+                            "{} output format isn't supported for the --show-coverage option",
+                            s
+                        ))
                         .emit();
                         return Err(1);
                     }
@@ -702,6 +750,7 @@ fn println_condition(condition: Condition) {
         let generate_redirect_map = matches.opt_present("generate-redirect-map");
         let show_type_layout = matches.opt_present("show-type-layout");
         let nocapture = matches.opt_present("nocapture");
+        let merge_doctests = matches.opt_present("merge-doctests");
         let generate_link_to_definition = matches.opt_present("generate-link-to-definition");
         let extern_html_root_takes_precedence =
             matches.opt_present("extern-html-root-takes-precedence");
@@ -714,6 +763,50 @@ fn println_condition(condition: Condition) {
             return Err(1);
         }
 
+        let api_diff_against = matches.opt_str("api-diff-against").map(PathBuf::from);
+        let json_input = input.extension().map_or(false, |e| e == "json");
+        if json_input && api_diff_against.is_none() {
+            diag.struct_err("JSON input files can only be used with the --api-diff-against option")
+                .emit();
+            return Err(1);
+        }
+        if api_diff_against.is_some() && !json_input && (show_coverage || !output_format.is_json())
+        {
+            diag.struct_err(
+                "--api-diff-against option can only be used with JSON output format or JSON input",
+            )
+            .emit();
+            return Err(1);
+        }
+
+        let json_call_graph = matches.opt_present("json-call-graph");
+        if json_call_graph && (show_coverage || !output_format.is_json()) {
+            diag.struct_err("--json-call-graph option can only be used with JSON output format")
+                .emit();
+            return Err(1);
+        }
+
+        let coverage_details = matches.opt_present("coverage-details");
+        let mut coverage_thresholds = Vec::new();
+        for threshold in matches.opt_strs("coverage-threshold") {
+            match threshold.parse() {
+                Ok(threshold) => coverage_thresholds.push(threshold),
+                Err(e) => {
+                    diag.struct_err(&format!("invalid `--coverage-threshold` option: {}", e))
+                        .emit();
+                    return Err(1);
+                }
+            }
+        }
+        if (coverage_details || !coverage_thresholds.is_empty()) && !show_coverage {
+            diag.struct_err(
+                "--coverage-details and --coverage-threshold options can only be used with \
+                 the --show-coverage option",
+            )
+            .emit();
+            return Err(1);
+        }
+
         let scrape_examples_options = ScrapeExamplesOptions::new(matches, &diag)?;
         let with_examples = matches.opt_strs("with-examples");
         let call_locations = crate::scrape_examples::load_call_locations(with_examples, &diag)?;
@@ -755,6 +848,7 @@ fn println_condition(condition: Condition) {
             run_check,
             no_run,
             nocapture,
+            merge_doctests,
             crate_name,
             output_format,
             json_unused_externs,
@@ -788,6 +882,10 @@ fn println_condition(condition: Condition) {
             generate_link_to_definition,
             call_locations,
             no_emit_shared: false,
+            api_diff_against,
+            json_call_graph,
+            coverage_details,
+            coverage_thresholds,
         };
         Ok((options, render_options))
     }
@@ -796,6 +894,11 @@ fn println_condition(condition: Condition) {
     pub(crate) fn markdown_input(&self) -> bool {
         self.input.extension().map_or(false, |e| e == "md" || e == "markdown")
     }
+
+    /// Returns `true` if the file given as `self.input` is rustdoc's own JSON output.
+    pub(crate) fn json_input(&self) -> bool {
+        self.input.extension().map_or(false, |e| e == "json")
+    }
 }
 
 /// Prints deprecation warnings for deprecated options
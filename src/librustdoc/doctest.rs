@@ -1,3 +1,5 @@
+mod merged;
+
 use rustc_ast as ast;
 use rustc_data_structures::fx::{FxHashMap, FxHashSet};
 use rustc_data_structures::sync::Lrc;
@@ -25,7 +27,7 @@
 use std::env;
 use std::io::{self, Write};
 use std::panic;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::{self, Command, Stdio};
 use std::str;
 use std::sync::atomic::{AtomicUsize, Ordering};
@@ -37,6 +39,8 @@
 use crate::lint::init_lints;
 use crate::passes::span_of_attrs;
 
+use self::merged::MergeableDoctest;
+
 /// Options that apply to all doctests in a crate or Markdown file (for `rustdoc foo.md`).
 #[derive(Clone, Default)]
 pub(crate) struct GlobalTestOptions {
@@ -117,7 +121,7 @@ pub(crate) fn run(options: RustdocOptions) -> Result<(), ErrorGuaranteed> {
             compiler.enter(|queries| {
                 let mut global_ctxt = queries.global_ctxt()?.take();
 
-                let collector = global_ctxt.enter(|tcx| {
+                let mut collector = global_ctxt.enter(|tcx| {
                     let crate_attrs = tcx.hir().attrs(CRATE_HIR_ID);
 
                     let opts = scrape_test_config(crate_attrs);
@@ -154,6 +158,8 @@ pub(crate) fn run(options: RustdocOptions) -> Result<(), ErrorGuaranteed> {
                     FatalError.raise();
                 }
 
+                collector.merge_doctests();
+
                 let unused_extern_reports = collector.unused_extern_reports.clone();
                 let compiling_test_count = collector.compiling_test_count.load(Ordering::SeqCst);
                 let ret: Result<_, ErrorGuaranteed> =
@@ -327,25 +333,7 @@ fn run_test(
     let rust_out = add_exe_suffix("rust_out".to_owned(), &target);
     let output_file = outdir.path().join(rust_out);
 
-    let rustc_binary = rustdoc_options
-        .test_builder
-        .as_deref()
-        .unwrap_or_else(|| rustc_interface::util::rustc_path().expect("found rustc"));
-    let mut compiler = Command::new(&rustc_binary);
-    compiler.arg("--crate-type").arg("bin");
-    for cfg in &rustdoc_options.cfgs {
-        compiler.arg("--cfg").arg(&cfg);
-    }
-    if !rustdoc_options.check_cfgs.is_empty() {
-        compiler.arg("-Z").arg("unstable-options");
-        for check_cfg in &rustdoc_options.check_cfgs {
-            compiler.arg("--check-cfg").arg(&check_cfg);
-        }
-    }
-    if let Some(sysroot) = rustdoc_options.maybe_sysroot {
-        compiler.arg("--sysroot").arg(sysroot);
-    }
-    compiler.arg("--edition").arg(&edition.to_string());
+    let mut compiler = rustc_command(&rustdoc_options, edition, target, supports_color);
     compiler.env("UNSTABLE_RUSTDOC_TEST_PATH", path);
     compiler.env("UNSTABLE_RUSTDOC_TEST_LINE", format!("{}", line as isize - line_offset as isize));
     compiler.arg("-o").arg(&output_file);
@@ -358,47 +346,9 @@ fn run_test(
         compiler.arg("-Z").arg("unstable-options");
         compiler.arg("-W").arg("unused_crate_dependencies");
     }
-    for lib_str in &rustdoc_options.lib_strs {
-        compiler.arg("-L").arg(&lib_str);
-    }
-    for extern_str in &rustdoc_options.extern_strs {
-        compiler.arg("--extern").arg(&extern_str);
-    }
-    compiler.arg("-Ccodegen-units=1");
-    for codegen_options_str in &rustdoc_options.codegen_options_strs {
-        compiler.arg("-C").arg(&codegen_options_str);
-    }
-    for unstable_option_str in &rustdoc_options.unstable_opts_strs {
-        compiler.arg("-Z").arg(&unstable_option_str);
-    }
     if no_run && !lang_string.compile_fail && rustdoc_options.persist_doctests.is_none() {
         compiler.arg("--emit=metadata");
     }
-    compiler.arg("--target").arg(match target {
-        TargetTriple::TargetTriple(s) => s,
-        TargetTriple::TargetJson { path_for_rustdoc, .. } => {
-            path_for_rustdoc.to_str().expect("target path must be valid unicode").to_string()
-        }
-    });
-    if let ErrorOutputType::HumanReadable(kind) = rustdoc_options.error_format {
-        let (short, color_config) = kind.unzip();
-
-        if short {
-            compiler.arg("--error-format").arg("short");
-        }
-
-        match color_config {
-            ColorConfig::Never => {
-                compiler.arg("--color").arg("never");
-            }
-            ColorConfig::Always => {
-                compiler.arg("--color").arg("always");
-            }
-            ColorConfig::Auto => {
-                compiler.arg("--color").arg(if supports_color { "always" } else { "never" });
-            }
-        }
-    }
 
     compiler.arg("-");
     compiler.stdin(Stdio::piped());
@@ -465,6 +415,84 @@ fn drop(&mut self) {
     }
 
     // Run the code!
+    let cmd = executable_command(&output_file, runtool, runtool_args, &rustdoc_options);
+    run_executable(cmd, rustdoc_options.nocapture, lang_string.should_panic)
+}
+
+/// Returns a command that compiles a doctest, or a merged doctest binary, read from stdin.
+fn rustc_command(
+    rustdoc_options: &RustdocOptions,
+    edition: Edition,
+    target: TargetTriple,
+    supports_color: bool,
+) -> Command {
+    let rustc_binary = rustdoc_options
+        .test_builder
+        .as_deref()
+        .unwrap_or_else(|| rustc_interface::util::rustc_path().expect("found rustc"));
+    let mut compiler = Command::new(&rustc_binary);
+    compiler.arg("--crate-type").arg("bin");
+    for cfg in &rustdoc_options.cfgs {
+        compiler.arg("--cfg").arg(&cfg);
+    }
+    if !rustdoc_options.check_cfgs.is_empty() {
+        compiler.arg("-Z").arg("unstable-options");
+        for check_cfg in &rustdoc_options.check_cfgs {
+            compiler.arg("--check-cfg").arg(&check_cfg);
+        }
+    }
+    if let Some(ref sysroot) = rustdoc_options.maybe_sysroot {
+        compiler.arg("--sysroot").arg(sysroot);
+    }
+    compiler.arg("--edition").arg(&edition.to_string());
+    for lib_str in &rustdoc_options.lib_strs {
+        compiler.arg("-L").arg(&lib_str);
+    }
+    for extern_str in &rustdoc_options.extern_strs {
+        compiler.arg("--extern").arg(&extern_str);
+    }
+    compiler.arg("-Ccodegen-units=1");
+    for codegen_options_str in &rustdoc_options.codegen_options_strs {
+        compiler.arg("-C").arg(&codegen_options_str);
+    }
+    for unstable_option_str in &rustdoc_options.unstable_opts_strs {
+        compiler.arg("-Z").arg(&unstable_option_str);
+    }
+    compiler.arg("--target").arg(match target {
+        TargetTriple::TargetTriple(s) => s,
+        TargetTriple::TargetJson { path_for_rustdoc, .. } => {
+            path_for_rustdoc.to_str().expect("target path must be valid unicode").to_string()
+        }
+    });
+    if let ErrorOutputType::HumanReadable(kind) = rustdoc_options.error_format {
+        let (short, color_config) = kind.unzip();
+
+        if short {
+            compiler.arg("--error-format").arg("short");
+        }
+
+        match color_config {
+            ColorConfig::Never => {
+                compiler.arg("--color").arg("never");
+            }
+            ColorConfig::Always => {
+                compiler.arg("--color").arg("always");
+            }
+            ColorConfig::Auto => {
+                compiler.arg("--color").arg(if supports_color { "always" } else { "never" });
+            }
+        }
+    }
+    compiler
+}
+
+/// Returns a command that runs a compiled doctest, with the `--runtool` if there is one.
+fn executable_command(
+    output_file: &Path,
+    runtool: Option<String>,
+    runtool_args: Vec<String>,
+    rustdoc_options: &RustdocOptions,
+) -> Command {
     let mut cmd;
 
     if let Some(tool) = runtool {
@@ -474,11 +502,18 @@ fn drop(&mut self) {
     } else {
         cmd = Command::new(output_file);
     }
-    if let Some(run_directory) = rustdoc_options.test_run_directory {
+    if let Some(ref run_directory) = rustdoc_options.test_run_directory {
         cmd.current_dir(run_directory);
     }
+    cmd
+}
 
-    let result = if rustdoc_options.nocapture {
+fn run_executable(
+    mut cmd: Command,
+    nocapture: bool,
+    should_panic: bool,
+) -> Result<(), TestFailure> {
+    let result = if nocapture {
         cmd.status().map(|status| process::Output {
             status,
             stdout: Vec::new(),
@@ -490,9 +525,9 @@ fn drop(&mut self) {
     match result {
         Err(e) => return Err(TestFailure::ExecutionError(e)),
         Ok(out) => {
-            if lang_string.should_panic && out.status.success() {
+            if should_panic && out.status.success() {
                 return Err(TestFailure::UnexpectedRunPass);
-            } else if !lang_string.should_panic && !out.status.success() {
+            } else if !should_panic && !out.status.success() {
                 return Err(TestFailure::ExecutionFailure(out));
             }
         }
@@ -515,7 +550,6 @@ pub(crate) fn make_test(
     let everything_else = everything_else.trim();
     let mut line_offset = 0;
     let mut prog = String::new();
-    let mut supports_color = false;
 
     if opts.attrs.is_empty() {
         // If there aren't any attributes supplied by #![doc(test(attr(...)))], then allow some
@@ -539,6 +573,97 @@ pub(crate) fn make_test(
 
     // Uses librustc_ast to parse the doctest and find if there's a main fn and the extern
     // crate already is included.
+    let Some(ParsedDoctest {
+        has_main: already_has_main,
+        has_extern_crate: already_has_extern_crate,
+        supports_color,
+    }) = parse_doctest(s, crates + everything_else, crate_name, edition)
+    else {
+        // If the parser panicked due to a fatal error, pass the test code through unchanged.
+        // The error will be reported during compilation.
+        return (s.to_owned(), 0, false);
+    };
+
+    // Don't inject `extern crate std` because it's already injected by the
+    // compiler.
+    if !already_has_extern_crate && !opts.no_crate_inject && crate_name != Some("std") {
+        if let Some(crate_name) = crate_name {
+            // Don't inject `extern crate` if the crate is never used.
+            // NOTE: this is terribly inaccurate because it doesn't actually
+            // parse the source, but only has false positives, not false
+            // negatives.
+            if s.contains(crate_name) {
+                prog.push_str(&format!("extern crate r#{crate_name};\n"));
+                line_offset += 1;
+            }
+        }
+    }
+
+    // FIXME: This code cannot yet handle no_std test cases yet
+    if dont_insert_main || already_has_main || prog.contains("![no_std]") {
+        prog.push_str(everything_else);
+    } else {
+        let returns_result = everything_else.trim_end().ends_with("(())");
+        // Give each doctest main function a unique name.
+        // This is for example needed for the tooling around `-C instrument-coverage`.
+        let inner_fn_name = if let Some(test_id) = test_id {
+            format!("_doctest_main_{test_id}")
+        } else {
+            "_inner".into()
+        };
+        let inner_attr = if test_id.is_some() { "#[allow(non_snake_case)] " } else { "" };
+        let (main_pre, main_post) = if returns_result {
+            (
+                format!(
+                    "fn main() {{ {inner_attr}fn {inner_fn_name}() -> Result<(), impl core::fmt::Debug> {{\n",
+                ),
+                format!("\n}} {inner_fn_name}().unwrap() }}"),
+            )
+        } else if test_id.is_some() {
+            (
+                format!("fn main() {{ {inner_attr}fn {inner_fn_name}() {{\n",),
+                format!("\n}} {inner_fn_name}() }}"),
+            )
+        } else {
+            ("fn main() {\n".into(), "\n}".into())
+        };
+        // Note on newlines: We insert a line/newline *before*, and *after*
+        // the doctest and adjust the `line_offset` accordingly.
+        // In the case of `-C instrument-coverage`, this means that the generated
+        // inner `main` function spans from the doctest opening codeblock to the
+        // closing one. For example
+        // /// ``` <- start of the inner main
+        // /// <- code under doctest
+        // /// ``` <- end of the inner main
+        line_offset += 1;
+
+        prog.extend([&main_pre, everything_else, &main_post].iter().cloned());
+    }
+
+    debug!("final doctest:\n{prog}");
+
+    (prog, line_offset, supports_color)
+}
+
+/// What [`parse_doctest`] found out about the items of a doctest.
+struct ParsedDoctest {
+    /// Whether the doctest has its own `fn main`.
+    has_main: bool,
+    /// Whether the doctest already has an `extern crate` item for the crate being tested.
+    has_extern_crate: bool,
+    /// Whether the output stream of the compiler supports colors.
+    supports_color: bool,
+}
+
+/// Parses `source`, the code of the doctest `s` without its crate attributes, to find its `main`
+/// function and `extern crate` items. Returns `None` if the parser panicked due to a fatal error.
+fn parse_doctest(
+    s: &str,
+    source: String,
+    crate_name: Option<&str>,
+    edition: Edition,
+) -> Option<ParsedDoctest> {
+    let mut supports_color = false;
     let result = rustc_driver::catch_fatal_errors(|| {
         rustc_span::create_session_if_not_set_then(edition, |_| {
             use rustc_errors::emitter::{Emitter, EmitterWriter};
@@ -547,7 +672,6 @@ pub(crate) fn make_test(
             use rustc_span::source_map::FilePathMapping;
 
             let filename = FileName::anon_source_code(s);
-            let source = crates + everything_else;
 
             // Any errors in parsing should also appear when the doctest is compiled for real, so just
             // send all the errors that librustc_ast emits directly into a `Sink` instead of stderr.
@@ -651,18 +775,13 @@ pub(crate) fn make_test(
             (found_main, found_extern_crate, found_macro)
         })
     });
-    let Ok((already_has_main, already_has_extern_crate, found_macro)) = result
-    else {
-        // If the parser panicked due to a fatal error, pass the test code through unchanged.
-        // The error will be reported during compilation.
-        return (s.to_owned(), 0, false);
-    };
+    let Ok((has_main, has_extern_crate, found_macro)) = result else { return None };
 
     // If a doctest's `fn main` is being masked by a wrapper macro, the parsing loop above won't
     // see it. In that case, run the old text-based scan to see if they at least have a main
     // function written inside a macro invocation. See
     // https://github.com/rust-lang/rust/issues/56898
-    let already_has_main = if found_macro && !already_has_main {
+    let has_main = if found_macro && !has_main {
         s.lines()
             .map(|line| {
                 let comment = line.find("//");
@@ -670,68 +789,10 @@ pub(crate) fn make_test(
             })
             .any(|code| code.contains("fn main"))
     } else {
-        already_has_main
+        has_main
     };
 
-    // Don't inject `extern crate std` because it's already injected by the
-    // compiler.
-    if !already_has_extern_crate && !opts.no_crate_inject && crate_name != Some("std") {
-        if let Some(crate_name) = crate_name {
-            // Don't inject `extern crate` if the crate is never used.
-            // NOTE: this is terribly inaccurate because it doesn't actually
-            // parse the source, but only has false positives, not false
-            // negatives.
-            if s.contains(crate_name) {
-                prog.push_str(&format!("extern crate r#{crate_name};\n"));
-                line_offset += 1;
-            }
-        }
-    }
-
-    // FIXME: This code cannot yet handle no_std test cases yet
-    if dont_insert_main || already_has_main || prog.contains("![no_std]") {
-        prog.push_str(everything_else);
-    } else {
-        let returns_result = everything_else.trim_end().ends_with("(())");
-        // Give each doctest main function a unique name.
-        // This is for example needed for the tooling around `-C instrument-coverage`.
-        let inner_fn_name = if let Some(test_id) = test_id {
-            format!("_doctest_main_{test_id}")
-        } else {
-            "_inner".into()
-        };
-        let inner_attr = if test_id.is_some() { "#[allow(non_snake_case)] " } else { "" };
-        let (main_pre, main_post) = if returns_result {
-            (
-                format!(
-                    "fn main() {{ {inner_attr}fn {inner_fn_name}() -> Result<(), impl core::fmt::Debug> {{\n",
-                ),
-                format!("\n}} {inner_fn_name}().unwrap() }}"),
-            )
-        } else if test_id.is_some() {
-            (
-                format!("fn main() {{ {inner_attr}fn {inner_fn_name}() {{\n",),
-                format!("\n}} {inner_fn_name}() }}"),
-            )
-        } else {
-            ("fn main() {\n".into(), "\n}".into())
-        };
-        // Note on newlines: We insert a line/newline *before*, and *after*
-        // the doctest and adjust the `line_offset` accordingly.
-        // In the case of `-C instrument-coverage`, this means that the generated
-        // inner `main` function spans from the doctest opening codeblock to the
-        // closing one. For example
-        // /// ``` <- start of the inner main
-        // /// <- code under doctest
-        // /// ``` <- end of the inner main
-        line_offset += 1;
-
-        prog.extend([&main_pre, everything_else, &main_post].iter().cloned());
-    }
-
-    debug!("final doctest:\n{prog}");
-
-    (prog, line_offset, supports_color)
+    Some(ParsedDoctest { has_main, has_extern_crate, supports_color })
 }
 
 fn check_if_attr_is_complete(source: &str, edition: Edition) -> bool {
@@ -931,6 +992,8 @@ pub(crate) struct Collector {
     visited_tests: FxHashMap<(String, usize), usize>,
     unused_extern_reports: Arc<Mutex<Vec<UnusedExterns>>>,
     compiling_test_count: AtomicUsize,
+    /// The doctests that `--merge-doctests` can compile into a single binary.
+    mergeable: Vec<MergeableDoctest>,
 }
 
 impl Collector {
@@ -957,6 +1020,7 @@ pub(crate) fn new(
             visited_tests: FxHashMap::default(),
             unused_extern_reports: Default::default(),
             compiling_test_count: AtomicUsize::new(0),
+            mergeable: Vec::new(),
         }
     }
 
@@ -1059,21 +1123,36 @@ fn add_test(&mut self, test: String, config: LangString, line: usize) {
             )
         };
 
+        let ignore = match config.ignore {
+            Ignore::All => true,
+            Ignore::None => false,
+            Ignore::Some(ref ignores) => ignores.iter().any(|s| target_str.contains(s)),
+        };
+        // The unused externs of a merged binary can't be attributed to a single doctest.
+        let json_unused_externs = self.rustdoc_options.json_unused_externs.is_enabled();
+        if self.rustdoc_options.merge_doctests && !json_unused_externs && !ignore {
+            let index = self.tests.len();
+            let path = path.clone();
+            if let Some(doctest) =
+                MergeableDoctest::new(&test, &config, edition, no_run, path, line, index)
+            {
+                self.mergeable.push(doctest);
+            }
+        }
+
         debug!("creating test {name}: {test}");
         self.tests.push(test::TestDescAndFn {
             desc: test::TestDesc {
                 name: test::DynTestName(name),
-                ignore: match config.ignore {
-                    Ignore::All => true,
-                    Ignore::None => false,
-                    Ignore::Some(ref ignores) => ignores.iter().any(|s| target_str.contains(s)),
-                },
+                ignore,
                 ignore_message: None,
                 // compiler failures are test failures
                 should_panic: test::ShouldPanic::No,
                 compile_fail: config.compile_fail,
                 no_run,
                 test_type: test::TestType::DocTest,
+                #[cfg(not(bootstrap))]
+                timeout: None,
             },
             testfn: test::DynTestFn(Box::new(move || {
                 let report_unused_externs = |uext| {
@@ -1098,54 +1177,7 @@ fn add_test(&mut self, test: String, config: LangString, line: usize) {
                 );
 
                 if let Err(err) = res {
-                    match err {
-                        TestFailure::CompileError => {
-                            eprint!("Couldn't compile the test.");
-                        }
-                        TestFailure::UnexpectedCompilePass => {
-                            eprint!("Test compiled successfully, but it's marked `compile_fail`.");
-                        }
-                        TestFailure::UnexpectedRunPass => {
-                            eprint!("Test executable succeeded, but it's marked `should_panic`.");
-                        }
-                        TestFailure::MissingErrorCodes(codes) => {
-                            eprint!("Some expected error codes were not found: {:?}", codes);
-                        }
-                        TestFailure::ExecutionError(err) => {
-                            eprint!("Couldn't run the test: {err}");
-                            if err.kind() == io::ErrorKind::PermissionDenied {
-                                eprint!(" - maybe your tempdir is mounted with noexec?");
-                            }
-                        }
-                        TestFailure::ExecutionFailure(out) => {
-                            eprintln!("Test executable failed ({reason}).", reason = out.status);
-
-                            // FIXME(#12309): An unfortunate side-effect of capturing the test
-                            // executable's output is that the relative ordering between the test's
-                            // stdout and stderr is lost. However, this is better than the
-                            // alternative: if the test executable inherited the parent's I/O
-                            // handles the output wouldn't be captured at all, even on success.
-                            //
-                            // The ordering could be preserved if the test process' stderr was
-                            // redirected to stdout, but that functionality does not exist in the
-                            // standard library, so it may not be portable enough.
-                            let stdout = str::from_utf8(&out.stdout).unwrap_or_default();
-                            let stderr = str::from_utf8(&out.stderr).unwrap_or_default();
-
-                            if !stdout.is_empty() || !stderr.is_empty() {
-                                eprintln!();
-
-                                if !stdout.is_empty() {
-                                    eprintln!("stdout:\n{stdout}");
-                                }
-
-                                if !stderr.is_empty() {
-                                    eprintln!("stderr:\n{stderr}");
-                                }
-                            }
-                        }
-                    }
-
+                    report_test_failure(err);
                     panic::resume_unwind(Box::new(()));
                 }
                 Ok(())
@@ -1207,6 +1239,57 @@ fn register_header(&mut self, name: &str, level: u32) {
     }
 }
 
+/// Prints why a doctest failed, to the output that libtest captures for the test.
+fn report_test_failure(err: TestFailure) {
+    match err {
+        TestFailure::CompileError => {
+            eprint!("Couldn't compile the test.");
+        }
+        TestFailure::UnexpectedCompilePass => {
+            eprint!("Test compiled successfully, but it's marked `compile_fail`.");
+        }
+        TestFailure::UnexpectedRunPass => {
+            eprint!("Test executable succeeded, but it's marked `should_panic`.");
+        }
+        TestFailure::MissingErrorCodes(codes) => {
+            eprint!("Some expected error codes were not found: {:?}", codes);
+        }
+        TestFailure::ExecutionError(err) => {
+            eprint!("Couldn't run the test: {err}");
+            if err.kind() == io::ErrorKind::PermissionDenied {
+                eprint!(" - maybe your tempdir is mounted with noexec?");
+            }
+        }
+        TestFailure::ExecutionFailure(out) => {
+            eprintln!("Test executable failed ({reason}).", reason = out.status);
+
+            // FIXME(#12309): An unfortunate side-effect of capturing the test
+            // executable's output is that the relative ordering between the test's
+            // stdout and stderr is lost. However, this is better than the
+            // alternative: if the test executable inherited the parent's I/O
+            // handles the output wouldn't be captured at all, even on success.
+            //
+            // The ordering could be preserved if the test process' stderr was
+            // redirected to stdout, but that functionality does not exist in the
+            // standard library, so it may not be portable enough.
+            let stdout = str::from_utf8(&out.stdout).unwrap_or_default();
+            let stderr = str::from_utf8(&out.stderr).unwrap_or_default();
+
+            if !stdout.is_empty() || !stderr.is_empty() {
+                eprintln!();
+
+                if !stdout.is_empty() {
+                    eprintln!("stdout:\n{stdout}");
+                }
+
+                if !stderr.is_empty() {
+                    eprintln!("stderr:\n{stderr}");
+                }
+            }
+        }
+    }
+}
+
 struct HirCollector<'a, 'hir, 'tcx> {
     sess: &'a Session,
     collector: &'a mut Collector,
@@ -0,0 +1,271 @@
+//! Support for `--merge-doctests`, which compiles the doctests that don't need a crate of their
+//! own into a single binary per edition, instead of one binary per doctest.
+//!
+//! Every doctest still runs in a process of its own, so that panics, `should_panic` and calls to
+//! `std::process::exit` behave like they do for separately compiled doctests. The merged binary
+//! picks the doctest to run from the `RUSTDOC_MERGED_DOCTEST` environment variable.
+//!
+//! `UNSTABLE_RUSTDOC_TEST_PATH` and `UNSTABLE_RUSTDOC_TEST_LINE` can only name the file and line
+//! of a whole crate, so every doctest is instead a module in a file of its own, with its code on
+//! the same lines as in the file it is from. The file is then remapped to that file with
+//! `--remap-path-prefix`, so that panics and errors point to the doctest.
+
+use rustc_data_structures::fx::FxHashMap;
+use rustc_span::edition::Edition;
+use tempfile::Builder as TempFileBuilder;
+
+use std::fmt::Write as _;
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::Arc;
+use std::{mem, panic, str};
+
+use crate::html::markdown::LangString;
+
+use super::{
+    add_exe_suffix, executable_command, parse_doctest, partition_source, report_test_failure,
+    run_executable, rustc_command, Collector, DirState, GlobalTestOptions,
+};
+
+/// The environment variable that tells a merged binary which doctest to run.
+const DOCTEST_INDEX_VAR: &str = "RUSTDOC_MERGED_DOCTEST";
+
+/// A doctest that can be compiled in the same binary as other doctests.
+pub(super) struct MergeableDoctest {
+    /// The index of the doctest in [`Collector::tests`].
+    test_index: usize,
+    edition: Edition,
+    /// The file the doctest is from.
+    path: PathBuf,
+    /// The line of the code block of the doctest in `path`.
+    line: usize,
+    /// The code of the doctest, which becomes the body of a function.
+    code: String,
+    no_run: bool,
+    should_panic: bool,
+}
+
+impl MergeableDoctest {
+    /// Returns `None` if the doctest needs a crate of its own.
+    pub(super) fn new(
+        test: &str,
+        config: &LangString,
+        edition: Edition,
+        no_run: bool,
+        path: PathBuf,
+        line: usize,
+        test_index: usize,
+    ) -> Option<MergeableDoctest> {
+        // Before the 2018 edition, paths in function bodies are relative to the module they are
+        // in, so doctests can't refer to the crate being tested from the module they are put in.
+        if config.compile_fail || config.test_harness || edition < Edition::Edition2018 {
+            return None;
+        }
+        // `--remap-path-prefix` takes the path to remap to after the last `=`.
+        if path.to_string_lossy().contains('=') {
+            return None;
+        }
+        let code = mergeable_code(test, edition)?;
+        Some(MergeableDoctest {
+            test_index,
+            edition,
+            path,
+            line,
+            code,
+            no_run,
+            should_panic: config.should_panic,
+        })
+    }
+}
+
+/// Returns the code of a doctest without the comments before it, or `None` if the doctest has
+/// crate attributes, `extern crate` items (which may be `#[macro_use]`) or its own `main`
+/// function.
+fn mergeable_code(test: &str, edition: Edition) -> Option<String> {
+    let (crate_attrs, everything_else, crates) = partition_source(test, edition);
+    if crate_attrs.contains("#![") || !crates.trim().is_empty() {
+        return None;
+    }
+    let code = everything_else.trim();
+    let parsed = parse_doctest(test, code.to_owned(), None, edition)?;
+    if parsed.has_main { None } else { Some(code.to_owned()) }
+}
+
+/// Returns the path of the file with the module of the doctest with index `i` in `dir`.
+fn module_file(dir: &Path, i: usize) -> PathBuf {
+    dir.join(format!("doctest_{i}.rs"))
+}
+
+/// Returns the source of a binary that runs one of `doctests`, chosen at runtime. The modules of
+/// the doctests are in the files given by [`module_file`] in `dir`.
+pub(super) fn merged_source(
+    doctests: &[MergeableDoctest],
+    crate_name: &str,
+    opts: &GlobalTestOptions,
+    dir: &Path,
+) -> String {
+    let mut source = String::new();
+    // Like `make_test`, allow the lints that are commonly triggered in doctests unless there are
+    // attributes from `#![doc(test(attr(...)))]`.
+    if opts.attrs.is_empty() {
+        source.push_str("#![allow(unused)]\n");
+    }
+    for attr in &opts.attrs {
+        writeln!(source, "#![{attr}]").unwrap();
+    }
+    if !opts.no_crate_inject
+        && crate_name != "std"
+        && doctests.iter().any(|doctest| doctest.code.contains(crate_name))
+    {
+        writeln!(source, "extern crate r#{crate_name};").unwrap();
+    }
+
+    for i in 0..doctests.len() {
+        writeln!(source, "#[path = {:?}]\nmod __doctest_{i};", module_file(dir, i)).unwrap();
+    }
+
+    writeln!(source, "fn main() {{\n    match std::env::var({DOCTEST_INDEX_VAR:?}).as_deref() {{")
+        .unwrap();
+    for i in 0..doctests.len() {
+        writeln!(source, "        Ok(\"{i}\") => __doctest_{i}::main(),").unwrap();
+    }
+    source.push_str("        _ => panic!(\"unknown doctest\"),\n    }\n}\n");
+    source
+}
+
+/// Returns the source of the module of `doctest`, whose code starts on the line after the one the
+/// code block starts on, like in the file the doctest is from.
+pub(super) fn module_source(doctest: &MergeableDoctest) -> String {
+    let mut source = "\n".repeat(doctest.line.saturating_sub(1));
+    if doctest.code.trim_end().ends_with("(())") {
+        writeln!(
+            source,
+            "pub(crate) fn main() {{ \
+             fn _inner() -> Result<(), impl core::fmt::Debug> {{\n{}\n}} _inner().unwrap() }}",
+            doctest.code
+        )
+        .unwrap();
+    } else {
+        writeln!(source, "pub(crate) fn main() {{\n{}\n}}", doctest.code).unwrap();
+    }
+    source
+}
+
+/// A compiled merged binary. The temporary directory it is in is removed once all of its tests
+/// have run.
+struct MergedBinary {
+    _dir: DirState,
+    path: PathBuf,
+}
+
+impl Collector {
+    /// Compiles the doctests that can be merged into one binary per edition, and makes their
+    /// tests run that binary. If a binary fails to compile, its doctests are compiled separately
+    /// as usual, which also reports their errors.
+    pub(crate) fn merge_doctests(&mut self) {
+        let mut by_edition = FxHashMap::<Edition, Vec<MergeableDoctest>>::default();
+        for doctest in mem::take(&mut self.mergeable) {
+            by_edition.entry(doctest.edition).or_default().push(doctest);
+        }
+
+        for (edition, doctests) in by_edition {
+            // A single doctest doesn't get any faster by being merged.
+            if doctests.len() < 2 {
+                continue;
+            }
+            let Some(binary) = self.compile_merged(edition, &doctests) else { continue };
+            let binary = Arc::new(binary);
+            for (i, doctest) in doctests.into_iter().enumerate() {
+                let binary = Arc::clone(&binary);
+                let rustdoc_options = self.rustdoc_options.clone();
+                let runtool = self.rustdoc_options.runtool.clone();
+                let runtool_args = self.rustdoc_options.runtool_args.clone();
+                self.tests[doctest.test_index].testfn = test::DynTestFn(Box::new(move || {
+                    if doctest.no_run {
+                        return Ok(());
+                    }
+                    let mut cmd =
+                        executable_command(&binary.path, runtool, runtool_args, &rustdoc_options);
+                    cmd.env(DOCTEST_INDEX_VAR, i.to_string());
+                    let res = run_executable(cmd, rustdoc_options.nocapture, doctest.should_panic);
+                    if let Err(err) = res {
+                        report_test_failure(err);
+                        panic::resume_unwind(Box::new(()));
+                    }
+                    Ok(())
+                }));
+            }
+        }
+    }
+
+    fn compile_merged(
+        &self,
+        edition: Edition,
+        doctests: &[MergeableDoctest],
+    ) -> Option<MergedBinary> {
+        let options = &self.rustdoc_options;
+
+        let dir = if let Some(mut path) = options.persist_doctests.clone() {
+            path.push(format!("merged_doctests_{edition}"));
+            if let Err(err) = std::fs::create_dir_all(&path) {
+                eprintln!("Couldn't create directory for doctest executables: {}", err);
+                panic::resume_unwind(Box::new(()));
+            }
+            DirState::Perm(path)
+        } else {
+            DirState::Temp(
+                TempFileBuilder::new()
+                    .prefix("rustdoctest")
+                    .tempdir()
+                    .expect("rustdoc needs a tempdir"),
+            )
+        };
+        let path = dir.path().join(add_exe_suffix("rust_out".to_owned(), &options.target));
+
+        let source = merged_source(doctests, &self.crate_name, &self.opts, dir.path());
+        debug!("merged doctests:\n{source}");
+        // There is one `--remap-path-prefix` per doctest, so pass them in an argument file to not
+        // run into limits on the length of command lines.
+        let mut remap_args = String::new();
+        for (i, doctest) in doctests.iter().enumerate() {
+            let file = module_file(dir.path(), i);
+            if let Err(err) = std::fs::write(&file, module_source(doctest)) {
+                debug!("couldn't write {}, compiling doctests separately: {err}", file.display());
+                return None;
+            }
+            let (from, to) = (file.display(), doctest.path.display());
+            writeln!(remap_args, "--remap-path-prefix={from}={to}").unwrap();
+        }
+        let args_file = dir.path().join("remap-path-prefix.args");
+        if let Err(err) = std::fs::write(&args_file, remap_args) {
+            debug!("couldn't write {}, compiling doctests separately: {err}", args_file.display());
+            return None;
+        }
+
+        let mut compiler = rustc_command(options, edition, options.target.clone(), false);
+        compiler.arg(format!("@{}", args_file.display()));
+        compiler.arg("-o").arg(&path);
+        if options.no_run && options.persist_doctests.is_none() {
+            compiler.arg("--emit=metadata");
+        }
+        compiler.arg("-");
+        compiler.stdin(Stdio::piped());
+        compiler.stderr(Stdio::piped());
+
+        let mut child = compiler.spawn().expect("Failed to spawn rustc process");
+        {
+            let stdin = child.stdin.as_mut().expect("Failed to open stdin");
+            stdin.write_all(source.as_bytes()).expect("could write out test sources");
+        }
+        let output = child.wait_with_output().expect("Failed to read stdout");
+        if !output.status.success() {
+            debug!(
+                "merged doctests failed to compile, compiling them separately:\n{}",
+                str::from_utf8(&output.stderr).unwrap_or_default()
+            );
+            return None;
+        }
+        Some(MergedBinary { _dir: dir, path })
+    }
+}
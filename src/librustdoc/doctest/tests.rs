@@ -1,5 +1,8 @@
+use super::merged::{merged_source, module_source, MergeableDoctest};
 use super::{make_test, GlobalTestOptions};
-use rustc_span::edition::DEFAULT_EDITION;
+use crate::html::markdown::LangString;
+use rustc_span::edition::{Edition, DEFAULT_EDITION};
+use std::path::{Path, PathBuf};
 
 #[test]
 fn make_test_basic() {
@@ -298,3 +301,85 @@ fn main() { #[allow(non_snake_case)] fn _doctest_main__some_unique_name() {
         make_test(input, None, false, &opts, DEFAULT_EDITION, Some("_some_unique_name"));
     assert_eq!((output, len), (expected, 2));
 }
+
+#[test]
+fn mergeable_doctests() {
+    // only doctests that don't need a crate of their own can be merged
+    let config = LangString::default();
+    let new = |test, config, edition, path: &str| {
+        MergeableDoctest::new(test, config, edition, false, PathBuf::from(path), 1, 0)
+    };
+    let mergeable = |test| new(test, &config, Edition::Edition2021, "lib.rs").is_some();
+    assert!(mergeable("assert_eq!(2+2, 4);"));
+    assert!(mergeable("// A comment\nuse asdf::qwop;\nqwop();"));
+    assert!(!mergeable("#![feature(asdfasdf)]\nassert_eq!(2+2, 4);"));
+    assert!(!mergeable("extern crate asdf;\nassert_eq!(2+2, 4);"));
+    assert!(!mergeable("fn main() {\n    assert_eq!(2+2, 4);\n}"));
+    assert!(new("let x = 1;", &config, Edition::Edition2015, "lib.rs").is_none());
+    assert!(new("let x = 1;", &config, Edition::Edition2021, "a=b.rs").is_none());
+    let compile_fail = LangString { compile_fail: true, ..LangString::default() };
+    assert!(new("let x = 1;", &compile_fail, Edition::Edition2021, "lib.rs").is_none());
+}
+
+#[test]
+fn merged_source_basic() {
+    // every doctest becomes a module in a file of its own, and `main` picks the one to run
+    let opts = GlobalTestOptions::default();
+    let config = LangString::default();
+    let doctests = [
+        "use asdf::qwop;\nqwop();",
+        "let x = std::fs::read(\"a\")?;\nOk::<(), std::io::Error>(())",
+    ];
+    let doctests = doctests
+        .iter()
+        .enumerate()
+        .map(|(i, test)| {
+            let path = PathBuf::from("lib.rs");
+            MergeableDoctest::new(test, &config, Edition::Edition2021, false, path, 1, i)
+        })
+        .collect::<Option<Vec<_>>>()
+        .unwrap();
+    let dir = Path::new("merged");
+    let expected = format!(
+        "#![allow(unused)]
+extern crate r#asdf;
+#[path = {:?}]
+mod __doctest_0;
+#[path = {:?}]
+mod __doctest_1;
+fn main() {{
+    match std::env::var(\"RUSTDOC_MERGED_DOCTEST\").as_deref() {{
+        Ok(\"0\") => __doctest_0::main(),
+        Ok(\"1\") => __doctest_1::main(),
+        _ => panic!(\"unknown doctest\"),
+    }}
+}}
+",
+        dir.join("doctest_0.rs"),
+        dir.join("doctest_1.rs"),
+    );
+    assert_eq!(merged_source(&doctests, "asdf", &opts, dir), expected);
+}
+
+#[test]
+fn merged_module_source() {
+    // the code of the doctest is on the same lines as in the file it's from
+    let config = LangString::default();
+    let new = |test, line| {
+        let path = PathBuf::from("lib.rs");
+        MergeableDoctest::new(test, &config, Edition::Edition2021, false, path, line, 0).unwrap()
+    };
+    let expected = "\n\npub(crate) fn main() {
+use asdf::qwop;
+qwop();
+}
+";
+    assert_eq!(module_source(&new("use asdf::qwop;\nqwop();", 3)), expected);
+    let expected = "pub(crate) fn main() { fn _inner() -> Result<(), impl core::fmt::Debug> {
+let x = std::fs::read(\"a\")?;
+Ok::<(), std::io::Error>(())
+} _inner().unwrap() }
+";
+    let input = "let x = std::fs::read(\"a\")?;\nOk::<(), std::io::Error>(())";
+    assert_eq!(module_source(&new(input, 1)), expected);
+}
@@ -12,7 +12,9 @@
 use crate::formats::Impl;
 use crate::html::format::join_with_double_colon;
 use crate::html::markdown::short_markdown_summary;
-use crate::html::render::search_index::get_function_type_for_search;
+use crate::html::render::search_index::{
+    get_function_type_for_search, get_stability_for_search, is_deprecated_for_search,
+};
 use crate::html::render::IndexItem;
 use crate::visit_lib::RustdocEffectiveVisibilities;
 
@@ -336,6 +338,8 @@ fn fold_item(&mut self, item: clean::Item) -> Option<clean::Item> {
                                     self.cache,
                                 ),
                                 aliases: item.attrs.get_doc_aliases(),
+                                stability: get_stability_for_search(&item, self.tcx),
+                                deprecated: is_deprecated_for_search(&item, self.tcx),
                             });
                         }
                     }
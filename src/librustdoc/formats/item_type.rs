@@ -1,6 +1,7 @@
 //! Item types.
 
 use std::fmt;
+use std::str::FromStr;
 
 use serde::{Serialize, Serializer};
 
@@ -184,3 +185,40 @@ fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.write_str(self.as_str())
     }
 }
+
+/// Parses the names returned by [`ItemType::as_str`].
+impl FromStr for ItemType {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "mod" => ItemType::Module,
+            "externcrate" => ItemType::ExternCrate,
+            "import" => ItemType::Import,
+            "struct" => ItemType::Struct,
+            "union" => ItemType::Union,
+            "enum" => ItemType::Enum,
+            "fn" => ItemType::Function,
+            "type" => ItemType::Typedef,
+            "static" => ItemType::Static,
+            "trait" => ItemType::Trait,
+            "impl" => ItemType::Impl,
+            "tymethod" => ItemType::TyMethod,
+            "method" => ItemType::Method,
+            "structfield" => ItemType::StructField,
+            "variant" => ItemType::Variant,
+            "macro" => ItemType::Macro,
+            "primitive" => ItemType::Primitive,
+            "associatedtype" => ItemType::AssocType,
+            "constant" => ItemType::Constant,
+            "associatedconstant" => ItemType::AssocConst,
+            "foreigntype" => ItemType::ForeignType,
+            "keyword" => ItemType::Keyword,
+            "opaque" => ItemType::OpaqueTy,
+            "attr" => ItemType::ProcAttribute,
+            "derive" => ItemType::ProcDerive,
+            "traitalias" => ItemType::TraitAlias,
+            _ => return Err(()),
+        })
+    }
+}
@@ -183,6 +183,12 @@ fn map_line(s: &str) -> Line<'_> {
     }
 }
 
+/// Returns the lines of a Rust code block that are shown in the rendered documentation, which
+/// leaves out the lines hidden with a leading `# `.
+pub(crate) fn shown_code_lines(code: &str) -> impl Iterator<Item = Cow<'_, str>> {
+    code.lines().filter_map(|l| map_line(l).for_html())
+}
+
 /// Convert chars from a title for an id.
 ///
 /// "Hello, world!" -> "hello-world"
@@ -268,7 +274,7 @@ fn next(&mut self) -> Option<Self::Item> {
             CodeBlockKind::Indented => Default::default(),
         };
 
-        let lines = origtext.lines().filter_map(|l| map_line(l).for_html());
+        let lines = shown_code_lines(&origtext);
         let text = lines.intersperse("\n".into()).collect::<String>();
 
         compile_fail = parse_result.compile_fail;
@@ -857,7 +863,7 @@ fn default() -> Self {
 }
 
 impl LangString {
-    fn parse_without_check(
+    pub(crate) fn parse_without_check(
         string: &str,
         allow_error_code_check: ErrorCodes,
         enable_per_target_ignores: bool,
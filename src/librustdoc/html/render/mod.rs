@@ -107,6 +107,18 @@ pub(crate) struct IndexItem {
     pub(crate) parent_idx: Option<usize>,
     pub(crate) search_type: Option<IndexItemFunctionType>,
     pub(crate) aliases: Box<[Symbol]>,
+    pub(crate) stability: Option<IndexItemStability>,
+    /// Whether the item is deprecated. Planned deprecations are not taken into account.
+    pub(crate) deprecated: bool,
+}
+
+/// The stability of an item, as shown in the search results.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum IndexItemStability {
+    /// The item is stable since the given version.
+    Stable { since: Symbol },
+    /// The item is only available on nightly, behind the given feature gate.
+    Unstable { feature: Symbol },
 }
 
 /// A type used for the search index.
@@ -2584,7 +2596,7 @@ pub(crate) enum ItemSection {
 }
 
 impl ItemSection {
-    const ALL: &'static [Self] = {
+    pub(crate) const ALL: &'static [Self] = {
         use ItemSection::*;
         // NOTE: The order here affects the order in the UI.
         &[
@@ -2646,7 +2658,7 @@ fn id(self) -> &'static str {
         }
     }
 
-    fn name(self) -> &'static str {
+    pub(crate) fn name(self) -> &'static str {
         match self {
             Self::Reexports => "Re-exports",
             Self::Modules => "Modules",
@@ -2677,7 +2689,7 @@ fn name(self) -> &'static str {
     }
 }
 
-fn item_ty_to_section(ty: ItemType) -> ItemSection {
+pub(crate) fn item_ty_to_section(ty: ItemType) -> ItemSection {
     match ty {
         ItemType::ExternCrate | ItemType::Import => ItemSection::Reexports,
         ItemType::Module => ItemSection::Modules,
@@ -1,10 +1,12 @@
 use std::collections::hash_map::Entry;
 use std::collections::BTreeMap;
 
+use rustc_attr::StabilityLevel;
 use rustc_data_structures::fx::FxHashMap;
+use rustc_middle::middle::stability;
 use rustc_middle::ty::TyCtxt;
 use rustc_span::def_id::LOCAL_CRATE;
-use rustc_span::symbol::Symbol;
+use rustc_span::symbol::{sym, Symbol};
 use serde::ser::{Serialize, SerializeStruct, Serializer};
 
 use crate::clean;
@@ -15,7 +17,9 @@
 use crate::formats::item_type::ItemType;
 use crate::html::format::join_with_double_colon;
 use crate::html::markdown::short_markdown_summary;
-use crate::html::render::{IndexItem, IndexItemFunctionType, RenderType, RenderTypeId};
+use crate::html::render::{
+    IndexItem, IndexItemFunctionType, IndexItemStability, RenderType, RenderTypeId,
+};
 
 /// Builds the search index from the collected metadata
 pub(crate) fn build_index<'tcx>(
@@ -42,6 +46,8 @@ pub(crate) fn build_index<'tcx>(
                 parent_idx: None,
                 search_type: get_function_type_for_search(item, tcx, impl_generics.as_ref(), cache),
                 aliases: item.attrs.get_doc_aliases(),
+                stability: get_stability_for_search(item, tcx),
+                deprecated: is_deprecated_for_search(item, tcx),
             });
         }
     }
@@ -180,6 +186,26 @@ fn convert_render_type(
         })
         .collect();
 
+    // Group the items by stability, which is far more compact than storing it per item since
+    // most items share a handful of values.
+    let mut unstable_features: BTreeMap<&str, Vec<usize>> = BTreeMap::new();
+    let mut stable_since: BTreeMap<&str, Vec<usize>> = BTreeMap::new();
+    let mut deprecated = vec![];
+    for (i, item) in crate_items.iter().enumerate() {
+        match &item.stability {
+            Some(IndexItemStability::Stable { since }) => {
+                stable_since.entry(since.as_str()).or_default().push(i)
+            }
+            Some(IndexItemStability::Unstable { feature }) => {
+                unstable_features.entry(feature.as_str()).or_default().push(i)
+            }
+            None => {}
+        }
+        if item.deprecated {
+            deprecated.push(i);
+        }
+    }
+
     struct CrateData<'a> {
         doc: String,
         items: Vec<&'a IndexItem>,
@@ -188,6 +214,12 @@ struct CrateData<'a> {
         //
         // To be noted: the `usize` elements are indexes to `items`.
         aliases: &'a BTreeMap<String, Vec<usize>>,
+        // The feature gates of the unstable items, and the versions since which the stable items
+        // are stable. Like for `aliases`, the `usize` elements are indexes to `items`.
+        unstable_features: BTreeMap<&'a str, Vec<usize>>,
+        stable_since: BTreeMap<&'a str, Vec<usize>>,
+        // The indexes to `items` of the deprecated items.
+        deprecated: Vec<usize>,
     }
 
     impl<'a> Serialize for CrateData<'a> {
@@ -196,8 +228,14 @@ fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
             S: Serializer,
         {
             let has_aliases = !self.aliases.is_empty();
-            let mut crate_data =
-                serializer.serialize_struct("CrateData", if has_aliases { 9 } else { 8 })?;
+            let has_unstable = !self.unstable_features.is_empty();
+            let has_stable = !self.stable_since.is_empty();
+            let has_deprecated = !self.deprecated.is_empty();
+            let len = 8 + [has_aliases, has_unstable, has_stable, has_deprecated]
+                .iter()
+                .filter(|&&has_field| has_field)
+                .count();
+            let mut crate_data = serializer.serialize_struct("CrateData", len)?;
             crate_data.serialize_field("doc", &self.doc)?;
             crate_data.serialize_field(
                 "t",
@@ -269,6 +307,15 @@ fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
             if has_aliases {
                 crate_data.serialize_field("a", &self.aliases)?;
             }
+            if has_unstable {
+                crate_data.serialize_field("u", &self.unstable_features)?;
+            }
+            if has_stable {
+                crate_data.serialize_field("s", &self.stable_since)?;
+            }
+            if has_deprecated {
+                crate_data.serialize_field("x", &self.deprecated)?;
+            }
             crate_data.end()
         }
     }
@@ -282,6 +329,9 @@ fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
             items: crate_items,
             paths: crate_paths,
             aliases: &aliases,
+            unstable_features,
+            stable_since,
+            deprecated,
         })
         .expect("failed serde conversion")
         // All these `replace` calls are because we have to go through JS string for JSON content.
@@ -311,6 +361,26 @@ pub(crate) fn get_function_type_for_search<'tcx>(
     Some(IndexItemFunctionType { inputs, output })
 }
 
+/// Returns the stability of `item` for the search index. The "rustc_private" crates are
+/// permanently unstable, so their items are not marked as unstable.
+pub(crate) fn get_stability_for_search(
+    item: &clean::Item,
+    tcx: TyCtxt<'_>,
+) -> Option<IndexItemStability> {
+    let stab = item.stability(tcx)?;
+    match stab.level {
+        StabilityLevel::Stable { since, .. } => Some(IndexItemStability::Stable { since }),
+        StabilityLevel::Unstable { .. } if stab.feature != sym::rustc_private => {
+            Some(IndexItemStability::Unstable { feature: stab.feature })
+        }
+        StabilityLevel::Unstable { .. } => None,
+    }
+}
+
+pub(crate) fn is_deprecated_for_search(item: &clean::Item, tcx: TyCtxt<'_>) -> bool {
+    item.deprecation(tcx).map_or(false, |depr| stability::deprecation_in_effect(&depr))
+}
+
 fn get_index_type(clean_type: &clean::Type, generics: Vec<RenderType>) -> RenderType {
     RenderType {
         id: get_index_type_id(clean_type),
@@ -552,6 +552,10 @@ function initSearch(rawSearchIndex) {
             foundElems: 0,
             literalSearch: false,
             error: null,
+            // Whether unstable and deprecated items are left out of the results. These are set
+            // from the settings when searching from the UI.
+            hideUnstable: false,
+            hideDeprecated: false,
         };
     }
 
@@ -732,6 +736,10 @@ function initSearch(rawSearchIndex) {
             for (const result of results) {
                 if (result.id > -1) {
                     const obj = searchIndex[result.id];
+                    if ((parsedQuery.hideUnstable && obj.unstable !== null) ||
+                        (parsedQuery.hideDeprecated && obj.deprecated)) {
+                        continue;
+                    }
                     obj.lev = result.lev;
                     const res = buildHrefAndPath(obj);
                     obj.displayPath = pathSplitter(res[0]);
@@ -1614,6 +1622,21 @@ function initSearch(rawSearchIndex) {
                 resultName.insertAdjacentHTML(
                     "beforeend",
                     item.displayPath + "<span class=\"" + type + "\">" + name + extra + "</span>");
+                if (item.deprecated) {
+                    resultName.insertAdjacentHTML(
+                        "beforeend",
+                        "<span class=\"stab deprecated\">Deprecated</span>");
+                }
+                if (item.unstable !== null) {
+                    const badge = document.createElement("span");
+                    badge.className = "stab unstable";
+                    badge.title = "This is a nightly-only experimental API (" + item.unstable +
+                        ")";
+                    badge.innerText = "Experimental";
+                    resultName.appendChild(badge);
+                } else if (item.since !== null) {
+                    resultName.title = "Stable since " + item.since;
+                }
                 link.appendChild(resultName);
 
                 const description = document.createElement("div");
@@ -1771,6 +1794,8 @@ function initSearch(rawSearchIndex) {
         }
 
         const query = parseQuery(searchState.input.value.trim());
+        query.hideUnstable = getSettingValue("hide-unstable-search-results") === "true";
+        query.hideDeprecated = getSettingValue("hide-deprecated-search-results") === "true";
         let filterCrates = getFilterCrates();
 
         if (!forced && query.userQuery === currentResults) {
@@ -1902,6 +1927,29 @@ function initSearch(rawSearchIndex) {
         };
     }
 
+    /**
+     * Convert an object mapping values to lists of item offsets, like the `u` and `s` fields of
+     * the search index, into an array with the value of each item.
+     *
+     * @param {Object|undefined} groups
+     *
+     * @return {Array<string>}
+     */
+    function expandItemGroups(groups) {
+        const values = [];
+        if (groups) {
+            for (const value in groups) {
+                if (!hasOwnPropertyRustdoc(groups, value)) {
+                    continue;
+                }
+                for (const offset of groups[value]) {
+                    values[offset] = value;
+                }
+            }
+        }
+        return values;
+    }
+
     function buildIndex(rawSearchIndex) {
         searchIndex = [];
         /**
@@ -1941,6 +1989,10 @@ function initSearch(rawSearchIndex) {
              * `a` defines aliases with an Array of pairs: [name, offset], where `offset`
              * points into the n/t/d/q/i/f arrays.
              *
+             * `u` maps the feature gates of unstable items to the offsets of those items, and
+             * `s` maps versions to the offsets of the items that are stable since then. `x` is
+             * a list of the offsets of deprecated items. Like `a`, they are omitted if empty.
+             *
              * `doc` contains the description of the crate.
              *
              * `p` is a list of path/type pairs. It is used for parents and function parameters.
@@ -1955,6 +2007,9 @@ function initSearch(rawSearchIndex) {
              *   i: Array<Number>,
              *   f: Array<RawFunctionSearchType>,
              *   p: Array<Object>,
+             *   u: Object,
+             *   s: Object,
+             *   x: Array<Number>,
              * }}
              */
             const crateCorpus = rawSearchIndex[crate];
@@ -1973,6 +2028,9 @@ function initSearch(rawSearchIndex) {
                 type: null,
                 id: id,
                 normalizedName: crate.indexOf("_") === -1 ? crate : crate.replace(/_/g, ""),
+                unstable: null,
+                since: null,
+                deprecated: false,
             };
             id += 1;
             searchIndex.push(crateRow);
@@ -1996,6 +2054,12 @@ function initSearch(rawSearchIndex) {
             // an array of [(String) alias name
             //             [Number] index to items]
             const aliases = crateCorpus.a;
+            // an array of (String | undefined) the feature gate of each unstable item
+            const itemUnstableFeatures = expandItemGroups(crateCorpus.u);
+            // an array of (String | undefined) the version each stable item is stable since
+            const itemStableSince = expandItemGroups(crateCorpus.s);
+            // a set of (Number) the offsets of deprecated items
+            const deprecatedItems = new Set(crateCorpus.x || []);
 
             // an array of [{name: String, ty: Number}]
             const lowercasePaths = [];
@@ -2037,6 +2101,9 @@ function initSearch(rawSearchIndex) {
                     type: buildFunctionSearchType(itemFunctionSearchTypes[i], lowercasePaths),
                     id: id,
                     normalizedName: word.indexOf("_") === -1 ? word : word.replace(/_/g, ""),
+                    unstable: itemUnstableFeatures[i] || null,
+                    since: itemStableSince[i] || null,
+                    deprecated: deprecatedItems.has(i),
                 };
                 id += 1;
                 searchIndex.push(row);
@@ -211,6 +211,16 @@
                 "js_name": "go-to-only-result",
                 "default": false,
             },
+            {
+                "name": "Hide nightly-only experimental items in search results",
+                "js_name": "hide-unstable-search-results",
+                "default": false,
+            },
+            {
+                "name": "Hide deprecated items in search results",
+                "js_name": "hide-deprecated-search-results",
+                "default": false,
+            },
             {
                 "name": "Show line numbers on code examples",
                 "js_name": "line-numbers",
@@ -0,0 +1,153 @@
+//! Collection of the items that function bodies refer to, for the `--json-call-graph` flag.
+//!
+//! This needs the typeck results of the bodies, which rustdoc does not compute otherwise, so the
+//! flag makes rustdoc report type errors in bodies that would usually be ignored.
+
+use std::iter::once;
+
+use rustc_data_structures::fx::FxIndexMap;
+use rustc_hir as hir;
+use rustc_hir::def::{DefKind, Res};
+use rustc_hir::intravisit::{self, Visitor};
+use rustc_middle::hir::nested_filter;
+use rustc_middle::ty::{self, DefIdTree, TyCtxt};
+use rustc_span::def_id::{DefId, LocalDefId};
+
+use rustdoc_json_types as types;
+
+use crate::json::conversions::from_item_id;
+
+/// Returns the items that the body of `def_id` refers to, sorted by path.
+pub(super) fn references(tcx: TyCtxt<'_>, def_id: LocalDefId) -> Vec<types::Reference> {
+    let Some(body_id) = tcx.hir().maybe_body_owned_by(def_id) else { return Vec::new() };
+    let mut finder = FindReferences {
+        tcx,
+        typeck_results: tcx.typeck_body(body_id),
+        references: FxIndexMap::default(),
+    };
+    finder.visit_nested_body(body_id);
+
+    let mut references: Vec<_> = finder
+        .references
+        .into_iter()
+        .map(|(def_id, kind)| types::Reference {
+            id: from_item_id(def_id.into(), tcx),
+            path: item_path(tcx, def_id),
+            kind,
+        })
+        .collect();
+    references.sort_by(|a, b| a.path.cmp(&b.path));
+    references
+}
+
+/// Returns the fully qualified path of `def_id`. Unlike the paths in the `paths` map, this also
+/// works for items that are not documented, like private functions or methods of external types.
+fn item_path(tcx: TyCtxt<'_>, def_id: DefId) -> Vec<String> {
+    if let Some(assoc_item) = tcx.opt_associated_item(def_id) {
+        if let Some(trait_item_def_id) = assoc_item.trait_item_def_id {
+            return item_path(tcx, trait_item_def_id);
+        }
+        if assoc_item.container == ty::ImplContainer {
+            // Impls have no name, so use the name of the type instead.
+            let self_ty = tcx.type_of(assoc_item.container_id(tcx));
+            let mut path = match self_ty.kind() {
+                ty::Adt(adt, _) => item_path(tcx, adt.did()),
+                _ => vec![tcx.crate_name(def_id.krate).to_string(), self_ty.to_string()],
+            };
+            path.push(assoc_item.name.to_string());
+            return path;
+        }
+    }
+
+    let crate_name = tcx.crate_name(def_id.krate).to_string();
+    let relative = tcx.def_path(def_id).data.into_iter().filter_map(|elem| {
+        // extern blocks (and a few others things) have an empty name.
+        match elem.data.get_opt_name() {
+            Some(s) if !s.is_empty() => Some(s.to_string()),
+            _ => None,
+        }
+    });
+    once(crate_name).chain(relative).collect()
+}
+
+/// Visitor for a function body that records the items its paths resolve to.
+struct FindReferences<'tcx> {
+    tcx: TyCtxt<'tcx>,
+    typeck_results: &'tcx ty::TypeckResults<'tcx>,
+    references: FxIndexMap<DefId, types::ReferenceKind>,
+}
+
+impl<'tcx> FindReferences<'tcx> {
+    fn record(&mut self, res: Res, kind: types::ReferenceKind) {
+        let Res::Def(def_kind, def_id) = res else { return };
+        let def_id = match def_kind {
+            // Constructors are not items of their own, so record the struct or variant instead.
+            DefKind::Ctor(..) => self.tcx.parent(def_id),
+            DefKind::Fn
+            | DefKind::AssocFn
+            | DefKind::Const
+            | DefKind::AssocConst
+            | DefKind::Static(_)
+            | DefKind::Struct
+            | DefKind::Union
+            | DefKind::Enum
+            | DefKind::Variant
+            | DefKind::Trait
+            | DefKind::TraitAlias
+            | DefKind::TyAlias
+            | DefKind::AssocTy
+            | DefKind::ForeignTy => def_id,
+            // Generic parameters, modules, closures and the like are not interesting here.
+            _ => return,
+        };
+        let recorded = self.references.entry(def_id).or_insert(kind);
+        if kind == types::ReferenceKind::Call {
+            *recorded = kind;
+        }
+    }
+}
+
+impl<'tcx> Visitor<'tcx> for FindReferences<'tcx> {
+    type NestedFilter = nested_filter::OnlyBodies;
+
+    fn nested_visit_map(&mut self) -> Self::Map {
+        self.tcx.hir()
+    }
+
+    fn visit_nested_body(&mut self, body_id: hir::BodyId) {
+        // Closures share the typeck results of their parent, but anonymous constants like array
+        // lengths have their own.
+        let old_typeck_results =
+            std::mem::replace(&mut self.typeck_results, self.tcx.typeck_body(body_id));
+        self.visit_body(self.tcx.hir().body(body_id));
+        self.typeck_results = old_typeck_results;
+    }
+
+    fn visit_expr(&mut self, ex: &'tcx hir::Expr<'tcx>) {
+        match ex.kind {
+            hir::ExprKind::Call(hir::Expr { kind: hir::ExprKind::Path(qpath), hir_id, .. }, _) => {
+                let res = self.typeck_results.qpath_res(qpath, *hir_id);
+                self.record(res, types::ReferenceKind::Call);
+            }
+            hir::ExprKind::MethodCall(..) => {
+                if let Some(def_id) = self.typeck_results.type_dependent_def_id(ex.hir_id) {
+                    let res = Res::Def(self.tcx.def_kind(def_id), def_id);
+                    self.record(res, types::ReferenceKind::Call);
+                }
+            }
+            // Type-relative paths like `Vec::new` are only resolved by typeck.
+            hir::ExprKind::Path(ref qpath) => {
+                let res = self.typeck_results.qpath_res(qpath, ex.hir_id);
+                self.record(res, types::ReferenceKind::Name);
+            }
+            _ => {}
+        }
+        intravisit::walk_expr(self, ex);
+    }
+
+    fn visit_path(&mut self, path: &hir::Path<'tcx>, _id: hir::HirId) {
+        // Paths in types and patterns are resolved without typeck.
+        self.record(path.res, types::ReferenceKind::Name);
+        intravisit::walk_path(self, path);
+    }
+}
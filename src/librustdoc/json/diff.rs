@@ -0,0 +1,970 @@
+//! Comparison of the public API of two versions of a crate, based on their JSON output.
+//!
+//! Item IDs are not stable between builds, so items are matched by the path under which they are
+//! reachable from the crate root instead. Every change is classified as [`Severity::Major`] or
+//! [`Severity::Minor`] following the rules of the [API evolution RFC][rfc1105], erring on the side
+//! of calling a change major when the JSON output does not carry enough information to tell.
+//!
+//! [rfc1105]: https://rust-lang.github.io/rfcs/1105-api-evolution.html
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+use rustc_data_structures::fx::FxHashSet;
+use rustdoc_json_types::{
+    Abi, Crate, FnDecl, GenericArg, GenericArgs, GenericBound, GenericParamDef, GenericParamDefKind,
+    Generics, Header, Id, Import, Item, ItemEnum, Module, StructKind, Term, TraitBoundModifier,
+    Type, TypeBindingKind, Variant, Visibility, WherePredicate, FORMAT_VERSION,
+};
+use serde::Deserialize;
+
+/// How a change affects the version number of the crate under semver.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum Severity {
+    /// The change is backwards compatible, and requires a minor version bump.
+    Minor,
+    /// The change can break dependent crates, and requires a major version bump.
+    Major,
+}
+
+#[derive(Debug)]
+pub(crate) struct Change {
+    pub(crate) severity: Severity,
+    /// The path of the affected item, or the header of the affected impl.
+    pub(crate) path: String,
+    pub(crate) description: String,
+}
+
+/// Reads the JSON output of rustdoc from `path`.
+pub(crate) fn load_crate(path: &Path) -> Result<Crate, String> {
+    #[derive(Deserialize)]
+    struct FormatVersion {
+        format_version: u32,
+    }
+
+    let contents = fs::read_to_string(path)
+        .map_err(|e| format!("failed to read `{}`: {}", path.display(), e))?;
+    // Check the version first, since older formats are unlikely to deserialize at all.
+    let FormatVersion { format_version } = serde_json::from_str(&contents)
+        .map_err(|e| format!("`{}` is not rustdoc JSON output: {}", path.display(), e))?;
+    if format_version != FORMAT_VERSION {
+        return Err(format!(
+            "`{}` uses version {} of the rustdoc JSON format, but this rustdoc uses version {}",
+            path.display(),
+            format_version,
+            FORMAT_VERSION,
+        ));
+    }
+    serde_json::from_str(&contents)
+        .map_err(|e| format!("`{}` is not rustdoc JSON output: {}", path.display(), e))
+}
+
+/// Compares the JSON files at `old` and `new`, and prints the report to stdout.
+pub(crate) fn run(old: &Path, new: &Path) -> Result<(), String> {
+    let old = load_crate(old)?;
+    let new = load_crate(new)?;
+    print!("{}", render_report(&diff(&old, &new)));
+    Ok(())
+}
+
+/// Computes the changes between the public APIs of `old` and `new`, sorted by path.
+pub(crate) fn diff(old: &Crate, new: &Crate) -> Vec<Change> {
+    let old_api = Api::collect(old);
+    let new_api = Api::collect(new);
+    let mut changes = Changes::default();
+
+    for (path, old_entry) in &old_api.entries {
+        match new_api.entries.get(path) {
+            Some(new_entry) => {
+                compare(&old_api, old_entry, &new_api, new_entry, path, &mut changes)
+            }
+            // A synthesized negative impl only mirrors the missing positive one, which is
+            // reported on its own.
+            None if is_synthetic_negative_impl(old_entry) => {}
+            None => {
+                let description = match old_entry {
+                    ApiEntry::Impl { item, synthetic: true } => {
+                        format!("no longer implements `{}`", impl_trait_name(item))
+                    }
+                    _ => "removed".to_string(),
+                };
+                changes.push(Severity::Major, path, description);
+            }
+        }
+    }
+    for (path, new_entry) in &new_api.entries {
+        if !old_api.entries.contains_key(path) && !is_synthetic_negative_impl(new_entry) {
+            let (severity, description) = addition(&old_api, &new_api, path, new_entry);
+            changes.push(severity, path, description);
+        }
+    }
+
+    let mut changes = changes.0;
+    changes.sort_by(|a, b| a.path.cmp(&b.path).then(b.severity.cmp(&a.severity)));
+    changes
+}
+
+/// Renders `changes` as text, followed by the version bump they require.
+pub(crate) fn render_report(changes: &[Change]) -> String {
+    let mut report = String::new();
+    for change in changes {
+        let severity = match change.severity {
+            Severity::Major => "major",
+            Severity::Minor => "minor",
+        };
+        writeln!(report, "{}: {}: {}", severity, change.path, change.description).unwrap();
+    }
+    let bump = match changes.iter().map(|change| change.severity).max() {
+        Some(Severity::Major) => "major",
+        Some(Severity::Minor) => "minor",
+        None => "patch",
+    };
+    writeln!(report, "required version bump: {}", bump).unwrap();
+    report
+}
+
+#[derive(Default)]
+struct Changes(Vec<Change>);
+
+impl Changes {
+    fn push(&mut self, severity: Severity, path: &str, description: String) {
+        self.0.push(Change { severity, path: path.to_string(), description });
+    }
+}
+
+/// A part of the public API of a crate.
+enum ApiEntry<'a> {
+    Item(&'a Item),
+    /// A re-export of an item that is not part of the JSON output, like an item of another crate
+    /// or a primitive type.
+    Reexport(&'a Import),
+    /// A trait impl, identified by its header rather than by a path.
+    Impl {
+        item: &'a Item,
+        synthetic: bool,
+    },
+}
+
+/// The public API of a crate, by path.
+struct Api<'a> {
+    krate: &'a Crate,
+    entries: BTreeMap<String, ApiEntry<'a>>,
+    /// The modules that were already visited, to handle glob re-export cycles.
+    visited_modules: FxHashSet<&'a Id>,
+}
+
+impl<'a> Api<'a> {
+    fn collect(krate: &'a Crate) -> Self {
+        let mut api =
+            Api { krate, entries: BTreeMap::new(), visited_modules: FxHashSet::default() };
+        if let Some(root) = krate.index.get(&krate.root) {
+            if let ItemEnum::Module(module) = &root.inner {
+                let name = root.name.clone().unwrap_or_default();
+                api.visited_modules.insert(&root.id);
+                api.collect_module(&name, module);
+            }
+        }
+        api
+    }
+
+    fn get(&self, id: &Id) -> Option<&'a Item> {
+        self.krate.index.get(id)
+    }
+
+    fn collect_module(&mut self, path: &str, module: &'a Module) {
+        for id in &module.items {
+            let Some(item) = self.get(id) else { continue };
+            match &item.inner {
+                ItemEnum::Import(_) if !is_public(item) => {}
+                ItemEnum::Import(import) if import.glob => {
+                    // Glob re-exports of enums bring their variants into scope, but those are
+                    // already reachable through the enum.
+                    let Some(target) = import.id.as_ref().and_then(|id| self.get(id)) else {
+                        continue;
+                    };
+                    if let ItemEnum::Module(module) = &target.inner {
+                        if self.visited_modules.insert(&target.id) {
+                            self.collect_module(path, module);
+                        }
+                    }
+                }
+                ItemEnum::Import(import) => {
+                    let item_path = format!("{}::{}", path, import.name);
+                    match import.id.as_ref().and_then(|id| self.get(id)) {
+                        Some(target) => self.collect_item(item_path, target),
+                        None => {
+                            self.entries.entry(item_path).or_insert(ApiEntry::Reexport(import));
+                        }
+                    }
+                }
+                _ => {
+                    if let Some(name) = &item.name {
+                        self.collect_item(format!("{}::{}", path, name), item);
+                    }
+                }
+            }
+        }
+    }
+
+    fn collect_item(&mut self, path: String, item: &'a Item) {
+        if !is_public(item) || self.entries.contains_key(&path) {
+            return;
+        }
+        self.entries.insert(path.clone(), ApiEntry::Item(item));
+        match &item.inner {
+            ItemEnum::Module(module) => {
+                if self.visited_modules.insert(&item.id) {
+                    self.collect_module(&path, module);
+                }
+            }
+            ItemEnum::Struct(struct_) => {
+                self.collect_struct_kind(&path, &struct_.kind);
+                self.collect_impls(&path, &struct_.impls);
+            }
+            ItemEnum::Union(union_) => {
+                self.collect_fields(&path, &union_.fields);
+                self.collect_impls(&path, &union_.impls);
+            }
+            ItemEnum::Enum(enum_) => {
+                for variant in enum_.variants.iter().filter_map(|id| self.get(id)) {
+                    let Some(name) = &variant.name else { continue };
+                    let variant_path = format!("{}::{}", path, name);
+                    self.entries.insert(variant_path.clone(), ApiEntry::Item(variant));
+                    match &variant.inner {
+                        ItemEnum::Variant(Variant::Tuple(fields)) => {
+                            self.collect_tuple_fields(&variant_path, fields)
+                        }
+                        ItemEnum::Variant(Variant::Struct { fields, .. }) => {
+                            self.collect_fields(&variant_path, fields)
+                        }
+                        _ => {}
+                    }
+                }
+                self.collect_impls(&path, &enum_.impls);
+            }
+            ItemEnum::Trait(trait_) => {
+                for trait_item in trait_.items.iter().filter_map(|id| self.get(id)) {
+                    if let Some(name) = &trait_item.name {
+                        self.entries
+                            .insert(format!("{}::{}", path, name), ApiEntry::Item(trait_item));
+                    }
+                }
+            }
+            ItemEnum::Primitive(primitive) => self.collect_impls(&path, &primitive.impls),
+            _ => {}
+        }
+    }
+
+    fn collect_struct_kind(&mut self, path: &str, kind: &'a StructKind) {
+        match kind {
+            StructKind::Unit => {}
+            StructKind::Tuple(fields) => self.collect_tuple_fields(path, fields),
+            StructKind::Plain { fields, .. } => self.collect_fields(path, fields),
+        }
+    }
+
+    fn collect_fields(&mut self, path: &str, fields: &[Id]) {
+        for field in fields.iter().filter_map(|id| self.get(id)) {
+            if let Some(name) = &field.name {
+                self.collect_item(format!("{}::{}", path, name), field);
+            }
+        }
+    }
+
+    fn collect_tuple_fields(&mut self, path: &str, fields: &[Option<Id>]) {
+        for (i, field) in fields.iter().enumerate() {
+            if let Some(field) = field.as_ref().and_then(|id| self.get(id)) {
+                self.collect_item(format!("{}::{}", path, i), field);
+            }
+        }
+    }
+
+    fn collect_impls(&mut self, path: &str, impls: &[Id]) {
+        for item in impls.iter().filter_map(|id| self.get(id)) {
+            let ItemEnum::Impl(impl_) = &item.inner else { continue };
+            if impl_.blanket_impl.is_some() {
+                // Blanket impls are part of the API of the crate that defines them.
+                continue;
+            }
+            match &impl_.trait_ {
+                None => {
+                    for impl_item in impl_.items.iter().filter_map(|id| self.get(id)) {
+                        if let Some(name) = &impl_item.name {
+                            self.collect_item(format!("{}::{}", path, name), impl_item);
+                        }
+                    }
+                }
+                Some(trait_) => {
+                    let header = format!(
+                        "impl {}{} for {}",
+                        if impl_.negative { "!" } else { "" },
+                        self.path(trait_),
+                        self.ty(&impl_.for_),
+                    );
+                    self.entries
+                        .entry(header)
+                        .or_insert(ApiEntry::Impl { item, synthetic: impl_.synthetic });
+                }
+            }
+        }
+    }
+
+    /// Renders a type with the full paths of the items it refers to, so that it can be compared
+    /// between builds.
+    fn ty(&self, ty: &Type) -> String {
+        match ty {
+            Type::ResolvedPath(path) => self.path(path),
+            Type::DynTrait(dyn_trait) => {
+                let mut out = format!(
+                    "dyn {}",
+                    dyn_trait
+                        .traits
+                        .iter()
+                        .map(|poly_trait| {
+                            format!(
+                                "{}{}",
+                                self.higher_ranked(&poly_trait.generic_params),
+                                self.path(&poly_trait.trait_)
+                            )
+                        })
+                        .collect::<Vec<_>>()
+                        .join(" + ")
+                );
+                if let Some(lifetime) = &dyn_trait.lifetime {
+                    write!(out, " + {}", lifetime).unwrap();
+                }
+                out
+            }
+            Type::Generic(name) | Type::Primitive(name) => name.clone(),
+            Type::FunctionPointer(fn_ptr) => format!(
+                "{}{}fn{}",
+                self.higher_ranked(&fn_ptr.generic_params),
+                header(&fn_ptr.header),
+                self.fn_decl(&fn_ptr.decl),
+            ),
+            Type::Tuple(types) if types.len() == 1 => format!("({},)", self.ty(&types[0])),
+            Type::Tuple(types) => format!("({})", self.types(types)),
+            Type::Slice(ty) => format!("[{}]", self.ty(ty)),
+            Type::Array { type_, len } => format!("[{}; {}]", self.ty(type_), len),
+            Type::ImplTrait(bounds) => format!("impl {}", self.bounds(bounds)),
+            Type::Infer => "_".to_string(),
+            Type::RawPointer { mutable, type_ } => {
+                format!("*{} {}", if *mutable { "mut" } else { "const" }, self.ty(type_))
+            }
+            Type::BorrowedRef { lifetime, mutable, type_ } => format!(
+                "&{}{}{}",
+                lifetime.as_ref().map(|lifetime| format!("{} ", lifetime)).unwrap_or_default(),
+                if *mutable { "mut " } else { "" },
+                self.ty(type_),
+            ),
+            Type::QualifiedPath { name, args, self_type, trait_ } => format!(
+                "<{} as {}>::{}{}",
+                self.ty(self_type),
+                self.path(trait_),
+                name,
+                self.generic_args(args),
+            ),
+        }
+    }
+
+    fn types(&self, types: &[Type]) -> String {
+        types.iter().map(|ty| self.ty(ty)).collect::<Vec<_>>().join(", ")
+    }
+
+    fn path(&self, path: &rustdoc_json_types::Path) -> String {
+        let name = match self.krate.paths.get(&path.id) {
+            Some(summary) => summary.path.join("::"),
+            None => path.name.clone(),
+        };
+        match &path.args {
+            Some(args) => format!("{}{}", name, self.generic_args(args)),
+            None => name,
+        }
+    }
+
+    fn generic_args(&self, args: &GenericArgs) -> String {
+        match args {
+            GenericArgs::AngleBracketed { args, bindings } => {
+                if args.is_empty() && bindings.is_empty() {
+                    return String::new();
+                }
+                let args = args.iter().map(|arg| match arg {
+                    GenericArg::Lifetime(lifetime) => lifetime.clone(),
+                    GenericArg::Type(ty) => self.ty(ty),
+                    GenericArg::Const(constant) => constant.expr.clone(),
+                    GenericArg::Infer => "_".to_string(),
+                });
+                let bindings = bindings.iter().map(|binding| match &binding.binding {
+                    TypeBindingKind::Equality(term) => format!(
+                        "{}{} = {}",
+                        binding.name,
+                        self.generic_args(&binding.args),
+                        self.term(term)
+                    ),
+                    TypeBindingKind::Constraint(bounds) => format!(
+                        "{}{}: {}",
+                        binding.name,
+                        self.generic_args(&binding.args),
+                        self.bounds(bounds)
+                    ),
+                });
+                format!("<{}>", args.chain(bindings).collect::<Vec<_>>().join(", "))
+            }
+            GenericArgs::Parenthesized { inputs, output } => {
+                let mut out = format!("({})", self.types(inputs));
+                if let Some(output) = output {
+                    write!(out, " -> {}", self.ty(output)).unwrap();
+                }
+                out
+            }
+        }
+    }
+
+    fn term(&self, term: &Term) -> String {
+        match term {
+            Term::Type(ty) => self.ty(ty),
+            Term::Constant(constant) => constant.expr.clone(),
+        }
+    }
+
+    fn bound(&self, bound: &GenericBound) -> String {
+        match bound {
+            GenericBound::TraitBound { trait_, generic_params, modifier } => {
+                let modifier = match modifier {
+                    TraitBoundModifier::None => "",
+                    TraitBoundModifier::Maybe => "?",
+                    TraitBoundModifier::MaybeConst => "~const ",
+                };
+                format!("{}{}{}", self.higher_ranked(generic_params), modifier, self.path(trait_))
+            }
+            GenericBound::Outlives(lifetime) => lifetime.clone(),
+        }
+    }
+
+    fn bounds(&self, bounds: &[GenericBound]) -> String {
+        bounds.iter().map(|bound| self.bound(bound)).collect::<Vec<_>>().join(" + ")
+    }
+
+    fn higher_ranked(&self, params: &[GenericParamDef]) -> String {
+        if params.is_empty() {
+            String::new()
+        } else {
+            let params = params.iter().map(|param| param.name.clone()).collect::<Vec<_>>();
+            format!("for<{}> ", params.join(", "))
+        }
+    }
+
+    fn fn_decl(&self, decl: &FnDecl) -> String {
+        let mut inputs = decl.inputs.iter().map(|(_, ty)| self.ty(ty)).collect::<Vec<_>>();
+        if decl.c_variadic {
+            inputs.push("...".to_string());
+        }
+        let mut out = format!("({})", inputs.join(", "));
+        if let Some(output) = &decl.output {
+            write!(out, " -> {}", self.ty(output)).unwrap();
+        }
+        out
+    }
+
+    /// Renders the generic parameters without their bounds, which are compared separately by
+    /// [`Api::generic_bounds`].
+    fn generic_params(&self, generics: &Generics) -> Vec<(String, bool)> {
+        generics
+            .params
+            .iter()
+            .filter(|param| {
+                !matches!(param.kind, GenericParamDefKind::Type { synthetic: true, .. })
+            })
+            .map(|param| match &param.kind {
+                GenericParamDefKind::Lifetime { .. } => (param.name.clone(), false),
+                GenericParamDefKind::Type { default, .. } => match default {
+                    Some(default) => (format!("{} = {}", param.name, self.ty(default)), true),
+                    None => (param.name.clone(), false),
+                },
+                GenericParamDefKind::Const { type_, default } => {
+                    (format!("const {}: {}", param.name, self.ty(type_)), default.is_some())
+                }
+            })
+            .collect()
+    }
+
+    /// Returns all bounds on the generic parameters, whether they are written inline or in a
+    /// where clause, as `T: Bound` strings.
+    fn generic_bounds(&self, generics: &Generics) -> BTreeSet<String> {
+        let mut bounds = BTreeSet::new();
+        for param in &generics.params {
+            match &param.kind {
+                GenericParamDefKind::Lifetime { outlives } => {
+                    bounds.extend(outlives.iter().map(|lt| format!("{}: {}", param.name, lt)));
+                }
+                GenericParamDefKind::Type { bounds: param_bounds, .. } => {
+                    bounds.extend(
+                        param_bounds
+                            .iter()
+                            .map(|bound| format!("{}: {}", param.name, self.bound(bound))),
+                    );
+                }
+                GenericParamDefKind::Const { .. } => {}
+            }
+        }
+        for predicate in &generics.where_predicates {
+            match predicate {
+                WherePredicate::BoundPredicate {
+                    type_,
+                    bounds: predicate_bounds,
+                    generic_params,
+                } => {
+                    let ty = format!("{}{}", self.higher_ranked(generic_params), self.ty(type_));
+                    bounds.extend(
+                        predicate_bounds
+                            .iter()
+                            .map(|bound| format!("{}: {}", ty, self.bound(bound))),
+                    );
+                }
+                WherePredicate::RegionPredicate { lifetime, bounds: predicate_bounds } => {
+                    bounds.extend(
+                        predicate_bounds
+                            .iter()
+                            .map(|bound| format!("{}: {}", lifetime, self.bound(bound))),
+                    );
+                }
+                WherePredicate::EqPredicate { lhs, rhs } => {
+                    bounds.insert(format!("{} == {}", self.ty(lhs), self.term(rhs)));
+                }
+            }
+        }
+        bounds
+    }
+}
+
+fn is_public(item: &Item) -> bool {
+    // Trait items and enum variants have the visibility of their parent.
+    matches!(item.visibility, Visibility::Public | Visibility::Default)
+}
+
+fn is_non_exhaustive(item: &Item) -> bool {
+    item.attrs.iter().any(|attr| attr == "#[non_exhaustive]")
+}
+
+fn header(header: &Header) -> String {
+    let mut out = String::new();
+    if header.const_ {
+        out.push_str("const ");
+    }
+    if header.async_ {
+        out.push_str("async ");
+    }
+    if header.unsafe_ {
+        out.push_str("unsafe ");
+    }
+    let (name, unwind) = match &header.abi {
+        Abi::Rust => return out,
+        Abi::C { unwind } => ("C", *unwind),
+        Abi::Cdecl { unwind } => ("cdecl", *unwind),
+        Abi::Stdcall { unwind } => ("stdcall", *unwind),
+        Abi::Fastcall { unwind } => ("fastcall", *unwind),
+        Abi::Aapcs { unwind } => ("aapcs", *unwind),
+        Abi::Win64 { unwind } => ("win64", *unwind),
+        Abi::SysV64 { unwind } => ("sysv64", *unwind),
+        Abi::System { unwind } => ("system", *unwind),
+        Abi::Other(name) => (name.as_str(), false),
+    };
+    write!(out, "extern \"{}{}\" ", name, if unwind { "-unwind" } else { "" }).unwrap();
+    out
+}
+
+fn is_synthetic_negative_impl(entry: &ApiEntry<'_>) -> bool {
+    matches!(
+        entry,
+        ApiEntry::Impl { item: Item { inner: ItemEnum::Impl(impl_), .. }, synthetic: true }
+            if impl_.negative
+    )
+}
+
+/// Returns the name of the trait that `item` implements, without its path.
+fn impl_trait_name(item: &Item) -> &str {
+    match &item.inner {
+        ItemEnum::Impl(rustdoc_json_types::Impl { trait_: Some(trait_), .. }) => {
+            trait_.name.rsplit("::").next().unwrap()
+        }
+        _ => unreachable!("trait impl entries are always trait impls"),
+    }
+}
+
+fn kind_name(inner: &ItemEnum) -> &'static str {
+    match inner {
+        ItemEnum::Module(_) => "module",
+        ItemEnum::ExternCrate { .. } => "extern crate",
+        ItemEnum::Import(_) => "re-export",
+        ItemEnum::Union(_) => "union",
+        ItemEnum::Struct(_) => "struct",
+        ItemEnum::StructField(_) => "field",
+        ItemEnum::Enum(_) => "enum",
+        ItemEnum::Variant(_) => "variant",
+        ItemEnum::Function(_) => "function",
+        ItemEnum::Trait(_) => "trait",
+        ItemEnum::TraitAlias(_) => "trait alias",
+        ItemEnum::Impl(_) => "impl",
+        ItemEnum::Typedef(_) => "type alias",
+        ItemEnum::OpaqueTy(_) => "opaque type",
+        ItemEnum::Constant(_) => "constant",
+        ItemEnum::Static(_) => "static",
+        ItemEnum::ForeignType => "foreign type",
+        ItemEnum::Macro(_) | ItemEnum::ProcMacro(_) => "macro",
+        ItemEnum::Primitive(_) => "primitive",
+        ItemEnum::AssocConst { .. } => "associated constant",
+        ItemEnum::AssocType { .. } => "associated type",
+    }
+}
+
+/// Returns the path of the item that contains the item at `path`.
+fn parent_path(path: &str) -> Option<&str> {
+    path.rsplit_once("::").map(|(parent, _)| parent)
+}
+
+/// Classifies the addition of the item at `path`. Most additions are minor, but some make it
+/// impossible to keep using the API in the same way.
+fn addition(old: &Api<'_>, new: &Api<'_>, path: &str, entry: &ApiEntry<'_>) -> (Severity, String) {
+    let item = match entry {
+        ApiEntry::Item(item) => item,
+        ApiEntry::Impl { item, synthetic: true } => {
+            return (Severity::Minor, format!("now implements `{}`", impl_trait_name(item)));
+        }
+        _ => return (Severity::Minor, "added".to_string()),
+    };
+    let old_parent = parent_path(path).and_then(|parent| old.entries.get(parent));
+    let new_parent = parent_path(path).and_then(|parent| new.entries.get(parent));
+    let (Some(ApiEntry::Item(old_parent)), Some(ApiEntry::Item(new_parent))) =
+        (old_parent, new_parent)
+    else {
+        return (Severity::Minor, "added".to_string());
+    };
+
+    match (&item.inner, &new_parent.inner) {
+        (ItemEnum::Function(function), ItemEnum::Trait(_)) if !function.has_body => {
+            (Severity::Major, "added required trait method".to_string())
+        }
+        (ItemEnum::AssocType { default: None, .. }, ItemEnum::Trait(_))
+        | (ItemEnum::AssocConst { default: None, .. }, ItemEnum::Trait(_)) => {
+            (Severity::Major, "added trait item without a default".to_string())
+        }
+        (ItemEnum::Variant(_), ItemEnum::Enum(_)) if !is_non_exhaustive(old_parent) => {
+            (Severity::Major, "added variant to an exhaustive enum".to_string())
+        }
+        (ItemEnum::StructField(_), _) if is_constructible(old_parent) => {
+            (Severity::Major, "added field to a struct that could be constructed".to_string())
+        }
+        _ => (Severity::Minor, "added".to_string()),
+    }
+}
+
+/// Whether dependent crates can construct `item` with a struct expression, and so break when a
+/// field is added.
+fn is_constructible(item: &Item) -> bool {
+    if is_non_exhaustive(item) {
+        return false;
+    }
+    match &item.inner {
+        ItemEnum::Struct(struct_) => match &struct_.kind {
+            StructKind::Unit => true,
+            StructKind::Tuple(fields) => fields.iter().all(Option::is_some),
+            StructKind::Plain { fields_stripped, .. } => !fields_stripped,
+        },
+        ItemEnum::Variant(_) => true,
+        _ => false,
+    }
+}
+
+fn compare(
+    old: &Api<'_>,
+    old_entry: &ApiEntry<'_>,
+    new: &Api<'_>,
+    new_entry: &ApiEntry<'_>,
+    path: &str,
+    changes: &mut Changes,
+) {
+    let (old_item, new_item) = match (old_entry, new_entry) {
+        (ApiEntry::Item(old_item), ApiEntry::Item(new_item)) => (old_item, new_item),
+        (ApiEntry::Reexport(old_import), ApiEntry::Reexport(new_import)) => {
+            if old_import.source != new_import.source {
+                changes.push(
+                    Severity::Major,
+                    path,
+                    format!(
+                        "now re-exports `{}` instead of `{}`",
+                        new_import.source, old_import.source
+                    ),
+                );
+            }
+            return;
+        }
+        (ApiEntry::Impl { item: old_item, .. }, ApiEntry::Impl { item: new_item, .. }) => {
+            if let (ItemEnum::Impl(old_impl), ItemEnum::Impl(new_impl)) =
+                (&old_item.inner, &new_item.inner)
+            {
+                // Synthesized auto trait impls carry the conditions under which the trait is
+                // implemented as bounds, so a new bound means fewer types implement it.
+                compare_bounds(
+                    old,
+                    &old_impl.generics,
+                    new,
+                    &new_impl.generics,
+                    path,
+                    false,
+                    changes,
+                );
+            }
+            return;
+        }
+        (ApiEntry::Reexport(import), ApiEntry::Item(item)) => {
+            changes.push(
+                Severity::Major,
+                path,
+                format!(
+                    "changed from a re-export of `{}` to a {}",
+                    import.source,
+                    kind_name(&item.inner)
+                ),
+            );
+            return;
+        }
+        (ApiEntry::Item(item), ApiEntry::Reexport(import)) => {
+            changes.push(
+                Severity::Major,
+                path,
+                format!(
+                    "changed from a {} to a re-export of `{}`",
+                    kind_name(&item.inner),
+                    import.source
+                ),
+            );
+            return;
+        }
+        _ => unreachable!("impls and items never share a path"),
+    };
+
+    if old_item.deprecation.is_none() && new_item.deprecation.is_some() {
+        changes.push(Severity::Minor, path, "deprecated".to_string());
+    }
+    if !is_non_exhaustive(old_item) && is_non_exhaustive(new_item) {
+        changes.push(Severity::Major, path, "became `#[non_exhaustive]`".to_string());
+    }
+
+    let mut changed = |old_value: String, new_value: String, what: &str| {
+        if old_value != new_value {
+            changes.push(
+                Severity::Major,
+                path,
+                format!("{} changed from `{}` to `{}`", what, old_value, new_value),
+            );
+        }
+    };
+    match (&old_item.inner, &new_item.inner) {
+        (ItemEnum::Function(old_fn), ItemEnum::Function(new_fn)) => {
+            changed(
+                format!("{}fn{}", header(&old_fn.header), old.fn_decl(&old_fn.decl)),
+                format!("{}fn{}", header(&new_fn.header), new.fn_decl(&new_fn.decl)),
+                "signature",
+            );
+            if old_fn.has_body && !new_fn.has_body {
+                changes.push(Severity::Major, path, "trait method no longer has a default".into());
+            }
+            compare_generics(old, &old_fn.generics, new, &new_fn.generics, path, false, changes);
+        }
+        (ItemEnum::StructField(old_ty), ItemEnum::StructField(new_ty)) => {
+            changed(old.ty(old_ty), new.ty(new_ty), "type");
+        }
+        (ItemEnum::Struct(old_struct), ItemEnum::Struct(new_struct)) => {
+            let kind = |kind: &StructKind| match kind {
+                StructKind::Unit => "unit struct",
+                StructKind::Tuple(_) => "tuple struct",
+                StructKind::Plain { .. } => "struct with named fields",
+            };
+            changed(kind(&old_struct.kind).to_string(), kind(&new_struct.kind).to_string(), "kind");
+            if is_constructible(old_item) && !is_constructible(new_item) {
+                changes.push(Severity::Major, path, "can no longer be constructed".to_string());
+            }
+            compare_generics(
+                old,
+                &old_struct.generics,
+                new,
+                &new_struct.generics,
+                path,
+                false,
+                changes,
+            );
+        }
+        (ItemEnum::Union(old_union), ItemEnum::Union(new_union)) => {
+            compare_generics(
+                old,
+                &old_union.generics,
+                new,
+                &new_union.generics,
+                path,
+                false,
+                changes,
+            );
+        }
+        (ItemEnum::Enum(old_enum), ItemEnum::Enum(new_enum)) => {
+            compare_generics(
+                old,
+                &old_enum.generics,
+                new,
+                &new_enum.generics,
+                path,
+                false,
+                changes,
+            );
+        }
+        (ItemEnum::Variant(old_variant), ItemEnum::Variant(new_variant)) => {
+            let kind = |variant: &Variant| match variant {
+                Variant::Plain(_) => "unit variant",
+                Variant::Tuple(_) => "tuple variant",
+                Variant::Struct { .. } => "struct variant",
+            };
+            changed(kind(old_variant).to_string(), kind(new_variant).to_string(), "kind");
+        }
+        (ItemEnum::Trait(old_trait), ItemEnum::Trait(new_trait)) => {
+            if !old_trait.is_unsafe && new_trait.is_unsafe {
+                changes.push(Severity::Major, path, "became an unsafe trait".to_string());
+            }
+            let old_supertraits: BTreeSet<_> =
+                old_trait.bounds.iter().map(|bound| old.bound(bound)).collect();
+            let new_supertraits: BTreeSet<_> =
+                new_trait.bounds.iter().map(|bound| new.bound(bound)).collect();
+            for bound in new_supertraits.difference(&old_supertraits) {
+                changes.push(Severity::Major, path, format!("added supertrait `{}`", bound));
+            }
+            for bound in old_supertraits.difference(&new_supertraits) {
+                changes.push(Severity::Major, path, format!("removed supertrait `{}`", bound));
+            }
+            compare_generics(
+                old,
+                &old_trait.generics,
+                new,
+                &new_trait.generics,
+                path,
+                true,
+                changes,
+            );
+        }
+        (ItemEnum::Typedef(old_typedef), ItemEnum::Typedef(new_typedef)) => {
+            changed(old.ty(&old_typedef.type_), new.ty(&new_typedef.type_), "type");
+            compare_generics(
+                old,
+                &old_typedef.generics,
+                new,
+                &new_typedef.generics,
+                path,
+                false,
+                changes,
+            );
+        }
+        (ItemEnum::Constant(old_constant), ItemEnum::Constant(new_constant)) => {
+            changed(old.ty(&old_constant.type_), new.ty(&new_constant.type_), "type");
+        }
+        (ItemEnum::Static(old_static), ItemEnum::Static(new_static)) => {
+            changed(old.ty(&old_static.type_), new.ty(&new_static.type_), "type");
+            if old_static.mutable != new_static.mutable {
+                let description =
+                    if new_static.mutable { "became mutable" } else { "became immutable" };
+                changes.push(Severity::Major, path, description.to_string());
+            }
+        }
+        (
+            ItemEnum::AssocConst { type_: old_ty, default: old_default },
+            ItemEnum::AssocConst { type_: new_ty, default: new_default },
+        ) => {
+            changed(old.ty(old_ty), new.ty(new_ty), "type");
+            if old_default.is_some() && new_default.is_none() {
+                changes.push(Severity::Major, path, "no longer has a default".to_string());
+            }
+        }
+        (
+            ItemEnum::AssocType {
+                generics: old_generics,
+                bounds: old_bounds,
+                default: old_default,
+            },
+            ItemEnum::AssocType {
+                generics: new_generics,
+                bounds: new_bounds,
+                default: new_default,
+            },
+        ) => {
+            changed(old.bounds(old_bounds), new.bounds(new_bounds), "bounds");
+            if old_default.is_some() && new_default.is_none() {
+                changes.push(Severity::Major, path, "no longer has a default".to_string());
+            }
+            compare_generics(old, old_generics, new, new_generics, path, true, changes);
+        }
+        (old_inner, new_inner) => {
+            let (old_kind, new_kind) = (kind_name(old_inner), kind_name(new_inner));
+            if old_kind != new_kind {
+                changes.push(
+                    Severity::Major,
+                    path,
+                    format!("changed from a {} to a {}", old_kind, new_kind),
+                );
+            }
+        }
+    }
+}
+
+/// Compares the generic parameters and bounds of an item. `invariant` is set for traits and
+/// their items, whose bounds constrain implementors, so that removing a bound is breaking too.
+fn compare_generics(
+    old: &Api<'_>,
+    old_generics: &Generics,
+    new: &Api<'_>,
+    new_generics: &Generics,
+    path: &str,
+    invariant: bool,
+    changes: &mut Changes,
+) {
+    let old_params = old.generic_params(old_generics);
+    let new_params = new.generic_params(new_generics);
+    if old_params != new_params {
+        let only_defaulted_added = new_params.starts_with(&old_params)
+            && new_params[old_params.len()..].iter().all(|&(_, has_default)| has_default);
+        let render = |params: &[(String, bool)]| {
+            params.iter().map(|(param, _)| param.as_str()).collect::<Vec<_>>().join(", ")
+        };
+        let severity = if only_defaulted_added { Severity::Minor } else { Severity::Major };
+        changes.push(
+            severity,
+            path,
+            format!(
+                "generic parameters changed from `<{}>` to `<{}>`",
+                render(&old_params),
+                render(&new_params)
+            ),
+        );
+    }
+
+    compare_bounds(old, old_generics, new, new_generics, path, invariant, changes);
+}
+
+/// Compares the bounds on the generic parameters of an item. See [`compare_generics`] for the
+/// meaning of `invariant`.
+fn compare_bounds(
+    old: &Api<'_>,
+    old_generics: &Generics,
+    new: &Api<'_>,
+    new_generics: &Generics,
+    path: &str,
+    invariant: bool,
+    changes: &mut Changes,
+) {
+    let old_bounds = old.generic_bounds(old_generics);
+    let new_bounds = new.generic_bounds(new_generics);
+    for bound in new_bounds.difference(&old_bounds) {
+        changes.push(Severity::Major, path, format!("added trait bound `{}`", bound));
+    }
+    for bound in old_bounds.difference(&new_bounds) {
+        let severity = if invariant { Severity::Major } else { Severity::Minor };
+        changes.push(severity, path, format!("removed trait bound `{}`", bound));
+    }
+}
@@ -4,7 +4,9 @@
 //! output. See [the RFC](https://github.com/rust-lang/rfcs/pull/2963) and the [`types`] module
 //! docs for usage and details.
 
+mod call_graph;
 mod conversions;
+pub(crate) mod diff;
 mod import_finder;
 
 use std::cell::RefCell;
@@ -41,6 +43,11 @@ pub(crate) struct JsonRenderer<'tcx> {
     out_path: PathBuf,
     cache: Rc<Cache>,
     imported_items: FxHashSet<DefId>,
+    /// The JSON output of another version of this crate to compare the public API against, if
+    /// `--api-diff-against` was passed.
+    api_diff_against: Option<PathBuf>,
+    /// The items that each function body refers to, if `--json-call-graph` was passed.
+    call_graph: Option<Rc<RefCell<FxHashMap<types::Id, Vec<types::Reference>>>>>,
 }
 
 impl<'tcx> JsonRenderer<'tcx> {
@@ -125,6 +132,10 @@ fn init(
                 out_path: options.output,
                 cache: Rc::new(cache),
                 imported_items,
+                api_diff_against: options.api_diff_against,
+                call_graph: options
+                    .json_call_graph
+                    .then(|| Rc::new(RefCell::new(FxHashMap::default()))),
             },
             krate,
         ))
@@ -154,6 +165,18 @@ fn item(&mut self, item: clean::Item) -> Result<(), Error> {
         let name = item.name;
         let item_id = item.item_id;
         if let Some(mut new_item) = self.convert_item(item) {
+            if let Some(call_graph) = &self.call_graph {
+                let local_def_id = item_id.as_def_id().and_then(|def_id| def_id.as_local());
+                if let (types::ItemEnum::Function(function), Some(def_id)) =
+                    (&new_item.inner, local_def_id)
+                {
+                    if function.has_body {
+                        let references = call_graph::references(self.tcx, def_id);
+                        call_graph.borrow_mut().insert(new_item.id.clone(), references);
+                    }
+                }
+            }
+
             let can_be_ignored = match new_item.inner {
                 types::ItemEnum::Trait(ref mut t) => {
                     t.implementations = self.get_trait_implementors(item_id.expect_def_id());
@@ -274,6 +297,10 @@ fn after_krate(&mut self) -> Result<(), Error> {
                     )
                 })
                 .collect(),
+            call_graph: self
+                .call_graph
+                .as_ref()
+                .map(|call_graph| call_graph.borrow().clone().into_iter().collect()),
             format_version: types::FORMAT_VERSION,
         };
         let out_dir = self.out_path.clone();
@@ -286,6 +313,12 @@ fn after_krate(&mut self) -> Result<(), Error> {
         serde_json::ser::to_writer(&mut file, &output).unwrap();
         try_err!(file.flush(), p);
 
+        if let Some(old_path) = &self.api_diff_against {
+            let old = diff::load_crate(old_path)
+                .map_err(|error| Error { file: PathBuf::new(), error })?;
+            print!("{}", diff::render_report(&diff::diff(&old, &output)));
+        }
+
         Ok(())
     }
 
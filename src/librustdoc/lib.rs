@@ -118,6 +118,7 @@ macro_rules! map {
 mod markdown;
 mod passes;
 mod scrape_examples;
+mod text;
 mod theme;
 mod visit;
 mod visit_ast;
@@ -522,6 +523,21 @@ fn opts() -> Vec<RustcOptGroup> {
                 "calculate percentage of public items with documentation",
             )
         }),
+        unstable("coverage-details", |o| {
+            o.optflag(
+                "",
+                "coverage-details",
+                "break the --show-coverage report down by item kind and list undocumented items",
+            )
+        }),
+        unstable("coverage-threshold", |o| {
+            o.optmulti(
+                "",
+                "coverage-threshold",
+                "fail if less than PERCENT of the items of KIND (or of all items) are documented",
+                "[KIND=]PERCENT",
+            )
+        }),
         unstable("enable-per-target-ignores", |o| {
             o.optflagmulti(
                 "",
@@ -573,6 +589,13 @@ fn opts() -> Vec<RustcOptGroup> {
         unstable("nocapture", |o| {
             o.optflag("", "nocapture", "Don't capture stdout and stderr of tests")
         }),
+        unstable("merge-doctests", |o| {
+            o.optflag(
+                "",
+                "merge-doctests",
+                "Compile the doctests that don't need a crate of their own into one binary",
+            )
+        }),
         unstable("generate-link-to-definition", |o| {
             o.optflag(
                 "",
@@ -607,6 +630,21 @@ fn opts() -> Vec<RustcOptGroup> {
                 "path to function call information (for displaying examples in the documentation)",
             )
         }),
+        unstable("api-diff-against", |o| {
+            o.optopt(
+                "",
+                "api-diff-against",
+                "compare the public API with the JSON output of another version of the crate",
+                "PATH",
+            )
+        }),
+        unstable("json-call-graph", |o| {
+            o.optflag(
+                "",
+                "json-call-graph",
+                "include the items that each function body refers to in the JSON output",
+            )
+        }),
         // deprecated / removed options
         unstable("disable-minification", |o| o.optflagmulti("", "disable-minification", "removed")),
         stable("plugin-path", |o| {
@@ -736,6 +774,12 @@ fn main_args(at_args: &[String]) -> MainResult {
         &options.unstable_opts,
     );
 
+    if options.json_input() {
+        // There is no crate to document, only two JSON files to compare.
+        let old = render_options.api_diff_against.expect("checked by `Options::from_matches`");
+        return wrap_return(&diag, json::diff::run(&old, &options.input));
+    }
+
     match (options.should_test, options.markdown_input()) {
         (true, true) => return wrap_return(&diag, markdown::test(options)),
         (true, false) => return doctest::run(options),
@@ -862,6 +906,22 @@ fn main_args(at_args: &[String]) -> MainResult {
                     config::OutputFormat::Json => sess.time("render_json", || {
                         run_renderer::<json::JsonRenderer<'_>>(krate, render_opts, cache, tcx)
                     }),
+                    config::OutputFormat::Markdown => sess.time("render_markdown", || {
+                        run_renderer::<text::TextRenderer<'_, text::Markdown>>(
+                            krate,
+                            render_opts,
+                            cache,
+                            tcx,
+                        )
+                    }),
+                    config::OutputFormat::Man => sess.time("render_man", || {
+                        run_renderer::<text::TextRenderer<'_, text::Man>>(
+                            krate,
+                            render_opts,
+                            cache,
+                            tcx,
+                        )
+                    }),
                 }
             })
         })
@@ -146,6 +146,7 @@ pub(crate) fn test(options: Options) -> Result<(), String> {
     let codes = ErrorCodes::from(options.unstable_features.is_nightly_build());
 
     find_testable_code(&input_str, &mut collector, codes, options.enable_per_target_ignores, None);
+    collector.merge_doctests();
 
     crate::doctest::run_tests(options.test_args, options.nocapture, collector.tests);
     Ok(())
@@ -1,6 +1,8 @@
 //! Calculates information used for the --show-coverage flag.
 use crate::clean;
 use crate::core::DocContext;
+use crate::formats::item_type::ItemType;
+use crate::html::format::join_with_double_colon;
 use crate::html::markdown::{find_testable_code, ErrorCodes};
 use crate::passes::check_doc_test_visibility::{should_have_doc_example, Tests};
 use crate::passes::Pass;
@@ -10,7 +12,7 @@
 use rustc_middle::lint::LintLevelSource;
 use rustc_middle::ty::DefIdTree;
 use rustc_session::lint;
-use rustc_span::FileName;
+use rustc_span::{FileName, Symbol};
 use serde::Serialize;
 
 use std::collections::BTreeMap;
@@ -23,10 +25,19 @@
 };
 
 fn calculate_doc_coverage(krate: clean::Crate, ctx: &mut DocContext<'_>) -> clean::Crate {
-    let mut calc = CoverageCalculator { items: Default::default(), ctx };
+    let mut calc = CoverageCalculator {
+        items: Default::default(),
+        kinds: Default::default(),
+        undocumented: Vec::new(),
+        path: Vec::new(),
+        ctx,
+    };
     calc.visit_crate(&krate);
+    calc.undocumented
+        .sort_by(|a, b| (&a.span.filename, a.span.begin).cmp(&(&b.span.filename, b.span.begin)));
 
     calc.print_results();
+    calc.check_thresholds();
 
     krate
 }
@@ -101,8 +112,39 @@ fn add_assign(&mut self, rhs: Self) {
     }
 }
 
+/// An item that should have docs but doesn't, listed by `--coverage-details`.
+#[derive(Serialize, Debug)]
+struct UndocumentedItem {
+    path: String,
+    /// The kind of the item, as named in `--coverage-threshold` options.
+    kind: &'static str,
+    span: Span,
+}
+
+/// The location of an [`UndocumentedItem`], like the spans of the JSON output.
+#[derive(Serialize, Debug)]
+struct Span {
+    filename: String,
+    /// Line (1-based) and column (0-based) of the first character of the item.
+    begin: (usize, usize),
+    /// Line (1-based) and column (0-based) of the character after the item.
+    end: (usize, usize),
+}
+
+/// The `--show-coverage` JSON output with `--coverage-details`.
+#[derive(Serialize)]
+struct DetailedReport<'a> {
+    files: BTreeMap<String, &'a ItemCount>,
+    kinds: BTreeMap<&'static str, &'a ItemCount>,
+    undocumented: &'a [UndocumentedItem],
+}
+
 struct CoverageCalculator<'a, 'b> {
     items: BTreeMap<FileName, ItemCount>,
+    kinds: BTreeMap<ItemType, ItemCount>,
+    undocumented: Vec<UndocumentedItem>,
+    /// The path of the item being visited. Impls are named after the type they are for.
+    path: Vec<Symbol>,
     ctx: &'a mut DocContext<'b>,
 }
 
@@ -118,14 +160,21 @@ fn limit_filename_len(filename: String) -> String {
 
 impl<'a, 'b> CoverageCalculator<'a, 'b> {
     fn to_json(&self) -> String {
-        serde_json::to_string(
-            &self
-                .items
-                .iter()
-                .map(|(k, v)| (k.prefer_local().to_string(), v))
-                .collect::<BTreeMap<String, &ItemCount>>(),
-        )
-        .expect("failed to convert JSON data to string")
+        let files = self
+            .items
+            .iter()
+            .map(|(k, v)| (k.prefer_local().to_string(), v))
+            .collect::<BTreeMap<String, &ItemCount>>();
+        let json = if self.ctx.render_options.coverage_details {
+            serde_json::to_string(&DetailedReport {
+                files,
+                kinds: self.kinds.iter().map(|(k, v)| (k.as_str(), v)).collect(),
+                undocumented: &self.undocumented,
+            })
+        } else {
+            serde_json::to_string(&files)
+        };
+        json.expect("failed to convert JSON data to string")
     }
 
     fn print_results(&self) {
@@ -152,12 +201,16 @@ fn print_table_record(
             );
         }
 
-        print_table_line();
-        println!(
-            "| {:<35} | {:>10} | {:>10} | {:>10} | {:>10} |",
-            "File", "Documented", "Percentage", "Examples", "Percentage",
-        );
-        print_table_line();
+        fn print_table_header(name: &str) {
+            print_table_line();
+            println!(
+                "| {:<35} | {:>10} | {:>10} | {:>10} | {:>10} |",
+                name, "Documented", "Percentage", "Examples", "Percentage",
+            );
+            print_table_line();
+        }
+
+        print_table_header("File");
 
         for (file, &count) in &self.items {
             if let Some(percentage) = count.percentage() {
@@ -180,6 +233,76 @@ fn print_table_record(
             total.examples_percentage().unwrap_or(0.0),
         );
         print_table_line();
+
+        if !self.ctx.render_options.coverage_details {
+            return;
+        }
+
+        println!();
+        print_table_header("Kind");
+        for (kind, &count) in &self.kinds {
+            if let Some(percentage) = count.percentage() {
+                print_table_record(
+                    kind.as_str(),
+                    count,
+                    percentage,
+                    count.examples_percentage().unwrap_or(0.),
+                );
+            }
+        }
+        print_table_line();
+
+        if !self.undocumented.is_empty() {
+            println!("\nUndocumented items:");
+            for item in &self.undocumented {
+                let (line, col) = item.span.begin;
+                println!(
+                    "    {} {} ({}:{}:{})",
+                    item.kind,
+                    item.path,
+                    item.span.filename,
+                    line,
+                    col + 1
+                );
+            }
+        }
+    }
+
+    /// Emits an error for each `--coverage-threshold` option that isn't met.
+    fn check_thresholds(&self) {
+        let mut total = ItemCount::default();
+        for &count in self.items.values() {
+            total += count;
+        }
+        for threshold in &self.ctx.render_options.coverage_thresholds {
+            let count = match threshold.kind {
+                Some(kind) => self.kinds.get(&kind).copied().unwrap_or_default(),
+                None => total,
+            };
+            // Kinds without items to document always meet their threshold.
+            let Some(percentage) = count.percentage() else { continue };
+            if percentage >= threshold.percentage {
+                continue;
+            }
+            let items = match threshold.kind {
+                Some(kind) => format!("`{}` items", kind),
+                None => "all items".to_owned(),
+            };
+            let msg = format!(
+                "documentation coverage of {} is {:.1}% ({} of {}), below the threshold of {}%",
+                items, percentage, count.with_docs, count.total, threshold.percentage
+            );
+            self.ctx.sess().struct_err(&msg).emit();
+        }
+    }
+}
+
+/// Returns the name of the type an impl is for, which its items are listed under.
+fn impl_name(impl_: &clean::Impl) -> Option<Symbol> {
+    match impl_.for_ {
+        clean::Type::Path { ref path } => Some(path.last()),
+        clean::Type::Primitive(prim) => Some(prim.as_sym()),
+        _ => None,
     }
 }
 
@@ -263,16 +386,47 @@ fn visit_item(&mut self, i: &clean::Item) {
                 if let Some(span) = i.span(self.ctx.tcx) {
                     let filename = span.filename(self.ctx.sess());
                     debug!("counting {:?} {:?} in {:?}", i.type_(), i.name, filename);
+                    if should_have_docs && !has_docs {
+                        let sess = self.ctx.sess();
+                        let (lo, hi) = (span.lo(sess), span.hi(sess));
+                        let mut path = self.path.clone();
+                        path.extend(i.name);
+                        self.undocumented.push(UndocumentedItem {
+                            path: join_with_double_colon(&path),
+                            kind: i.type_().as_str(),
+                            span: Span {
+                                filename: filename.prefer_local().to_string(),
+                                begin: (lo.line, lo.col.to_usize()),
+                                end: (hi.line, hi.col.to_usize()),
+                            },
+                        });
+                    }
+
+                    let should_have_example = should_have_doc_example(self.ctx, i);
                     self.items.entry(filename).or_default().count_item(
                         has_docs,
                         has_doc_example,
-                        should_have_doc_example(self.ctx, i),
+                        should_have_example,
+                        should_have_docs,
+                    );
+                    self.kinds.entry(i.type_()).or_default().count_item(
+                        has_docs,
+                        has_doc_example,
+                        should_have_example,
                         should_have_docs,
                     );
                 }
             }
         }
 
-        self.visit_item_recur(i)
+        let name = match *i.kind {
+            clean::ImplItem(ref impl_) => impl_name(impl_),
+            _ => i.name,
+        };
+        self.path.extend(name);
+        self.visit_item_recur(i);
+        if name.is_some() {
+            self.path.pop();
+        }
     }
 }
@@ -0,0 +1,270 @@
+//! The man page output format. Every page is written to `man3/<path>.3rs` in the output
+//! directory, with the segments of the item's path joined by dots, so that the output directory
+//! can be added to `MANPATH`.
+
+use std::path::PathBuf;
+
+use pulldown_cmark::{Event, HeadingLevel, Parser, Tag};
+use rustc_data_structures::fx::{FxHashMap, FxHashSet};
+use rustc_span::Symbol;
+
+use crate::formats::cache::Cache;
+use crate::formats::item_type::ItemType;
+use crate::html::format::join_with_double_colon;
+use crate::html::markdown::main_body_opts;
+
+use super::{LinkTarget, Page, TextFormat};
+
+/// The man page section of Rust API documentation.
+const SECTION: &str = "3rs";
+
+pub(crate) struct Man {
+    /// The paths that more than one page would be named after, like a module and a macro of the
+    /// same name. The pages of the items that aren't modules get the item type as a suffix.
+    ambiguous_paths: FxHashSet<Vec<Symbol>>,
+}
+
+impl Man {
+    fn page_name(&self, path: &[Symbol], ty: ItemType) -> String {
+        let mut name = path.iter().map(|s| s.as_str()).collect::<Vec<_>>().join(".");
+        if ty != ItemType::Module && self.ambiguous_paths.contains(path) {
+            name.push('.');
+            name.push_str(ty.as_str());
+        }
+        name
+    }
+}
+
+impl TextFormat for Man {
+    const NAME: &'static str = "man";
+
+    fn new(cache: &Cache) -> Self {
+        let mut pages = FxHashMap::<&[Symbol], usize>::default();
+        for (path, ty) in cache.paths.values() {
+            // Variants are documented on the page of their enum.
+            if *ty != ItemType::Variant {
+                *pages.entry(path.as_slice()).or_default() += 1;
+            }
+        }
+        let ambiguous_paths =
+            pages.into_iter().filter(|&(_, count)| count > 1).map(|(path, _)| path.to_vec());
+        Man { ambiguous_paths: ambiguous_paths.collect() }
+    }
+
+    fn href(&self, _target: &LinkTarget<'_>, _from: &[Symbol]) -> Option<String> {
+        None
+    }
+
+    fn render(&self, page: &Page<'_>, cache: &Cache) -> (PathBuf, String) {
+        let name = self.page_name(page.path, page.ty);
+        let mut file = PathBuf::from("man3");
+        file.push(format!("{}.{}", name, SECTION));
+
+        let krate = page.path[0];
+        let source = match cache.crate_version {
+            Some(ref version) => format!("{} {}", krate, version),
+            None => krate.to_string(),
+        };
+        let mut roff = Roff::default();
+        roff.request(&format!(
+            ".TH {} {} \"\" {} \"Rust Library Documentation\"",
+            quote(&name),
+            SECTION,
+            quote(&source)
+        ));
+        roff.request(".SH NAME");
+        roff.text(&format!("{} {}", page.kind, join_with_double_colon(page.path)));
+        if !page.summary.is_empty() {
+            roff.out.push_str(" \\- ");
+            roff.text(&page.summary);
+        }
+        if let Some(ref declaration) = page.declaration {
+            roff.request(".SH SYNOPSIS");
+            roff.preformatted(declaration);
+        }
+        if !page.body.is_empty() {
+            if !page.body.trim_start().starts_with("## ") {
+                roff.request(".SH DESCRIPTION");
+            }
+            roff.markdown(&page.body);
+        }
+        if !page.children.is_empty() {
+            roff.request(".SH \"SEE ALSO\"");
+            for (i, (path, ty)) in page.children.iter().enumerate() {
+                let comma = if i + 1 < page.children.len() { "," } else { "" };
+                roff.request(&format!(
+                    ".BR {} ({}){}",
+                    escape(&self.page_name(path, *ty)),
+                    SECTION,
+                    comma
+                ));
+            }
+        }
+        roff.out.push('\n');
+        (file, roff.out)
+    }
+}
+
+/// A man page being written in the roff language, with the man(7) macros.
+#[derive(Default)]
+struct Roff {
+    out: String,
+    /// The stack of lists being written. Ordered lists have the number of their next item.
+    lists: Vec<Option<u64>>,
+    /// Whether the next paragraph starts a list item, and so doesn't need a request of its own.
+    in_new_list_item: bool,
+    /// The text of the heading being written.
+    heading: Option<String>,
+    /// Whether the current table row has cells yet.
+    in_table_row: bool,
+}
+
+impl Roff {
+    /// Writes a request on a line of its own.
+    fn request(&mut self, request: &str) {
+        if !self.out.is_empty() && !self.out.ends_with('\n') {
+            self.out.push('\n');
+        }
+        self.out.push_str(request);
+        self.out.push('\n');
+    }
+
+    fn text(&mut self, text: &str) {
+        if let Some(ref mut heading) = self.heading {
+            heading.push_str(text);
+            return;
+        }
+        for (i, line) in text.split('\n').enumerate() {
+            if i > 0 {
+                self.out.push('\n');
+            }
+            // Lines that start with a control character would be interpreted as requests.
+            if (self.out.is_empty() || self.out.ends_with('\n'))
+                && (line.starts_with('.') || line.starts_with('\''))
+            {
+                self.out.push_str("\\&");
+            }
+            self.out.push_str(&escape(line));
+        }
+    }
+
+    fn preformatted(&mut self, code: &str) {
+        self.request(".nf");
+        self.text(code.trim_end_matches('\n'));
+        self.request(".fi");
+    }
+
+    /// Starts a paragraph, unless it is the first one of a list item.
+    fn paragraph(&mut self) {
+        if self.in_new_list_item {
+            self.in_new_list_item = false;
+        } else if self.lists.is_empty() {
+            self.request(".PP");
+        } else {
+            self.request(".IP");
+        }
+    }
+
+    fn markdown(&mut self, md: &str) {
+        for event in Parser::new_ext(md, main_body_opts()) {
+            match event {
+                Event::Start(Tag::Paragraph) => self.paragraph(),
+                Event::Start(Tag::Heading(..)) => self.heading = Some(String::new()),
+                Event::End(Tag::Heading(level, ..)) => {
+                    let heading = self.heading.take().unwrap_or_default();
+                    if level <= HeadingLevel::H2 {
+                        self.request(&format!(".SH {}", quote(&heading.to_uppercase())));
+                    } else {
+                        self.request(&format!(".SS {}", quote(&heading)));
+                    }
+                }
+                Event::Start(Tag::BlockQuote) => self.request(".RS 4"),
+                Event::End(Tag::BlockQuote) => self.request(".RE"),
+                Event::Start(Tag::CodeBlock(_)) => {
+                    self.paragraph();
+                    self.request(".RS 4");
+                    self.request(".nf");
+                }
+                Event::End(Tag::CodeBlock(_)) => {
+                    self.request(".fi");
+                    self.request(".RE");
+                }
+                Event::Start(Tag::List(first)) => {
+                    if !self.lists.is_empty() {
+                        self.request(".RS 2");
+                    }
+                    self.lists.push(first);
+                }
+                Event::End(Tag::List(_)) => {
+                    self.lists.pop();
+                    if !self.lists.is_empty() {
+                        self.request(".RE");
+                    }
+                }
+                Event::Start(Tag::Item) => {
+                    match self.lists.last_mut() {
+                        Some(Some(number)) => {
+                            self.request(&format!(".IP {}. 4", number));
+                            *number += 1;
+                        }
+                        _ => self.request(".IP \\(bu 2"),
+                    }
+                    self.in_new_list_item = true;
+                }
+                Event::Start(Tag::Table(_)) => {
+                    self.paragraph();
+                    self.request(".nf");
+                }
+                Event::End(Tag::Table(_)) => self.request(".fi"),
+                Event::End(Tag::TableHead | Tag::TableRow) => {
+                    self.out.push('\n');
+                    self.in_table_row = false;
+                }
+                Event::Start(Tag::TableCell) => {
+                    if self.in_table_row {
+                        self.out.push_str(" | ");
+                    }
+                    self.in_table_row = true;
+                }
+                Event::Start(Tag::FootnoteDefinition(name)) => {
+                    self.paragraph();
+                    self.text(&format!("[{}] ", name));
+                }
+                Event::Start(Tag::Emphasis) => self.font("\\fI"),
+                Event::Start(Tag::Strong) => self.font("\\fB"),
+                Event::End(Tag::Emphasis | Tag::Strong) => self.font("\\fP"),
+                Event::Text(text) => self.text(&text),
+                Event::Code(code) => {
+                    self.font("\\fB");
+                    self.text(&code);
+                    self.font("\\fP");
+                }
+                Event::FootnoteReference(name) => self.text(&format!("[{}]", name)),
+                Event::SoftBreak => self.text("\n"),
+                Event::HardBreak => self.request(".br"),
+                Event::Rule => self.request(".PP"),
+                Event::TaskListMarker(checked) => self.text(if checked { "[x] " } else { "[ ] " }),
+                // Only the text of links and images is shown. Anchors and other HTML are dropped.
+                Event::Html(_) | Event::Start(_) | Event::End(_) => {}
+            }
+        }
+    }
+
+    /// Changes the font, unless a heading is being written since headings have a font of their
+    /// own.
+    fn font(&mut self, escape: &str) {
+        if self.heading.is_none() {
+            self.out.push_str(escape);
+        }
+    }
+}
+
+/// Escapes the characters of `text` that roff would interpret.
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\e").replace('-', "\\-")
+}
+
+/// Returns `text` as a single argument of a request.
+fn quote(text: &str) -> String {
+    format!("\"{}\"", escape(text).replace('"', "\\(dq"))
+}
@@ -0,0 +1,81 @@
+//! The Markdown output format, which writes a `.md` file for every page of the HTML output.
+
+use std::path::PathBuf;
+
+use rustc_span::Symbol;
+
+use crate::formats::cache::Cache;
+use crate::formats::item_type::ItemType;
+use crate::html::format::{href_relative_parts, join_with_double_colon};
+
+use super::{code_block, LinkTarget, Page, TextFormat};
+
+pub(crate) struct Markdown;
+
+impl TextFormat for Markdown {
+    const NAME: &'static str = "markdown";
+
+    fn new(_cache: &Cache) -> Self {
+        Markdown
+    }
+
+    fn href(&self, target: &LinkTarget<'_>, from: &[Symbol]) -> Option<String> {
+        let module = module_path(target.path, target.ty);
+        let mut href = String::new();
+        let extension = match target.remote {
+            // Other crates are linked to their HTML documentation.
+            Some(root) => {
+                href.push_str(root.trim_end_matches('/'));
+                for name in module {
+                    href.push('/');
+                    href.push_str(name.as_str());
+                }
+                href.push('/');
+                "html"
+            }
+            None => {
+                for name in href_relative_parts(module, from) {
+                    href.push_str(name.as_str());
+                    href.push('/');
+                }
+                "md"
+            }
+        };
+        href.push_str(&file_name(target.path, target.ty, extension));
+        href.push_str(&target.fragment);
+        Some(href)
+    }
+
+    fn render(&self, page: &Page<'_>, _cache: &Cache) -> (PathBuf, String) {
+        let mut file: PathBuf =
+            module_path(page.path, page.ty).iter().map(|s| s.as_str()).collect();
+        file.push(file_name(page.path, page.ty, "md"));
+
+        let mut contents = format!("# {} `{}`\n", page.kind, join_with_double_colon(page.path));
+        if let Some(ref declaration) = page.declaration {
+            contents.push('\n');
+            contents.push_str(&code_block(declaration));
+            contents.push('\n');
+        }
+        if !page.body.is_empty() {
+            contents.push('\n');
+            contents.push_str(page.body.trim_start());
+            contents.push('\n');
+        }
+        (file, contents)
+    }
+}
+
+/// Returns the path of the directory of the page for `path`. Like in the HTML output, every
+/// module has a directory of its own that contains its `index.md` and the pages of its items.
+fn module_path(path: &[Symbol], ty: ItemType) -> &[Symbol] {
+    if ty == ItemType::Module { path } else { &path[..path.len() - 1] }
+}
+
+fn file_name(path: &[Symbol], ty: ItemType, extension: &str) -> String {
+    if ty == ItemType::Module {
+        format!("index.{}", extension)
+    } else {
+        format!("{}.{}.{}", ty, path.last().unwrap(), extension)
+    }
+}
@@ -0,0 +1,626 @@
+//! Rustdoc's Markdown and man page backends.
+//!
+//! Both backends share [`TextRenderer`], which walks the crate like the HTML backend does and
+//! builds a [`Page`] for every module and item, with a body written in Markdown. The [`Markdown`]
+//! format then writes the pages to a directory of `.md` files laid out like the HTML output, and
+//! the [`Man`] format converts them to man pages.
+
+mod man;
+mod markdown;
+mod print;
+
+pub(crate) use self::man::Man;
+pub(crate) use self::markdown::Markdown;
+
+use std::fs;
+use std::ops::Range;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+use itertools::Itertools;
+use pulldown_cmark::{CodeBlockKind, Event, LinkType, Parser, Tag};
+use rustc_hir::def::{CtorKind, DefKind};
+use rustc_hir::def_id::DefId;
+use rustc_middle::ty::{DefIdTree, TyCtxt};
+use rustc_span::hygiene::MacroKind;
+use rustc_span::Symbol;
+
+use crate::clean::{self, types::ExternalLocation};
+use crate::config::RenderOptions;
+use crate::error::Error;
+use crate::formats::cache::Cache;
+use crate::formats::item_type::ItemType;
+use crate::formats::FormatRenderer;
+use crate::html::markdown::{
+    main_body_opts, markdown_links, plain_text_summary, shown_code_lines, ErrorCodes, LangString,
+    MarkdownLink,
+};
+use crate::html::render::{item_ty_to_section, ItemSection};
+use crate::try_err;
+
+/// The documentation of a module or of an item, before it is converted to an output format.
+pub(crate) struct Page<'a> {
+    /// The path of the documented item.
+    pub(crate) path: &'a [Symbol],
+    pub(crate) ty: ItemType,
+    /// The kind of the item, like "Struct" or "Crate".
+    pub(crate) kind: &'static str,
+    /// The first paragraph of the documentation, as plain text.
+    pub(crate) summary: String,
+    /// The declaration of the item, as Rust code.
+    pub(crate) declaration: Option<String>,
+    /// The documentation of the item, followed by sections listing its fields, variants, methods,
+    /// implementations and so on, in Markdown. Its headings start at level 2.
+    pub(crate) body: String,
+    /// The items of a module, which have pages of their own.
+    pub(crate) children: Vec<(Vec<Symbol>, ItemType)>,
+}
+
+/// The page an item is documented on, and the anchor of the item on that page.
+pub(crate) struct LinkTarget<'a> {
+    /// The path of the item the page documents.
+    pub(crate) path: &'a [Symbol],
+    pub(crate) ty: ItemType,
+    /// The anchor of the item on the page, including the leading `#`, or an empty string.
+    pub(crate) fragment: String,
+    /// The root URL of the HTML documentation of an external crate, if it isn't documented in the
+    /// same output directory.
+    pub(crate) remote: Option<&'a str>,
+}
+
+/// An output format of [`TextRenderer`].
+pub(crate) trait TextFormat: Sized {
+    /// The name of the format. Used for performance profiling.
+    const NAME: &'static str;
+
+    fn new(cache: &Cache) -> Self;
+
+    /// Returns the URL of `target` relative to a page of the module `from`, or `None` if the format
+    /// doesn't support links.
+    fn href(&self, target: &LinkTarget<'_>, from: &[Symbol]) -> Option<String>;
+
+    /// Returns the path of the file for `page`, relative to the output directory, and its contents.
+    fn render(&self, page: &Page<'_>, cache: &Cache) -> (PathBuf, String);
+}
+
+pub(crate) struct TextRenderer<'tcx, F> {
+    tcx: TyCtxt<'tcx>,
+    cache: Rc<Cache>,
+    format: Rc<F>,
+    /// The output directory.
+    dst: PathBuf,
+    /// The path of the module being rendered.
+    current: Vec<Symbol>,
+    /// Whether the module being rendered is stripped, in which case its items don't get pages.
+    in_stripped_module: bool,
+}
+
+impl<'tcx, F: TextFormat> FormatRenderer<'tcx> for TextRenderer<'tcx, F> {
+    fn descr() -> &'static str {
+        F::NAME
+    }
+
+    const RUN_ON_MODULE: bool = true;
+
+    fn init(
+        krate: clean::Crate,
+        options: RenderOptions,
+        cache: Cache,
+        tcx: TyCtxt<'tcx>,
+    ) -> Result<(Self, clean::Crate), Error> {
+        let renderer = TextRenderer {
+            tcx,
+            format: Rc::new(F::new(&cache)),
+            cache: Rc::new(cache),
+            dst: options.output,
+            current: Vec::new(),
+            in_stripped_module: false,
+        };
+        Ok((renderer, krate))
+    }
+
+    fn make_child_renderer(&self) -> Self {
+        TextRenderer {
+            tcx: self.tcx,
+            cache: Rc::clone(&self.cache),
+            format: Rc::clone(&self.format),
+            dst: self.dst.clone(),
+            current: self.current.clone(),
+            in_stripped_module: self.in_stripped_module,
+        }
+    }
+
+    fn item(&mut self, item: clean::Item) -> Result<(), Error> {
+        // Re-exports are listed on the page of their module.
+        if self.in_stripped_module
+            || item.is_stripped()
+            || matches!(*item.kind, clean::ImportItem(_))
+        {
+            return Ok(());
+        }
+        let mut path = self.current.clone();
+        path.push(item.name.unwrap());
+        self.write_page(&item, &path)
+    }
+
+    fn mod_item_in(&mut self, item: &clean::Item) -> Result<(), Error> {
+        self.current.push(item.name.unwrap());
+        self.in_stripped_module |= item.is_stripped();
+        if self.in_stripped_module {
+            return Ok(());
+        }
+        let path = self.current.clone();
+        self.write_page(item, &path)
+    }
+
+    fn mod_item_out(&mut self) -> Result<(), Error> {
+        self.current.pop();
+        Ok(())
+    }
+
+    fn after_krate(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn cache(&self) -> &Cache {
+        &self.cache
+    }
+}
+
+impl<'tcx, F: TextFormat> TextRenderer<'tcx, F> {
+    fn write_page(&self, item: &clean::Item, path: &[Symbol]) -> Result<(), Error> {
+        let tcx = self.tcx;
+        let mut body = self.docs(item, 1);
+        let mut children = Vec::new();
+        match *item.kind {
+            clean::ModuleItem(ref m) => children = self.module_sections(&mut body, &m.items),
+            clean::StructItem(ref s) => {
+                match s.ctor_kind {
+                    None => self.fields_section(&mut body, "Fields", &s.fields),
+                    // Like the HTML output, only list the fields of tuple structs if they have
+                    // docs.
+                    Some(CtorKind::Fn) if s.fields.iter().any(|f| f.doc_value().is_some()) => {
+                        self.fields_section(&mut body, "Tuple Fields", &s.fields)
+                    }
+                    _ => {}
+                }
+                self.impl_sections(&mut body, item.item_id.as_def_id());
+            }
+            clean::UnionItem(ref u) => {
+                self.fields_section(&mut body, "Fields", &u.fields);
+                self.impl_sections(&mut body, item.item_id.as_def_id());
+            }
+            clean::EnumItem(ref e) => {
+                let variants = e.variants().map(|v| (v, print::print_variant(v, tcx)));
+                self.entry_section(&mut body, "Variants", variants);
+                self.impl_sections(&mut body, item.item_id.as_def_id());
+            }
+            clean::PrimitiveItem(_) | clean::ForeignTypeItem => {
+                self.impl_sections(&mut body, item.item_id.as_def_id());
+            }
+            clean::TraitItem(ref t) => self.trait_sections(&mut body, t),
+            _ => {}
+        }
+
+        let page = Page {
+            path,
+            ty: item.type_(),
+            kind: page_kind(item),
+            summary: item.doc_value().map(|s| plain_text_summary(&s)).unwrap_or_default(),
+            declaration: print::print_declaration(item, tcx),
+            body,
+            children,
+        };
+        let (file, contents) = self.format.render(&page, &self.cache);
+        let file = self.dst.join(file);
+        if let Some(dir) = file.parent() {
+            try_err!(fs::create_dir_all(dir), dir);
+        }
+        try_err!(fs::write(&file, contents), file);
+        Ok(())
+    }
+
+    /// Lists the items of a module, grouped by kind, and returns the ones that have pages.
+    fn module_sections(
+        &self,
+        out: &mut String,
+        items: &[clean::Item],
+    ) -> Vec<(Vec<Symbol>, ItemType)> {
+        let mut items: Vec<_> = items.iter().filter(|i| !i.is_stripped()).collect();
+        items.sort_by_cached_key(|i| i.name.map(|name| name.to_string()));
+        let mut children = Vec::new();
+        for &section in ItemSection::ALL {
+            let mut in_section = items.iter().filter(|i| item_ty_to_section(i.type_()) == section);
+            let Some(first) = in_section.next() else { continue };
+            out.push_str(&format!("\n\n## {}\n", section.name()));
+            for item in std::iter::once(first).chain(in_section) {
+                let entry = match *item.kind {
+                    clean::ImportItem(ref import) => {
+                        let vis = print::print_visibility(item, self.tcx);
+                        format!("`{}{}`", vis, print::print_import(import))
+                    }
+                    clean::ExternCrateItem { ref src } => {
+                        let vis = print::print_visibility(item, self.tcx);
+                        let name = item.name.unwrap();
+                        match *src {
+                            Some(src) => format!("`{}extern crate {} as {};`", vis, src, name),
+                            None => format!("`{}extern crate {};`", vis, name),
+                        }
+                    }
+                    _ => {
+                        let Some(name) = item.name else { continue };
+                        let mut path = self.current.clone();
+                        path.push(name);
+                        let target = LinkTarget {
+                            path: &path,
+                            ty: item.type_(),
+                            fragment: String::new(),
+                            remote: None,
+                        };
+                        let entry = match self.format.href(&target, &self.current) {
+                            Some(href) => format!("[`{}`]({})", name, href),
+                            None => format!("`{}`", name),
+                        };
+                        children.push((path, item.type_()));
+                        match item.doc_value().map(|s| plain_text_summary(&s)) {
+                            Some(summary) if !summary.is_empty() => {
+                                format!("{}: {}", entry, summary)
+                            }
+                            _ => entry,
+                        }
+                    }
+                };
+                out.push_str("\n- ");
+                out.push_str(&entry);
+            }
+        }
+        children
+    }
+
+    fn fields_section(&self, out: &mut String, title: &str, fields: &[clean::Item]) {
+        let fields = fields
+            .iter()
+            .filter_map(|field| print::print_field(field, self.tcx).map(|code| (field, code)));
+        self.entry_section(out, title, fields);
+    }
+
+    fn trait_sections(&self, out: &mut String, t: &clean::Trait) {
+        let tcx = self.tcx;
+        let items = move |filter: fn(&clean::Item) -> bool| {
+            t.items
+                .iter()
+                .filter(move |item| !item.is_stripped() && filter(item))
+                .filter_map(move |item| print::print_assoc_item(item, true, tcx).map(|c| (item, c)))
+        };
+        self.entry_section(out, "Required Associated Types", items(|i| i.is_ty_associated_type()));
+        self.entry_section(out, "Provided Associated Types", items(|i| i.is_associated_type()));
+        self.entry_section(
+            out,
+            "Required Associated Constants",
+            items(|i| i.is_ty_associated_const()),
+        );
+        self.entry_section(
+            out,
+            "Provided Associated Constants",
+            items(|i| i.is_associated_const()),
+        );
+        self.entry_section(out, "Required Methods", items(|i| i.is_ty_method()));
+        self.entry_section(out, "Provided Methods", items(|i| i.is_method()));
+
+        let implementors = self.cache.implementors.get(&t.def_id).map_or(&[][..], |v| v.as_slice());
+        let headers = implementors.iter().map(|i| print::print_impl(i.inner_impl(), tcx));
+        list_section(out, "Implementors", headers.collect());
+    }
+
+    /// Lists the implementations of the type `did`. Only the items of inherent impls are listed,
+    /// trait implementations are listed by their headers.
+    fn impl_sections(&self, out: &mut String, did: Option<DefId>) {
+        let tcx = self.tcx;
+        let impls =
+            did.and_then(|did| self.cache.impls.get(&did)).map_or(&[][..], |v| v.as_slice());
+
+        let inherent = impls.iter().map(|i| i.inner_impl()).filter(|i| i.trait_.is_none());
+        let mut wrote_title = false;
+        for i in inherent {
+            let items =
+                i.items.iter().filter(|item| !item.is_stripped()).filter_map(|item| {
+                    print::print_assoc_item(item, false, tcx).map(|c| (item, c))
+                });
+            let mut entries = String::new();
+            self.entries(&mut entries, items);
+            if entries.is_empty() {
+                continue;
+            }
+            if !wrote_title {
+                out.push_str("\n\n## Implementations");
+                wrote_title = true;
+            }
+            out.push_str(&format!("\n\n### `{}`", one_line(&print::print_impl(i, tcx))));
+            out.push_str(&entries);
+        }
+
+        let trait_impls = |filter: fn(&clean::Impl) -> bool| {
+            impls
+                .iter()
+                .map(|i| i.inner_impl())
+                .filter(move |i| i.trait_.is_some() && filter(i))
+                .map(|i| print::print_impl(i, tcx))
+                .collect()
+        };
+        list_section(
+            out,
+            "Trait Implementations",
+            trait_impls(|i| !i.kind.is_auto() && !i.kind.is_blanket()),
+        );
+        list_section(out, "Auto Trait Implementations", trait_impls(|i| i.kind.is_auto()));
+        list_section(out, "Blanket Implementations", trait_impls(|i| i.kind.is_blanket()));
+    }
+
+    /// Writes a section that documents each of `items` under its declaration.
+    fn entry_section<'a>(
+        &self,
+        out: &mut String,
+        title: &str,
+        items: impl Iterator<Item = (&'a clean::Item, String)>,
+    ) {
+        let mut entries = String::new();
+        self.entries(&mut entries, items);
+        if !entries.is_empty() {
+            out.push_str("\n\n## ");
+            out.push_str(title);
+            out.push_str(&entries);
+        }
+    }
+
+    fn entries<'a>(
+        &self,
+        out: &mut String,
+        items: impl Iterator<Item = (&'a clean::Item, String)>,
+    ) {
+        for (item, code) in items {
+            out.push_str("\n\n");
+            if let Some(name) = item.name {
+                out.push_str(&format!("<a id=\"{}.{}\"></a>\n", item.type_(), name));
+            }
+            out.push_str(&code_block(&code));
+            let docs = self.docs(item, 3);
+            if !docs.is_empty() {
+                out.push_str("\n\n");
+                out.push_str(&docs);
+            }
+        }
+    }
+
+    /// Returns the documentation of `item` for a page of the current module. Its intra-doc links
+    /// are resolved, the hidden lines of its Rust code blocks are removed and its headings are
+    /// moved down by `heading_offset` levels.
+    fn docs(&self, item: &clean::Item, heading_offset: usize) -> String {
+        let Some(md) = item.collapsed_doc_value() else { return String::new() };
+        let item_links =
+            self.cache.intra_doc_links.get(&item.item_id).map_or(&[][..], |v| v.as_slice());
+
+        let mut edits = markdown_links(&md, |link: MarkdownLink| {
+            let item_link = item_links.iter().find(|l| l.link == link.link)?;
+            let href = self.link_target(item_link.page_id).and_then(|mut target| {
+                if let Some(ref fragment) = item_link.fragment {
+                    fragment.render(&mut target.fragment, self.tcx);
+                }
+                self.format.href(&target, &self.current)
+            });
+            link_edit(&md, &link, &item_link.link_text, href)
+        });
+
+        for (event, range) in Parser::new_ext(&md, main_body_opts()).into_offset_iter() {
+            match event {
+                Event::Start(Tag::Heading(level, ..)) => {
+                    let start = range.end - md[range.clone()].trim_start().len();
+                    if md[start..].starts_with('#') {
+                        let shift = heading_offset.min(6 - level as usize);
+                        edits.push((start..start, "#".repeat(shift)));
+                    }
+                }
+                Event::Start(Tag::CodeBlock(kind)) => {
+                    let is_rust = match kind {
+                        CodeBlockKind::Fenced(ref lang) => {
+                            LangString::parse_without_check(lang, ErrorCodes::No, false).rust
+                        }
+                        CodeBlockKind::Indented => true,
+                    };
+                    if is_rust {
+                        let fenced = matches!(kind, CodeBlockKind::Fenced(_));
+                        edits.push((range.clone(), strip_hidden_lines(&md[range], fenced)));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        // Several links can share the definition of a reference link.
+        edits.sort_by_key(|(range, _)| (range.start, range.end));
+        edits.dedup_by_key(|(range, _)| range.clone());
+        let mut docs = String::with_capacity(md.len());
+        let mut last = 0;
+        for (range, replacement) in edits {
+            if range.start < last {
+                continue;
+            }
+            docs.push_str(&md[last..range.start]);
+            docs.push_str(&replacement);
+            last = range.end;
+        }
+        docs.push_str(&md[last..]);
+        docs.trim_end().to_owned()
+    }
+
+    /// Returns the page `did` is documented on, like `html::format::href` does for HTML.
+    fn link_target(&self, did: DefId) -> Option<LinkTarget<'_>> {
+        let tcx = self.tcx;
+        let cache = &self.cache;
+        let did = match tcx.def_kind(did) {
+            DefKind::AssocTy | DefKind::AssocFn | DefKind::AssocConst | DefKind::Variant => {
+                // documented on their parent's page
+                tcx.parent(did)
+            }
+            _ => did,
+        };
+        if !did.is_local()
+            && !cache.effective_visibilities.is_directly_public(tcx, did)
+            && !cache.document_private
+            && !cache.primitive_locations.values().any(|&id| id == did)
+        {
+            return None;
+        }
+        if let Some(&(ref path, ty)) = cache.paths.get(&did) {
+            return Some(LinkTarget { path, ty, fragment: String::new(), remote: None });
+        }
+        let &(ref path, ty) = cache.external_paths.get(&did)?;
+        let remote = match cache.extern_locations[&did.krate] {
+            ExternalLocation::Remote(ref url) => Some(url.as_str()),
+            ExternalLocation::Local => None,
+            ExternalLocation::Unknown => return None,
+        };
+        Some(LinkTarget { path, ty, fragment: String::new(), remote })
+    }
+}
+
+/// Returns the edit that turns an intra-doc link into a link to `href`, or into plain text if
+/// there is no `href`.
+fn link_edit(
+    md: &str,
+    link: &MarkdownLink,
+    link_text: &str,
+    href: Option<String>,
+) -> Option<(Range<usize>, String)> {
+    match link.kind {
+        // The range is the destination of an inline link, or of the definition of a reference
+        // link, which only needs to be replaced.
+        LinkType::Inline | LinkType::Reference | LinkType::Collapsed | LinkType::Shortcut => {
+            Some((link.range.clone(), href?))
+        }
+        // Links without a definition, like `[Foo]`, `[Foo][]` and `[text][Foo]`, are only links
+        // for rustdoc. The range is the `Foo` between the brackets.
+        _ => {
+            let start = link.range.start.checked_sub(1)?;
+            let end = link.range.end + 1;
+            if md.get(start..link.range.start) != Some("[")
+                || md.get(link.range.end..end) != Some("]")
+            {
+                return None;
+            }
+            if md[..start].ends_with(']') {
+                // `[text][Foo]`
+                match href {
+                    Some(href) => Some((start..end, format!("({})", href))),
+                    None => {
+                        let text_start = md[..start - 1].rfind('[')?;
+                        Some((text_start..end, md[text_start + 1..start - 1].to_owned()))
+                    }
+                }
+            } else {
+                // `[Foo]` or `[Foo][]`
+                let end = if md[end..].starts_with("[]") { end + 2 } else { end };
+                let text = if link.link.contains('`') {
+                    format!("`{}`", link_text)
+                } else {
+                    link_text.to_owned()
+                };
+                match href {
+                    Some(href) => Some((start..end, format!("[{}]({})", text, href))),
+                    None => Some((start..end, text)),
+                }
+            }
+        }
+    }
+}
+
+/// Removes the hidden lines of a Rust code block. Fenced code blocks are also marked as Rust
+/// code, since rustdoc treats code blocks without a language as Rust code.
+fn strip_hidden_lines(block: &str, fenced: bool) -> String {
+    let mut lines: Vec<&str> = block.lines().collect();
+    let mut out = String::with_capacity(block.len());
+    let mut closing_fence = None;
+    if fenced {
+        let opening_fence = lines.remove(0);
+        let info_len = opening_fence.trim_start().trim_start_matches(['`', '~']).len();
+        out.push_str(&opening_fence[..opening_fence.len() - info_len]);
+        out.push_str("rust\n");
+        // The closing fence is missing if the code block runs to the end of the documentation.
+        let is_fence = |line: &&str| {
+            let line = line.trim();
+            !line.is_empty() && line.chars().all(|c| c == '`' || c == '~')
+        };
+        if lines.last().map_or(false, is_fence) {
+            closing_fence = lines.pop();
+        }
+    }
+    for line in shown_code_lines(&lines.join("\n")).chain(closing_fence.map(Into::into)) {
+        out.push_str(&line);
+        out.push('\n');
+    }
+    if !block.ends_with('\n') {
+        out.pop();
+    }
+    out
+}
+
+/// Joins the lines of `code`, like the where clause of an impl header, into a single line.
+fn one_line(code: &str) -> String {
+    code.split_whitespace().join(" ")
+}
+
+/// Returns `code` as a Rust code block, using a fence that doesn't appear in it.
+fn code_block(code: &str) -> String {
+    let mut fence = String::from("```");
+    while code.contains(&fence) {
+        fence.push('`');
+    }
+    format!("{fence}rust\n{code}\n{fence}")
+}
+
+/// Writes a section that lists `entries` as code.
+fn list_section(out: &mut String, title: &str, mut entries: Vec<String>) {
+    if entries.is_empty() {
+        return;
+    }
+    entries.sort();
+    out.push_str("\n\n## ");
+    out.push_str(title);
+    out.push('\n');
+    for entry in entries {
+        out.push_str("\n- `");
+        out.push_str(&one_line(&entry));
+        out.push('`');
+    }
+}
+
+fn page_kind(item: &clean::Item) -> &'static str {
+    match *item.kind {
+        clean::ModuleItem(_) => {
+            if item.is_crate() {
+                "Crate"
+            } else {
+                "Module"
+            }
+        }
+        clean::FunctionItem(..) | clean::ForeignFunctionItem(..) => "Function",
+        clean::TraitItem(..) => "Trait",
+        clean::StructItem(..) => "Struct",
+        clean::UnionItem(..) => "Union",
+        clean::EnumItem(..) => "Enum",
+        clean::TypedefItem(..) => "Type Definition",
+        clean::MacroItem(..) => "Macro",
+        clean::ProcMacroItem(ref mac) => match mac.kind {
+            MacroKind::Bang => "Macro",
+            MacroKind::Attr => "Attribute Macro",
+            MacroKind::Derive => "Derive Macro",
+        },
+        clean::PrimitiveItem(..) => "Primitive Type",
+        clean::StaticItem(..) | clean::ForeignStaticItem(..) => "Static",
+        clean::ConstantItem(..) => "Constant",
+        clean::ForeignTypeItem => "Foreign Type",
+        clean::KeywordItem => "Keyword",
+        clean::OpaqueTyItem(..) => "Opaque Type",
+        clean::TraitAliasItem(..) => "Trait Alias",
+        _ => "Item",
+    }
+}
@@ -0,0 +1,610 @@
+//! Plain-text rendering of item declarations, for the Markdown and man page backends.
+//!
+//! This mirrors the alternate (`{:#}`) mode of the `print` methods in `html::format`, which can't
+//! be used here because they need an HTML `Context`.
+
+use rustc_data_structures::fx::FxHashSet;
+use rustc_hir as hir;
+use rustc_hir::def::CtorKind;
+use rustc_middle::ty::{self, TyCtxt};
+use rustc_span::hygiene::MacroKind;
+use rustc_span::symbol::kw;
+
+use itertools::Itertools;
+
+use crate::clean;
+use crate::html::format::{
+    print_abi_with_space, print_constness_with_space, print_default_space,
+    visibility_to_src_with_space, PrintWithSpace,
+};
+
+pub(super) fn print_type(t: &clean::Type, tcx: TyCtxt<'_>) -> String {
+    match *t {
+        clean::Generic(name) => name.to_string(),
+        clean::Type::Path { ref path } => print_path(path, path.is_assoc_ty(), tcx),
+        clean::DynTrait(ref bounds, ref lt) => {
+            let mut s = String::from("dyn ");
+            s.push_str(&bounds.iter().map(|b| print_poly_trait(b, tcx)).join(" + "));
+            if let Some(lt) = lt {
+                s.push_str(" + ");
+                s.push_str(lt.0.as_str());
+            }
+            s
+        }
+        clean::Infer => "_".to_owned(),
+        clean::Primitive(clean::PrimitiveType::Never) => "!".to_owned(),
+        clean::Primitive(prim) => prim.as_sym().to_string(),
+        clean::BareFunction(ref decl) => format!(
+            "{}{}{:#}fn{}",
+            print_hrtb_with_space(&decl.generic_params, tcx),
+            decl.unsafety.print_with_space(),
+            print_abi_with_space(decl.abi),
+            print_fn_decl(&decl.decl, tcx),
+        ),
+        clean::Tuple(ref types) => match &types[..] {
+            [one] => format!("({},)", print_type(one, tcx)),
+            many => format!("({})", many.iter().map(|t| print_type(t, tcx)).join(", ")),
+        },
+        clean::Slice(ref t) => format!("[{}]", print_type(t, tcx)),
+        clean::Array(ref t, ref n) => format!("[{}; {}]", print_type(t, tcx), n),
+        clean::RawPointer(m, ref t) => {
+            let m = match m {
+                hir::Mutability::Mut => "mut",
+                hir::Mutability::Not => "const",
+            };
+            format!("*{} {}", m, print_type(t, tcx))
+        }
+        clean::BorrowedRef { ref lifetime, mutability, ref type_ } => {
+            let lt = lifetime.as_ref().map(|l| format!("{} ", l.0)).unwrap_or_default();
+            let m = mutability.print_with_space();
+            match **type_ {
+                clean::DynTrait(ref bounds, ref trait_lt)
+                    if bounds.len() > 1 || trait_lt.is_some() =>
+                {
+                    format!("&{}{}({})", lt, m, print_type(type_, tcx))
+                }
+                _ => format!("&{}{}{}", lt, m, print_type(type_, tcx)),
+            }
+        }
+        clean::ImplTrait(ref bounds) => format!("impl {}", print_generic_bounds(bounds, tcx)),
+        clean::QPath(box clean::QPathData {
+            ref assoc,
+            ref self_type,
+            ref trait_,
+            should_show_cast,
+        }) => {
+            let self_type = print_type(self_type, tcx);
+            let args = print_generic_args(&assoc.args, tcx);
+            if should_show_cast {
+                let trait_ = print_path(trait_, false, tcx);
+                format!("<{} as {}>::{}{}", self_type, trait_, assoc.name, args)
+            } else {
+                format!("{}::{}{}", self_type, assoc.name, args)
+            }
+        }
+    }
+}
+
+/// Prints the last segment of `path`, or all of them if `print_all` is set.
+fn print_path(path: &clean::Path, print_all: bool, tcx: TyCtxt<'_>) -> String {
+    let mut s = String::new();
+    let last = path.segments.last().unwrap();
+    if print_all {
+        for seg in &path.segments[..path.segments.len() - 1] {
+            if seg.name != kw::PathRoot {
+                s.push_str(seg.name.as_str());
+            }
+            s.push_str("::");
+        }
+    }
+    s.push_str(last.name.as_str());
+    s.push_str(&print_generic_args(&last.args, tcx));
+    s
+}
+
+fn print_generic_args(args: &clean::GenericArgs, tcx: TyCtxt<'_>) -> String {
+    match args {
+        clean::GenericArgs::AngleBracketed { args, bindings } => {
+            if args.is_empty() && bindings.is_empty() {
+                return String::new();
+            }
+            let args = args.iter().map(|arg| match arg {
+                clean::GenericArg::Lifetime(lt) => lt.0.to_string(),
+                clean::GenericArg::Type(ty) => print_type(ty, tcx),
+                clean::GenericArg::Const(ct) => ct.expr(tcx),
+                clean::GenericArg::Infer => "_".to_owned(),
+            });
+            let bindings = bindings.iter().map(|binding| print_type_binding(binding, tcx));
+            format!("<{}>", args.chain(bindings).join(", "))
+        }
+        clean::GenericArgs::Parenthesized { inputs, output } => {
+            let mut s = format!("({})", inputs.iter().map(|t| print_type(t, tcx)).join(", "));
+            if let Some(ty) = output {
+                s.push_str(" -> ");
+                s.push_str(&print_type(ty, tcx));
+            }
+            s
+        }
+    }
+}
+
+fn print_type_binding(binding: &clean::TypeBinding, tcx: TyCtxt<'_>) -> String {
+    let mut s = binding.assoc.name.to_string();
+    s.push_str(&print_generic_args(&binding.assoc.args, tcx));
+    match binding.kind {
+        clean::TypeBindingKind::Equality { ref term } => {
+            s.push_str(" = ");
+            match term {
+                clean::Term::Type(ty) => s.push_str(&print_type(ty, tcx)),
+                clean::Term::Constant(ct) => s.push_str(&ct.expr(tcx)),
+            }
+        }
+        clean::TypeBindingKind::Constraint { ref bounds } => {
+            if !bounds.is_empty() {
+                s.push_str(": ");
+                s.push_str(&print_generic_bounds(bounds, tcx));
+            }
+        }
+    }
+    s
+}
+
+fn print_poly_trait(poly_trait: &clean::PolyTrait, tcx: TyCtxt<'_>) -> String {
+    format!(
+        "{}{}",
+        print_hrtb_with_space(&poly_trait.generic_params, tcx),
+        print_path(&poly_trait.trait_, false, tcx)
+    )
+}
+
+fn print_hrtb_with_space(params: &[clean::GenericParamDef], tcx: TyCtxt<'_>) -> String {
+    if params.is_empty() {
+        String::new()
+    } else {
+        format!("for<{}> ", params.iter().map(|p| print_generic_param(p, tcx)).join(", "))
+    }
+}
+
+pub(super) fn print_generic_bounds(bounds: &[clean::GenericBound], tcx: TyCtxt<'_>) -> String {
+    let mut bounds_dup = FxHashSet::default();
+    bounds
+        .iter()
+        .filter(|b| bounds_dup.insert(*b))
+        .map(|bound| match bound {
+            clean::GenericBound::Outlives(lt) => lt.0.to_string(),
+            clean::GenericBound::TraitBound(ty, modifier) => {
+                let modifier = match modifier {
+                    hir::TraitBoundModifier::None => "",
+                    hir::TraitBoundModifier::Maybe => "?",
+                    // ~const is experimental; do not display those bounds in rustdoc
+                    hir::TraitBoundModifier::MaybeConst => "",
+                };
+                format!("{}{}", modifier, print_poly_trait(ty, tcx))
+            }
+        })
+        .join(" + ")
+}
+
+fn print_generic_param(param: &clean::GenericParamDef, tcx: TyCtxt<'_>) -> String {
+    let mut s = String::new();
+    match &param.kind {
+        clean::GenericParamDefKind::Lifetime { outlives } => {
+            s.push_str(param.name.as_str());
+            if !outlives.is_empty() {
+                s.push_str(": ");
+                s.push_str(&outlives.iter().map(|lt| lt.0.to_string()).join(" + "));
+            }
+        }
+        clean::GenericParamDefKind::Type { bounds, default, .. } => {
+            s.push_str(param.name.as_str());
+            if !bounds.is_empty() {
+                s.push_str(": ");
+                s.push_str(&print_generic_bounds(bounds, tcx));
+            }
+            if let Some(ty) = default {
+                s.push_str(" = ");
+                s.push_str(&print_type(ty, tcx));
+            }
+        }
+        clean::GenericParamDefKind::Const { ty, default, .. } => {
+            s.push_str(&format!("const {}: {}", param.name, print_type(ty, tcx)));
+            if let Some(default) = default {
+                s.push_str(" = ");
+                s.push_str(default);
+            }
+        }
+    }
+    s
+}
+
+pub(super) fn print_generics(generics: &clean::Generics, tcx: TyCtxt<'_>) -> String {
+    let mut real_params =
+        generics.params.iter().filter(|p| !p.is_synthetic_type_param()).peekable();
+    if real_params.peek().is_none() {
+        return String::new();
+    }
+    format!("<{}>", real_params.map(|p| print_generic_param(p, tcx)).join(", "))
+}
+
+/// Prints the where clause of `generics` on lines of its own, or nothing if there is none.
+pub(super) fn print_where_clause(generics: &clean::Generics, tcx: TyCtxt<'_>) -> String {
+    let predicates: Vec<_> = generics
+        .where_predicates
+        .iter()
+        .filter(|pred| match pred {
+            clean::WherePredicate::BoundPredicate { bounds, .. } => !bounds.is_empty(),
+            _ => true,
+        })
+        .map(|pred| match pred {
+            clean::WherePredicate::BoundPredicate { ty, bounds, bound_params } => {
+                let hrtb = if bound_params.is_empty() {
+                    String::new()
+                } else {
+                    format!("for<{}> ", bound_params.iter().map(|lt| lt.0.to_string()).join(", "))
+                };
+                format!("{}{}: {}", hrtb, print_type(ty, tcx), print_generic_bounds(bounds, tcx))
+            }
+            clean::WherePredicate::RegionPredicate { lifetime, bounds } => {
+                format!("{}: {}", lifetime.0, print_generic_bounds(bounds, tcx))
+            }
+            clean::WherePredicate::EqPredicate { lhs, rhs, .. } => {
+                let rhs = match rhs {
+                    clean::Term::Type(ty) => print_type(ty, tcx),
+                    clean::Term::Constant(ct) => ct.expr(tcx),
+                };
+                format!("{} == {}", print_type(lhs, tcx), rhs)
+            }
+        })
+        .collect();
+    if predicates.is_empty() {
+        return String::new();
+    }
+    let mut s = String::from("\nwhere");
+    for pred in predicates {
+        s.push_str("\n    ");
+        s.push_str(&pred);
+        s.push(',');
+    }
+    s
+}
+
+fn print_fn_decl(decl: &clean::FnDecl, tcx: TyCtxt<'_>) -> String {
+    let mut args = decl
+        .inputs
+        .values
+        .iter()
+        .map(|input| match input.to_self() {
+            Some(clean::SelfValue) => "self".to_owned(),
+            Some(clean::SelfBorrowed(Some(lt), mtbl)) => {
+                format!("&{} {}self", lt.0, mtbl.print_with_space())
+            }
+            Some(clean::SelfBorrowed(None, mtbl)) => format!("&{}self", mtbl.print_with_space()),
+            Some(clean::SelfExplicit(typ)) => format!("self: {}", print_type(&typ, tcx)),
+            None => format!(
+                "{}{}: {}",
+                if input.is_const { "const " } else { "" },
+                input.name,
+                print_type(&input.type_, tcx)
+            ),
+        })
+        .join(", ");
+    if decl.c_variadic {
+        args.push_str(", ...");
+    }
+    match decl.output {
+        clean::FnRetTy::Return(clean::Tuple(ref tys)) if tys.is_empty() => format!("({})", args),
+        clean::FnRetTy::Return(ref ty) => format!("({}) -> {}", args, print_type(ty, tcx)),
+        clean::FnRetTy::DefaultReturn => format!("({})", args),
+    }
+}
+
+pub(super) fn print_visibility(item: &clean::Item, tcx: TyCtxt<'_>) -> String {
+    match item.item_id.as_def_id() {
+        Some(def_id) => visibility_to_src_with_space(item.visibility(tcx), tcx, def_id).to_string(),
+        None => String::new(),
+    }
+}
+
+fn print_function(item: &clean::Item, f: &clean::Function, tcx: TyCtxt<'_>) -> String {
+    let header = item.fn_header(tcx).expect("printing a function which isn't a function");
+    format!(
+        "{vis}{default}{constness}{asyncness}{unsafety}{abi:#}fn \
+         {name}{generics}{decl}{where_clause}",
+        vis = print_visibility(item, tcx),
+        default = print_default_space(item.is_default()),
+        constness = print_constness_with_space(&header.constness, item.const_stability(tcx)),
+        asyncness = header.asyncness.print_with_space(),
+        unsafety = header.unsafety.print_with_space(),
+        abi = print_abi_with_space(header.abi),
+        name = item.name.unwrap(),
+        generics = print_generics(&f.generics, tcx),
+        decl = print_fn_decl(&f.decl, tcx),
+        where_clause = print_where_clause(&f.generics, tcx),
+    )
+}
+
+/// Prints the fields of a struct, union or struct variant, one per line.
+fn print_fields(
+    fields: &[clean::Item],
+    has_stripped_entries: bool,
+    indent: &str,
+    tcx: TyCtxt<'_>,
+) -> String {
+    let mut s = String::from("{");
+    for field in fields {
+        if let clean::StructFieldItem(ref ty) = *field.kind {
+            s.push_str(&format!(
+                "\n{}    {}{}: {},",
+                indent,
+                print_visibility(field, tcx),
+                field.name.unwrap(),
+                print_type(ty, tcx)
+            ));
+        }
+    }
+    if has_stripped_entries {
+        s.push_str(&format!("\n{}    /* private fields */", indent));
+    }
+    if s.len() > 1 {
+        s.push('\n');
+        s.push_str(indent);
+    }
+    s.push('}');
+    s
+}
+
+fn print_tuple_fields(fields: &[clean::Item], tcx: TyCtxt<'_>) -> String {
+    let fields = fields.iter().map(|field| match *field.kind {
+        clean::StructFieldItem(ref ty) => {
+            format!("{}{}", print_visibility(field, tcx), print_type(ty, tcx))
+        }
+        _ => "_".to_owned(),
+    });
+    format!("({})", fields.join(", "))
+}
+
+/// Prints a variant the way it is written in its enum, without the trailing comma.
+pub(super) fn print_variant(item: &clean::Item, tcx: TyCtxt<'_>) -> String {
+    let name = item.name.unwrap();
+    match *item.kind {
+        clean::VariantItem(clean::Variant::CLike(ref discriminant)) => {
+            match discriminant.as_ref().and_then(|d| d.expr(tcx)) {
+                Some(expr) => format!("{} = {}", name, expr),
+                None => name.to_string(),
+            }
+        }
+        clean::VariantItem(clean::Variant::Tuple(ref fields)) => {
+            format!("{}{}", name, print_tuple_fields(fields, tcx))
+        }
+        clean::VariantItem(clean::Variant::Struct(ref s)) => {
+            format!("{} {}", name, print_fields(&s.fields, s.has_stripped_entries(), "    ", tcx))
+        }
+        _ => unreachable!(),
+    }
+}
+
+/// Prints a struct field as `name: Type`.
+pub(super) fn print_field(item: &clean::Item, tcx: TyCtxt<'_>) -> Option<String> {
+    match *item.kind {
+        clean::StructFieldItem(ref ty) => Some(format!("{}: {}", item.name?, print_type(ty, tcx))),
+        _ => None,
+    }
+}
+
+/// Prints the declaration of an associated item, as it appears in a trait or impl block. Provided
+/// methods get a `{ ... }` body when `in_trait` is set.
+pub(super) fn print_assoc_item(
+    item: &clean::Item,
+    in_trait: bool,
+    tcx: TyCtxt<'_>,
+) -> Option<String> {
+    let name = item.name?;
+    let s = match *item.kind {
+        clean::TyMethodItem(ref f) => format!("{};", print_function(item, f, tcx)),
+        clean::MethodItem(ref f, _) if in_trait => {
+            format!("{} {{ ... }}", print_function(item, f, tcx))
+        }
+        clean::MethodItem(ref f, _) => print_function(item, f, tcx),
+        clean::TyAssocConstItem(ref ty) => {
+            format!("{}const {}: {};", print_visibility(item, tcx), name, print_type(ty, tcx))
+        }
+        clean::AssocConstItem(ref ty, ref kind) => format!(
+            "{}const {}: {} = {};",
+            print_visibility(item, tcx),
+            name,
+            print_type(ty, tcx),
+            kind.expr(tcx)
+        ),
+        clean::TyAssocTypeItem(ref generics, ref bounds) => {
+            let bounds = if bounds.is_empty() {
+                String::new()
+            } else {
+                format!(": {}", print_generic_bounds(bounds, tcx))
+            };
+            format!(
+                "type {}{}{}{};",
+                name,
+                print_generics(generics, tcx),
+                bounds,
+                print_where_clause(generics, tcx)
+            )
+        }
+        clean::AssocTypeItem(ref typedef, _) => format!(
+            "{}type {}{}{} = {};",
+            print_visibility(item, tcx),
+            name,
+            print_generics(&typedef.generics, tcx),
+            print_where_clause(&typedef.generics, tcx),
+            print_type(&typedef.type_, tcx)
+        ),
+        _ => return None,
+    };
+    Some(s)
+}
+
+/// Prints the header of an impl block, without the opening brace.
+pub(super) fn print_impl(i: &clean::Impl, tcx: TyCtxt<'_>) -> String {
+    let mut s =
+        format!("{}impl{} ", i.unsafety.print_with_space(), print_generics(&i.generics, tcx));
+    if let Some(ref trait_) = i.trait_ {
+        if i.polarity == ty::ImplPolarity::Negative {
+            s.push('!');
+        }
+        s.push_str(&print_path(trait_, false, tcx));
+        s.push_str(" for ");
+    }
+    let for_ = i.kind.as_blanket_ty().unwrap_or(&i.for_);
+    s.push_str(&print_type(for_, tcx));
+    s.push_str(&print_where_clause(&i.generics, tcx));
+    s
+}
+
+pub(super) fn print_import(import: &clean::Import) -> String {
+    match import.kind {
+        clean::ImportKind::Simple(name) => {
+            if name == import.source.path.last() {
+                format!("use {};", import.source.path.whole_name())
+            } else {
+                format!("use {} as {};", import.source.path.whole_name(), name)
+            }
+        }
+        clean::ImportKind::Glob => {
+            if import.source.path.segments.is_empty() {
+                "use *;".to_owned()
+            } else {
+                format!("use {}::*;", import.source.path.whole_name())
+            }
+        }
+    }
+}
+
+/// Prints the declaration of an item that gets a page of its own, or `None` for items that don't
+/// have one, like modules.
+pub(super) fn print_declaration(item: &clean::Item, tcx: TyCtxt<'_>) -> Option<String> {
+    let vis = print_visibility(item, tcx);
+    let name = item.name?;
+    let s = match *item.kind {
+        clean::FunctionItem(ref f) | clean::ForeignFunctionItem(ref f) => {
+            print_function(item, f, tcx)
+        }
+        clean::StructItem(ref s) => {
+            let generics = print_generics(&s.generics, tcx);
+            let where_clause = print_where_clause(&s.generics, tcx);
+            match s.ctor_kind {
+                None => {
+                    let sep = if where_clause.is_empty() { " " } else { "\n" };
+                    let fields = print_fields(&s.fields, s.has_stripped_entries(), "", tcx);
+                    format!("{vis}struct {name}{generics}{where_clause}{sep}{fields}")
+                }
+                Some(CtorKind::Fn) => {
+                    let fields = print_tuple_fields(&s.fields, tcx);
+                    format!("{vis}struct {name}{generics}{fields}{where_clause};")
+                }
+                Some(CtorKind::Const) => {
+                    format!("{vis}struct {name}{generics}{where_clause};")
+                }
+            }
+        }
+        clean::UnionItem(ref u) => {
+            let generics = print_generics(&u.generics, tcx);
+            let where_clause = print_where_clause(&u.generics, tcx);
+            let sep = if where_clause.is_empty() { " " } else { "\n" };
+            let fields = print_fields(&u.fields, u.has_stripped_entries(), "", tcx);
+            format!("{vis}union {name}{generics}{where_clause}{sep}{fields}")
+        }
+        clean::EnumItem(ref e) => {
+            let mut s = format!(
+                "{}enum {}{}{}",
+                vis,
+                name,
+                print_generics(&e.generics, tcx),
+                print_where_clause(&e.generics, tcx)
+            );
+            s.push_str(if e.generics.where_predicates.is_empty() { " {" } else { "\n{" });
+            for variant in e.variants() {
+                s.push_str(&format!("\n    {},", print_variant(variant, tcx)));
+            }
+            if e.has_stripped_entries() {
+                s.push_str("\n    // some variants omitted");
+            }
+            s.push_str(if s.ends_with('{') { "}" } else { "\n}" });
+            s
+        }
+        clean::TypedefItem(ref t) => format!(
+            "{}type {}{}{} = {};",
+            vis,
+            name,
+            print_generics(&t.generics, tcx),
+            print_where_clause(&t.generics, tcx),
+            print_type(&t.type_, tcx)
+        ),
+        clean::OpaqueTyItem(ref t) => format!(
+            "type {}{}{} = impl {};",
+            name,
+            print_generics(&t.generics, tcx),
+            print_where_clause(&t.generics, tcx),
+            print_generic_bounds(&t.bounds, tcx)
+        ),
+        clean::StaticItem(ref s) | clean::ForeignStaticItem(ref s) => format!(
+            "{}static {}{}: {};",
+            vis,
+            s.mutability.print_with_space(),
+            name,
+            print_type(&s.type_, tcx)
+        ),
+        clean::ConstantItem(ref c) => {
+            format!("{}const {}: {} = {};", vis, name, print_type(&c.type_, tcx), c.expr(tcx))
+        }
+        clean::TraitItem(ref t) => {
+            let bounds = if t.bounds.is_empty() {
+                String::new()
+            } else {
+                format!(": {}", print_generic_bounds(&t.bounds, tcx))
+            };
+            let mut s = format!(
+                "{}{}{}trait {}{}{}{}",
+                vis,
+                t.unsafety(tcx).print_with_space(),
+                if t.is_auto(tcx) { "auto " } else { "" },
+                name,
+                print_generics(&t.generics, tcx),
+                bounds,
+                print_where_clause(&t.generics, tcx)
+            );
+            s.push_str(if t.generics.where_predicates.is_empty() { " {" } else { "\n{" });
+            for item in &t.items {
+                if let Some(decl) = print_assoc_item(item, true, tcx) {
+                    s.push_str("\n    ");
+                    s.push_str(&decl.replace('\n', "\n    "));
+                }
+            }
+            s.push_str(if s.ends_with('{') { "}" } else { "\n}" });
+            s
+        }
+        clean::TraitAliasItem(ref t) => format!(
+            "trait {}{}{} = {};",
+            name,
+            print_generics(&t.generics, tcx),
+            print_where_clause(&t.generics, tcx),
+            print_generic_bounds(&t.bounds, tcx)
+        ),
+        clean::ForeignTypeItem => format!("{}type {};", vis, name),
+        clean::MacroItem(ref m) => m.source.clone(),
+        clean::ProcMacroItem(ref m) => match m.kind {
+            MacroKind::Bang => format!("{}!() {{ /* proc-macro */ }}", name),
+            MacroKind::Attr => format!("#[{}]", name),
+            MacroKind::Derive => {
+                let mut s = format!("#[derive({})]", name);
+                if !m.helpers.is_empty() {
+                    s.push_str("\n\n// Attributes available to this derive:");
+                    for attr in &m.helpers {
+                        s.push_str(&format!("\n#[{}]", attr));
+                    }
+                }
+                s
+            }
+        },
+        _ => return None,
+    };
+    Some(s)
+}
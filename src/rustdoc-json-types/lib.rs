@@ -9,7 +9,7 @@
 use serde::{Deserialize, Serialize};
 
 /// rustdoc format-version.
-pub const FORMAT_VERSION: u32 = 23;
+pub const FORMAT_VERSION: u32 = 24;
 
 /// A `Crate` is the root of the emitted JSON blob. It contains all type/documentation information
 /// about the language items in the local crate, as well as info about external items to allow
@@ -29,6 +29,9 @@ pub struct Crate {
     pub paths: HashMap<Id, ItemSummary>,
     /// Maps `crate_id` of items to a crate name and html_root_url if it exists.
     pub external_crates: HashMap<u32, ExternalCrate>,
+    /// Maps the IDs of functions and methods in `index` that have a body to the items their body
+    /// refers to. Only present if the `--json-call-graph` flag was passed.
+    pub call_graph: Option<HashMap<Id, Vec<Reference>>>,
     /// A single version number to be used in the future when making backwards incompatible changes
     /// to the JSON output.
     pub format_version: u32,
@@ -61,6 +64,44 @@ pub struct ItemSummary {
     pub kind: ItemKind,
 }
 
+/// An item that the body of a function refers to.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Reference {
+    /// The ID of the referenced item. It is only guaranteed to be in the `index` or `paths` of
+    /// [`Crate`] if the item is part of the documentation.
+    pub id: Id,
+    /// The fully qualified path of the referenced item, starting with the name of its crate (e.g.
+    /// `["alloc", "vec", "Vec", "push"]` for [`Vec::push`]).
+    ///
+    /// Associated items of inherent impls are named after their self type, and associated items
+    /// of trait impls are named after the corresponding trait item.
+    pub path: Vec<String>,
+    /// Whether the item is called or only named.
+    pub kind: ReferenceKind,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReferenceKind {
+    /// The item is a function, method or tuple constructor that is called.
+    ///
+    /// ```rust
+    /// # fn f() -> Vec<i32> {
+    /// Vec::new()
+    /// # }
+    /// ```
+    Call,
+    /// The item is named without being called, like a type, a constant, or a function that is
+    /// used as a value.
+    ///
+    /// ```rust
+    /// # fn f() {
+    /// let new: fn() -> Vec<i32> = Vec::new;
+    /// # }
+    /// ```
+    Name,
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Item {
     /// The unique identifier of this item. Can be used to find this item in various mappings.
@@ -22,6 +22,7 @@ pub const m_test: test::TestDescAndFn =
             no_run: false,
             should_panic: test::ShouldPanic::No,
             test_type: test::TestType::Unknown,
+            timeout: ::core::option::Option::None,
         },
         testfn: test::StaticTestFn(|| test::assert_test_result(m_test())),
     };
@@ -40,6 +41,7 @@ pub const z_test: test::TestDescAndFn =
             no_run: false,
             should_panic: test::ShouldPanic::No,
             test_type: test::TestType::Unknown,
+            timeout: ::core::option::Option::None,
         },
         testfn: test::StaticTestFn(|| test::assert_test_result(z_test())),
     };
@@ -58,6 +60,7 @@ pub const a_test: test::TestDescAndFn =
             no_run: false,
             should_panic: test::ShouldPanic::No,
             test_type: test::TestType::Unknown,
+            timeout: ::core::option::Option::None,
         },
         testfn: test::StaticTestFn(|| test::assert_test_result(a_test())),
     };
@@ -0,0 +1,9 @@
+mod helper {
+    pub fn greeting() -> &'static str {
+        "hello"
+    }
+}
+
+fn main() {
+    println!("{} from {}", helper::greeting(), file!());
+}
@@ -0,0 +1,2 @@
+// Makes the object file of this crate, and so its session directory, larger than 1 MiB.
+pub static DATA: [u8; 2 * 1024 * 1024] = [1; 2 * 1024 * 1024];
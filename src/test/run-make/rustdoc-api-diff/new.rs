@@ -0,0 +1,31 @@
+use std::marker::PhantomData;
+
+pub struct Parser {
+    pub strict: bool,
+    marker: PhantomData<*const ()>,
+}
+
+impl Parser {
+    pub fn new() -> Parser {
+        Parser { strict: false, marker: PhantomData }
+    }
+
+    pub fn parse(&self, input: &str) -> Option<usize> {
+        Some(input.len())
+    }
+}
+
+#[non_exhaustive]
+pub enum Mode {
+    Fast,
+    Slow,
+    Lazy,
+}
+
+pub trait Visit {
+    fn visit(&self);
+
+    fn leave(&self) {}
+}
+
+pub fn added<T: Clone>(_: T) {}
@@ -0,0 +1,20 @@
+pub struct Parser {
+    pub strict: bool,
+}
+
+impl Parser {
+    pub fn parse(&self, input: &str) -> usize {
+        input.len()
+    }
+}
+
+pub enum Mode {
+    Fast,
+    Slow,
+}
+
+pub trait Visit {
+    fn visit(&self);
+}
+
+pub fn removed() {}
@@ -0,0 +1,30 @@
+//! The foo crate.
+
+pub mod inner {
+    /// Makes a [`Bar`](crate::Bar).
+    ///
+    /// ```
+    /// # let hidden = 1;
+    /// let bar = foo::inner::make();
+    /// ```
+    pub fn make() -> crate::Bar {
+        crate::Bar { value: 0 }
+    }
+}
+
+/// A bar, made by [`inner::make`].
+///
+/// # Examples
+///
+/// Some text.
+pub struct Bar {
+    /// The value.
+    pub value: u32,
+}
+
+impl Bar {
+    /// Returns the value.
+    pub fn get(&self) -> u32 {
+        self.value
+    }
+}
@@ -0,0 +1,36 @@
+#!/usr/bin/env python
+
+# Checks the SARIF log that rustc printed to its stderr. The log has to be a single JSON document,
+# which is only written once the emitter is dropped at the end of the compilation.
+
+import sys
+import json
+
+success = sys.argv[1] == "success"
+log = json.load(sys.stdin)
+
+assert log["version"] == "2.1.0", log["version"]
+[run] = log["runs"]
+driver = run["tool"]["driver"]
+assert driver["name"] == "rustc", driver
+assert run["invocations"] == [{"executionSuccessful": success}], run["invocations"]
+
+# There is no result for "aborting due to previous error" or the number of warnings.
+[result] = run["results"]
+[location] = result["locations"]
+location = location["physicalLocation"]
+assert location["region"]["startLine"] == 2, location
+
+if success:
+    assert result["level"] == "warning", result
+    assert result["ruleId"] == "unused_variables", result
+    assert location["artifactLocation"]["uri"].endswith("warning.rs"), location
+    # The suggestion to prefix the variable with an underscore.
+    assert result["fixes"], result
+else:
+    assert result["level"] == "error", result
+    assert result["ruleId"] == "E0308", result
+    assert location["artifactLocation"]["uri"].endswith("error.rs"), location
+    rule = driver["rules"][result["ruleIndex"]]
+    assert rule["id"] == "E0308", rule
+    assert rule["helpUri"].endswith("#E0308"), rule
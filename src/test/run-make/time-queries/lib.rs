@@ -0,0 +1,10 @@
+#![crate_type = "lib"]
+
+pub struct Point {
+    pub x: i32,
+    pub y: i32,
+}
+
+pub fn manhattan(a: &Point, b: &Point) -> i32 {
+    (a.x - b.x).abs() + (a.y - b.y).abs()
+}
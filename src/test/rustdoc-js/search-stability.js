@@ -0,0 +1,29 @@
+// ignore-order
+
+const QUERY = 'stability';
+
+const EXPECTED = {
+    'others': [
+        {
+            'path': 'search_stability',
+            'name': 'StabilityStable',
+            'unstable': null,
+            'since': '1.0.0',
+            'deprecated': false,
+        },
+        {
+            'path': 'search_stability',
+            'name': 'StabilityUnstable',
+            'unstable': 'stability_experiment',
+            'since': null,
+            'deprecated': false,
+        },
+        {
+            'path': 'search_stability',
+            'name': 'StabilityDeprecated',
+            'unstable': null,
+            'since': '1.0.0',
+            'deprecated': true,
+        },
+    ],
+};
@@ -0,0 +1,12 @@
+#![feature(staged_api)]
+#![stable(feature = "rust1", since = "1.0.0")]
+
+#[stable(feature = "rust1", since = "1.0.0")]
+pub struct StabilityStable;
+
+#[unstable(feature = "stability_experiment", issue = "none")]
+pub struct StabilityUnstable;
+
+#[stable(feature = "rust1", since = "1.0.0")]
+#[deprecated(since = "1.1.0", note = "use `StabilityStable` instead")]
+pub struct StabilityDeprecated;
@@ -0,0 +1,42 @@
+// compile-flags: --json-call-graph
+
+// @set counter = "$.index[*][?(@.name=='Counter')].id"
+pub struct Counter(pub u32);
+
+impl Counter {
+    // @set bump = "$.index[*][?(@.name=='bump')].id"
+    pub fn bump(&mut self) {
+        self.0 = step(self.0);
+    }
+}
+
+// @set step = "$.index[*][?(@.name=='step')].id"
+pub fn step(x: u32) -> u32 {
+    x + LIMIT
+}
+
+// @set limit = "$.index[*][?(@.name=='LIMIT')].id"
+pub const LIMIT: u32 = 1;
+
+pub fn run() -> Counter {
+    let mut counter = Counter(0);
+    counter.bump();
+    counter
+}
+
+// Required methods have no body.
+pub trait Reset {
+    fn reset(&mut self);
+}
+
+// @count "$.call_graph[*]" 3
+// @count "$.call_graph[*][?(@.kind=='call')]" 3
+// @has "$.call_graph[*][*].id" $step
+// @has "$.call_graph[*][*].id" $bump
+// @has "$.call_graph[*][*].path" '["call_graph", "Counter", "bump"]'
+// Calling a tuple struct constructor refers to the struct.
+// @has "$.call_graph[*][*].id" $counter
+
+// @count "$.call_graph[*][?(@.kind=='name')]" 1
+// @is "$.call_graph[*][?(@.kind=='name')].id" $limit
+// @is "$.call_graph[*][?(@.kind=='name')].path" '["call_graph", "LIMIT"]'
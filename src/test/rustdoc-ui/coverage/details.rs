@@ -0,0 +1,15 @@
+// compile-flags:-Z unstable-options --show-coverage --coverage-details
+// check-pass
+
+//! Crate docs
+
+/// Documented
+pub struct Foo {
+    pub field: u32,
+}
+
+impl Foo {
+    pub fn method(&self) {}
+}
+
+pub fn undocumented() {}
@@ -0,0 +1,15 @@
+// compile-flags:-Z unstable-options --show-coverage --coverage-threshold 50
+// compile-flags:--coverage-threshold fn=100 --coverage-threshold struct=100
+
+//! Crate docs
+
+/// Documented
+pub struct Foo {
+    pub field: u32,
+}
+
+impl Foo {
+    pub fn method(&self) {}
+}
+
+pub fn undocumented() {}
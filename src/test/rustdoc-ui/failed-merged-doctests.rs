@@ -0,0 +1,33 @@
+// ignore-windows
+// The exit status of failed doctests is printed differently on Windows.
+
+// edition:2021
+// compile-flags:--test -Zunstable-options --merge-doctests --test-args=--test-threads=1
+// rustc-env:RUST_BACKTRACE=0
+// normalize-stdout-test: "src/test/rustdoc-ui" -> "$$DIR"
+// normalize-stdout-test "finished in \d+\.\d+s" -> "finished in $$TIME"
+// failure-status: 101
+
+// These doctests are merged, so the panic is reported at its line in this file.
+/// ```
+/// assert_eq!(1 + 1, 2);
+/// ```
+///
+/// ```
+/// panic!("merged");
+/// ```
+pub struct Merged;
+
+// These doctests define the same symbol, so their merged binary fails to compile and they are
+// compiled separately, which reports the panic at its line in the generated source.
+/// ```edition2018
+/// #[no_mangle]
+/// pub extern "C" fn hook() {}
+/// ```
+///
+/// ```edition2018
+/// #[no_mangle]
+/// pub extern "C" fn hook() {}
+/// panic!("separate");
+/// ```
+pub struct Separate;
@@ -0,0 +1,35 @@
+// edition:2021
+// compile-flags:--test -Zunstable-options --merge-doctests --test-args=--test-threads=1
+// normalize-stdout-test: "src/test/rustdoc-ui" -> "$$DIR"
+// normalize-stdout-test "finished in \d+\.\d+s" -> "finished in $$TIME"
+// check-pass
+
+//! ```
+//! assert_eq!(merged_doctests::answer(), 42);
+//! ```
+
+/// ```
+/// let answer: u32 = "42".parse()?;
+/// assert_eq!(answer, merged_doctests::answer());
+/// Ok::<(), std::num::ParseIntError>(())
+/// ```
+///
+/// ```should_panic
+/// assert_eq!(merged_doctests::answer(), 0);
+/// ```
+///
+/// ```no_run
+/// loop {}
+/// ```
+pub fn answer() -> u32 {
+    42
+}
+
+/// This doctest has its own `main`, so it is compiled separately.
+///
+/// ```
+/// fn main() {
+///     std::process::exit(0);
+/// }
+/// ```
+pub struct Standalone;
@@ -0,0 +1,133 @@
+// run-pass
+// Test that users are able to use stable mir APIs to retrieve information of the current crate
+
+// ignore-stage1
+// ignore-cross-compile
+// ignore-remote
+// edition: 2021
+
+#![feature(rustc_private)]
+
+extern crate rustc_driver;
+extern crate rustc_smir;
+
+use rustc_smir::stable_mir::mir::{Operand, StatementKind, TerminatorKind};
+use rustc_smir::stable_mir::ty::{RigidTy, TyKind};
+use rustc_smir::very_unstable::hir::def_id::CRATE_DEF_ID;
+use rustc_smir::{rustc_internal, rustc_internal::StableMir, stable_mir};
+use std::io::Write;
+use std::ops::ControlFlow;
+
+const CRATE_NAME: &str = "input";
+
+/// This function uses the Stable MIR APIs to get information about the test crate.
+fn test_stable_mir() -> ControlFlow<()> {
+    // Get the local crate using stable_mir API.
+    let local = stable_mir::local_crate();
+    assert_eq!(&local.name, CRATE_NAME);
+    assert!(local.is_local);
+
+    assert_eq!(stable_mir::entry_fn(), None);
+
+    // Find items in the local crate.
+    let items = stable_mir::all_local_items();
+    assert!(get_item(&items, "foo::bar").is_some());
+
+    // Find the `std` crate.
+    assert!(stable_mir::find_crate("std").is_some());
+    assert!(stable_mir::external_crates().iter().any(|krate| krate.name == "core"));
+
+    let bar = get_item(&items, "bar").unwrap();
+    assert_eq!(bar.kind(), Some(stable_mir::ItemKind::Fn));
+    let body = bar.body().unwrap();
+    assert_eq!(body.locals.len(), 2);
+    assert_eq!(body.arg_count, 1);
+    assert_eq!(body.blocks.len(), 1);
+    let block = &body.blocks[0];
+    assert_eq!(block.statements.len(), 1);
+    match &block.statements[0].kind {
+        StatementKind::Assign(..) => {}
+        other => panic!("{other:?}"),
+    }
+    match &block.terminator.kind {
+        TerminatorKind::Return => {}
+        other => panic!("{other:?}"),
+    }
+    match body.ret_local().ty.kind() {
+        TyKind::RigidTy(RigidTy::Int(_)) => {}
+        other => panic!("{other:?}"),
+    }
+
+    let foo_bar = get_item(&items, "foo_bar").unwrap();
+    let body = foo_bar.body().unwrap();
+    assert_eq!(body.arg_count, 2);
+    let block = &body.blocks[0];
+    match &block.terminator.kind {
+        TerminatorKind::Call { func: Operand::Constant(_), args, .. } => assert_eq!(args.len(), 1),
+        other => panic!("{other:?}"),
+    }
+
+    let lines = foo_bar.span().get_lines();
+    assert_eq!(lines.start_line, 6);
+    assert!(foo_bar.span().get_filename().ends_with("input.rs"));
+
+    // Items without MIR, like the crate root module, have neither a kind nor a body.
+    let krate = rustc_internal::crate_item(CRATE_DEF_ID.to_def_id());
+    assert_eq!(krate.kind(), None);
+    assert!(krate.body().is_none());
+
+    ControlFlow::Break(())
+}
+
+// Use internal API to find a function in a crate.
+fn get_item<'a>(
+    items: &'a stable_mir::CrateItems,
+    name: &str,
+) -> Option<&'a stable_mir::CrateItem> {
+    items.iter().find(|crate_item| crate_item.name() == name)
+}
+
+/// This test will generate and analyze a dummy crate using the stable mir.
+/// For that, it will first write the dummy crate into a file.
+/// Then it will create a `StableMir` using custom arguments and then
+/// it will run the compiler.
+fn main() {
+    let path = "input.rs";
+    generate_input(&path).unwrap();
+    let args = vec![
+        "rustc".to_string(),
+        "--crate-type=lib".to_string(),
+        "--crate-name".to_string(),
+        CRATE_NAME.to_string(),
+        path.to_string(),
+    ];
+    rustc_driver::catch_fatal_errors(|| StableMir::new(args, test_stable_mir).run().unwrap())
+        .unwrap();
+}
+
+fn generate_input(path: &str) -> std::io::Result<()> {
+    let mut file = std::fs::File::create(path)?;
+    write!(
+        file,
+        r#"
+    pub fn bar(x: i32) -> i32 {{
+        x
+    }}
+
+    pub fn foo_bar(x: i32, y: i32) -> i64 {{
+        let x_64 = foo(x);
+        let y_64 = foo(y);
+        x_64.wrapping_add(y_64)
+    }}
+
+    pub fn foo(x: i32) -> i64 {{
+        x as i64
+    }}
+
+    pub mod foo {{
+        pub fn bar() {{}}
+    }}
+    "#
+    )?;
+    Ok(())
+}
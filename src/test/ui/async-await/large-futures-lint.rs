@@ -0,0 +1,20 @@
+// build-fail
+// edition:2021
+// compile-flags: -Z future-size-limit=1000
+
+#![deny(large_futures)]
+
+async fn big() { //~ ERROR large future with a size of 2001 bytes
+    let buf = [0u8; 2000];
+    std::future::pending::<()>().await;
+    drop(buf);
+}
+
+fn main() {
+    let _ = big();
+    let _ = async { //~ ERROR large future with a size of 3001 bytes
+        let buf = [0u8; 3000];
+        std::future::pending::<()>().await;
+        drop(buf);
+    };
+}
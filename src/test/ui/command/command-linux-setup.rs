@@ -0,0 +1,68 @@
+// run-pass
+// only-linux - these options are specific to linux
+// ignore-emscripten no processes
+// ignore-sgx no processes
+
+#![feature(linux_process_setup)]
+#![feature(rustc_private)]
+
+extern crate libc;
+
+use std::fs;
+use std::os::linux::process::{CommandExt, Namespace, Resource};
+use std::process::Command;
+
+fn main() {
+    // The child process sees the limits that were set for it.
+    let output = Command::new("sh")
+        .arg("-c")
+        .arg("ulimit -S -n; ulimit -c")
+        .setrlimit(Resource::NoFile, 64, 128)
+        .setrlimit(Resource::Core, 0, 0)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "64\n0\n");
+
+    // A file descriptor without the close-on-exec flag is inherited, unless the child process
+    // should only inherit the standard ones.
+    let fd = unsafe { libc::dup(libc::STDERR_FILENO) };
+    assert!(fd > libc::STDERR_FILENO);
+    let script = format!("test -e /proc/self/fd/{fd}");
+
+    let status = Command::new("sh").arg("-c").arg(&script).status().unwrap();
+    assert!(status.success());
+
+    let status =
+        Command::new("sh").arg("-c").arg(&script).close_other_fds(true).status().unwrap();
+    assert!(!status.success());
+
+    // Errors to set an option are returned by `spawn`.
+    let err = Command::new("sh").cgroup("/nonexistent/cgroup").spawn().unwrap_err();
+    assert_eq!(err.raw_os_error(), Some(libc::ENOENT));
+
+    // The child process runs in new namespaces, if the kernel lets it create them. Otherwise,
+    // `spawn` returns the error from `unshare`.
+    let res = Command::new("readlink")
+        .arg("/proc/self/ns/uts")
+        .unshare(Namespace::User)
+        .unshare(Namespace::Uts)
+        .output();
+    match res {
+        Ok(output) => {
+            assert!(output.status.success());
+            let child_ns = String::from_utf8(output.stdout).unwrap();
+            let parent_ns = fs::read_link("/proc/self/ns/uts").unwrap();
+            assert_ne!(child_ns.trim_end(), parent_ns.to_str().unwrap());
+        }
+        Err(err) => assert!(
+            matches!(
+                err.raw_os_error(),
+                Some(libc::EPERM | libc::EINVAL | libc::ENOSPC | libc::ENOSYS)
+            ),
+            "unexpected error: {err}"
+        ),
+    }
+
+    unsafe { libc::close(fd) };
+}
@@ -0,0 +1,24 @@
+// build-fail
+// compile-flags: -Z future-size-limit=1000
+
+#![feature(generators, generator_trait)]
+#![deny(large_futures)]
+
+use std::ops::Generator;
+use std::pin::Pin;
+
+// The size of a generic generator is checked for every instantiation of it.
+fn hold<const N: usize>() -> impl Generator<Yield = (), Return = ()> {
+    || { //~ ERROR large generator with a size of 2001 bytes
+        let buf = [0u8; N];
+        yield;
+        drop(buf);
+    }
+}
+
+fn main() {
+    let mut small = hold::<10>();
+    let mut big = hold::<2000>();
+    Pin::new(&mut small).resume(());
+    Pin::new(&mut big).resume(());
+}
@@ -0,0 +1,24 @@
+// build-fail
+// compile-flags: -Z future-size-limit=1000
+
+#![feature(generators, generator_trait)]
+#![deny(large_futures)]
+
+use std::ops::Generator;
+use std::pin::Pin;
+
+fn main() {
+    let mut big = || { //~ ERROR large generator with a size of 2001 bytes
+        let buf = [0u8; 2000];
+        yield;
+        yield;
+        drop(buf);
+    };
+    let mut small = || {
+        let buf = [0u8; 10];
+        yield;
+        drop(buf);
+    };
+    Pin::new(&mut big).resume(());
+    Pin::new(&mut small).resume(());
+}
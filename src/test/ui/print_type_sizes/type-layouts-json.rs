@@ -0,0 +1,36 @@
+// compile-flags: --print type-layouts=json -Z unstable-options
+// build-pass
+// ignore-pass
+// ^-- needed because `--pass check` does not emit the output needed.
+
+// This file checks the machine-readable layout dump: niches, tag
+// encodings and per-variant padding of a struct, a tagged enum and a
+// niche-filled enum.
+//
+// It avoids using u64/i64 because on some targets that is only 4-byte
+// aligned (while on most it is 8-byte aligned) and so the resulting
+// padding and overall computed sizes can be quite different.
+
+#![feature(start)]
+#![allow(dead_code)]
+
+struct S {
+    a: bool,
+    b: bool,
+    g: u32,
+}
+
+enum E {
+    A(u32),
+    B(u32, u32),
+}
+
+enum N {
+    Big(u8, bool),
+    Small(u8),
+}
+
+#[start]
+fn start(_: isize, _: *const *const u8) -> isize {
+    0
+}
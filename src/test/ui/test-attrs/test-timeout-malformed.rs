@@ -0,0 +1,11 @@
+// compile-flags: --test
+
+#![feature(test_timeout)]
+
+#[test]
+#[test_timeout = "soon"] //~ ERROR malformed `test_timeout` attribute input
+fn not_a_number() {}
+
+#[test]
+#[test_timeout = "0"] //~ ERROR malformed `test_timeout` attribute input
+fn zero() {}
@@ -993,6 +993,9 @@ pub fn make_test_description<R: Read>(
         compile_fail: false,
         no_run: false,
         test_type: test::TestType::Unknown,
+        // compiletest is built against the bootstrap compiler's libtest.
+        #[cfg(not(bootstrap))]
+        timeout: None,
     }
 }
 
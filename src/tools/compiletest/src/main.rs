@@ -513,6 +513,11 @@ pub fn test_opts(config: &Config) -> test::TestOpts {
         list: false,
         options: test::Options::new(),
         time_options: None,
+        // compiletest is built against the bootstrap compiler's libtest.
+        #[cfg(not(bootstrap))]
+        shard: None,
+        #[cfg(not(bootstrap))]
+        test_timeout: None,
         force_run_in_process: false,
         fail_fast: std::env::var_os("RUSTC_TEST_FAIL_FAST").is_some(),
     }
@@ -43,6 +43,7 @@ fn errors_on_missing_links() {
         )]),
         paths: HashMap::new(),
         external_crates: HashMap::new(),
+        call_graph: None,
         format_version: rustdoc_json_types::FORMAT_VERSION,
     };
 
@@ -32,7 +32,9 @@
 };
 use rustc_session::{config::CrateType, search_paths::PathKind, CtfeBacktrace};
 
-use miri::{BacktraceStyle, ProvenanceMode, RetagFields};
+use miri::{
+    BacktraceStyle, BorrowTrackerMethod, ProvenanceMode, RetagFields, Schedule, ScheduleLogMode,
+};
 
 struct MiriCompilerCalls {
     miri_config: miri::MiriConfig,
@@ -82,6 +84,29 @@ fn after_analysis<'tcx>(
                 env::set_current_dir(cwd).unwrap();
             }
 
+            if let Some(seeds) = config.many_seeds.clone() {
+                for seed in seeds {
+                    eprintln!("Trying seed: {seed}");
+                    let config = miri::MiriConfig { seed: Some(seed), ..config.clone() };
+                    match miri::eval_entry(tcx, entry_def_id, entry_type, config) {
+                        Some(0) => {}
+                        Some(return_code) => {
+                            eprintln!("Failing seed: {seed}");
+                            std::process::exit(
+                                i32::try_from(return_code).expect("Return value was too large!"),
+                            );
+                        }
+                        None => {
+                            eprintln!("Failing seed: {seed}");
+                            break;
+                        }
+                    }
+                }
+                // Either all seeds passed, or the failure has been reported as an error.
+                compiler.session().abort_if_errors();
+                std::process::exit(0);
+            }
+
             if let Some(return_code) = miri::eval_entry(tcx, entry_def_id, entry_type, config) {
                 std::process::exit(
                     i32::try_from(return_code).expect("Return value was too large!"),
@@ -315,6 +340,10 @@ fn main() {
             miri_config.validate = false;
         } else if arg == "-Zmiri-disable-stacked-borrows" {
             miri_config.borrow_tracker = None;
+        } else if arg == "-Zmiri-tree-borrows" {
+            miri_config.borrow_tracker = Some(BorrowTrackerMethod::TreeBorrows);
+            // Tree Borrows does not track wildcard pointers.
+            miri_config.provenance_mode = ProvenanceMode::Strict;
         } else if arg == "-Zmiri-disable-data-race-detector" {
             miri_config.data_race_detector = false;
             miri_config.weak_memory_emulation = false;
@@ -512,12 +541,67 @@ fn main() {
             };
 
             miri_config.num_cpus = num_cpus;
+        } else if let Some(param) = arg.strip_prefix("-Zmiri-record-schedule=") {
+            if miri_config.schedule_log.is_some() {
+                show_error!(
+                    "-Zmiri-record-schedule and -Zmiri-replay-schedule can only be given once and cannot be combined"
+                );
+            }
+            miri_config.schedule_log = Some(ScheduleLogMode::Record(PathBuf::from(param)));
+        } else if let Some(param) = arg.strip_prefix("-Zmiri-replay-schedule=") {
+            if miri_config.schedule_log.is_some() {
+                show_error!(
+                    "-Zmiri-record-schedule and -Zmiri-replay-schedule can only be given once and cannot be combined"
+                );
+            }
+            let text = std::fs::read_to_string(param).unwrap_or_else(|err| {
+                show_error!("-Zmiri-replay-schedule could not read `{}`: {}", param, err)
+            });
+            let schedule = Schedule::parse(&text).unwrap_or_else(|err| {
+                show_error!("-Zmiri-replay-schedule could not parse `{}`: {}", param, err)
+            });
+            miri_config.schedule_log = Some(ScheduleLogMode::Replay(schedule));
+        } else if let Some(param) = arg.strip_prefix("-Zmiri-coverage=") {
+            miri_config.coverage_out = Some(param.to_string());
+        } else if let Some(param) = arg.strip_prefix("-Zmiri-many-seeds=") {
+            let seeds = param
+                .split_once("..")
+                .and_then(|(start, end)| Some(start.parse::<u64>().ok()?..end.parse::<u64>().ok()?))
+                .unwrap_or_else(|| {
+                    show_error!(
+                        "-Zmiri-many-seeds requires a range of seeds of the form `<from>..<to>`"
+                    )
+                });
+            miri_config.many_seeds = Some(seeds);
         } else {
             // Forward to rustc.
             rustc_args.push(arg);
         }
     }
 
+    // A replayed schedule only reproduces the recorded run with the seed of that run.
+    if let Some(ScheduleLogMode::Replay(Schedule { seed: Some(seed), .. })) =
+        miri_config.schedule_log
+    {
+        match miri_config.seed {
+            Some(other) if other != seed =>
+                show_error!(
+                    "-Zmiri-seed={} conflicts with the seed {} of the replayed schedule",
+                    other,
+                    seed
+                ),
+            _ => miri_config.seed = Some(seed),
+        }
+    }
+    if miri_config.many_seeds.is_some() {
+        if miri_config.seed.is_some() {
+            show_error!("-Zmiri-many-seeds cannot be combined with -Zmiri-seed");
+        }
+        if matches!(miri_config.schedule_log, Some(ScheduleLogMode::Replay(_))) {
+            show_error!("-Zmiri-many-seeds cannot be combined with -Zmiri-replay-schedule");
+        }
+    }
+
     debug!("rustc arguments: {:?}", rustc_args);
     debug!("crate arguments: {:?}", miri_config.args);
     run_compiler(rustc_args, /* target_crate: */ true, &mut MiriCompilerCalls { miri_config })
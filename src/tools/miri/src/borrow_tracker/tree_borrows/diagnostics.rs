@@ -0,0 +1,188 @@
+use std::fmt;
+
+use rustc_span::{Span, SpanData};
+use rustc_target::abi::Size;
+
+use crate::borrow_tracker::tree_borrows::{err_tb_ub, perms::Permission};
+use crate::borrow_tracker::AccessKind;
+use crate::*;
+
+/// A change of permission of a tag, recorded so that errors can explain how the tag lost
+/// the permission that the failing access required.
+#[derive(Clone, Debug)]
+pub struct Event {
+    pub from: Permission,
+    pub to: Permission,
+    /// The access that caused the transition.
+    pub access_kind: AccessKind,
+    /// Whether the access happened through a tag that is not a child of this one.
+    pub is_foreign: bool,
+    pub access_range: AllocRange,
+    pub span: Span,
+}
+
+/// Everything we remember about a tag for diagnostics.
+#[derive(Clone, Debug)]
+pub struct NodeDebugInfo {
+    pub tag: BorTag,
+    pub initial_perm: Permission,
+    pub created: Span,
+    pub history: Vec<Event>,
+}
+
+impl NodeDebugInfo {
+    pub fn new(tag: BorTag, initial_perm: Permission, created: Span) -> Self {
+        Self { tag, initial_perm, created, history: Vec::new() }
+    }
+
+    /// Records a transition, unless it repeats the last recorded one, which happens when the
+    /// same access changes the permission of several locations.
+    pub fn record(&mut self, event: Event) {
+        if let Some(last) = self.history.last() {
+            if last.from == event.from
+                && last.to == event.to
+                && last.access_kind == event.access_kind
+                && last.is_foreign == event.is_foreign
+                && last.access_range == event.access_range
+                && last.span == event.span
+            {
+                return;
+            }
+        }
+        self.history.push(event);
+    }
+}
+
+/// The notes attached to a Tree Borrows error, each pointing at the code that caused it.
+#[derive(Clone, Debug, Default)]
+pub struct HistoryData {
+    pub events: Vec<(String, SpanData)>,
+}
+
+impl HistoryData {
+    /// Explains where `info` was created, and how it reached its state at `offset`.
+    fn extend_with(&mut self, info: &NodeDebugInfo, role: &str, offset: Size) {
+        let tag = info.tag;
+        self.events.push((
+            format!(
+                "the {role} tag {tag:?} was created here, in the initial state {}",
+                info.initial_perm
+            ),
+            info.created.data(),
+        ));
+        for event in &info.history {
+            let range = event.access_range;
+            if !(range.start <= offset && offset < range.end()) {
+                continue;
+            }
+            let relation = if event.is_foreign { "foreign" } else { "child" };
+            self.events.push((
+                format!(
+                    "the {role} tag {tag:?} later transitioned to {} due to a {relation} {} at offsets [{:#x}..{:#x}]",
+                    event.to,
+                    event.access_kind,
+                    range.start.bytes(),
+                    range.end().bytes(),
+                ),
+                event.span.data(),
+            ));
+        }
+    }
+}
+
+/// The operation that failed.
+#[derive(Copy, Clone, Debug)]
+pub(super) enum Operation {
+    Access(AccessKind),
+    Dealloc,
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Operation::Access(kind) => write!(f, "{kind}"),
+            Operation::Dealloc => write!(f, "deallocation"),
+        }
+    }
+}
+
+/// Why an operation is UB.
+#[derive(Copy, Clone, Debug)]
+pub(super) enum TbErrorKind {
+    /// The conflicting tag is the accessed tag or one of its parents, and its permission does
+    /// not allow the access.
+    ChildAccessForbidden(Permission),
+    /// The conflicting tag is protected, and the (foreign) access would take away a permission
+    /// it already used.
+    ProtectedTransition { from: Permission, to: Permission },
+    /// The conflicting tag is strongly protected, so the allocation cannot be deallocated.
+    ProtectedDealloc,
+}
+
+/// A Tree Borrows error, before it is turned into an `InterpError`.
+pub(super) struct TbError<'node> {
+    pub kind: TbErrorKind,
+    pub operation: Operation,
+    pub alloc_id: AllocId,
+    /// The location at which the error occurred.
+    pub offset: Size,
+    pub accessed_info: &'node NodeDebugInfo,
+    pub conflicting_info: &'node NodeDebugInfo,
+}
+
+impl TbError<'_> {
+    pub fn build<'tcx>(self) -> InterpError<'tcx> {
+        let accessed = self.accessed_info.tag;
+        let conflicting = self.conflicting_info.tag;
+        let operation = self.operation;
+        let title = format!("{operation} through {accessed:?} is forbidden");
+        let mut details = Vec::new();
+        match self.kind {
+            TbErrorKind::ChildAccessForbidden(perm) =>
+                if accessed == conflicting {
+                    details.push(format!(
+                        "the accessed tag {accessed:?} has state {perm} which forbids this {operation}"
+                    ));
+                } else {
+                    details.push(format!(
+                        "the accessed tag {accessed:?} is a child of the conflicting tag {conflicting:?}"
+                    ));
+                    details.push(format!(
+                        "the conflicting tag {conflicting:?} has state {perm} which forbids this child {operation}"
+                    ));
+                },
+            TbErrorKind::ProtectedTransition { from, to } => {
+                details.push(format!(
+                    "the accessed tag {accessed:?} is foreign to the protected tag {conflicting:?} (i.e., it is not a child)"
+                ));
+                details.push(format!(
+                    "this {operation} would cause the protected tag {conflicting:?} to transition from {from} to {to}"
+                ));
+                let lost = if to == Permission::Disabled { "read and write" } else { "write" };
+                details.push(format!(
+                    "this is a loss of {lost} permissions, which is not allowed for protected tags"
+                ));
+            }
+            TbErrorKind::ProtectedDealloc => {
+                details.push(format!(
+                    "the allocation of the accessed tag {accessed:?} also contains the strongly protected tag {conflicting:?}"
+                ));
+                details.push(format!(
+                    "the strongly protected tag {conflicting:?} disallows deallocations"
+                ));
+            }
+        }
+        details.push(format!(
+            "this error occurs at {:?}[{:#x}]",
+            self.alloc_id,
+            self.offset.bytes()
+        ));
+
+        let mut history = HistoryData::default();
+        history.extend_with(self.accessed_info, "accessed", self.offset);
+        if accessed != conflicting {
+            history.extend_with(self.conflicting_info, "conflicting", self.offset);
+        }
+        err_tb_ub(title, details, history)
+    }
+}
@@ -0,0 +1,454 @@
+//! Implements "Tree Borrows", an alternative to Stacked Borrows in which the tags of an
+//! allocation form a tree instead of a stack per location.
+//!
+//! Each retag inserts the new tag as a child of the tag it was derived from, and every tag has a
+//! permission per location (see `perms.rs`). Compared to Stacked Borrows, raw pointers do not
+//! get new tags, and mutable references start out `Reserved`, which accepts the accesses that
+//! happen during a two-phase borrow.
+
+pub mod diagnostics;
+mod perms;
+mod tree;
+
+use log::trace;
+
+use rustc_middle::mir::{Mutability, RetagKind};
+use rustc_middle::ty::{
+    self,
+    layout::{HasParamEnv, LayoutOf},
+    Ty,
+};
+use rustc_target::abi::{Abi, Size};
+
+use crate::borrow_tracker::{
+    tree_borrows::diagnostics::HistoryData, AccessKind, GlobalStateInner, ProtectorKind,
+    RetagFields,
+};
+use crate::*;
+
+pub use perms::Permission;
+pub use tree::Tree;
+
+pub type AllocState = Tree;
+
+pub fn err_tb_ub<'tcx>(
+    title: String,
+    details: Vec<String>,
+    history: HistoryData,
+) -> InterpError<'tcx> {
+    err_machine_stop!(TerminationInfo::TreeBorrowsUb { title, details, history })
+}
+
+/// Glue code to connect with Miri Machine Hooks
+impl<'tcx> Tree {
+    pub fn new_allocation(
+        id: AllocId,
+        size: Size,
+        state: &mut GlobalStateInner,
+        _kind: MemoryKind<MiriMemoryKind>,
+        machine: &MiriMachine<'_, 'tcx>,
+    ) -> Self {
+        let tag = state.base_ptr_tag(id, machine);
+        Tree::new(tag, size, machine.current_span())
+    }
+
+    /// Checks an access, and updates the permissions of all tags of the allocation.
+    pub fn before_memory_access(
+        &mut self,
+        access_kind: AccessKind,
+        alloc_id: AllocId,
+        prov: ProvenanceExtra,
+        range: AllocRange,
+        machine: &MiriMachine<'_, 'tcx>,
+    ) -> InterpResult<'tcx> {
+        trace!(
+            "{access_kind} with tag {prov:?}: {:?}, size {}",
+            Pointer::new(alloc_id, range.start),
+            range.size.bytes()
+        );
+        // Tree Borrows does not track wildcard pointers, so accesses through them are not
+        // checked at all. `-Zmiri-tree-borrows` implies strict provenance to rule them out.
+        let ProvenanceExtra::Concrete(tag) = prov else {
+            return Ok(());
+        };
+        let global = machine.borrow_tracker.as_ref().unwrap().borrow();
+        self.perform_access(
+            access_kind,
+            tag,
+            range,
+            &global.protected_tags,
+            alloc_id,
+            machine.current_span(),
+        )
+    }
+
+    /// Checks that the allocation can be deallocated.
+    pub fn before_memory_deallocation(
+        &mut self,
+        alloc_id: AllocId,
+        prov: ProvenanceExtra,
+        range: AllocRange,
+        machine: &MiriMachine<'_, 'tcx>,
+    ) -> InterpResult<'tcx> {
+        trace!("deallocation with tag {prov:?}: {alloc_id:?}, size {}", range.size.bytes());
+        let ProvenanceExtra::Concrete(tag) = prov else {
+            return Ok(());
+        };
+        let global = machine.borrow_tracker.as_ref().unwrap().borrow();
+        self.dealloc(tag, range, &global.protected_tags, alloc_id, machine.current_span())
+    }
+}
+
+impl VisitTags for Tree {
+    fn visit_tags(&self, _visit: &mut dyn FnMut(BorTag)) {
+        // Tree Borrows does not track exposed tags, so the tree keeps no tag alive by itself.
+    }
+}
+
+/// Policy for a new borrow.
+#[derive(Debug, Clone, Copy)]
+struct NewPermission {
+    /// Permission of the new tag on the range of the reborrow.
+    initial_state: Permission,
+    protector: Option<ProtectorKind>,
+}
+
+impl<'tcx> NewPermission {
+    /// Determines the permission of a new reference from its pointee type.
+    /// Returns `None` if the reference keeps the tag of its parent: this is the case for
+    /// `&mut` to `!Unpin` types and `&` to types with interior mutability, since both may
+    /// alias with other pointers.
+    fn from_ref_ty(
+        pointee: Ty<'tcx>,
+        mutability: Mutability,
+        kind: RetagKind,
+        cx: &crate::MiriInterpCx<'_, 'tcx>,
+    ) -> Option<Self> {
+        let ty_is_freeze = pointee.is_freeze(*cx.tcx, cx.param_env());
+        let initial_state = match mutability {
+            Mutability::Mut if pointee.is_unpin(*cx.tcx, cx.param_env()) =>
+                Permission::Reserved { ty_is_freeze },
+            Mutability::Not if ty_is_freeze => Permission::Frozen,
+            _ => return None,
+        };
+        // Two-phase borrows are not protected: `Reserved` is what makes them work.
+        let protector = (kind == RetagKind::FnEntry).then_some(ProtectorKind::StrongProtector);
+        Some(NewPermission { initial_state, protector })
+    }
+
+    /// Boxes are treated like mutable references, except that they get a weak protector since
+    /// they may be deallocated while protected.
+    fn from_box_ty(ty: Ty<'tcx>, kind: RetagKind, cx: &crate::MiriInterpCx<'_, 'tcx>) -> Self {
+        let ty_is_freeze = ty.boxed_ty().is_freeze(*cx.tcx, cx.param_env());
+        NewPermission {
+            initial_state: Permission::Reserved { ty_is_freeze },
+            protector: (kind == RetagKind::FnEntry).then_some(ProtectorKind::WeakProtector),
+        }
+    }
+}
+
+/// Retagging/reborrowing.
+impl<'mir: 'ecx, 'tcx: 'mir, 'ecx> EvalContextPrivExt<'mir, 'tcx, 'ecx>
+    for crate::MiriInterpCx<'mir, 'tcx>
+{
+}
+trait EvalContextPrivExt<'mir: 'ecx, 'tcx: 'mir, 'ecx>: crate::MiriInterpCxExt<'mir, 'tcx> {
+    /// Inserts `new_tag` into the tree of the allocation `place` points to.
+    /// Returns the `AllocId` of that allocation, or `None` if no new tag was created and the
+    /// pointer should keep its tag.
+    fn tb_reborrow(
+        &mut self,
+        place: &MPlaceTy<'tcx, Provenance>,
+        ptr_size: Size,
+        new_perm: NewPermission,
+        new_tag: BorTag,
+    ) -> InterpResult<'tcx, Option<AllocId>> {
+        let this = self.eval_context_mut();
+
+        if ptr_size == Size::ZERO {
+            // There are no locations for the new tag to have permissions on.
+            trace!(
+                "reborrow of size 0: {:?} keeps its tag (pointee {})",
+                place.ptr, place.layout.ty
+            );
+            return Ok(None);
+        }
+
+        let (alloc_id, base_offset, parent_prov) = this.ptr_get_alloc_id(place.ptr)?;
+        // Tree Borrows does not track wildcard pointers, so there is no parent to derive from.
+        let ProvenanceExtra::Concrete(parent_tag) = parent_prov else {
+            return Ok(None);
+        };
+        let (alloc_size, _align, alloc_kind) = this.get_alloc_info(alloc_id);
+        match alloc_kind {
+            AllocKind::LiveData => {}
+            AllocKind::Function | AllocKind::VTable | AllocKind::Dead => {
+                // No tree for these allocations.
+                return Ok(None);
+            }
+        }
+
+        // Ensure we bail out if the pointer goes out-of-bounds (see miri#1050).
+        if base_offset + ptr_size > alloc_size {
+            throw_ub!(PointerOutOfBounds {
+                alloc_id,
+                alloc_size,
+                ptr_offset: this.machine_usize_to_isize(base_offset.bytes()),
+                ptr_size,
+                msg: CheckInAllocMsg::InboundsTest
+            });
+        }
+
+        let range = alloc_range(base_offset, ptr_size);
+        trace!(
+            "reborrow: reference {new_tag:?} derived from {parent_tag:?} (pointee {}): {:?}, size {}",
+            place.layout.ty,
+            Pointer::new(alloc_id, base_offset),
+            ptr_size.bytes()
+        );
+        {
+            let global = this.machine.borrow_tracker.as_ref().unwrap().borrow();
+            if global.tracked_pointer_tags.contains(&new_tag) {
+                this.emit_diagnostic(NonHaltingDiagnostic::CreatedPointerTag(
+                    new_tag.inner(),
+                    Some(format!(
+                        "{:?} (pointee type {})",
+                        new_perm.initial_state, place.layout.ty
+                    )),
+                    Some((alloc_id, range, parent_prov)),
+                ));
+            }
+        }
+
+        let span = this.machine.current_span();
+        // This should have alloc_extra data, but `get_alloc_extra` can still fail
+        // if converting this alloc_id from a global to a local one
+        // uncovers a non-supported `extern static`.
+        let alloc_extra = this.get_alloc_extra(alloc_id)?;
+        let mut tree_borrows = alloc_extra.borrow_tracker_tb().borrow_mut();
+        // Every reborrow reads the memory through the parent tag.
+        {
+            let global = this.machine.borrow_tracker.as_ref().unwrap().borrow();
+            tree_borrows.perform_access(
+                AccessKind::Read,
+                parent_tag,
+                range,
+                &global.protected_tags,
+                alloc_id,
+                span,
+            )?;
+        }
+        // Make sure the data race model also knows about this.
+        if let Some(data_race) = alloc_extra.data_race.as_ref() {
+            data_race.read(alloc_id, range, &this.machine)?;
+        }
+        tree_borrows.new_child(parent_tag, new_tag, new_perm.initial_state, range, span);
+        drop(tree_borrows);
+
+        if let Some(protect) = new_perm.protector {
+            this.frame_mut().extra.borrow_tracker.as_mut().unwrap().protected_tags.push(new_tag);
+            this.machine
+                .borrow_tracker
+                .as_mut()
+                .unwrap()
+                .get_mut()
+                .protected_tags
+                .insert(new_tag, protect);
+        }
+
+        Ok(Some(alloc_id))
+    }
+
+    /// Retags an individual pointer, returning the retagged version.
+    fn tb_retag_reference(
+        &mut self,
+        val: &ImmTy<'tcx, Provenance>,
+        new_perm: NewPermission,
+    ) -> InterpResult<'tcx, ImmTy<'tcx, Provenance>> {
+        let this = self.eval_context_mut();
+        // We want a place for where the ptr *points to*, so we get one.
+        let place = this.ref_to_mplace(val)?;
+        // FIXME: If we cannot determine the size (because the unsized tail is an `extern type`),
+        // bail out -- we cannot reasonably figure out which memory range to reborrow.
+        // See https://github.com/rust-lang/unsafe-code-guidelines/issues/276.
+        let Some(size) = this.size_and_align_of_mplace(&place)?.map(|(size, _)| size) else {
+            return Ok(val.clone());
+        };
+
+        let new_tag = this.machine.borrow_tracker.as_mut().unwrap().get_mut().new_ptr();
+        let Some(alloc_id) = this.tb_reborrow(&place, size, new_perm, new_tag)? else {
+            return Ok(val.clone());
+        };
+
+        let new_place = place.map_provenance(|p| {
+            p.map(|_| {
+                // The reborrow found the allocation, so the new pointer is tied to it.
+                Provenance::Concrete { alloc_id, tag: new_tag }
+            })
+        });
+        Ok(ImmTy::from_immediate(new_place.to_ref(this), val.layout))
+    }
+}
+
+impl<'mir, 'tcx: 'mir> EvalContextExt<'mir, 'tcx> for crate::MiriInterpCx<'mir, 'tcx> {}
+pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
+    fn tb_retag_ptr_value(
+        &mut self,
+        kind: RetagKind,
+        val: &ImmTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx, ImmTy<'tcx, Provenance>> {
+        let this = self.eval_context_mut();
+        // Raw pointers are not retagged, they simply share the tag of their parent.
+        let new_perm = match val.layout.ty.kind() {
+            &ty::Ref(_, pointee, mutability) =>
+                NewPermission::from_ref_ty(pointee, mutability, kind, this),
+            _ => None,
+        };
+        match new_perm {
+            Some(new_perm) => this.tb_retag_reference(val, new_perm),
+            None => Ok(val.clone()),
+        }
+    }
+
+    fn tb_retag_place_contents(
+        &mut self,
+        kind: RetagKind,
+        place: &PlaceTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+        let retag_fields = this.machine.borrow_tracker.as_mut().unwrap().get_mut().retag_fields;
+        let mut visitor = RetagVisitor { ecx: this, kind, retag_fields };
+        return visitor.visit_value(place);
+
+        // The actual visitor.
+        struct RetagVisitor<'ecx, 'mir, 'tcx> {
+            ecx: &'ecx mut MiriInterpCx<'mir, 'tcx>,
+            kind: RetagKind,
+            retag_fields: RetagFields,
+        }
+        impl<'ecx, 'mir, 'tcx> RetagVisitor<'ecx, 'mir, 'tcx> {
+            #[inline(always)] // yes this helps in our benchmarks
+            fn retag_ptr_inplace(
+                &mut self,
+                place: &PlaceTy<'tcx, Provenance>,
+                new_perm: Option<NewPermission>,
+            ) -> InterpResult<'tcx> {
+                let Some(new_perm) = new_perm else {
+                    return Ok(());
+                };
+                let val = self.ecx.read_immediate(&self.ecx.place_to_op(place)?)?;
+                let val = self.ecx.tb_retag_reference(&val, new_perm)?;
+                self.ecx.write_immediate(*val, place)?;
+                Ok(())
+            }
+        }
+        impl<'ecx, 'mir, 'tcx> MutValueVisitor<'mir, 'tcx, MiriMachine<'mir, 'tcx>>
+            for RetagVisitor<'ecx, 'mir, 'tcx>
+        {
+            type V = PlaceTy<'tcx, Provenance>;
+
+            #[inline(always)]
+            fn ecx(&mut self) -> &mut MiriInterpCx<'mir, 'tcx> {
+                self.ecx
+            }
+
+            fn visit_box(&mut self, place: &PlaceTy<'tcx, Provenance>) -> InterpResult<'tcx> {
+                let new_perm = NewPermission::from_box_ty(place.layout.ty, self.kind, self.ecx);
+                self.retag_ptr_inplace(place, Some(new_perm))
+            }
+
+            fn visit_value(&mut self, place: &PlaceTy<'tcx, Provenance>) -> InterpResult<'tcx> {
+                // If this place is smaller than a pointer, we know that it can't contain any
+                // pointers we need to retag, so we can stop recursion early.
+                // This optimization is crucial for ZSTs, because they can contain way more fields
+                // than we can ever visit.
+                if place.layout.is_sized() && place.layout.size < self.ecx.pointer_size() {
+                    return Ok(());
+                }
+
+                // Check the type of this value to see what to do with it (retag, or recurse).
+                match place.layout.ty.kind() {
+                    &ty::Ref(_, pointee, mutability) => {
+                        let new_perm =
+                            NewPermission::from_ref_ty(pointee, mutability, self.kind, self.ecx);
+                        self.retag_ptr_inplace(place, new_perm)?;
+                    }
+                    ty::RawPtr(..) => {
+                        // We do *not* want to recurse into raw pointers -- wide raw pointers have
+                        // fields, and for dyn Trait pointees those can have reference type!
+                    }
+                    ty::Adt(adt, _) if adt.is_box() => {
+                        // Recurse for boxes, they require some tricky handling and will end up in `visit_box` above.
+                        // (Yes this means we technically also recursively retag the allocator itself
+                        // even if field retagging is not enabled. *shrug*)
+                        self.walk_value(place)?;
+                    }
+                    _ => {
+                        // Not a reference/pointer/box. Only recurse if configured appropriately.
+                        let recurse = match self.retag_fields {
+                            RetagFields::No => false,
+                            RetagFields::Yes => true,
+                            RetagFields::OnlyScalar => {
+                                // Matching `ArgAbi::new` at the time of writing, only fields of
+                                // `Scalar` and `ScalarPair` ABI are considered.
+                                matches!(place.layout.abi, Abi::Scalar(..) | Abi::ScalarPair(..))
+                            }
+                        };
+                        if recurse {
+                            self.walk_value(place)?;
+                        }
+                    }
+                }
+
+                Ok(())
+            }
+        }
+    }
+
+    /// After a stack frame got pushed, retag the return place so that we are sure
+    /// it does not alias with anything.
+    ///
+    /// This is a HACK because there is nothing in MIR that would make the retag
+    /// explicit. Also see <https://github.com/rust-lang/rust/issues/71117>.
+    fn tb_retag_return_place(&mut self) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+        let return_place = &this.frame().return_place;
+        if return_place.layout.is_zst() {
+            // There may not be any memory here, nothing to do.
+            return Ok(());
+        }
+        // We need this to be in-memory to use tagged pointers.
+        let return_place = this.force_allocation(&return_place.clone())?;
+
+        // We have to turn the place into a pointer to use the existing code.
+        // (The pointer type does not matter, so we use a raw pointer.)
+        let ptr_layout = this.layout_of(this.tcx.mk_mut_ptr(return_place.layout.ty))?;
+        let val = ImmTy::from_immediate(return_place.to_ref(this), ptr_layout);
+        // Reborrow it. With protection! That is part of the point.
+        let ty_is_freeze = return_place.layout.ty.is_freeze(*this.tcx, this.param_env());
+        let new_perm = NewPermission {
+            initial_state: Permission::Reserved { ty_is_freeze },
+            protector: Some(ProtectorKind::StrongProtector),
+        };
+        let val = this.tb_retag_reference(&val, new_perm)?;
+        // And use reborrowed pointer for return place.
+        let return_place = this.ref_to_mplace(&val)?;
+        this.frame_mut().return_place = return_place.into();
+
+        Ok(())
+    }
+
+    /// Tree Borrows does not track exposed tags: with strict provenance, nothing is exposed.
+    fn tb_expose_tag(&mut self, alloc_id: AllocId, tag: BorTag) -> InterpResult<'tcx> {
+        trace!("Tree Borrows ignores the exposure of tag {tag:?} in {alloc_id:?}");
+        Ok(())
+    }
+
+    /// Prints the tree of the given allocation.
+    fn print_tree(&mut self, alloc_id: AllocId) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+        let alloc_extra = this.get_alloc_extra(alloc_id)?;
+        alloc_extra.borrow_tracker_tb().borrow().print();
+        Ok(())
+    }
+}
@@ -0,0 +1,153 @@
+use std::fmt;
+
+use crate::borrow_tracker::AccessKind;
+
+/// The permission a tag has on a single location of its allocation.
+///
+/// Permissions only ever decrease along the sequence `Reserved -> Active -> Frozen -> Disabled`,
+/// except for the transition from `Reserved` to `Active` on the first write through the tag.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Permission {
+    /// A mutable reference that has not been written to yet, e.g. a two-phase borrow.
+    /// It can be read through and tolerates reads through foreign tags. The first write through
+    /// it makes it `Active`. Types with interior mutability (`ty_is_freeze == false`) also
+    /// tolerate foreign writes, as long as they are not protected.
+    Reserved { ty_is_freeze: bool },
+    /// A mutable reference that has been written to. Any foreign access invalidates its
+    /// uniqueness.
+    Active,
+    /// A shared reference, or a mutable reference that has seen a foreign read.
+    /// It can be read through, but not written to.
+    Frozen,
+    /// The tag has been invalidated by a foreign write and cannot be used for any access.
+    Disabled,
+}
+
+impl fmt::Display for Permission {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Permission::Reserved { .. } => write!(f, "Reserved"),
+            Permission::Active => write!(f, "Active"),
+            Permission::Frozen => write!(f, "Frozen"),
+            Permission::Disabled => write!(f, "Disabled"),
+        }
+    }
+}
+
+impl Permission {
+    /// Abbreviated name, used when printing the tree.
+    pub fn short_name(self) -> &'static str {
+        match self {
+            Permission::Reserved { .. } => "Res",
+            Permission::Active => "Act",
+            Permission::Frozen => "Frz",
+            Permission::Disabled => "Dis",
+        }
+    }
+
+    /// Whether an access of the given kind through this tag or one of its children is allowed.
+    pub fn grants(self, access: AccessKind) -> bool {
+        match self {
+            Permission::Disabled => false,
+            Permission::Frozen => access == AccessKind::Read,
+            Permission::Reserved { .. } | Permission::Active => true,
+        }
+    }
+
+    /// The permission after an access through this tag or one of its children.
+    /// Returns `None` if the access is not allowed.
+    pub fn child_access(self, access: AccessKind) -> Option<Self> {
+        if !self.grants(access) {
+            return None;
+        }
+        Some(match (self, access) {
+            (Permission::Reserved { .. }, AccessKind::Write) => Permission::Active,
+            (perm, _) => perm,
+        })
+    }
+
+    /// The permission after an access through a tag that is neither this tag nor one of its
+    /// children. Such accesses are always allowed; whether they are allowed for *protected*
+    /// tags is decided by `is_protector_violation`.
+    pub fn foreign_access(self, access: AccessKind, protected: bool) -> Self {
+        match (self, access) {
+            (Permission::Disabled, _) => Permission::Disabled,
+            (Permission::Frozen, AccessKind::Read) => Permission::Frozen,
+            // Unprotected reserved tags are just like raw pointers, and do not mind foreign reads.
+            (Permission::Reserved { .. }, AccessKind::Read) if !protected => self,
+            (Permission::Reserved { .. } | Permission::Active, AccessKind::Read) =>
+                Permission::Frozen,
+            // Interior mutability: unprotected reserved tags to `!Freeze` types may still be
+            // written through after a foreign write.
+            (Permission::Reserved { ty_is_freeze: false }, AccessKind::Write) if !protected =>
+                self,
+            (_, AccessKind::Write) => Permission::Disabled,
+        }
+    }
+
+    /// Whether a protected tag going from `self` to `new` due to a foreign access is UB.
+    /// Protected tags may lose the ability to write only if they never used it (i.e., they
+    /// were still `Reserved`), and must never lose the ability to read.
+    pub fn is_protector_violation(self, new: Self) -> bool {
+        match (self, new) {
+            (Permission::Active, perm) => perm != Permission::Active,
+            (perm, Permission::Disabled) => perm != Permission::Disabled,
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL: [Permission; 5] = [
+        Permission::Reserved { ty_is_freeze: true },
+        Permission::Reserved { ty_is_freeze: false },
+        Permission::Active,
+        Permission::Frozen,
+        Permission::Disabled,
+    ];
+
+    #[test]
+    fn child_accesses() {
+        let res = Permission::Reserved { ty_is_freeze: true };
+        assert_eq!(res.child_access(AccessKind::Read), Some(res));
+        assert_eq!(res.child_access(AccessKind::Write), Some(Permission::Active));
+        assert_eq!(Permission::Frozen.child_access(AccessKind::Read), Some(Permission::Frozen));
+        assert_eq!(Permission::Frozen.child_access(AccessKind::Write), None);
+        assert_eq!(Permission::Disabled.child_access(AccessKind::Read), None);
+    }
+
+    #[test]
+    fn foreign_accesses_never_increase_permissions() {
+        for perm in ALL {
+            for protected in [false, true] {
+                for access in [AccessKind::Read, AccessKind::Write] {
+                    let new = perm.foreign_access(access, protected);
+                    // Anything the new permission allows, the old one allowed as well.
+                    for child in [AccessKind::Read, AccessKind::Write] {
+                        assert!(!new.grants(child) || perm.grants(child));
+                    }
+                    // Foreign accesses are idempotent.
+                    assert_eq!(new.foreign_access(access, protected), new);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn protectors() {
+        let res = Permission::Reserved { ty_is_freeze: true };
+        let new = res.foreign_access(AccessKind::Read, true);
+        assert_eq!(new, Permission::Frozen);
+        assert!(!res.is_protector_violation(new));
+        let new = Permission::Active.foreign_access(AccessKind::Read, true);
+        assert!(Permission::Active.is_protector_violation(new));
+        let new = Permission::Frozen.foreign_access(AccessKind::Write, true);
+        assert!(Permission::Frozen.is_protector_violation(new));
+        let interior = Permission::Reserved { ty_is_freeze: false };
+        assert_eq!(interior.foreign_access(AccessKind::Write, false), interior);
+        assert_eq!(interior.foreign_access(AccessKind::Write, true), Permission::Disabled);
+    }
+}
@@ -0,0 +1,330 @@
+//! The tree of tags of an allocation, and the per-location permissions of its tags.
+//!
+//! Every access is classified, for every tag of the allocation, as either a *child* access
+//! (it happened through that tag or one of its descendants) or a *foreign* access (all other
+//! accesses). The permission of each tag then evolves according to `perms.rs`.
+
+use rustc_data_structures::fx::{FxHashMap, FxHashSet};
+use rustc_span::Span;
+use rustc_target::abi::Size;
+use smallvec::SmallVec;
+
+use crate::borrow_tracker::tree_borrows::diagnostics::{
+    Event, NodeDebugInfo, Operation, TbError, TbErrorKind,
+};
+use crate::borrow_tracker::tree_borrows::perms::Permission;
+use crate::borrow_tracker::{AccessKind, ProtectorKind};
+use crate::*;
+
+/// The state of a tag at a single location.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+struct LocationState {
+    /// Whether the location was part of the range of the retag that created the tag, or has
+    /// been accessed through the tag or one of its children since. Protectors only apply to
+    /// initialized locations.
+    initialized: bool,
+    permission: Permission,
+}
+
+#[derive(Clone, Debug)]
+struct Node {
+    parent: Option<BorTag>,
+    children: SmallVec<[BorTag; 4]>,
+    /// The permission at the locations where the tag is not initialized.
+    default_initial_perm: Permission,
+    debug_info: NodeDebugInfo,
+}
+
+/// Extra per-allocation state: the tree of all tags derived from the base tag.
+#[derive(Clone, Debug)]
+pub struct Tree {
+    /// The base tag of the allocation.
+    root: BorTag,
+    nodes: FxHashMap<BorTag, Node>,
+    /// The per-location state of the tags. Tags that are missing from the map of some location
+    /// are not initialized there, and have their `default_initial_perm`.
+    rperms: RangeMap<FxHashMap<BorTag, LocationState>>,
+}
+
+impl Tree {
+    /// Creates the tree of a new allocation, in which the base tag is `Active` everywhere.
+    pub fn new(root: BorTag, size: Size, span: Span) -> Self {
+        let root_node = Node {
+            parent: None,
+            children: SmallVec::new(),
+            default_initial_perm: Permission::Active,
+            debug_info: NodeDebugInfo::new(root, Permission::Active, span),
+        };
+        let mut nodes = FxHashMap::default();
+        nodes.insert(root, root_node);
+        let mut root_perms = FxHashMap::default();
+        root_perms
+            .insert(root, LocationState { initialized: true, permission: Permission::Active });
+        Tree { root, nodes, rperms: RangeMap::new(size, root_perms) }
+    }
+
+    /// Inserts `new_tag` as a child of `parent_tag`. The new tag is initialized with
+    /// permission `perm` on `range`.
+    pub fn new_child(
+        &mut self,
+        parent_tag: BorTag,
+        new_tag: BorTag,
+        perm: Permission,
+        range: AllocRange,
+        span: Span,
+    ) {
+        self.nodes
+            .get_mut(&parent_tag)
+            .expect("the parent tag of a retag must be in the tree")
+            .children
+            .push(new_tag);
+        self.nodes.insert(
+            new_tag,
+            Node {
+                parent: Some(parent_tag),
+                children: SmallVec::new(),
+                default_initial_perm: perm,
+                debug_info: NodeDebugInfo::new(new_tag, perm, span),
+            },
+        );
+        for (_offset, perms) in self.rperms.iter_mut(range.start, range.size) {
+            perms.insert(new_tag, LocationState { initialized: true, permission: perm });
+        }
+    }
+
+    /// All tags in the order of a depth-first traversal, starting at the root.
+    fn tags_in_tree_order(&self) -> Vec<BorTag> {
+        let mut order = Vec::with_capacity(self.nodes.len());
+        let mut stack = vec![self.root];
+        while let Some(tag) = stack.pop() {
+            order.push(tag);
+            // Push the children in reverse so that they are visited in creation order.
+            stack.extend(self.nodes[&tag].children.iter().rev().copied());
+        }
+        order
+    }
+
+    /// Applies an access through `tag` to all tags of the allocation, and reports UB if the
+    /// access is not allowed.
+    pub fn perform_access<'tcx>(
+        &mut self,
+        access_kind: AccessKind,
+        tag: BorTag,
+        range: AllocRange,
+        protected_tags: &FxHashMap<BorTag, ProtectorKind>,
+        alloc_id: AllocId,
+        operation_span: Span,
+    ) -> InterpResult<'tcx> {
+        self.perform_operation(
+            Operation::Access(access_kind),
+            access_kind,
+            tag,
+            range,
+            protected_tags,
+            alloc_id,
+            operation_span,
+        )
+    }
+
+    /// Checks that the allocation can be deallocated through `tag`. Deallocation counts as a
+    /// write to the entire allocation, and additionally requires that no tag is strongly
+    /// protected.
+    pub fn dealloc<'tcx>(
+        &mut self,
+        tag: BorTag,
+        range: AllocRange,
+        protected_tags: &FxHashMap<BorTag, ProtectorKind>,
+        alloc_id: AllocId,
+        operation_span: Span,
+    ) -> InterpResult<'tcx> {
+        self.perform_operation(
+            Operation::Dealloc,
+            AccessKind::Write,
+            tag,
+            range,
+            protected_tags,
+            alloc_id,
+            operation_span,
+        )?;
+        for conflicting in self.tags_in_tree_order() {
+            if protected_tags.get(&conflicting) == Some(&ProtectorKind::StrongProtector) {
+                return Err(TbError {
+                    kind: TbErrorKind::ProtectedDealloc,
+                    operation: Operation::Dealloc,
+                    alloc_id,
+                    offset: range.start,
+                    accessed_info: &self.node(tag).debug_info,
+                    conflicting_info: &self.nodes[&conflicting].debug_info,
+                }
+                .build()
+                .into());
+            }
+        }
+        Ok(())
+    }
+
+    fn node(&self, tag: BorTag) -> &Node {
+        self.nodes.get(&tag).unwrap_or_else(|| {
+            panic!("tag {tag:?} is used for an access but is not in the tree of its allocation")
+        })
+    }
+
+    fn perform_operation<'tcx>(
+        &mut self,
+        operation: Operation,
+        access_kind: AccessKind,
+        tag: BorTag,
+        range: AllocRange,
+        protected_tags: &FxHashMap<BorTag, ProtectorKind>,
+        alloc_id: AllocId,
+        operation_span: Span,
+    ) -> InterpResult<'tcx> {
+        // The accessed tag and its ancestors see a child access, starting with the accessed tag
+        // itself so that the closest conflicting tag gets reported.
+        let mut local = Vec::new();
+        let mut cur = Some(tag);
+        while let Some(t) = cur {
+            local.push(t);
+            cur = self.node(t).parent;
+        }
+        let local_set: FxHashSet<BorTag> = local.iter().copied().collect();
+        let foreign: Vec<BorTag> =
+            self.tags_in_tree_order().into_iter().filter(|t| !local_set.contains(t)).collect();
+
+        let Tree { nodes, rperms, .. } = self;
+        for (offset, perms) in rperms.iter_mut(range.start, range.size) {
+            let local_tags = local.iter().map(|t| (*t, false));
+            let foreign_tags = foreign.iter().map(|t| (*t, true));
+            for (node_tag, is_foreign) in local_tags.chain(foreign_tags) {
+                let node = nodes.get_mut(&node_tag).unwrap();
+                let old = perms.get(&node_tag).copied().unwrap_or(LocationState {
+                    initialized: false,
+                    permission: node.default_initial_perm,
+                });
+                let new = if is_foreign {
+                    let protected = protected_tags.contains_key(&node_tag);
+                    let permission = old.permission.foreign_access(access_kind, protected);
+                    if protected
+                        && old.initialized
+                        && old.permission.is_protector_violation(permission)
+                    {
+                        let kind = TbErrorKind::ProtectedTransition {
+                            from: old.permission,
+                            to: permission,
+                        };
+                        return Err(error(kind, operation, alloc_id, offset, nodes, tag, node_tag));
+                    }
+                    LocationState { permission, ..old }
+                } else {
+                    let Some(permission) = old.permission.child_access(access_kind) else {
+                        let kind = TbErrorKind::ChildAccessForbidden(old.permission);
+                        return Err(error(kind, operation, alloc_id, offset, nodes, tag, node_tag));
+                    };
+                    LocationState { initialized: true, permission }
+                };
+                if new == old {
+                    continue;
+                }
+                if new.permission != old.permission {
+                    node.debug_info.record(Event {
+                        from: old.permission,
+                        to: new.permission,
+                        access_kind,
+                        is_foreign,
+                        access_range: range,
+                        span: operation_span,
+                    });
+                }
+                perms.insert(node_tag, new);
+            }
+        }
+        return Ok(());
+
+        fn error<'tcx>(
+            kind: TbErrorKind,
+            operation: Operation,
+            alloc_id: AllocId,
+            offset: Size,
+            nodes: &FxHashMap<BorTag, Node>,
+            accessed: BorTag,
+            conflicting: BorTag,
+        ) -> InterpErrorInfo<'tcx> {
+            TbError {
+                kind,
+                operation,
+                alloc_id,
+                offset,
+                accessed_info: &nodes[&accessed].debug_info,
+                conflicting_info: &nodes[&conflicting].debug_info,
+            }
+            .build()
+            .into()
+        }
+    }
+}
+
+/// Integration with the BorTag garbage collector
+impl Tree {
+    /// Removes the tags that are not in `live_tags` and have no live descendants.
+    /// The state of such a tag can no longer influence any access: accesses through it or its
+    /// children are impossible, and protected tags are always live.
+    pub fn remove_unreachable_tags(&mut self, live_tags: &FxHashSet<BorTag>) {
+        let mut removed = FxHashSet::default();
+        // Visiting children before their parents lets us decide in one pass.
+        for tag in self.tags_in_tree_order().into_iter().rev() {
+            let node = &self.nodes[&tag];
+            if tag != self.root
+                && !live_tags.contains(&tag)
+                && node.children.iter().all(|child| removed.contains(child))
+            {
+                removed.insert(tag);
+            }
+        }
+        if removed.is_empty() {
+            return;
+        }
+        self.nodes.retain(|tag, _| !removed.contains(tag));
+        for node in self.nodes.values_mut() {
+            node.children.retain(|child| !removed.contains(child));
+        }
+        for perms in self.rperms.iter_mut_all() {
+            perms.retain(|tag, _| !removed.contains(tag));
+        }
+    }
+}
+
+impl Tree {
+    /// Prints the tree for every range of locations that have the same states.
+    /// Uninitialized states are shown in parentheses.
+    pub fn print(&self) {
+        let order = self.tags_in_tree_order();
+        let mut depths = FxHashMap::default();
+        for tag in &order {
+            let depth = self.nodes[tag].parent.map_or(0, |parent| depths[&parent] + 1);
+            depths.insert(*tag, depth);
+        }
+
+        let mut ranges: Vec<(std::ops::Range<u64>, &FxHashMap<BorTag, LocationState>)> =
+            Vec::new();
+        for (range, perms) in self.rperms.iter_all() {
+            match ranges.last_mut() {
+                Some((last_range, last_perms)) if *last_perms == perms =>
+                    last_range.end = range.end,
+                _ => ranges.push((range, perms)),
+            }
+        }
+
+        for (range, perms) in ranges {
+            println!("{range:?}:");
+            for tag in &order {
+                let indent = "|  ".repeat(depths[tag]);
+                let perm = match perms.get(tag) {
+                    Some(state) if state.initialized => state.permission.short_name().to_string(),
+                    Some(state) => format!("({})", state.permission.short_name()),
+                    None => format!("({})", self.nodes[tag].default_initial_perm.short_name()),
+                };
+                println!("  {indent}{perm} {tag:?}");
+            }
+        }
+    }
+}
@@ -575,7 +575,6 @@ fn atomic_compare_exchange_scalar(
         fail: AtomicReadOrd,
         can_fail_spuriously: bool,
     ) -> InterpResult<'tcx, Immediate<Provenance>> {
-        use rand::Rng as _;
         let this = self.eval_context_mut();
         this.atomic_access_check(place)?;
 
@@ -589,10 +588,10 @@ fn atomic_compare_exchange_scalar(
         let eq = this.binary_op(mir::BinOp::Eq, &old, expect_old)?;
         // If the operation would succeed, but is "weak", fail some portion
         // of the time, based on `success_rate`.
-        let success_rate = 1.0 - this.machine.cmpxchg_weak_failure_rate;
         let cmpxchg_success = eq.to_scalar().to_bool()?
             && if can_fail_spuriously {
-                this.machine.rng.get_mut().gen_bool(success_rate)
+                let failure_rate = this.machine.cmpxchg_weak_failure_rate;
+                !this.decide_bool(DecisionKind::CmpxchgWeakFail, failure_rate)?
             } else {
                 true
             };
@@ -3,6 +3,7 @@
 #[macro_use]
 pub mod sync;
 pub mod init_once;
+pub mod schedule_log;
 pub mod thread;
-mod vector_clock;
+pub mod vector_clock;
 pub mod weak_memory;
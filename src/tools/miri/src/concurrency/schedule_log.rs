@@ -0,0 +1,288 @@
+//! Recording and replaying of the random choices that decide how threads interleave and which
+//! stores atomic loads read from (`-Zmiri-record-schedule` and `-Zmiri-replay-schedule`).
+//!
+//! A schedule file is a text file that starts with the seed of the recorded run, followed by one
+//! decision per line in the order in which the decisions were made:
+//!
+//! ```text
+//! seed 42
+//! preempt 0 x812
+//! preempt 1
+//! load-from 2
+//! cmpxchg-weak-fail 0
+//! ```
+//!
+//! `x<count>` repeats a decision `count` times, and lines starting with `#` are comments.
+//! When replaying, every decision is taken from the file instead of the random number generator,
+//! so files can be edited by hand to explore other interleavings. The random number generator is
+//! still queried at every decision point, so that the remaining uses of the seed (such as base
+//! addresses and `getrandom`) see the same values as in the recorded run.
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use crate::*;
+
+/// A kind of choice that is recorded in a schedule.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DecisionKind {
+    /// Whether the active thread is preempted at the end of a basic block (`0` or `1`).
+    Preempt,
+    /// Whether a `compare_exchange_weak` that could succeed fails spuriously (`0` or `1`).
+    CmpxchgWeakFail,
+    /// Which store in the store buffer an atomic load reads from, among the stores it may read
+    /// from: `0` is the latest of them, `1` the one before, and so on.
+    LoadFrom,
+}
+
+impl DecisionKind {
+    fn name(self) -> &'static str {
+        match self {
+            DecisionKind::Preempt => "preempt",
+            DecisionKind::CmpxchgWeakFail => "cmpxchg-weak-fail",
+            DecisionKind::LoadFrom => "load-from",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        [DecisionKind::Preempt, DecisionKind::CmpxchgWeakFail, DecisionKind::LoadFrom]
+            .into_iter()
+            .find(|kind| kind.name() == name)
+    }
+}
+
+impl fmt::Display for DecisionKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// A decision, repeated `count` times.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Decision {
+    pub kind: DecisionKind,
+    pub value: u64,
+    pub count: u64,
+}
+
+/// A parsed schedule file.
+#[derive(Clone, Debug)]
+pub struct Schedule {
+    /// The seed of the recorded run, if the file specifies one.
+    pub seed: Option<u64>,
+    pub decisions: Vec<Decision>,
+}
+
+impl Schedule {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut seed = None;
+        let mut decisions = Vec::new();
+        for (line_idx, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let err = |msg: &str| format!("line {}: {msg}: `{line}`", line_idx + 1);
+            let mut words = line.split_whitespace();
+            let name = words.next().unwrap();
+            let value = words
+                .next()
+                .ok_or_else(|| err("missing value"))?
+                .parse::<u64>()
+                .map_err(|_| err("the value must be an integer that fits into u64"))?;
+            let count = match words.next() {
+                None => 1,
+                Some(count) =>
+                    match count.strip_prefix('x').and_then(|count| count.parse::<u64>().ok()) {
+                        Some(count) if count > 0 => count,
+                        _ =>
+                            return Err(err("the repetition count must be of the form `x<count>`")),
+                    },
+            };
+            if words.next().is_some() {
+                return Err(err("unexpected trailing text"));
+            }
+            if name == "seed" {
+                if seed.is_some() || !decisions.is_empty() {
+                    return Err(err("the seed must be specified once, before all decisions"));
+                }
+                seed = Some(value);
+                continue;
+            }
+            let kind = DecisionKind::from_name(name).ok_or_else(|| err("unknown decision"))?;
+            decisions.push(Decision { kind, value, count });
+        }
+        Ok(Schedule { seed, decisions })
+    }
+}
+
+/// Whether the decisions of a run are recorded or replayed.
+#[derive(Clone, Debug)]
+pub enum ScheduleLogMode {
+    /// Record the decisions, and write them to the given file at the end of the run.
+    Record(PathBuf),
+    /// Take the decisions from a previously recorded schedule.
+    Replay(Schedule),
+}
+
+/// The decisions made (or to be made) by the current run.
+#[derive(Debug)]
+pub struct ScheduleLog {
+    /// Where to write the schedule at the end of the run. `None` when replaying.
+    record_to: Option<PathBuf>,
+    seed: u64,
+    decisions: Vec<Decision>,
+    /// When replaying: the index of the next decision in `decisions`, and how many times it has
+    /// already been used.
+    next: (usize, u64),
+}
+
+impl ScheduleLog {
+    pub fn new(mode: &ScheduleLogMode, seed: u64) -> Self {
+        match mode {
+            ScheduleLogMode::Record(path) =>
+                ScheduleLog {
+                    record_to: Some(path.clone()),
+                    seed,
+                    decisions: Vec::new(),
+                    next: (0, 0),
+                },
+            ScheduleLogMode::Replay(schedule) =>
+                ScheduleLog {
+                    record_to: None,
+                    seed,
+                    decisions: schedule.decisions.clone(),
+                    next: (0, 0),
+                },
+        }
+    }
+
+    /// Makes a decision of the given kind. `random` makes the decision at random; it is called
+    /// even when replaying, to keep the random number generator in sync with the recorded run.
+    /// Replayed values are checked to be at most `max`.
+    pub fn decide<'tcx>(
+        &mut self,
+        kind: DecisionKind,
+        max: u64,
+        random: impl FnOnce() -> u64,
+    ) -> InterpResult<'tcx, u64> {
+        let value = random();
+        if self.record_to.is_some() {
+            match self.decisions.last_mut() {
+                Some(last) if last.kind == kind && last.value == value => last.count += 1,
+                _ => self.decisions.push(Decision { kind, value, count: 1 }),
+            }
+            return Ok(value);
+        }
+
+        let (idx, used) = &mut self.next;
+        let Some(decision) = self.decisions.get(*idx) else {
+            throw_machine_stop!(TerminationInfo::ScheduleDiverged(format!(
+                "the program made a `{kind}` decision after all {} decisions of the schedule were replayed",
+                self.decisions.iter().map(|d| d.count).sum::<u64>(),
+            )));
+        };
+        if decision.kind != kind {
+            throw_machine_stop!(TerminationInfo::ScheduleDiverged(format!(
+                "the program made a `{kind}` decision where the schedule has a `{}` decision",
+                decision.kind,
+            )));
+        }
+        if decision.value > max {
+            throw_machine_stop!(TerminationInfo::ScheduleDiverged(format!(
+                "the schedule has a `{kind} {}` decision, but the value must be at most {max} here",
+                decision.value,
+            )));
+        }
+        let value = decision.value;
+        *used += 1;
+        if *used == decision.count {
+            *idx += 1;
+            *used = 0;
+        }
+        Ok(value)
+    }
+
+    /// Writes the recorded schedule to its file. Does nothing when replaying.
+    pub fn write(&self) -> io::Result<()> {
+        let Some(path) = &self.record_to else { return Ok(()) };
+        let mut out = format!("seed {}\n", self.seed);
+        for decision in &self.decisions {
+            out.push_str(&format!("{} {}", decision.kind, decision.value));
+            if decision.count > 1 {
+                out.push_str(&format!(" x{}", decision.count));
+            }
+            out.push('\n');
+        }
+        fs::write(path, out)
+    }
+}
+
+impl<'mir, 'tcx: 'mir> EvalContextExt<'mir, 'tcx> for crate::MiriInterpCx<'mir, 'tcx> {}
+pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
+    /// Decides at random, with the given probability, whether to do something. The decision is
+    /// recorded or replayed if a schedule log is active, unless there is no choice to make
+    /// because the probability is `0` or `1`.
+    fn decide_bool(&self, kind: DecisionKind, probability: f64) -> InterpResult<'tcx, bool> {
+        use rand::Rng as _;
+
+        let this = self.eval_context_ref();
+        let random = || u64::from(this.machine.rng.borrow_mut().gen_bool(probability));
+        let value = match &this.machine.schedule_log {
+            Some(log) if probability > 0.0 && probability < 1.0 =>
+                log.borrow_mut().decide(kind, 1, random)?,
+            _ => random(),
+        };
+        Ok(value != 0)
+    }
+
+    /// Picks an index in `0..len` uniformly at random. The decision is recorded or replayed if a
+    /// schedule log is active, unless there is no choice to make because `len` is `1`.
+    fn decide_index(&self, kind: DecisionKind, len: usize) -> InterpResult<'tcx, usize> {
+        use rand::Rng as _;
+
+        assert!(len > 0, "cannot pick an index in an empty range");
+        let this = self.eval_context_ref();
+        let random = || u64::try_from(this.machine.rng.borrow_mut().gen_range(0..len)).unwrap();
+        let value = match &this.machine.schedule_log {
+            Some(log) if len > 1 =>
+                log.borrow_mut().decide(kind, u64::try_from(len - 1).unwrap(), random)?,
+            _ => random(),
+        };
+        Ok(usize::try_from(value).unwrap())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let schedule = Schedule::parse(
+            "# a comment\nseed 3\n\npreempt 0 x5\nload-from 2\ncmpxchg-weak-fail 1\n",
+        )
+        .unwrap();
+        assert_eq!(schedule.seed, Some(3));
+        assert_eq!(
+            schedule.decisions,
+            [
+                Decision { kind: DecisionKind::Preempt, value: 0, count: 5 },
+                Decision { kind: DecisionKind::LoadFrom, value: 2, count: 1 },
+                Decision { kind: DecisionKind::CmpxchgWeakFail, value: 1, count: 1 },
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!(Schedule::parse("preempt").is_err());
+        assert!(Schedule::parse("preempt x").is_err());
+        assert!(Schedule::parse("preempt 1 5").is_err());
+        assert!(Schedule::parse("preempt 1 x0").is_err());
+        assert!(Schedule::parse("yield 1").is_err());
+        assert!(Schedule::parse("preempt 1\nseed 3").is_err());
+    }
+}
@@ -26,6 +26,8 @@ enum SchedulingAction {
     ExecuteStep,
     /// Execute a timeout callback.
     ExecuteTimeoutCallback,
+    /// Execute the callbacks of the threads blocked on file descriptions.
+    ExecuteIoCallbacks,
     /// Wait for a bit, until there is a timeout to be called.
     Sleep(Duration),
 }
@@ -37,6 +39,8 @@ pub trait MachineCallback<'mir, 'tcx>: VisitTags {
 
 type TimeoutCallback<'mir, 'tcx> = Box<dyn MachineCallback<'mir, 'tcx> + 'tcx>;
 
+type IoCallback<'mir, 'tcx> = Box<dyn MachineCallback<'mir, 'tcx> + 'tcx>;
+
 /// A thread identifier.
 #[derive(Clone, Copy, Debug, PartialOrd, Ord, PartialEq, Eq, Hash)]
 pub struct ThreadId(u32);
@@ -294,6 +298,20 @@ fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
     }
 }
 
+/// Callbacks are also used to implement blocking on file descriptions. For example, reading from
+/// an empty socket creates a callback that retries the read whenever file descriptions might have
+/// become ready, and unblocks the thread once the read succeeds.
+struct IoCallbackInfo<'mir, 'tcx> {
+    /// The called function.
+    callback: IoCallback<'mir, 'tcx>,
+}
+
+impl<'mir, 'tcx> std::fmt::Debug for IoCallbackInfo<'mir, 'tcx> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "IoCallback")
+    }
+}
+
 /// A set of threads.
 #[derive(Debug)]
 pub struct ThreadManager<'mir, 'tcx> {
@@ -313,6 +331,13 @@ pub struct ThreadManager<'mir, 'tcx> {
     yield_active_thread: bool,
     /// Callbacks that are called once the specified time passes.
     timeout_callbacks: FxHashMap<ThreadId, TimeoutCallbackInfo<'mir, 'tcx>>,
+    /// Callbacks of threads that are blocked until some file description becomes ready. They are
+    /// called on their thread after file descriptions might have changed their readiness, and
+    /// are responsible for unblocking the thread if it can make progress.
+    io_callbacks: FxHashMap<ThreadId, IoCallbackInfo<'mir, 'tcx>>,
+    /// A flag that indicates that file descriptions might have changed their readiness since the
+    /// I/O callbacks were last called.
+    io_event_pending: bool,
 }
 
 impl VisitTags for ThreadManager<'_, '_> {
@@ -321,8 +346,10 @@ fn visit_tags(&self, visit: &mut dyn FnMut(BorTag)) {
             threads,
             thread_local_alloc_ids,
             timeout_callbacks,
+            io_callbacks,
             active_thread: _,
             yield_active_thread: _,
+            io_event_pending: _,
             sync,
         } = self;
 
@@ -335,6 +362,9 @@ fn visit_tags(&self, visit: &mut dyn FnMut(BorTag)) {
         for callback in timeout_callbacks.values() {
             callback.callback.visit_tags(visit);
         }
+        for callback in io_callbacks.values() {
+            callback.callback.visit_tags(visit);
+        }
         sync.visit_tags(visit);
     }
 }
@@ -351,6 +381,8 @@ fn default() -> Self {
             thread_local_alloc_ids: Default::default(),
             yield_active_thread: false,
             timeout_callbacks: FxHashMap::default(),
+            io_callbacks: FxHashMap::default(),
+            io_event_pending: false,
         }
     }
 }
@@ -593,6 +625,26 @@ fn unregister_timeout_callback_if_exists(&mut self, thread: ThreadId) {
         self.timeout_callbacks.remove(&thread);
     }
 
+    /// Register the given `callback` to be called whenever file descriptions might have become
+    /// ready, until the callback unblocks `thread`.
+    ///
+    /// The callback will be called with `thread` being the active thread, and
+    /// the callback may not change the active thread.
+    fn register_io_callback(&mut self, thread: ThreadId, callback: IoCallback<'mir, 'tcx>) {
+        self.io_callbacks.try_insert(thread, IoCallbackInfo { callback }).unwrap();
+    }
+
+    /// Unregister the I/O callback for the `thread`.
+    fn unregister_io_callback_if_exists(&mut self, thread: ThreadId) {
+        self.io_callbacks.remove(&thread);
+    }
+
+    /// Note that file descriptions might have changed their readiness, so the I/O callbacks have
+    /// to be called before the blocked threads can be considered deadlocked.
+    fn notify_io_event(&mut self) {
+        self.io_event_pending = true;
+    }
+
     /// Get a callback that is ready to be called.
     fn get_ready_callback(
         &mut self,
@@ -679,6 +731,14 @@ fn schedule(&mut self, clock: &Clock) -> InterpResult<'tcx, SchedulingAction> {
         if potential_sleep_time == Some(Duration::new(0, 0)) {
             return Ok(SchedulingAction::ExecuteTimeoutCallback);
         }
+        // Similarly, threads blocked on file descriptions that might have become ready get a
+        // chance to unblock before we pick the next thread.
+        if self.io_event_pending {
+            self.io_event_pending = false;
+            if !self.io_callbacks.is_empty() {
+                return Ok(SchedulingAction::ExecuteIoCallbacks);
+            }
+        }
         // No callbacks immediately scheduled, pick a regular thread to execute.
         // The active thread blocked or yielded. So we go search for another enabled thread.
         // Crucially, we start searching at the current active thread ID, rather than at 0, since we
@@ -748,6 +808,29 @@ fn run_timeout_callback(&mut self) -> InterpResult<'tcx> {
         Ok(())
     }
 
+    /// Execute the callbacks of all threads blocked on file descriptions, each on its own
+    /// thread. Callbacks that did not unblock their thread stay registered.
+    fn run_io_callbacks(&mut self) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+        // We iterate over the threads in the order of their indices to keep the scheduler
+        // deterministic.
+        let mut threads: Vec<ThreadId> =
+            this.machine.threads.io_callbacks.keys().copied().collect();
+        threads.sort();
+        for thread in threads {
+            // A previous callback may have unregistered this one, e.g. by closing a file.
+            let Some(info) = this.machine.threads.io_callbacks.remove(&thread) else { continue };
+            let callback = info.callback;
+            let old_thread = this.set_active_thread(thread);
+            callback.call(this)?;
+            this.set_active_thread(old_thread);
+            if this.machine.threads.threads[thread].state == ThreadState::BlockedOnSync {
+                this.machine.threads.register_io_callback(thread, callback);
+            }
+        }
+        Ok(())
+    }
+
     #[inline]
     fn run_on_stack_empty(&mut self) -> InterpResult<'tcx, Poll<()>> {
         let this = self.eval_context_mut();
@@ -976,13 +1059,12 @@ fn yield_active_thread(&mut self) {
     }
 
     #[inline]
-    fn maybe_preempt_active_thread(&mut self) {
-        use rand::Rng as _;
-
+    fn maybe_preempt_active_thread(&mut self) -> InterpResult<'tcx> {
         let this = self.eval_context_mut();
-        if this.machine.rng.get_mut().gen_bool(this.machine.preemption_rate) {
+        if this.decide_bool(DecisionKind::Preempt, this.machine.preemption_rate)? {
             this.yield_active_thread();
         }
+        Ok(())
     }
 
     #[inline]
@@ -1005,6 +1087,29 @@ fn unregister_timeout_callback_if_exists(&mut self, thread: ThreadId) {
         this.machine.threads.unregister_timeout_callback_if_exists(thread);
     }
 
+    /// Block `thread` until `callback`, which is called whenever file descriptions might have
+    /// become ready, unblocks it.
+    #[inline]
+    fn block_thread_on_io(&mut self, thread: ThreadId, callback: IoCallback<'mir, 'tcx>) {
+        let this = self.eval_context_mut();
+        this.machine.threads.block_thread(thread);
+        this.machine.threads.register_io_callback(thread, callback);
+    }
+
+    #[inline]
+    fn unregister_io_callback_if_exists(&mut self, thread: ThreadId) {
+        let this = self.eval_context_mut();
+        this.machine.threads.unregister_io_callback_if_exists(thread);
+    }
+
+    /// Note that file descriptions might have changed their readiness, e.g. because data was
+    /// written to them or they were closed, so threads blocked on them have to be reconsidered.
+    #[inline]
+    fn notify_io_event(&mut self) {
+        let this = self.eval_context_mut();
+        this.machine.threads.notify_io_event();
+    }
+
     /// Run the core interpreter loop. Returns only when an interrupt occurs (an error or program
     /// termination).
     fn run_threads(&mut self) -> InterpResult<'tcx, !> {
@@ -1023,6 +1128,9 @@ fn run_threads(&mut self) -> InterpResult<'tcx, !> {
                 SchedulingAction::ExecuteTimeoutCallback => {
                     this.run_timeout_callback()?;
                 }
+                SchedulingAction::ExecuteIoCallbacks => {
+                    this.run_io_callbacks()?;
+                }
                 SchedulingAction::Sleep(duration) => {
                     this.machine.clock.sleep(duration);
                 }
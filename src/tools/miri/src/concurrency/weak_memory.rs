@@ -286,7 +286,7 @@ fn buffered_read(
         global: &DataRaceState,
         thread_mgr: &ThreadManager<'_, '_>,
         is_seqcst: bool,
-        rng: &mut (impl rand::Rng + ?Sized),
+        choose: impl FnOnce(usize) -> InterpResult<'tcx, usize>,
         validate: impl FnOnce() -> InterpResult<'tcx>,
     ) -> InterpResult<'tcx, (Scalar<Provenance>, LoadRecency)> {
         // Having a live borrow to store_buffer while calling validate_atomic_load is fine
@@ -297,7 +297,7 @@ fn buffered_read(
             // as the race detector will update it
             let (.., clocks) = global.current_thread_state(thread_mgr);
             // Load from a valid entry in the store buffer
-            self.fetch_store(is_seqcst, &clocks, &mut *rng)
+            self.fetch_store(is_seqcst, &clocks, choose)?
         };
 
         // Unlike in buffered_atomic_write, thread clock updates have to be done
@@ -325,14 +325,14 @@ fn buffered_write(
     }
 
     #[allow(clippy::if_same_then_else, clippy::needless_bool)]
-    /// Selects a valid store element in the buffer.
-    fn fetch_store<R: rand::Rng + ?Sized>(
+    /// Selects a valid store element in the buffer. `choose` picks the index of the store among
+    /// the valid ones, starting with the latest.
+    fn fetch_store(
         &self,
         is_seqcst: bool,
         clocks: &ThreadClockSet,
-        rng: &mut R,
-    ) -> (&StoreElement, LoadRecency) {
-        use rand::seq::IteratorRandom;
+        choose: impl FnOnce(usize) -> InterpResult<'tcx, usize>,
+    ) -> InterpResult<'tcx, (&StoreElement, LoadRecency)> {
         let mut found_sc = false;
         // FIXME: we want an inclusive take_while (stops after a false predicate, but
         // includes the element that gave the false), but such function doesn't yet
@@ -403,11 +403,13 @@ fn fetch_store<R: rand::Rng + ?Sized>(
                 }
             });
 
-        let chosen = candidates.choose(rng).expect("store buffer cannot be empty");
+        let candidates: Vec<&StoreElement> = candidates.collect();
+        assert!(!candidates.is_empty(), "store buffer cannot be empty");
+        let chosen = candidates[choose(candidates.len())?];
         if std::ptr::eq(chosen, self.buffer.back().expect("store buffer cannot be empty")) {
-            (chosen, LoadRecency::Latest)
+            Ok((chosen, LoadRecency::Latest))
         } else {
-            (chosen, LoadRecency::Outdated)
+            Ok((chosen, LoadRecency::Outdated))
         }
     }
 
@@ -544,7 +546,6 @@ fn buffered_atomic_read(
                 if atomic == AtomicReadOrd::SeqCst {
                     global.sc_read(&this.machine.threads);
                 }
-                let mut rng = this.machine.rng.borrow_mut();
                 let buffer = alloc_buffers.get_or_create_store_buffer(
                     alloc_range(base_offset, place.layout.size),
                     latest_in_mo,
@@ -553,7 +554,7 @@ fn buffered_atomic_read(
                     global,
                     &this.machine.threads,
                     atomic == AtomicReadOrd::SeqCst,
-                    &mut *rng,
+                    |len| this.decide_index(DecisionKind::LoadFrom, len),
                     validate,
                 )?;
                 if global.track_outdated_loads && recency == LoadRecency::Outdated {
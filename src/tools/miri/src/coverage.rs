@@ -0,0 +1,439 @@
+//! Coverage of the MIR that was interpreted (`-Zmiri-coverage`).
+//!
+//! For every function of a local crate, we count how often each of its basic blocks was
+//! executed. At the end of the run, the counts are written to `<prefix>.json`, which lists the
+//! blocks that were never executed and whether they are `unsafe` code, and to `<prefix>.info`,
+//! an lcov tracefile that maps the counts back to source lines.
+//!
+//! Cleanup (unwinding) blocks are reported in the JSON file but not counted in the totals, since
+//! most of them are only reachable by panicking.
+//!
+//! Whether code is `unsafe` is only known for the crate being interpreted: the MIR of other crates,
+//! including the other local crates, does not record `unsafe` blocks. Their safety is reported as
+//! unknown (`null`), and counted separately in the totals.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+
+use rustc_data_structures::fx::FxHashMap;
+use rustc_hir::def::DefKind;
+use rustc_hir::def_id::{DefId, LOCAL_CRATE};
+use rustc_index::vec::IndexVec;
+use rustc_middle::mir::{self, BasicBlock, ClearCrossCrate, Safety, SourceScope};
+use rustc_middle::ty::TyCtxt;
+use rustc_span::{hygiene::walk_chain, Span};
+
+/// The execution counts of the basic blocks of the functions of the local crates.
+#[derive(Debug)]
+pub struct Coverage {
+    /// The files are written to this path, with `.json` and `.info` appended.
+    out: String,
+    counts: FxHashMap<DefId, IndexVec<BasicBlock, u64>>,
+}
+
+impl Coverage {
+    pub fn new(out: String) -> Self {
+        Coverage { out, counts: FxHashMap::default() }
+    }
+
+    /// Records that `block` of `def_id`, which has `num_blocks` blocks, is being executed.
+    pub fn record(&mut self, def_id: DefId, block: BasicBlock, num_blocks: usize) {
+        let counts =
+            self.counts.entry(def_id).or_insert_with(|| IndexVec::from_elem_n(0, num_blocks));
+        counts[block] += 1;
+    }
+
+    /// Writes the JSON report and the lcov tracefile.
+    pub fn write(&self, tcx: TyCtxt<'_>) -> io::Result<()> {
+        let functions = self.function_reports(tcx);
+        fs::write(format!("{}.json", self.out), to_json(&functions))?;
+        fs::write(format!("{}.info", self.out), to_lcov(&functions))?;
+        Ok(())
+    }
+
+    /// Collects the functions with MIR in the crate being interpreted, and all functions of other
+    /// local crates that were executed at least once, sorted by name.
+    fn function_reports(&self, tcx: TyCtxt<'_>) -> Vec<FunctionReport> {
+        let mut def_ids: Vec<DefId> = tcx
+            .mir_keys(())
+            .iter()
+            .map(|def_id| def_id.to_def_id())
+            .filter(|&def_id| {
+                matches!(
+                    tcx.def_kind(def_id),
+                    DefKind::Fn | DefKind::AssocFn | DefKind::Closure | DefKind::Generator
+                )
+            })
+            .collect();
+        def_ids.extend(self.counts.keys().filter(|def_id| def_id.krate != LOCAL_CRATE));
+        let mut functions: Vec<FunctionReport> = def_ids
+            .into_iter()
+            .map(|def_id| FunctionReport::new(tcx, def_id, self.counts.get(&def_id)))
+            .collect();
+        functions.sort_by(|a, b| (&a.name, &a.location).cmp(&(&b.name, &b.location)));
+        functions
+    }
+}
+
+/// A location in the source code, as reported to the user.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct SourceLocation {
+    file: String,
+    /// 1-based.
+    line: usize,
+    /// 1-based.
+    column: usize,
+}
+
+impl SourceLocation {
+    fn new(tcx: TyCtxt<'_>, span: Span) -> Self {
+        let loc = tcx.sess.source_map().lookup_char_pos(span.lo());
+        SourceLocation {
+            file: loc.file.name.prefer_local().to_string(),
+            line: loc.line,
+            column: loc.col.0 + 1,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct BlockReport {
+    block: BasicBlock,
+    count: u64,
+    /// `None` if the block is in a function of another crate, whose MIR has no safety information.
+    is_unsafe: Option<bool>,
+    is_cleanup: bool,
+    /// Where the block starts, if it has any code that maps to the body of the function.
+    location: Option<SourceLocation>,
+    /// The lines of `location.file` with code of this block.
+    lines: Vec<usize>,
+}
+
+#[derive(Clone, Debug)]
+struct FunctionReport {
+    name: String,
+    location: SourceLocation,
+    /// `None` if the function is from another crate, whose MIR has no safety information.
+    is_unsafe_fn: Option<bool>,
+    blocks: Vec<BlockReport>,
+}
+
+impl FunctionReport {
+    fn new(tcx: TyCtxt<'_>, def_id: DefId, counts: Option<&IndexVec<BasicBlock, u64>>) -> Self {
+        let body = tcx.optimized_mir(def_id);
+        let location = SourceLocation::new(tcx, body.span);
+        let is_unsafe_fn = scope_safety(body, mir::OUTERMOST_SOURCE_SCOPE)
+            .map(|safety| matches!(safety, Safety::FnUnsafe));
+        let blocks = body
+            .basic_blocks
+            .iter_enumerated()
+            .map(|(block, data)| {
+                let source_infos = data
+                    .statements
+                    .iter()
+                    .map(|stmt| &stmt.source_info)
+                    .chain(data.terminator.as_ref().map(|term| &term.source_info));
+                let mut is_unsafe = Some(false);
+                let mut first = None;
+                let mut lines = Vec::new();
+                for source_info in source_infos {
+                    let safety = scope_safety(body, source_info.scope);
+                    is_unsafe = is_unsafe.zip(safety).map(|(is_unsafe, safety)| {
+                        is_unsafe || matches!(safety, Safety::FnUnsafe | Safety::ExplicitUnsafe(_))
+                    });
+                    // Map code that was expanded from macros to the macro invocation in the body.
+                    let span = walk_chain(source_info.span, body.span.ctxt());
+                    if span.is_dummy() || !body.span.contains(span) {
+                        continue;
+                    }
+                    let loc = SourceLocation::new(tcx, span);
+                    if loc.file != location.file {
+                        continue;
+                    }
+                    if !lines.contains(&loc.line) {
+                        lines.push(loc.line);
+                    }
+                    first.get_or_insert(loc);
+                }
+                BlockReport {
+                    block,
+                    count: counts.map_or(0, |counts| counts[block]),
+                    is_unsafe,
+                    is_cleanup: data.is_cleanup,
+                    location: first,
+                    lines,
+                }
+            })
+            .collect();
+        FunctionReport { name: tcx.def_path_str(def_id), location, is_unsafe_fn, blocks }
+    }
+
+    /// How often the function was called: the count of its first block.
+    fn calls(&self) -> u64 {
+        self.blocks.first().map_or(0, |block| block.count)
+    }
+
+    fn counted_blocks(&self) -> impl Iterator<Item = &BlockReport> {
+        self.blocks.iter().filter(|block| !block.is_cleanup)
+    }
+}
+
+/// Returns `None` for the MIR of other crates, which does not know about `unsafe` blocks.
+fn scope_safety(body: &mir::Body<'_>, scope: SourceScope) -> Option<Safety> {
+    match &body.source_scopes[scope].local_data {
+        ClearCrossCrate::Set(data) => Some(data.safety),
+        ClearCrossCrate::Clear => None,
+    }
+}
+
+/// Formats an optional boolean as JSON, with `null` for unknown values.
+fn json_option(value: Option<bool>) -> &'static str {
+    match value {
+        Some(true) => "true",
+        Some(false) => "false",
+        None => "null",
+    }
+}
+
+/// Block counts that are summed up over several functions.
+#[derive(Default)]
+struct Totals {
+    functions: usize,
+    functions_executed: usize,
+    blocks: usize,
+    blocks_executed: usize,
+    unsafe_blocks: usize,
+    unsafe_blocks_executed: usize,
+    unknown_safety_blocks: usize,
+    unknown_safety_blocks_executed: usize,
+}
+
+impl Totals {
+    fn add(&mut self, function: &FunctionReport) {
+        self.functions += 1;
+        self.functions_executed += usize::from(function.calls() > 0);
+        for block in function.counted_blocks() {
+            self.blocks += 1;
+            self.blocks_executed += usize::from(block.count > 0);
+            match block.is_unsafe {
+                Some(true) => {
+                    self.unsafe_blocks += 1;
+                    self.unsafe_blocks_executed += usize::from(block.count > 0);
+                }
+                Some(false) => {}
+                None => {
+                    self.unknown_safety_blocks += 1;
+                    self.unknown_safety_blocks_executed += usize::from(block.count > 0);
+                }
+            }
+        }
+    }
+
+    fn write_json_fields(&self, out: &mut String) {
+        write!(
+            out,
+            "\"blocks\": {}, \"blocks_executed\": {}, \"unsafe_blocks\": {}, \
+             \"unsafe_blocks_executed\": {}, \"unknown_safety_blocks\": {}, \
+             \"unknown_safety_blocks_executed\": {}",
+            self.blocks,
+            self.blocks_executed,
+            self.unsafe_blocks,
+            self.unsafe_blocks_executed,
+            self.unknown_safety_blocks,
+            self.unknown_safety_blocks_executed,
+        )
+        .unwrap();
+    }
+}
+
+fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if u32::from(c) < 0x20 => write!(out, "\\u{:04x}", u32::from(c)).unwrap(),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+fn json_location(location: &SourceLocation) -> String {
+    format!(
+        "\"file\": {}, \"line\": {}, \"column\": {}",
+        json_string(&location.file),
+        location.line,
+        location.column
+    )
+}
+
+fn to_json(functions: &[FunctionReport]) -> String {
+    let mut out = String::from("{\n  \"functions\": [");
+    let mut totals = Totals::default();
+    for (i, function) in functions.iter().enumerate() {
+        let mut function_totals = Totals::default();
+        function_totals.add(function);
+        totals.add(function);
+
+        out.push_str(if i == 0 { "\n" } else { ",\n" });
+        write!(
+            out,
+            "    {{\"name\": {}, {}, \"unsafe_fn\": {}, \"calls\": {}, ",
+            json_string(&function.name),
+            json_location(&function.location),
+            json_option(function.is_unsafe_fn),
+            function.calls(),
+        )
+        .unwrap();
+        function_totals.write_json_fields(&mut out);
+        out.push_str(", \"unexecuted_blocks\": [");
+        let unexecuted = function.blocks.iter().filter(|block| block.count == 0);
+        for (j, block) in unexecuted.enumerate() {
+            if j > 0 {
+                out.push_str(", ");
+            }
+            write!(
+                out,
+                "{{\"block\": \"{:?}\", \"unsafe\": {}, \"cleanup\": {}",
+                block.block,
+                json_option(block.is_unsafe),
+                block.is_cleanup
+            )
+            .unwrap();
+            if let Some(location) = &block.location {
+                write!(out, ", {}", json_location(location)).unwrap();
+            }
+            out.push('}');
+        }
+        out.push_str("]}");
+    }
+    out.push_str("\n  ],\n  \"totals\": {");
+    write!(
+        out,
+        "\"functions\": {}, \"functions_executed\": {}, ",
+        totals.functions, totals.functions_executed
+    )
+    .unwrap();
+    totals.write_json_fields(&mut out);
+    out.push_str("}\n}\n");
+    out
+}
+
+fn to_lcov(functions: &[FunctionReport]) -> String {
+    let mut by_file: BTreeMap<&str, Vec<&FunctionReport>> = BTreeMap::new();
+    for function in functions {
+        by_file.entry(&function.location.file).or_default().push(function);
+    }
+
+    let mut out = String::new();
+    for (file, functions) in by_file {
+        writeln!(out, "TN:").unwrap();
+        writeln!(out, "SF:{file}").unwrap();
+        // A line is as often executed as the most often executed block with code on it.
+        let mut lines: BTreeMap<usize, u64> = BTreeMap::new();
+        for function in &functions {
+            writeln!(out, "FN:{},{}", function.location.line, function.name).unwrap();
+            for block in function.counted_blocks() {
+                for &line in &block.lines {
+                    let count = lines.entry(line).or_default();
+                    *count = (*count).max(block.count);
+                }
+            }
+        }
+        for function in &functions {
+            writeln!(out, "FNDA:{},{}", function.calls(), function.name).unwrap();
+        }
+        writeln!(out, "FNF:{}", functions.len()).unwrap();
+        writeln!(out, "FNH:{}", functions.iter().filter(|f| f.calls() > 0).count()).unwrap();
+        for (line, count) in &lines {
+            writeln!(out, "DA:{line},{count}").unwrap();
+        }
+        writeln!(out, "LF:{}", lines.len()).unwrap();
+        writeln!(out, "LH:{}", lines.values().filter(|&&count| count > 0).count()).unwrap();
+        writeln!(out, "end_of_record").unwrap();
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rustc_index::vec::Idx;
+
+    fn location(line: usize) -> SourceLocation {
+        SourceLocation { file: "src/main.rs".to_string(), line, column: 1 }
+    }
+
+    fn block(index: usize, count: u64, is_unsafe: Option<bool>, lines: &[usize]) -> BlockReport {
+        BlockReport {
+            block: BasicBlock::from_usize(index),
+            count,
+            is_unsafe,
+            is_cleanup: false,
+            location: lines.first().map(|&line| location(line)),
+            lines: lines.to_vec(),
+        }
+    }
+
+    fn functions() -> Vec<FunctionReport> {
+        vec![
+            FunctionReport {
+                name: "main".to_string(),
+                location: location(1),
+                is_unsafe_fn: Some(false),
+                blocks: vec![block(0, 1, Some(false), &[2]), block(1, 0, Some(true), &[3, 4])],
+            },
+            FunctionReport {
+                name: "unused".to_string(),
+                location: location(7),
+                is_unsafe_fn: Some(true),
+                blocks: vec![block(0, 0, Some(true), &[8])],
+            },
+            FunctionReport {
+                name: "dep::helper".to_string(),
+                location: SourceLocation { file: "dep/src/lib.rs".to_string(), line: 1, column: 1 },
+                is_unsafe_fn: None,
+                blocks: vec![block(0, 1, None, &[])],
+            },
+        ]
+    }
+
+    #[test]
+    fn lcov() {
+        assert_eq!(
+            to_lcov(&functions()),
+            "TN:\nSF:dep/src/lib.rs\nFN:1,dep::helper\nFNDA:1,dep::helper\nFNF:1\nFNH:1\n\
+             LF:0\nLH:0\nend_of_record\n\
+             TN:\nSF:src/main.rs\nFN:1,main\nFN:7,unused\nFNDA:1,main\nFNDA:0,unused\n\
+             FNF:2\nFNH:1\n\
+             DA:2,1\nDA:3,0\nDA:4,0\nDA:8,0\nLF:4\nLH:1\nend_of_record\n"
+        );
+    }
+
+    #[test]
+    fn json() {
+        let json = to_json(&functions());
+        assert!(json.contains(
+            "\"totals\": {\"functions\": 3, \"functions_executed\": 2, \"blocks\": 4, \
+             \"blocks_executed\": 2, \"unsafe_blocks\": 2, \"unsafe_blocks_executed\": 0, \
+             \"unknown_safety_blocks\": 1, \"unknown_safety_blocks_executed\": 1}"
+        ));
+        assert!(json.contains(
+            "{\"name\": \"dep::helper\", \"file\": \"dep/src/lib.rs\", \"line\": 1, \"column\": 1, \
+             \"unsafe_fn\": null, \"calls\": 1, "
+        ));
+        assert!(json.contains(
+            "\"unexecuted_blocks\": [{\"block\": \"bb1\", \"unsafe\": true, \"cleanup\": false, \
+             \"file\": \"src/main.rs\", \"line\": 3, \"column\": 1}]"
+        ));
+        assert_eq!(json_string("a\"b\\c\n"), "\"a\\\"b\\\\c\\n\"");
+    }
+}
@@ -7,6 +7,7 @@
 use rustc_target::abi::{Align, Size};
 
 use crate::borrow_tracker::stacked_borrows::diagnostics::TagHistory;
+use crate::borrow_tracker::tree_borrows::diagnostics as tree_diagnostics;
 use crate::*;
 
 /// Details of premature program termination.
@@ -22,8 +23,14 @@ pub enum TerminationInfo {
         help: Option<String>,
         history: Option<TagHistory>,
     },
+    TreeBorrowsUb {
+        title: String,
+        details: Vec<String>,
+        history: tree_diagnostics::HistoryData,
+    },
     Int2PtrWithStrictProvenance,
     Deadlock,
+    ScheduleDiverged(String),
     MultipleSymbolDefinitions {
         link_name: Symbol,
         first: SpanData,
@@ -50,7 +57,9 @@ fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
                     "integer-to-pointer casts and `ptr::from_exposed_addr` are not supported with `-Zmiri-strict-provenance`"
                 ),
             StackedBorrowsUb { msg, .. } => write!(f, "{msg}"),
+            TreeBorrowsUb { title, .. } => write!(f, "{title}"),
             Deadlock => write!(f, "the evaluated program deadlocked"),
+            ScheduleDiverged(msg) => write!(f, "{msg}"),
             MultipleSymbolDefinitions { link_name, .. } =>
                 write!(f, "multiple definitions of symbol `{link_name}`"),
             SymbolShimClashing { link_name, .. } =>
@@ -167,8 +176,9 @@ pub fn report_error<'tcx, 'mir>(
             Abort(_) => Some("abnormal termination"),
             UnsupportedInIsolation(_) | Int2PtrWithStrictProvenance =>
                 Some("unsupported operation"),
-            StackedBorrowsUb { .. } => Some("Undefined Behavior"),
+            StackedBorrowsUb { .. } | TreeBorrowsUb { .. } => Some("Undefined Behavior"),
             Deadlock => Some("deadlock"),
+            ScheduleDiverged(_) => Some("schedule replay failure"),
             MultipleSymbolDefinitions { .. } | SymbolShimClashing { .. } => None,
         };
         #[rustfmt::skip]
@@ -196,6 +206,16 @@ pub fn report_error<'tcx, 'mir>(
                 }
                 helps
             }
+            TreeBorrowsUb { title: _, details, history } => {
+                msg.extend(details.clone());
+                let mut helps = vec![
+                    (None, format!("this indicates a potential bug in the program: it performed an invalid operation, but the Tree Borrows rules it violated are still experimental")),
+                ];
+                for (msg, span) in history.events.iter().cloned() {
+                    helps.push((Some(span), msg));
+                }
+                helps
+            }
             MultipleSymbolDefinitions { first, first_crate, second, second_crate, .. } =>
                 vec![
                     (Some(*first), format!("it's first defined here, in crate `{first_crate}`")),
@@ -203,6 +223,8 @@ pub fn report_error<'tcx, 'mir>(
                 ],
             SymbolShimClashing { link_name, span } =>
                 vec![(Some(*span), format!("the `{link_name}` symbol is defined here"))],
+            ScheduleDiverged(_) =>
+                vec![(None, format!("schedules can only be replayed with the same program, input and Miri flags as the run that recorded them"))],
             Int2PtrWithStrictProvenance =>
                 vec![(None, format!("use Strict Provenance APIs (https://doc.rust-lang.org/nightly/std/ptr/index.html#strict-provenance, https://crates.io/crates/sptr) instead"))],
             _ => vec![],
@@ -2,6 +2,7 @@
 
 use std::ffi::{OsStr, OsString};
 use std::iter;
+use std::ops::Range;
 use std::panic::{self, AssertUnwindSafe};
 use std::path::PathBuf;
 use std::task::Poll;
@@ -143,6 +144,15 @@ pub struct MiriConfig {
     pub gc_interval: u32,
     /// The number of CPUs to be reported by miri.
     pub num_cpus: u32,
+    /// Whether to record the decisions of the scheduler and the weak memory emulation, or to
+    /// replay previously recorded ones.
+    pub schedule_log: Option<ScheduleLogMode>,
+    /// If `Some`, record which basic blocks of local functions are executed, and write a coverage
+    /// report to files with the specified prefix.
+    pub coverage_out: Option<String>,
+    /// If `Some`, the driver runs the program once for every seed in this range, stopping at the
+    /// first run that fails.
+    pub many_seeds: Option<Range<u64>>,
 }
 
 impl Default for MiriConfig {
@@ -176,6 +186,9 @@ fn default() -> MiriConfig {
             external_so_file: None,
             gc_interval: 10_000,
             num_cpus: 1,
+            schedule_log: None,
+            many_seeds: None,
+            coverage_out: None,
         }
     }
 }
@@ -443,6 +456,18 @@ pub fn eval_entry<'tcx>(
         EnvVars::cleanup(&mut ecx).expect("error during env var cleanup");
     }
 
+    // Save the recorded schedule, also (and especially) if the program failed.
+    if let Some(schedule_log) = &ecx.machine.schedule_log {
+        if let Err(err) = schedule_log.borrow().write() {
+            tcx.sess.warn(format!("failed to write the recorded schedule: {err}"));
+        }
+    }
+    if let Some(coverage) = &ecx.machine.coverage {
+        if let Err(err) = coverage.write(tcx) {
+            tcx.sess.warn(format!("failed to write the coverage report: {err}"));
+        }
+    }
+
     // Process the result.
     let (return_code, leak_check) = report_error(&ecx, res)?;
     if leak_check && !ignore_leaks {
@@ -56,6 +56,7 @@
 mod borrow_tracker;
 mod clock;
 mod concurrency;
+mod coverage;
 mod diagnostics;
 mod eval;
 mod helpers;
@@ -87,6 +88,7 @@
 pub use crate::borrow_tracker::stacked_borrows::{
     EvalContextExt as _, Item, Permission, Stack, Stacks,
 };
+pub use crate::borrow_tracker::tree_borrows::EvalContextExt as _;
 pub use crate::borrow_tracker::{
     BorTag, BorrowTrackerMethod, CallId, EvalContextExt as _, RetagFields,
 };
@@ -94,9 +96,11 @@
 pub use crate::concurrency::{
     data_race::{AtomicFenceOrd, AtomicReadOrd, AtomicRwOrd, AtomicWriteOrd, EvalContextExt as _},
     init_once::{EvalContextExt as _, InitOnceId},
+    schedule_log::{DecisionKind, EvalContextExt as _, Schedule, ScheduleLog, ScheduleLogMode},
     sync::{CondvarId, EvalContextExt as _, MutexId, RwLockId, SyncId},
     thread::{EvalContextExt as _, StackEmptyCallback, ThreadId, ThreadManager, Time},
 };
+pub use crate::coverage::Coverage;
 pub use crate::diagnostics::{
     report_error, EvalContextExt as _, NonHaltingDiagnostic, TerminationInfo,
 };
@@ -433,6 +433,9 @@ pub struct MiriMachine<'mir, 'tcx> {
     /// Needs to be queried by ptr_to_int, hence needs interior mutability.
     pub(crate) rng: RefCell<StdRng>,
 
+    /// Records or replays the random decisions of the scheduler and the weak memory emulation.
+    pub(crate) schedule_log: Option<RefCell<ScheduleLog>>,
+
     /// The allocation IDs to report when they are being allocated
     /// (helps for debugging memory leaks and use after free bugs).
     tracked_alloc_ids: FxHashSet<AllocId>,
@@ -469,6 +472,9 @@ pub struct MiriMachine<'mir, 'tcx> {
     pub(crate) since_gc: u32,
     /// The number of CPUs to be reported by miri.
     pub(crate) num_cpus: u32,
+
+    /// If `Some`, the execution counts of the basic blocks of local functions are recorded.
+    pub(crate) coverage: Option<Coverage>,
 }
 
 impl<'mir, 'tcx> MiriMachine<'mir, 'tcx> {
@@ -510,6 +516,9 @@ pub(crate) fn new(config: &MiriConfig, layout_cx: LayoutCx<'tcx, TyCtxt<'tcx>>)
             local_crates,
             extern_statics: FxHashMap::default(),
             rng: RefCell::new(rng),
+            schedule_log: config.schedule_log.as_ref().map(|mode| {
+                RefCell::new(ScheduleLog::new(mode, config.seed.unwrap_or(0)))
+            }),
             tracked_alloc_ids: config.tracked_alloc_ids.clone(),
             check_alignment: config.check_alignment,
             cmpxchg_weak_failure_rate: config.cmpxchg_weak_failure_rate,
@@ -548,6 +557,7 @@ pub(crate) fn new(config: &MiriConfig, layout_cx: LayoutCx<'tcx, TyCtxt<'tcx>>)
             gc_interval: config.gc_interval,
             since_gc: 0,
             num_cpus: config.num_cpus,
+            coverage: config.coverage_out.clone().map(Coverage::new),
         }
     }
 
@@ -680,6 +690,7 @@ fn visit_tags(&self, visit: &mut dyn FnMut(BorTag)) {
             backtrace_style: _,
             local_crates: _,
             rng: _,
+            schedule_log: _,
             tracked_alloc_ids: _,
             check_alignment: _,
             cmpxchg_weak_failure_rate: _,
@@ -692,6 +703,7 @@ fn visit_tags(&self, visit: &mut dyn FnMut(BorTag)) {
             gc_interval: _,
             since_gc: _,
             num_cpus: _,
+            coverage: _,
         } = self;
 
         threads.visit_tags(visit);
@@ -1127,6 +1139,18 @@ fn stack_mut<'a>(
     fn before_terminator(ecx: &mut InterpCx<'mir, 'tcx, Self>) -> InterpResult<'tcx> {
         ecx.machine.basic_block_count += 1u64; // a u64 that is only incremented by 1 will "never" overflow
         ecx.machine.since_gc += 1;
+        // Record coverage of the MIR of local functions.
+        if ecx.machine.coverage.is_some() {
+            let frame = ecx.frame();
+            if let (ty::InstanceDef::Item(def), Some(loc)) =
+                (frame.instance.def, frame.current_loc().left())
+            {
+                if ecx.machine.local_crates.contains(&def.did.krate) {
+                    let num_blocks = frame.body.basic_blocks.len();
+                    ecx.machine.coverage.as_mut().unwrap().record(def.did, loc.block, num_blocks);
+                }
+            }
+        }
         // Possibly report our progress.
         if let Some(report_progress) = ecx.machine.report_progress {
             if ecx.machine.basic_block_count % u64::from(report_progress) == 0 {
@@ -1146,7 +1170,7 @@ fn before_terminator(ecx: &mut InterpCx<'mir, 'tcx, Self>) -> InterpResult<'tcx>
         }
 
         // These are our preemption points.
-        ecx.maybe_preempt_active_thread();
+        ecx.maybe_preempt_active_thread()?;
 
         // Make sure some time passes.
         ecx.machine.clock.tick();
@@ -7,6 +7,7 @@
 pub mod intrinsics;
 pub mod unix;
 pub mod windows;
+pub mod x86;
 
 pub mod dlsym;
 pub mod env;
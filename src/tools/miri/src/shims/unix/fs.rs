@@ -1,4 +1,5 @@
 use std::borrow::Cow;
+use std::cell::RefCell;
 use std::collections::BTreeMap;
 use std::convert::TryInto;
 use std::fs::{
@@ -6,6 +7,7 @@
 };
 use std::io::{self, ErrorKind, IsTerminal, Read, Seek, SeekFrom, Write};
 use std::path::{Path, PathBuf};
+use std::rc::Rc;
 use std::time::SystemTime;
 
 use log::trace;
@@ -13,18 +15,35 @@
 use rustc_data_structures::fx::FxHashMap;
 use rustc_target::abi::{Align, Size};
 
+use crate::concurrency::data_race;
+use crate::concurrency::thread::MachineCallback;
 use crate::shims::os_str::bytes_to_os_str;
 use crate::*;
 use shims::os_str::os_str_to_bytes;
+use shims::unix::net::Network;
 use shims::time::system_time_to_duration;
 
 #[derive(Debug)]
-struct FileHandle {
+pub(crate) struct FileHandle {
     file: File,
     writable: bool,
 }
 
-trait FileDescriptor: std::fmt::Debug {
+/// The readiness of a file description, as reported by `epoll`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct Readiness {
+    /// Reading would not block.
+    pub readable: bool,
+    /// Writing would not block.
+    pub writable: bool,
+    /// The other end shut down writing, so reads will return end-of-file once the buffered data
+    /// is consumed.
+    pub read_closed: bool,
+    /// The other end shut down both reading and writing.
+    pub hangup: bool,
+}
+
+pub(crate) trait FileDescriptor: std::fmt::Debug {
     fn name(&self) -> &'static str;
 
     fn as_file_handle<'tcx>(&self) -> InterpResult<'tcx, &FileHandle> {
@@ -66,6 +85,35 @@ fn close<'tcx>(
 
     fn is_tty(&self) -> bool;
 
+    /// Whether reads and writes that would block park the calling thread until the file
+    /// description becomes ready, instead of failing with `EAGAIN`.
+    fn is_blocking(&self) -> bool {
+        false
+    }
+
+    /// The current readiness of the file description and a counter that changes whenever that
+    /// readiness might have changed, or `None` if the file description cannot be used with
+    /// `epoll`.
+    fn readiness(&self) -> Option<(Readiness, u64)> {
+        None
+    }
+
+    fn as_epoll(&self) -> Option<&shims::unix::linux::epoll::Epoll> {
+        None
+    }
+
+    fn as_inet_socket(&self) -> Option<&shims::unix::net::InetSocket> {
+        None
+    }
+
+    /// Makes the active thread synchronize with the threads that wrote the data it just read from
+    /// this file description.
+    fn data_race_acquire(&self, _data_race: &data_race::GlobalState, _thread: ThreadId) {}
+
+    /// Makes the data the active thread is about to write to this file description carry the
+    /// thread's clock, so that threads reading it later synchronize with the writer.
+    fn data_race_release(&self, _data_race: &data_race::GlobalState, _thread: ThreadId) {}
+
     #[cfg(unix)]
     fn as_unix_host_fd(&self) -> Option<i32> {
         None
@@ -274,7 +322,9 @@ fn is_tty(&self) -> bool {
 
 #[derive(Debug)]
 pub struct FileHandler {
-    handles: BTreeMap<i32, Box<dyn FileDescriptor>>,
+    pub(crate) handles: BTreeMap<i32, Box<dyn FileDescriptor>>,
+    /// The loopback network that the TCP and UDP sockets in `handles` are attached to.
+    pub(crate) network: Rc<RefCell<Network>>,
 }
 
 impl VisitTags for FileHandler {
@@ -294,10 +344,10 @@ pub(crate) fn new(mute_stdout_stderr: bool) -> FileHandler {
             handles.insert(1i32, Box::new(io::stdout()));
             handles.insert(2i32, Box::new(io::stderr()));
         }
-        FileHandler { handles }
+        FileHandler { handles, network: Rc::default() }
     }
 
-    fn insert_fd(&mut self, file_handle: Box<dyn FileDescriptor>) -> i32 {
+    pub(crate) fn insert_fd(&mut self, file_handle: Box<dyn FileDescriptor>) -> i32 {
         self.insert_fd_with_min_fd(file_handle, 0)
     }
 
@@ -376,6 +426,90 @@ fn macos_stat_write_buf(
         Ok(0)
     }
 
+    /// Reads at most `count` bytes from `fd` into `buf`, returning the number of bytes read, or
+    /// `None` if `fd` is a blocking file description that has no data to read yet.
+    fn try_read(
+        &mut self,
+        fd: i32,
+        buf: Pointer<Option<Provenance>>,
+        count: u64,
+    ) -> InterpResult<'tcx, Option<i64>> {
+        let this = self.eval_context_mut();
+        let communicate = this.machine.communicate();
+
+        if let Some(file_descriptor) = this.machine.file_handler.handles.get_mut(&fd) {
+            trace!("read: FD mapped to {:?}", file_descriptor);
+            let blocking = file_descriptor.is_blocking();
+            // We want to read at most `count` bytes. We are sure that `count` is not negative
+            // because it was a target's `usize`. Also we are sure that its smaller than
+            // `usize::MAX` because it is bounded by the host's `isize`.
+            let mut bytes = vec![0; usize::try_from(count).unwrap()];
+            // `File::read` never returns a value larger than `count`,
+            // so this cannot fail.
+            let result =
+                file_descriptor.read(communicate, &mut bytes)?.map(|c| i64::try_from(c).unwrap());
+
+            match result {
+                Ok(read_bytes) => {
+                    if let Some(data_race) = &this.machine.data_race {
+                        let thread = this.machine.threads.get_active_thread_id();
+                        this.machine.file_handler.handles[&fd].data_race_acquire(data_race, thread);
+                    }
+                    // Reading may have made room for writers of the same file description.
+                    this.notify_io_event();
+                    // If reading to `bytes` did not fail, we write those bytes to the buffer.
+                    this.write_bytes_ptr(buf, bytes)?;
+                    Ok(Some(read_bytes))
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock && blocking => Ok(None),
+                Err(e) => {
+                    this.set_last_error_from_io_error(e.kind())?;
+                    Ok(Some(-1))
+                }
+            }
+        } else {
+            trace!("read: FD not found");
+            this.handle_not_found().map(Some)
+        }
+    }
+
+    /// Writes at most `count` bytes from `buf` to `fd`, returning the number of bytes written, or
+    /// `None` if `fd` is a blocking file description that has no room for the data yet.
+    fn try_write(
+        &mut self,
+        fd: i32,
+        buf: Pointer<Option<Provenance>>,
+        count: u64,
+    ) -> InterpResult<'tcx, Option<i64>> {
+        let this = self.eval_context_mut();
+        let communicate = this.machine.communicate();
+
+        if let Some(file_descriptor) = this.machine.file_handler.handles.get(&fd) {
+            let blocking = file_descriptor.is_blocking();
+            let bytes = this.read_bytes_ptr_strip_provenance(buf, Size::from_bytes(count))?;
+            let result =
+                file_descriptor.write(communicate, bytes)?.map(|c| i64::try_from(c).unwrap());
+            match result {
+                Err(e) if e.kind() == ErrorKind::WouldBlock && blocking => Ok(None),
+                result => {
+                    if result.is_ok() {
+                        // Only a write that succeeded can synchronize with the readers of the
+                        // file description, which it may also have made ready.
+                        if let Some(data_race) = &this.machine.data_race {
+                            let thread = this.machine.threads.get_active_thread_id();
+                            this.machine.file_handler.handles[&fd]
+                                .data_race_release(data_race, thread);
+                        }
+                        this.notify_io_event();
+                    }
+                    this.try_unwrap_io_result(result).map(Some)
+                }
+            }
+        } else {
+            this.handle_not_found().map(Some)
+        }
+    }
+
     /// Function used when a handle is not found inside `FileHandler`. It returns `Ok(-1)`and sets
     /// the last OS error to `libc::EBADF` (invalid file descriptor). This function uses
     /// `T: From<i32>` instead of `i32` directly because some fs functions return different integer
@@ -710,6 +844,8 @@ fn close(&mut self, fd_op: &OpTy<'tcx, Provenance>) -> InterpResult<'tcx, Scalar
         Ok(Scalar::from_i32(
             if let Some(file_descriptor) = this.machine.file_handler.handles.remove(&fd) {
                 let result = file_descriptor.close(this.machine.communicate())?;
+                // Closing may have hung up the other end of a socket.
+                this.notify_io_event();
                 this.try_unwrap_io_result(result)?
             } else {
                 this.handle_not_found()?
@@ -722,7 +858,8 @@ fn read(
         fd: i32,
         buf: Pointer<Option<Provenance>>,
         count: u64,
-    ) -> InterpResult<'tcx, i64> {
+        dest: &PlaceTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx> {
         let this = self.eval_context_mut();
 
         // Isolation check is done via `FileDescriptor` trait.
@@ -742,34 +879,44 @@ fn read(
         let count = count
             .min(u64::try_from(this.machine_isize_max()).unwrap())
             .min(u64::try_from(isize::MAX).unwrap());
-        let communicate = this.machine.communicate();
 
-        if let Some(file_descriptor) = this.machine.file_handler.handles.get_mut(&fd) {
-            trace!("read: FD mapped to {:?}", file_descriptor);
-            // We want to read at most `count` bytes. We are sure that `count` is not negative
-            // because it was a target's `usize`. Also we are sure that its smaller than
-            // `usize::MAX` because it is bounded by the host's `isize`.
-            let mut bytes = vec![0; usize::try_from(count).unwrap()];
-            // `File::read` never returns a value larger than `count`,
-            // so this cannot fail.
-            let result =
-                file_descriptor.read(communicate, &mut bytes)?.map(|c| i64::try_from(c).unwrap());
+        if let Some(result) = this.try_read(fd, buf, count)? {
+            this.write_scalar(Scalar::from_machine_isize(result, this), dest)?;
+        } else {
+            // The file description is empty, wait until some data arrives.
+            struct Callback<'tcx> {
+                thread: ThreadId,
+                fd: i32,
+                buf: Pointer<Option<Provenance>>,
+                count: u64,
+                dest: PlaceTy<'tcx, Provenance>,
+            }
 
-            match result {
-                Ok(read_bytes) => {
-                    // If reading to `bytes` did not fail, we write those bytes to the buffer.
-                    this.write_bytes_ptr(buf, bytes)?;
-                    Ok(read_bytes)
+            impl<'tcx> VisitTags for Callback<'tcx> {
+                fn visit_tags(&self, visit: &mut dyn FnMut(BorTag)) {
+                    let Callback { thread: _, fd: _, buf, count: _, dest } = self;
+                    buf.visit_tags(visit);
+                    dest.visit_tags(visit);
                 }
-                Err(e) => {
-                    this.set_last_error_from_io_error(e.kind())?;
-                    Ok(-1)
+            }
+
+            impl<'mir, 'tcx: 'mir> MachineCallback<'mir, 'tcx> for Callback<'tcx> {
+                fn call(&self, this: &mut MiriInterpCx<'mir, 'tcx>) -> InterpResult<'tcx> {
+                    if let Some(result) = this.try_read(self.fd, self.buf, self.count)? {
+                        this.unblock_thread(self.thread);
+                        this.write_scalar(Scalar::from_machine_isize(result, this), &self.dest)?;
+                    }
+                    Ok(())
                 }
             }
-        } else {
-            trace!("read: FD not found");
-            this.handle_not_found()
+
+            let thread = this.get_active_thread();
+            this.block_thread_on_io(
+                thread,
+                Box::new(Callback { thread, fd, buf, count, dest: dest.clone() }),
+            );
         }
+        Ok(())
     }
 
     fn write(
@@ -777,7 +924,8 @@ fn write(
         fd: i32,
         buf: Pointer<Option<Provenance>>,
         count: u64,
-    ) -> InterpResult<'tcx, i64> {
+        dest: &PlaceTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx> {
         let this = self.eval_context_mut();
 
         // Isolation check is done via `FileDescriptor` trait.
@@ -795,16 +943,44 @@ fn write(
         let count = count
             .min(u64::try_from(this.machine_isize_max()).unwrap())
             .min(u64::try_from(isize::MAX).unwrap());
-        let communicate = this.machine.communicate();
 
-        if let Some(file_descriptor) = this.machine.file_handler.handles.get(&fd) {
-            let bytes = this.read_bytes_ptr_strip_provenance(buf, Size::from_bytes(count))?;
-            let result =
-                file_descriptor.write(communicate, bytes)?.map(|c| i64::try_from(c).unwrap());
-            this.try_unwrap_io_result(result)
+        if let Some(result) = this.try_write(fd, buf, count)? {
+            this.write_scalar(Scalar::from_machine_isize(result, this), dest)?;
         } else {
-            this.handle_not_found()
+            // The file description is full, wait until some of its data is consumed.
+            struct Callback<'tcx> {
+                thread: ThreadId,
+                fd: i32,
+                buf: Pointer<Option<Provenance>>,
+                count: u64,
+                dest: PlaceTy<'tcx, Provenance>,
+            }
+
+            impl<'tcx> VisitTags for Callback<'tcx> {
+                fn visit_tags(&self, visit: &mut dyn FnMut(BorTag)) {
+                    let Callback { thread: _, fd: _, buf, count: _, dest } = self;
+                    buf.visit_tags(visit);
+                    dest.visit_tags(visit);
+                }
+            }
+
+            impl<'mir, 'tcx: 'mir> MachineCallback<'mir, 'tcx> for Callback<'tcx> {
+                fn call(&self, this: &mut MiriInterpCx<'mir, 'tcx>) -> InterpResult<'tcx> {
+                    if let Some(result) = this.try_write(self.fd, self.buf, self.count)? {
+                        this.unblock_thread(self.thread);
+                        this.write_scalar(Scalar::from_machine_isize(result, this), &self.dest)?;
+                    }
+                    Ok(())
+                }
+            }
+
+            let thread = this.get_active_thread();
+            this.block_thread_on_io(
+                thread,
+                Box::new(Callback { thread, fd, buf, count, dest: dest.clone() }),
+            );
         }
+        Ok(())
     }
 
     fn lseek64(
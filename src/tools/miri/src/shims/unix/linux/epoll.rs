@@ -0,0 +1,438 @@
+//! Emulation of `epoll` instances, which report the readiness of the file descriptions that are
+//! registered with them.
+//!
+//! Only file descriptions that are emulated by Miri itself (such as eventfds and sockets created
+//! by `socketpair`) can be registered. Registrations are keyed by file descriptor and are dropped
+//! once that file descriptor is closed, even if the file description is still open through
+//! another, duplicated file descriptor.
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::io;
+use std::rc::Rc;
+use std::time::Duration;
+
+use crate::concurrency::thread::{MachineCallback, Time};
+use crate::*;
+use shims::unix::fs::FileDescriptor;
+
+/// An `epoll` instance. Duplicated file descriptors share the same interest list.
+#[derive(Debug)]
+pub struct Epoll {
+    interest_list: Rc<RefCell<BTreeMap<i32, EpollInterest>>>,
+}
+
+/// The registration of a file descriptor with an `epoll` instance.
+#[derive(Debug)]
+struct EpollInterest {
+    /// The events the program is interested in, including flags like `EPOLLET`.
+    events: u32,
+    /// The user data that is reported together with the events.
+    data: u64,
+    /// The readiness counter of the file description when events were last reported, which
+    /// edge-triggered registrations only report again once it changed.
+    reported_generation: Option<u64>,
+    /// Whether an event was reported for an `EPOLLONESHOT` registration, which disables it until
+    /// it is modified with `EPOLL_CTL_MOD`.
+    disabled: bool,
+}
+
+impl FileDescriptor for Epoll {
+    fn name(&self) -> &'static str {
+        "epoll"
+    }
+
+    fn close<'tcx>(
+        self: Box<Self>,
+        _communicate_allowed: bool,
+    ) -> InterpResult<'tcx, io::Result<i32>> {
+        Ok(Ok(0))
+    }
+
+    fn dup(&mut self) -> io::Result<Box<dyn FileDescriptor>> {
+        Ok(Box::new(Epoll { interest_list: Rc::clone(&self.interest_list) }))
+    }
+
+    fn is_tty(&self) -> bool {
+        false
+    }
+
+    fn as_epoll(&self) -> Option<&Epoll> {
+        Some(self)
+    }
+}
+
+/// The values of the `EPOLL*` event flags on the target.
+struct EpollFlags {
+    epollin: u32,
+    epollout: u32,
+    epollrdhup: u32,
+    epollpri: u32,
+    epollerr: u32,
+    epollhup: u32,
+    epollet: u32,
+    epolloneshot: u32,
+}
+
+impl<'mir, 'tcx: 'mir> EvalContextExtPrivate<'mir, 'tcx> for crate::MiriInterpCx<'mir, 'tcx> {}
+trait EvalContextExtPrivate<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
+    fn epoll_flags(&self) -> InterpResult<'tcx, EpollFlags> {
+        let this = self.eval_context_ref();
+        // The flags are `c_int`s in `libc`, but `EPOLLET` has the sign bit set.
+        let flag = |name| this.eval_libc(name)?.to_u32();
+        Ok(EpollFlags {
+            epollin: flag("EPOLLIN")?,
+            epollout: flag("EPOLLOUT")?,
+            epollrdhup: flag("EPOLLRDHUP")?,
+            epollpri: flag("EPOLLPRI")?,
+            epollerr: flag("EPOLLERR")?,
+            epollhup: flag("EPOLLHUP")?,
+            epollet: flag("EPOLLET")?,
+            epolloneshot: flag("EPOLLONESHOT")?,
+        })
+    }
+
+    /// Writes the events of at most `maxevents` ready file descriptors registered with `epfd`
+    /// to the `epoll_event` array at `events`, and returns their number.
+    fn epoll_collect_ready(
+        &mut self,
+        epfd: i32,
+        events: Pointer<Option<Provenance>>,
+        maxevents: u32,
+    ) -> InterpResult<'tcx, u32> {
+        let this = self.eval_context_mut();
+        let flags = this.epoll_flags()?;
+
+        let handles = &this.machine.file_handler.handles;
+        // The `epoll` instance might have been closed while we were waiting on it, in which case
+        // no events will ever be reported.
+        let Some(epoll) = handles.get(&epfd).and_then(|fd| fd.as_epoll()) else { return Ok(0) };
+        let mut interest_list = epoll.interest_list.borrow_mut();
+
+        // Registrations of closed file descriptors are dropped.
+        interest_list.retain(|fd, _| handles.contains_key(fd));
+
+        let mut ready = Vec::new();
+        for (fd, interest) in interest_list.iter_mut() {
+            if ready.len() == usize::try_from(maxevents).unwrap() {
+                break;
+            }
+            if interest.disabled {
+                continue;
+            }
+            let file_descriptor = &handles[fd];
+            let (readiness, generation) = file_descriptor.readiness().unwrap();
+            let mut ready_events = 0;
+            if readiness.readable {
+                ready_events |= flags.epollin;
+            }
+            if readiness.writable {
+                ready_events |= flags.epollout;
+            }
+            if readiness.read_closed {
+                ready_events |= flags.epollrdhup;
+            }
+            if readiness.hangup {
+                ready_events |= flags.epollhup;
+            }
+            // Hangups and errors are always reported, even if they were not asked for.
+            let ready_events = ready_events & (interest.events | flags.epollhup | flags.epollerr);
+            if ready_events == 0 {
+                continue;
+            }
+            if interest.events & flags.epollet != 0 {
+                if interest.reported_generation == Some(generation) {
+                    continue;
+                }
+                interest.reported_generation = Some(generation);
+            }
+            if interest.events & flags.epolloneshot != 0 {
+                interest.disabled = true;
+            }
+            // The thread that observes the readiness synchronizes with the threads that caused it.
+            if let Some(data_race) = &this.machine.data_race {
+                file_descriptor
+                    .data_race_acquire(data_race, this.machine.threads.get_active_thread_id());
+            }
+            ready.push((ready_events, interest.data));
+        }
+        drop(interest_list);
+
+        let event_layout = this.libc_ty_layout("epoll_event")?;
+        for (i, &(ready_events, data)) in ready.iter().enumerate() {
+            let offset = event_layout.size * u64::try_from(i).unwrap();
+            let event = MPlaceTy::from_aligned_ptr(events.offset(offset, this)?, event_layout);
+            this.write_int_fields_named(
+                &[("events", ready_events.into()), ("u64", data.into())],
+                &event,
+            )?;
+        }
+        Ok(u32::try_from(ready.len()).unwrap())
+    }
+}
+
+impl<'mir, 'tcx: 'mir> EvalContextExt<'mir, 'tcx> for crate::MiriInterpCx<'mir, 'tcx> {}
+pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
+    /// Implements `int epoll_create(int size)`.
+    fn epoll_create(
+        &mut self,
+        size: &OpTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx, Scalar<Provenance>> {
+        let this = self.eval_context_mut();
+
+        // The size is only a hint that is ignored since Linux 2.6.8, but it must be positive.
+        let size = this.read_scalar(size)?.to_i32()?;
+        if size <= 0 {
+            let einval = this.eval_libc("EINVAL")?;
+            this.set_last_error(einval)?;
+            return Ok(Scalar::from_i32(-1));
+        }
+
+        let fd = this
+            .machine
+            .file_handler
+            .insert_fd(Box::new(Epoll { interest_list: Default::default() }));
+        Ok(Scalar::from_i32(fd))
+    }
+
+    /// Implements `int epoll_create1(int flags)`.
+    fn epoll_create1(
+        &mut self,
+        flags: &OpTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx, Scalar<Provenance>> {
+        let this = self.eval_context_mut();
+
+        let flags = this.read_scalar(flags)?.to_i32()?;
+        // `EPOLL_CLOEXEC` has no effect since Miri does not support `exec`.
+        if flags & !this.eval_libc_i32("EPOLL_CLOEXEC")? != 0 {
+            let einval = this.eval_libc("EINVAL")?;
+            this.set_last_error(einval)?;
+            return Ok(Scalar::from_i32(-1));
+        }
+
+        let fd = this
+            .machine
+            .file_handler
+            .insert_fd(Box::new(Epoll { interest_list: Default::default() }));
+        Ok(Scalar::from_i32(fd))
+    }
+
+    /// Implements `int epoll_ctl(int epfd, int op, int fd, struct epoll_event *event)`.
+    fn epoll_ctl(
+        &mut self,
+        epfd: &OpTy<'tcx, Provenance>,
+        op: &OpTy<'tcx, Provenance>,
+        fd: &OpTy<'tcx, Provenance>,
+        event: &OpTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx, Scalar<Provenance>> {
+        let this = self.eval_context_mut();
+
+        let epfd = this.read_scalar(epfd)?.to_i32()?;
+        let op = this.read_scalar(op)?.to_i32()?;
+        let fd = this.read_scalar(fd)?.to_i32()?;
+
+        let epoll_ctl_add = this.eval_libc_i32("EPOLL_CTL_ADD")?;
+        let epoll_ctl_mod = this.eval_libc_i32("EPOLL_CTL_MOD")?;
+        let epoll_ctl_del = this.eval_libc_i32("EPOLL_CTL_DEL")?;
+
+        // `EPOLL_CTL_DEL` ignores the event, which may then be null.
+        let new_interest = if op == epoll_ctl_add || op == epoll_ctl_mod {
+            let event = this.deref_operand(event)?;
+            let events = this.read_scalar(&this.mplace_field_named(&event, "events")?.into())?;
+            let events = events.to_u32()?;
+            let data = this.read_scalar(&this.mplace_field_named(&event, "u64")?.into())?;
+            let data = data.to_u64()?;
+
+            let flags = this.epoll_flags()?;
+            let supported = flags.epollin
+                | flags.epollout
+                | flags.epollrdhup
+                | flags.epollpri
+                | flags.epollerr
+                | flags.epollhup
+                | flags.epollet
+                | flags.epolloneshot;
+            if events & !supported != 0 {
+                throw_unsup_format!(
+                    "`epoll_ctl` with unsupported events {:#x}",
+                    events & !supported
+                );
+            }
+            Some(EpollInterest { events, data, reported_generation: None, disabled: false })
+        } else if op == epoll_ctl_del {
+            None
+        } else {
+            let einval = this.eval_libc("EINVAL")?;
+            this.set_last_error(einval)?;
+            return Ok(Scalar::from_i32(-1));
+        };
+
+        let handles = &this.machine.file_handler.handles;
+        let (Some(epoll), Some(file_descriptor)) = (handles.get(&epfd), handles.get(&fd)) else {
+            let ebadf = this.eval_libc("EBADF")?;
+            this.set_last_error(ebadf)?;
+            return Ok(Scalar::from_i32(-1));
+        };
+        let Some(epoll) = epoll.as_epoll() else {
+            let einval = this.eval_libc("EINVAL")?;
+            this.set_last_error(einval)?;
+            return Ok(Scalar::from_i32(-1));
+        };
+        if epfd == fd {
+            let einval = this.eval_libc("EINVAL")?;
+            this.set_last_error(einval)?;
+            return Ok(Scalar::from_i32(-1));
+        }
+        if file_descriptor.readiness().is_none() {
+            // Like for regular files on Linux, readiness is not tracked for this file description.
+            let eperm = this.eval_libc("EPERM")?;
+            this.set_last_error(eperm)?;
+            return Ok(Scalar::from_i32(-1));
+        }
+
+        let interest_list = Rc::clone(&epoll.interest_list);
+        let mut interest_list = interest_list.borrow_mut();
+        let error = match new_interest {
+            Some(new_interest) if op == epoll_ctl_add =>
+                interest_list.try_insert(fd, new_interest).err().map(|_| "EEXIST"),
+            Some(new_interest) =>
+                match interest_list.get_mut(&fd) {
+                    Some(interest) => {
+                        *interest = new_interest;
+                        None
+                    }
+                    None => Some("ENOENT"),
+                },
+            None => interest_list.remove(&fd).is_none().then_some("ENOENT"),
+        };
+        drop(interest_list);
+        if let Some(error) = error {
+            let error = this.eval_libc(error)?;
+            this.set_last_error(error)?;
+            return Ok(Scalar::from_i32(-1));
+        }
+
+        // The registered file description might already be ready.
+        this.notify_io_event();
+        Ok(Scalar::from_i32(0))
+    }
+
+    /// Implements
+    /// `int epoll_wait(int epfd, struct epoll_event *events, int maxevents, int timeout)`.
+    fn epoll_wait(
+        &mut self,
+        epfd: &OpTy<'tcx, Provenance>,
+        events: &OpTy<'tcx, Provenance>,
+        maxevents: &OpTy<'tcx, Provenance>,
+        timeout: &OpTy<'tcx, Provenance>,
+        dest: &PlaceTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+
+        let epfd = this.read_scalar(epfd)?.to_i32()?;
+        let events = this.read_pointer(events)?;
+        let maxevents = this.read_scalar(maxevents)?.to_i32()?;
+        let timeout = this.read_scalar(timeout)?.to_i32()?;
+
+        let Ok(maxevents) = u32::try_from(maxevents) else {
+            let einval = this.eval_libc("EINVAL")?;
+            this.set_last_error(einval)?;
+            this.write_scalar(Scalar::from_i32(-1), dest)?;
+            return Ok(());
+        };
+        let Some(file_descriptor) = this.machine.file_handler.handles.get(&epfd) else {
+            let ebadf = this.eval_libc("EBADF")?;
+            this.set_last_error(ebadf)?;
+            this.write_scalar(Scalar::from_i32(-1), dest)?;
+            return Ok(());
+        };
+        if maxevents == 0 || file_descriptor.as_epoll().is_none() {
+            let einval = this.eval_libc("EINVAL")?;
+            this.set_last_error(einval)?;
+            this.write_scalar(Scalar::from_i32(-1), dest)?;
+            return Ok(());
+        }
+
+        // Check that the *entire* buffer is actually valid memory.
+        let event_layout = this.libc_ty_layout("epoll_event")?;
+        this.check_ptr_access_align(
+            events,
+            event_layout.size * u64::from(maxevents),
+            event_layout.align.abi,
+            CheckInAllocMsg::MemoryAccessTest,
+        )?;
+
+        let ready = this.epoll_collect_ready(epfd, events, maxevents)?;
+        if ready > 0 || timeout == 0 {
+            this.write_scalar(Scalar::from_u32(ready), dest)?;
+            return Ok(());
+        }
+
+        // Nothing is ready yet, so we block until some registered file description becomes
+        // ready or the timeout expires.
+        struct Callback<'tcx> {
+            thread: ThreadId,
+            epfd: i32,
+            events: Pointer<Option<Provenance>>,
+            maxevents: u32,
+            dest: PlaceTy<'tcx, Provenance>,
+        }
+
+        impl<'tcx> VisitTags for Callback<'tcx> {
+            fn visit_tags(&self, visit: &mut dyn FnMut(BorTag)) {
+                let Callback { thread: _, epfd: _, events, maxevents: _, dest } = self;
+                events.visit_tags(visit);
+                dest.visit_tags(visit);
+            }
+        }
+
+        impl<'mir, 'tcx: 'mir> MachineCallback<'mir, 'tcx> for Callback<'tcx> {
+            fn call(&self, this: &mut MiriInterpCx<'mir, 'tcx>) -> InterpResult<'tcx> {
+                let ready = this.epoll_collect_ready(self.epfd, self.events, self.maxevents)?;
+                if ready > 0 {
+                    this.unblock_thread(self.thread);
+                    this.unregister_timeout_callback_if_exists(self.thread);
+                    this.write_scalar(Scalar::from_u32(ready), &self.dest)?;
+                }
+                Ok(())
+            }
+        }
+
+        struct TimeoutCallback<'tcx> {
+            thread: ThreadId,
+            dest: PlaceTy<'tcx, Provenance>,
+        }
+
+        impl<'tcx> VisitTags for TimeoutCallback<'tcx> {
+            fn visit_tags(&self, visit: &mut dyn FnMut(BorTag)) {
+                let TimeoutCallback { thread: _, dest } = self;
+                dest.visit_tags(visit);
+            }
+        }
+
+        impl<'mir, 'tcx: 'mir> MachineCallback<'mir, 'tcx> for TimeoutCallback<'tcx> {
+            fn call(&self, this: &mut MiriInterpCx<'mir, 'tcx>) -> InterpResult<'tcx> {
+                this.unregister_io_callback_if_exists(self.thread);
+                this.unblock_thread(self.thread);
+                this.write_scalar(Scalar::from_i32(0), &self.dest)?;
+                Ok(())
+            }
+        }
+
+        let thread = this.get_active_thread();
+        this.block_thread_on_io(
+            thread,
+            Box::new(Callback { thread, epfd, events, maxevents, dest: dest.clone() }),
+        );
+        // A negative timeout means to wait forever.
+        if let Ok(timeout) = u64::try_from(timeout) {
+            let timeout_time = this.machine.clock.now().checked_add(Duration::from_millis(timeout));
+            this.register_timeout_callback(
+                thread,
+                Time::Monotonic(timeout_time.unwrap()),
+                Box::new(TimeoutCallback { thread, dest: dest.clone() }),
+            );
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,169 @@
+//! Emulation of `eventfd` file descriptions: a 64-bit counter that writes add to and reads take
+//! from, used e.g. by async runtimes to wake up threads waiting in `epoll_wait`.
+
+use std::cell::RefCell;
+use std::io;
+use std::rc::Rc;
+
+use rustc_target::abi::{Endian, HasDataLayout};
+
+use crate::concurrency::data_race;
+use crate::concurrency::vector_clock::VClock;
+use crate::*;
+use shims::unix::fs::{FileDescriptor, Readiness};
+
+/// The largest value the counter can hold.
+const MAX_COUNTER: u64 = u64::MAX - 1;
+
+/// An `eventfd` file description. Duplicated file descriptors share the same counter.
+#[derive(Debug)]
+struct Event {
+    state: Rc<RefCell<EventState>>,
+}
+
+#[derive(Debug)]
+struct EventState {
+    counter: u64,
+    /// Whether `EFD_SEMAPHORE` was passed, in which case reads decrement the counter by one
+    /// instead of resetting it.
+    semaphore: bool,
+    /// Whether `EFD_NONBLOCK` was passed.
+    nonblocking: bool,
+    /// The endianness of the target, which the counter is read and written in.
+    endian: Endian,
+    /// Incremented whenever the counter changes, for edge-triggered `epoll`.
+    generation: u64,
+    /// The clocks of all threads that wrote to the counter, which readers synchronize with.
+    clock: VClock,
+}
+
+impl FileDescriptor for Event {
+    fn name(&self) -> &'static str {
+        "event"
+    }
+
+    fn read<'tcx>(
+        &mut self,
+        _communicate_allowed: bool,
+        bytes: &mut [u8],
+    ) -> InterpResult<'tcx, io::Result<usize>> {
+        let mut state = self.state.borrow_mut();
+        let Some(bytes) = bytes.get_mut(..8) else {
+            return Ok(Err(io::ErrorKind::InvalidInput.into()));
+        };
+        if state.counter == 0 {
+            return Ok(Err(io::ErrorKind::WouldBlock.into()));
+        }
+        let value = if state.semaphore { 1 } else { state.counter };
+        state.counter -= value;
+        state.generation += 1;
+        bytes.copy_from_slice(&match state.endian {
+            Endian::Little => value.to_le_bytes(),
+            Endian::Big => value.to_be_bytes(),
+        });
+        Ok(Ok(8))
+    }
+
+    fn write<'tcx>(
+        &self,
+        _communicate_allowed: bool,
+        bytes: &[u8],
+    ) -> InterpResult<'tcx, io::Result<usize>> {
+        let mut state = self.state.borrow_mut();
+        let Some(bytes) = bytes.get(..8) else {
+            return Ok(Err(io::ErrorKind::InvalidInput.into()));
+        };
+        let bytes = bytes.try_into().unwrap();
+        let value = match state.endian {
+            Endian::Little => u64::from_le_bytes(bytes),
+            Endian::Big => u64::from_be_bytes(bytes),
+        };
+        if value == u64::MAX {
+            return Ok(Err(io::ErrorKind::InvalidInput.into()));
+        }
+        if MAX_COUNTER - state.counter < value {
+            return Ok(Err(io::ErrorKind::WouldBlock.into()));
+        }
+        state.counter += value;
+        state.generation += 1;
+        Ok(Ok(8))
+    }
+
+    fn close<'tcx>(
+        self: Box<Self>,
+        _communicate_allowed: bool,
+    ) -> InterpResult<'tcx, io::Result<i32>> {
+        Ok(Ok(0))
+    }
+
+    fn dup(&mut self) -> io::Result<Box<dyn FileDescriptor>> {
+        Ok(Box::new(Event { state: Rc::clone(&self.state) }))
+    }
+
+    fn is_tty(&self) -> bool {
+        false
+    }
+
+    fn is_blocking(&self) -> bool {
+        !self.state.borrow().nonblocking
+    }
+
+    fn readiness(&self) -> Option<(Readiness, u64)> {
+        let state = self.state.borrow();
+        let readiness = Readiness {
+            readable: state.counter > 0,
+            writable: state.counter < MAX_COUNTER,
+            read_closed: false,
+            hangup: false,
+        };
+        Some((readiness, state.generation))
+    }
+
+    fn data_race_acquire(&self, data_race: &data_race::GlobalState, thread: ThreadId) {
+        data_race.validate_lock_acquire(&self.state.borrow().clock, thread);
+    }
+
+    fn data_race_release(&self, data_race: &data_race::GlobalState, thread: ThreadId) {
+        data_race.validate_lock_release_shared(&mut self.state.borrow_mut().clock, thread);
+    }
+}
+
+impl<'mir, 'tcx: 'mir> EvalContextExt<'mir, 'tcx> for crate::MiriInterpCx<'mir, 'tcx> {}
+pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
+    /// Implements `int eventfd(unsigned int initval, int flags)`.
+    fn eventfd(
+        &mut self,
+        initval: &OpTy<'tcx, Provenance>,
+        flags: &OpTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx, Scalar<Provenance>> {
+        let this = self.eval_context_mut();
+
+        let initval = this.read_scalar(initval)?.to_u32()?;
+        let flags = this.read_scalar(flags)?.to_i32()?;
+
+        let efd_cloexec = this.eval_libc_i32("EFD_CLOEXEC")?;
+        let efd_nonblock = this.eval_libc_i32("EFD_NONBLOCK")?;
+        let efd_semaphore = this.eval_libc_i32("EFD_SEMAPHORE")?;
+
+        if flags & !(efd_cloexec | efd_nonblock | efd_semaphore) != 0 {
+            let einval = this.eval_libc("EINVAL")?;
+            this.set_last_error(einval)?;
+            return Ok(Scalar::from_i32(-1));
+        }
+        // `EFD_CLOEXEC` has no effect since Miri does not support `exec`.
+
+        let state = EventState {
+            counter: initval.into(),
+            semaphore: flags & efd_semaphore != 0,
+            nonblocking: flags & efd_nonblock != 0,
+            endian: this.data_layout().endian,
+            generation: 0,
+            clock: VClock::default(),
+        };
+        let fd = this
+            .machine
+            .file_handler
+            .insert_fd(Box::new(Event { state: Rc::new(RefCell::new(state)) }));
+        Ok(Scalar::from_i32(fd))
+    }
+}
@@ -1,3 +1,5 @@
 pub mod dlsym;
+pub mod epoll;
+pub mod eventfd;
 pub mod foreign_items;
 pub mod sync;
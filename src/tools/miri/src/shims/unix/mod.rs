@@ -2,6 +2,8 @@
 pub mod foreign_items;
 
 mod fs;
+mod net;
+mod socket;
 mod sync;
 mod thread;
 
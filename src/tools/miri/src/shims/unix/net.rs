@@ -0,0 +1,1437 @@
+//! Emulation of TCP and UDP sockets on a virtual loopback network.
+//!
+//! All sockets of the program are attached to one in-interpreter [`Network`], so sockets can only
+//! communicate with other sockets of the same program, and no host sockets are ever created. This
+//! means networking also works with isolation enabled. Only the loopback addresses (`127.0.0.0/8`
+//! and `::1`) and the unspecified addresses can be used; connecting or sending anywhere else fails
+//! with `ENETUNREACH`. IPv4 and IPv6 are separate networks: IPv6 sockets never see IPv4 traffic.
+//!
+//! TCP connections carry their data in the same buffers as the socket pairs of `socketpair`. A
+//! connection is established as soon as `connect` is called, and is queued at the listener until
+//! it is accepted. The `listen` backlog is not enforced. UDP datagrams are delivered immediately,
+//! and are dropped when there is no socket bound to their destination or its queue is full.
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::io;
+use std::iter;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::ops::RangeInclusive;
+use std::rc::Rc;
+
+use log::trace;
+
+use rustc_data_structures::fx::FxHashMap;
+use rustc_target::abi::{Align, Size};
+
+use crate::concurrency::data_race;
+use crate::concurrency::thread::MachineCallback;
+use crate::concurrency::vector_clock::VClock;
+use crate::*;
+use shims::unix::fs::{EvalContextExt as _, FileDescriptor, Readiness};
+use shims::unix::socket::{EvalContextExt as _, SocketEnd, MAX_SOCKET_BUFFER_SIZE};
+
+/// The ports that are picked from when binding to port 0. This is the default range on Linux.
+const EPHEMERAL_PORTS: RangeInclusive<u16> = 32768..=60999;
+
+/// The largest payload of a UDP datagram.
+const MAX_DATAGRAM_SIZE: usize = 65507;
+
+/// The name of the `libc` error constant that an operation fails with.
+type NetResult<T> = Result<T, &'static str>;
+
+/// The sockets bound to an address, by address.
+#[derive(Debug)]
+pub(crate) struct Network {
+    /// The local addresses of all bound TCP sockets, with the connection queues of those that are
+    /// listening.
+    stream_sockets: FxHashMap<SocketAddr, Option<Rc<RefCell<Listener>>>>,
+    /// The receive queues of all bound UDP sockets.
+    datagram_sockets: FxHashMap<SocketAddr, Rc<RefCell<DatagramQueue>>>,
+    /// The port to try next when binding to port 0.
+    next_ephemeral_port: u16,
+}
+
+impl Default for Network {
+    fn default() -> Self {
+        Network {
+            stream_sockets: FxHashMap::default(),
+            datagram_sockets: FxHashMap::default(),
+            next_ephemeral_port: *EPHEMERAL_PORTS.start(),
+        }
+    }
+}
+
+impl Network {
+    fn is_in_use(&self, addr: SocketAddr, datagram: bool) -> bool {
+        if datagram {
+            self.datagram_sockets.keys().any(|&bound| addrs_overlap(bound, addr))
+        } else {
+            self.stream_sockets.keys().any(|&bound| addrs_overlap(bound, addr))
+        }
+    }
+
+    /// Picks a port that is free on `ip`, going round-robin through the ephemeral ports.
+    fn ephemeral_port(&mut self, ip: IpAddr, datagram: bool) -> Option<u16> {
+        for _ in EPHEMERAL_PORTS {
+            let port = self.next_ephemeral_port;
+            self.next_ephemeral_port =
+                if port == *EPHEMERAL_PORTS.end() { *EPHEMERAL_PORTS.start() } else { port + 1 };
+            if !self.is_in_use(SocketAddr::new(ip, port), datagram) {
+                return Some(port);
+            }
+        }
+        None
+    }
+
+    /// The connection queue of the socket listening on `addr`.
+    fn listener(&self, addr: SocketAddr) -> Option<Rc<RefCell<Listener>>> {
+        let (_, listener) =
+            self.stream_sockets.iter().find(|(&bound, _)| addrs_overlap(bound, addr))?;
+        listener.clone()
+    }
+
+    /// The receive queue of the UDP socket bound to `addr`.
+    fn datagram_queue(&self, addr: SocketAddr) -> Option<&Rc<RefCell<DatagramQueue>>> {
+        self.datagram_sockets
+            .iter()
+            .find_map(|(&bound, queue)| addrs_overlap(bound, addr).then_some(queue))
+    }
+
+    /// Delivers a datagram to the UDP socket bound to `to`, if there is one that accepts it.
+    fn deliver(
+        &self,
+        from: SocketAddr,
+        to: SocketAddr,
+        bytes: &[u8],
+        data_race: Option<(&data_race::GlobalState, ThreadId)>,
+    ) {
+        let Some(queue) = self.datagram_queue(to) else { return };
+        let mut queue = queue.borrow_mut();
+        if matches!(queue.peer, Some(peer) if peer != from) {
+            // Connected sockets only receive datagrams from their peer.
+            return;
+        }
+        if queue.bytes + bytes.len() > MAX_SOCKET_BUFFER_SIZE {
+            return;
+        }
+        if let Some((data_race, thread)) = data_race {
+            data_race.validate_lock_release_shared(&mut queue.clock, thread);
+        }
+        queue.datagrams.push_back((from, bytes.to_vec()));
+        queue.bytes += bytes.len();
+        queue.generation += 1;
+    }
+}
+
+/// Whether sockets bound to `a` and `b` would get the same traffic: the ports are equal, and so
+/// are the addresses, unless one of them is the unspecified address.
+fn addrs_overlap(a: SocketAddr, b: SocketAddr) -> bool {
+    a.port() == b.port()
+        && a.is_ipv6() == b.is_ipv6()
+        && (a.ip() == b.ip() || a.ip().is_unspecified() || b.ip().is_unspecified())
+}
+
+/// Resolves the destination of a connection or datagram in the loopback network.
+fn route(addr: SocketAddr) -> NetResult<SocketAddr> {
+    let ip = match addr.ip() {
+        // Like on Linux, the unspecified address refers to the local host.
+        IpAddr::V4(ip) if ip.is_unspecified() => IpAddr::V4(Ipv4Addr::LOCALHOST),
+        IpAddr::V6(ip) if ip.is_unspecified() => IpAddr::V6(Ipv6Addr::LOCALHOST),
+        ip if ip.is_loopback() => ip,
+        _ => return Err("ENETUNREACH"),
+    };
+    Ok(SocketAddr::new(ip, addr.port()))
+}
+
+/// The address that traffic from a socket bound to `local` to `dest` is sent from.
+fn source_addr(local: SocketAddr, dest: SocketAddr) -> SocketAddr {
+    if local.ip().is_unspecified() { SocketAddr::new(dest.ip(), local.port()) } else { local }
+}
+
+fn unspecified_addr(ipv6: bool) -> SocketAddr {
+    let ip =
+        if ipv6 { IpAddr::V6(Ipv6Addr::UNSPECIFIED) } else { IpAddr::V4(Ipv4Addr::UNSPECIFIED) };
+    SocketAddr::new(ip, 0)
+}
+
+fn io_error_to_errno(err: io::Error) -> &'static str {
+    match err.kind() {
+        io::ErrorKind::WouldBlock => "EAGAIN",
+        io::ErrorKind::BrokenPipe => "EPIPE",
+        kind => bug!("unexpected socket error {kind:?}"),
+    }
+}
+
+fn errno_to_io_error(errno: &'static str) -> io::Error {
+    match errno {
+        "EAGAIN" => io::ErrorKind::WouldBlock,
+        "EPIPE" => io::ErrorKind::BrokenPipe,
+        // `EDESTADDRREQ` has no `io::ErrorKind`.
+        "ENOTCONN" | "EDESTADDRREQ" => io::ErrorKind::NotConnected,
+        _ => io::ErrorKind::InvalidInput,
+    }
+    .into()
+}
+
+/// The connections to a listening TCP socket.
+#[derive(Debug, Default)]
+struct Listener {
+    /// The connections that were established but not yet accepted.
+    pending: VecDeque<PendingConnection>,
+    /// Incremented whenever `pending` changes, for edge-triggered `epoll`.
+    generation: u64,
+    /// The clocks of all threads that connected, which `accept` synchronizes with.
+    clock: VClock,
+}
+
+#[derive(Debug)]
+struct PendingConnection {
+    /// The end of the connection that `accept` returns.
+    end: SocketEnd,
+    /// The address the connection was made to.
+    local: SocketAddr,
+    /// The address of the connecting socket.
+    peer: SocketAddr,
+}
+
+/// The datagrams received by a UDP socket.
+#[derive(Debug, Default)]
+struct DatagramQueue {
+    /// The sender and payload of every datagram.
+    datagrams: VecDeque<(SocketAddr, Vec<u8>)>,
+    /// The total size of the payloads in `datagrams`.
+    bytes: usize,
+    /// The address the socket is connected to. Only datagrams from there are received.
+    peer: Option<SocketAddr>,
+    /// Incremented whenever `datagrams` changes, for edge-triggered `epoll`.
+    generation: u64,
+    /// The clocks of all threads that sent datagrams, which receivers synchronize with.
+    clock: VClock,
+}
+
+impl DatagramQueue {
+    /// Receives the next datagram. The part of it that does not fit into `bytes` is lost. With
+    /// `peek`, the datagram stays in the queue.
+    fn recv(&mut self, bytes: &mut [u8], peek: bool) -> Option<(usize, SocketAddr)> {
+        let (from, data) = self.datagrams.front()?;
+        let from = *from;
+        let count = bytes.len().min(data.len());
+        bytes[..count].copy_from_slice(&data[..count]);
+        if !peek {
+            let (_, data) = self.datagrams.pop_front().unwrap();
+            self.bytes -= data.len();
+            self.generation += 1;
+        }
+        Some((count, from))
+    }
+}
+
+/// The entry of a bound socket in the network, which is removed when the socket is closed.
+#[derive(Debug)]
+struct Binding {
+    network: Rc<RefCell<Network>>,
+    addr: SocketAddr,
+    datagram: bool,
+}
+
+impl Drop for Binding {
+    fn drop(&mut self) {
+        let mut network = self.network.borrow_mut();
+        if self.datagram {
+            network.datagram_sockets.remove(&self.addr);
+        } else {
+            network.stream_sockets.remove(&self.addr);
+        }
+    }
+}
+
+#[derive(Debug)]
+enum SocketKind {
+    /// A TCP socket that is neither listening nor connected.
+    Stream,
+    /// A listening TCP socket.
+    Listener(Rc<RefCell<Listener>>),
+    /// A connected TCP socket.
+    Connected { end: SocketEnd, peer: SocketAddr },
+    /// A UDP socket.
+    Datagram(Rc<RefCell<DatagramQueue>>),
+}
+
+/// The state of a socket, shared by all file descriptors duplicated from it.
+#[derive(Debug)]
+struct InetSocketState {
+    /// Whether this is an `AF_INET6` socket.
+    ipv6: bool,
+    /// Whether `SOCK_NONBLOCK` or `FIONBIO` was used.
+    nonblocking: bool,
+    /// The values set with `setsockopt`, by level and option name.
+    options: FxHashMap<(i32, i32), i32>,
+    /// The local address. Sockets returned by `accept` share the port of the listener.
+    local: Option<SocketAddr>,
+    /// The entry of `local` in the network, unless the socket was returned by `accept`.
+    binding: Option<Binding>,
+    kind: SocketKind,
+}
+
+/// A TCP or UDP socket.
+#[derive(Debug, Clone)]
+pub struct InetSocket {
+    state: Rc<RefCell<InetSocketState>>,
+    network: Rc<RefCell<Network>>,
+}
+
+impl InetSocket {
+    fn new(network: &Rc<RefCell<Network>>, ipv6: bool, kind: SocketKind) -> Self {
+        let state = InetSocketState {
+            ipv6,
+            nonblocking: false,
+            options: FxHashMap::default(),
+            local: None,
+            binding: None,
+            kind,
+        };
+        InetSocket { state: Rc::new(RefCell::new(state)), network: Rc::clone(network) }
+    }
+
+    fn is_datagram(&self) -> bool {
+        matches!(self.state.borrow().kind, SocketKind::Datagram(_))
+    }
+
+    /// Binds the socket to `addr`, picking a free port if the port of `addr` is 0.
+    fn bind(&self, addr: SocketAddr) -> NetResult<()> {
+        let datagram = self.is_datagram();
+        let mut state = self.state.borrow_mut();
+        if addr.is_ipv6() != state.ipv6 || state.local.is_some() {
+            return Err("EINVAL");
+        }
+        if !addr.ip().is_loopback() && !addr.ip().is_unspecified() {
+            return Err("EADDRNOTAVAIL");
+        }
+        let mut network = self.network.borrow_mut();
+        let addr = if addr.port() == 0 {
+            let port = network.ephemeral_port(addr.ip(), datagram).ok_or("EADDRINUSE")?;
+            SocketAddr::new(addr.ip(), port)
+        } else if network.is_in_use(addr, datagram) {
+            return Err("EADDRINUSE");
+        } else {
+            addr
+        };
+        match &state.kind {
+            SocketKind::Datagram(queue) => {
+                network.datagram_sockets.insert(addr, Rc::clone(queue));
+            }
+            _ => {
+                network.stream_sockets.insert(addr, None);
+            }
+        }
+        state.local = Some(addr);
+        state.binding = Some(Binding { network: Rc::clone(&self.network), addr, datagram });
+        Ok(())
+    }
+
+    fn listen(&self) -> NetResult<()> {
+        match self.state.borrow().kind {
+            SocketKind::Stream => {}
+            SocketKind::Listener(_) => return Ok(()),
+            SocketKind::Connected { .. } => return Err("EINVAL"),
+            SocketKind::Datagram(_) => return Err("EOPNOTSUPP"),
+        }
+        let ipv6 = self.state.borrow().ipv6;
+        if self.state.borrow().local.is_none() {
+            self.bind(unspecified_addr(ipv6))?;
+        }
+        let mut state = self.state.borrow_mut();
+        let listener = Rc::new(RefCell::new(Listener::default()));
+        self.network
+            .borrow_mut()
+            .stream_sockets
+            .insert(state.local.unwrap(), Some(Rc::clone(&listener)));
+        state.kind = SocketKind::Listener(listener);
+        Ok(())
+    }
+
+    /// Connects a TCP socket to the listener at `addr`, or sets the peer of a UDP socket.
+    fn connect(
+        &self,
+        addr: SocketAddr,
+        data_race: Option<(&data_race::GlobalState, ThreadId)>,
+    ) -> NetResult<()> {
+        match self.state.borrow().kind {
+            SocketKind::Stream | SocketKind::Datagram(_) => {}
+            SocketKind::Listener(_) => return Err("EINVAL"),
+            SocketKind::Connected { .. } => return Err("EISCONN"),
+        }
+        if addr.is_ipv6() != self.state.borrow().ipv6 {
+            return Err("EINVAL");
+        }
+        let dest = route(addr)?;
+        if self.state.borrow().local.is_none() {
+            self.bind(SocketAddr::new(dest.ip(), 0))?;
+        }
+
+        let mut state = self.state.borrow_mut();
+        let local = source_addr(state.local.unwrap(), dest);
+        if let SocketKind::Datagram(queue) = &state.kind {
+            queue.borrow_mut().peer = Some(dest);
+            return Ok(());
+        }
+        let listener = self.network.borrow().listener(dest).ok_or("ECONNREFUSED")?;
+        let mut listener = listener.borrow_mut();
+        if let Some((data_race, thread)) = data_race {
+            data_race.validate_lock_release_shared(&mut listener.clock, thread);
+        }
+        let (end, server_end) = SocketEnd::pair();
+        listener.pending.push_back(PendingConnection { end: server_end, local: dest, peer: local });
+        listener.generation += 1;
+        state.kind = SocketKind::Connected { end, peer: dest };
+        Ok(())
+    }
+
+    /// Takes the next connection to a listening socket.
+    fn accept(
+        &self,
+        data_race: Option<(&data_race::GlobalState, ThreadId)>,
+    ) -> NetResult<(InetSocket, SocketAddr)> {
+        let state = self.state.borrow();
+        let SocketKind::Listener(listener) = &state.kind else { return Err("EINVAL") };
+        let mut listener = listener.borrow_mut();
+        let PendingConnection { end, local, peer } =
+            listener.pending.pop_front().ok_or("EAGAIN")?;
+        listener.generation += 1;
+        if let Some((data_race, thread)) = data_race {
+            data_race.validate_lock_acquire(&listener.clock, thread);
+        }
+        let socket =
+            InetSocket::new(&self.network, state.ipv6, SocketKind::Connected { end, peer });
+        socket.state.borrow_mut().local = Some(local);
+        Ok((socket, peer))
+    }
+
+    /// Sends `bytes` to the peer of a connected socket. UDP sockets can also send to `to`.
+    fn send(
+        &self,
+        bytes: &[u8],
+        to: Option<SocketAddr>,
+        data_race: Option<(&data_race::GlobalState, ThreadId)>,
+    ) -> NetResult<usize> {
+        let state = self.state.borrow();
+        let queue = match &state.kind {
+            SocketKind::Connected { end, .. } => {
+                let count = end.write(bytes).map_err(io_error_to_errno)?;
+                // Only data that was actually written can synchronize with its reader.
+                if let Some((data_race, thread)) = data_race {
+                    end.data_race_release(data_race, thread);
+                }
+                return Ok(count);
+            }
+            SocketKind::Datagram(queue) => Rc::clone(queue),
+            SocketKind::Stream | SocketKind::Listener(_) => return Err("ENOTCONN"),
+        };
+        let ipv6 = state.ipv6;
+        drop(state);
+
+        if bytes.len() > MAX_DATAGRAM_SIZE {
+            return Err("EMSGSIZE");
+        }
+        let to = match to {
+            Some(to) if to.is_ipv6() != ipv6 => return Err("EINVAL"),
+            Some(to) => route(to)?,
+            None => queue.borrow().peer.ok_or("EDESTADDRREQ")?,
+        };
+        if self.state.borrow().local.is_none() {
+            self.bind(unspecified_addr(ipv6))?;
+        }
+        let from = source_addr(self.state.borrow().local.unwrap(), to);
+        self.network.borrow().deliver(from, to, bytes, data_race);
+        Ok(bytes.len())
+    }
+
+    /// Receives data from the peer of a TCP socket, or the next datagram of a UDP socket. With
+    /// `peek`, the data stays in the socket.
+    fn recv(
+        &self,
+        bytes: &mut [u8],
+        peek: bool,
+        data_race: Option<(&data_race::GlobalState, ThreadId)>,
+    ) -> NetResult<(usize, SocketAddr)> {
+        let state = self.state.borrow();
+        match &state.kind {
+            SocketKind::Connected { end, peer } => {
+                let count = end.read(bytes, peek).map_err(io_error_to_errno)?;
+                if let Some((data_race, thread)) = data_race {
+                    end.data_race_acquire(data_race, thread);
+                }
+                Ok((count, *peer))
+            }
+            SocketKind::Datagram(queue) => {
+                let mut queue = queue.borrow_mut();
+                let (count, from) = queue.recv(bytes, peek).ok_or("EAGAIN")?;
+                if let Some((data_race, thread)) = data_race {
+                    data_race.validate_lock_acquire(&queue.clock, thread);
+                }
+                Ok((count, from))
+            }
+            SocketKind::Stream | SocketKind::Listener(_) => Err("ENOTCONN"),
+        }
+    }
+
+    fn shutdown(&self, read: bool, write: bool) -> NetResult<()> {
+        match &self.state.borrow().kind {
+            SocketKind::Connected { end, .. } => {
+                end.shutdown(read, write);
+                Ok(())
+            }
+            _ => Err("ENOTCONN"),
+        }
+    }
+
+    fn local_addr(&self) -> SocketAddr {
+        let state = self.state.borrow();
+        state.local.unwrap_or_else(|| unspecified_addr(state.ipv6))
+    }
+
+    fn peer_addr(&self) -> NetResult<SocketAddr> {
+        match &self.state.borrow().kind {
+            SocketKind::Connected { peer, .. } => Ok(*peer),
+            SocketKind::Datagram(queue) => queue.borrow().peer.ok_or("ENOTCONN"),
+            SocketKind::Stream | SocketKind::Listener(_) => Err("ENOTCONN"),
+        }
+    }
+}
+
+impl FileDescriptor for InetSocket {
+    fn name(&self) -> &'static str {
+        "socket"
+    }
+
+    fn read<'tcx>(
+        &mut self,
+        _communicate_allowed: bool,
+        bytes: &mut [u8],
+    ) -> InterpResult<'tcx, io::Result<usize>> {
+        Ok(self
+            .recv(bytes, /* peek */ false, None)
+            .map(|(count, _)| count)
+            .map_err(errno_to_io_error))
+    }
+
+    fn write<'tcx>(
+        &self,
+        _communicate_allowed: bool,
+        bytes: &[u8],
+    ) -> InterpResult<'tcx, io::Result<usize>> {
+        Ok(self.send(bytes, None, None).map_err(errno_to_io_error))
+    }
+
+    fn close<'tcx>(
+        self: Box<Self>,
+        _communicate_allowed: bool,
+    ) -> InterpResult<'tcx, io::Result<i32>> {
+        Ok(Ok(0))
+    }
+
+    fn dup(&mut self) -> io::Result<Box<dyn FileDescriptor>> {
+        Ok(Box::new(self.clone()))
+    }
+
+    fn is_tty(&self) -> bool {
+        false
+    }
+
+    fn is_blocking(&self) -> bool {
+        !self.state.borrow().nonblocking
+    }
+
+    fn readiness(&self) -> Option<(Readiness, u64)> {
+        Some(match &self.state.borrow().kind {
+            SocketKind::Connected { end, .. } => end.readiness(),
+            SocketKind::Listener(listener) => {
+                let listener = listener.borrow();
+                let readiness =
+                    Readiness { readable: !listener.pending.is_empty(), ..Readiness::default() };
+                (readiness, listener.generation)
+            }
+            SocketKind::Datagram(queue) => {
+                let queue = queue.borrow();
+                let readiness = Readiness {
+                    readable: !queue.datagrams.is_empty(),
+                    writable: true,
+                    ..Readiness::default()
+                };
+                (readiness, queue.generation)
+            }
+            // Like on Linux, a TCP socket that was never connected is reported as hung up.
+            SocketKind::Stream =>
+                (Readiness { writable: true, hangup: true, ..Readiness::default() }, 0),
+        })
+    }
+
+    fn as_inet_socket(&self) -> Option<&InetSocket> {
+        Some(self)
+    }
+
+    fn data_race_acquire(&self, data_race: &data_race::GlobalState, thread: ThreadId) {
+        match &self.state.borrow().kind {
+            SocketKind::Connected { end, .. } => end.data_race_acquire(data_race, thread),
+            SocketKind::Datagram(queue) =>
+                data_race.validate_lock_acquire(&queue.borrow().clock, thread),
+            SocketKind::Stream | SocketKind::Listener(_) => {}
+        }
+    }
+
+    fn data_race_release(&self, data_race: &data_race::GlobalState, thread: ThreadId) {
+        match &self.state.borrow().kind {
+            SocketKind::Connected { end, .. } => end.data_race_release(data_race, thread),
+            SocketKind::Datagram(queue) => {
+                let Some(peer) = queue.borrow().peer else { return };
+                if let Some(peer_queue) = self.network.borrow().datagram_queue(peer) {
+                    data_race
+                        .validate_lock_release_shared(&mut peer_queue.borrow_mut().clock, thread);
+                }
+            }
+            SocketKind::Stream | SocketKind::Listener(_) => {}
+        }
+    }
+}
+
+impl<'mir, 'tcx: 'mir> EvalContextExtPrivate<'mir, 'tcx> for crate::MiriInterpCx<'mir, 'tcx> {}
+trait EvalContextExtPrivate<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
+    /// Sets the last error to the `libc` error constant `errno` and returns `-1`.
+    fn set_errno_and_fail<T: From<i32>>(&mut self, errno: &str) -> InterpResult<'tcx, T> {
+        let this = self.eval_context_mut();
+        let errno = this.eval_libc(errno)?;
+        this.set_last_error(errno)?;
+        Ok((-1).into())
+    }
+
+    /// The TCP or UDP socket behind `fd`.
+    fn inet_socket(&self, fd: i32) -> NetResult<InetSocket> {
+        let this = self.eval_context_ref();
+        let file_descriptor = this.machine.file_handler.handles.get(&fd).ok_or("EBADF")?;
+        file_descriptor.as_inet_socket().cloned().ok_or("ENOTSOCK")
+    }
+
+    fn data_race_and_thread(&self) -> Option<(&data_race::GlobalState, ThreadId)> {
+        let this = self.eval_context_ref();
+        let thread = this.get_active_thread();
+        this.machine.data_race.as_ref().map(|data_race| (data_race, thread))
+    }
+
+    /// Reads the `sockaddr_in` or `sockaddr_in6` of `len` bytes at `ptr`. Returns `None` if the
+    /// address family is not supported or `len` is too small.
+    fn read_socket_addr(
+        &self,
+        ptr: Pointer<Option<Provenance>>,
+        len: u64,
+    ) -> InterpResult<'tcx, Option<SocketAddr>> {
+        let this = self.eval_context_ref();
+
+        let sockaddr_in = this.libc_ty_layout("sockaddr_in")?;
+        let sockaddr_in6 = this.libc_ty_layout("sockaddr_in6")?;
+        if len < sockaddr_in.size.bytes() {
+            return Ok(None);
+        }
+        // The family is at the same offset in all socket address types.
+        let place = MPlaceTy::from_aligned_ptr(ptr, sockaddr_in);
+        let family = this.mplace_field_named(&place, "sin_family")?;
+        let family = this.read_scalar(&family.into())?.to_uint(family.layout.size)?;
+        // Ports and addresses are stored in network byte order, so we read them as bytes.
+        let field_bytes =
+            |place: &MPlaceTy<'tcx, Provenance>, name: &str| -> InterpResult<'tcx, Vec<u8>> {
+                let field = this.mplace_field_named(place, name)?;
+                Ok(this.read_bytes_ptr_strip_provenance(field.ptr, field.layout.size)?.to_vec())
+            };
+
+        if family == u128::try_from(this.eval_libc_i32("AF_INET")?).unwrap() {
+            let port = field_bytes(&place, "sin_port")?;
+            let ip = <[u8; 4]>::try_from(field_bytes(&place, "sin_addr")?).unwrap();
+            Ok(Some(SocketAddr::new(
+                IpAddr::V4(ip.into()),
+                u16::from_be_bytes(port.try_into().unwrap()),
+            )))
+        } else if family == u128::try_from(this.eval_libc_i32("AF_INET6")?).unwrap() {
+            if len < sockaddr_in6.size.bytes() {
+                return Ok(None);
+            }
+            let place = MPlaceTy::from_aligned_ptr(ptr, sockaddr_in6);
+            let port = field_bytes(&place, "sin6_port")?;
+            let ip = <[u8; 16]>::try_from(field_bytes(&place, "sin6_addr")?).unwrap();
+            Ok(Some(SocketAddr::new(
+                IpAddr::V6(ip.into()),
+                u16::from_be_bytes(port.try_into().unwrap()),
+            )))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Writes `addr` to the buffer of `*len` bytes at `ptr`, truncating it if it does not fit,
+    /// and stores the size of the full address in `*len`. Does nothing if `ptr` is null.
+    fn write_socket_addr(
+        &mut self,
+        addr: SocketAddr,
+        ptr: Pointer<Option<Provenance>>,
+        len: &OpTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+
+        if this.ptr_is_null(ptr)? {
+            return Ok(());
+        }
+        let len = this.deref_operand(len)?;
+        let buf_len = this.read_scalar(&len.into())?.to_u32()?;
+
+        // Build the address in a temporary allocation, so that we can copy just the part that
+        // fits into the buffer.
+        let (layout, prefix) = match addr {
+            SocketAddr::V4(_) => (this.libc_ty_layout("sockaddr_in")?, "sin"),
+            SocketAddr::V6(_) => (this.libc_ty_layout("sockaddr_in6")?, "sin6"),
+        };
+        let family = this.eval_libc_i32(if addr.is_ipv4() { "AF_INET" } else { "AF_INET6" })?;
+        let tmp = this.allocate(layout, MiriMemoryKind::Machine.into())?;
+        this.write_bytes_ptr(tmp.ptr, iter::repeat(0u8).take(layout.size.bytes_usize()))?;
+        if matches!(&*this.tcx.sess.target.os, "macos" | "freebsd") {
+            let len_field = this.mplace_field_named(&tmp, &format!("{prefix}_len"))?;
+            this.write_int(layout.size.bytes(), &len_field.into())?;
+        }
+        let family_field = this.mplace_field_named(&tmp, &format!("{prefix}_family"))?;
+        this.write_int(family, &family_field.into())?;
+        let port_field = this.mplace_field_named(&tmp, &format!("{prefix}_port"))?;
+        this.write_bytes_ptr(port_field.ptr, addr.port().to_be_bytes())?;
+        let ip_field = this.mplace_field_named(&tmp, &format!("{prefix}_addr"))?;
+        match addr.ip() {
+            IpAddr::V4(ip) => this.write_bytes_ptr(ip_field.ptr, ip.octets())?,
+            IpAddr::V6(ip) => this.write_bytes_ptr(ip_field.ptr, ip.octets())?,
+        }
+
+        let size = layout.size.min(Size::from_bytes(buf_len));
+        this.mem_copy(
+            tmp.ptr,
+            layout.align.abi,
+            ptr,
+            Align::ONE,
+            size,
+            /* nonoverlapping */ true,
+        )?;
+        this.deallocate_ptr(tmp.ptr, None, MiriMemoryKind::Machine.into())?;
+        this.write_int(layout.size.bytes(), &len.into())?;
+        Ok(())
+    }
+
+    /// Accepts a connection on `fd`, returning the new file descriptor, or `None` if `fd` is a
+    /// blocking socket that has no pending connections yet.
+    fn try_accept(
+        &mut self,
+        fd: i32,
+        nonblocking: bool,
+        addr: Pointer<Option<Provenance>>,
+        addrlen: &OpTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx, Option<i32>> {
+        let this = self.eval_context_mut();
+
+        let socket = match this.inet_socket(fd) {
+            Ok(socket) => socket,
+            Err(errno) => return this.set_errno_and_fail(errno).map(Some),
+        };
+        match socket.accept(this.data_race_and_thread()) {
+            Ok((new_socket, peer)) => {
+                new_socket.state.borrow_mut().nonblocking = nonblocking;
+                let new_fd = this.machine.file_handler.insert_fd(Box::new(new_socket));
+                this.write_socket_addr(peer, addr, addrlen)?;
+                Ok(Some(new_fd))
+            }
+            Err("EAGAIN") if socket.is_blocking() => Ok(None),
+            Err(errno) => this.set_errno_and_fail(errno).map(Some),
+        }
+    }
+
+    /// Sends at most `len` bytes from `buf` on `fd`, returning the number of bytes sent, or `None`
+    /// if `blocking` and the send buffer is full.
+    fn try_send(
+        &mut self,
+        fd: i32,
+        buf: Pointer<Option<Provenance>>,
+        len: u64,
+        to: Option<SocketAddr>,
+        blocking: bool,
+    ) -> InterpResult<'tcx, Option<i64>> {
+        let this = self.eval_context_mut();
+
+        let socket = match this.inet_socket(fd) {
+            Ok(socket) => socket,
+            Err(errno) => return this.set_errno_and_fail(errno).map(Some),
+        };
+        let bytes = this.read_bytes_ptr_strip_provenance(buf, Size::from_bytes(len))?;
+        match socket.send(bytes, to, this.data_race_and_thread()) {
+            Ok(count) => {
+                // Sending may have made the receiving socket ready.
+                this.notify_io_event();
+                Ok(Some(i64::try_from(count).unwrap()))
+            }
+            Err("EAGAIN") if blocking => Ok(None),
+            Err(errno) => this.set_errno_and_fail(errno).map(Some),
+        }
+    }
+
+    /// Receives at most `len` bytes on `fd` into `buf`, returning the number of bytes received,
+    /// or `None` if `blocking` and there is nothing to receive yet.
+    #[allow(clippy::too_many_arguments)]
+    fn try_recv(
+        &mut self,
+        fd: i32,
+        buf: Pointer<Option<Provenance>>,
+        len: u64,
+        peek: bool,
+        blocking: bool,
+        addr: Pointer<Option<Provenance>>,
+        addrlen: Option<&OpTy<'tcx, Provenance>>,
+    ) -> InterpResult<'tcx, Option<i64>> {
+        let this = self.eval_context_mut();
+
+        let socket = match this.inet_socket(fd) {
+            Ok(socket) => socket,
+            Err(errno) => return this.set_errno_and_fail(errno).map(Some),
+        };
+        let mut bytes = vec![0; usize::try_from(len).unwrap()];
+        match socket.recv(&mut bytes, peek, this.data_race_and_thread()) {
+            Ok((count, from)) => {
+                // Receiving may have made room for writers.
+                this.notify_io_event();
+                this.write_bytes_ptr(buf, bytes[..count].iter().copied())?;
+                if let Some(addrlen) = addrlen {
+                    this.write_socket_addr(from, addr, addrlen)?;
+                }
+                Ok(Some(i64::try_from(count).unwrap()))
+            }
+            Err("EAGAIN") if blocking => Ok(None),
+            Err(errno) => this.set_errno_and_fail(errno).map(Some),
+        }
+    }
+
+    /// Checks the `(level, name)` of a socket option, which is only supported for the options
+    /// that `std` uses.
+    fn check_socket_option(&self, level: i32, name: i32) -> InterpResult<'tcx> {
+        let this = self.eval_context_ref();
+
+        let mut supported = vec![
+            ("SOL_SOCKET", "SO_REUSEADDR"),
+            ("SOL_SOCKET", "SO_KEEPALIVE"),
+            ("SOL_SOCKET", "SO_BROADCAST"),
+            ("IPPROTO_TCP", "TCP_NODELAY"),
+            ("IPPROTO_IP", "IP_TTL"),
+            ("IPPROTO_IPV6", "IPV6_V6ONLY"),
+        ];
+        if this.tcx.sess.target.os == "macos" {
+            supported.push(("SOL_SOCKET", "SO_NOSIGPIPE"));
+        }
+        for (level_name, option_name) in supported {
+            if level == this.eval_libc_i32(level_name)?
+                && name == this.eval_libc_i32(option_name)?
+            {
+                return Ok(());
+            }
+        }
+        throw_unsup_format!("socket option {name} at level {level} is not supported");
+    }
+}
+
+impl<'mir, 'tcx: 'mir> EvalContextExt<'mir, 'tcx> for crate::MiriInterpCx<'mir, 'tcx> {}
+pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
+    /// Implements `int socket(int domain, int type, int protocol)`.
+    ///
+    /// Only TCP and UDP sockets over IPv4 and IPv6 are supported.
+    fn socket(
+        &mut self,
+        domain: &OpTy<'tcx, Provenance>,
+        type_: &OpTy<'tcx, Provenance>,
+        protocol: &OpTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx, Scalar<Provenance>> {
+        let this = self.eval_context_mut();
+
+        let domain = this.read_scalar(domain)?.to_i32()?;
+        let type_ = this.read_scalar(type_)?.to_i32()?;
+        let protocol = this.read_scalar(protocol)?.to_i32()?;
+        let (type_, nonblocking) = this.split_socket_type_flags(type_)?;
+
+        let ipv6 = if domain == this.eval_libc_i32("AF_INET")? {
+            false
+        } else if domain == this.eval_libc_i32("AF_INET6")? {
+            true
+        } else {
+            throw_unsup_format!(
+                "`socket` is only supported with the `AF_INET` and `AF_INET6` domains"
+            );
+        };
+        let kind = if type_ == this.eval_libc_i32("SOCK_STREAM")?
+            && (protocol == 0 || protocol == this.eval_libc_i32("IPPROTO_TCP")?)
+        {
+            SocketKind::Stream
+        } else if type_ == this.eval_libc_i32("SOCK_DGRAM")?
+            && (protocol == 0 || protocol == this.eval_libc_i32("IPPROTO_UDP")?)
+        {
+            SocketKind::Datagram(Rc::new(RefCell::new(DatagramQueue::default())))
+        } else {
+            throw_unsup_format!("`socket` is only supported for TCP and UDP sockets");
+        };
+
+        let socket = InetSocket::new(&this.machine.file_handler.network, ipv6, kind);
+        socket.state.borrow_mut().nonblocking = nonblocking;
+        let fd = this.machine.file_handler.insert_fd(Box::new(socket));
+        Ok(Scalar::from_i32(fd))
+    }
+
+    fn bind(
+        &mut self,
+        fd: &OpTy<'tcx, Provenance>,
+        addr: &OpTy<'tcx, Provenance>,
+        addrlen: &OpTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx, Scalar<Provenance>> {
+        let this = self.eval_context_mut();
+
+        let fd = this.read_scalar(fd)?.to_i32()?;
+        let addr = this.read_pointer(addr)?;
+        let addrlen = this.read_scalar(addrlen)?.to_u32()?;
+
+        let socket = match this.inet_socket(fd) {
+            Ok(socket) => socket,
+            Err(errno) => return this.set_errno_and_fail(errno),
+        };
+        let Some(addr) = this.read_socket_addr(addr, addrlen.into())? else {
+            return this.set_errno_and_fail("EINVAL");
+        };
+        trace!("bind: binding fd {fd} to {addr}");
+        match socket.bind(addr) {
+            Ok(()) => Ok(Scalar::from_i32(0)),
+            Err(errno) => this.set_errno_and_fail(errno),
+        }
+    }
+
+    /// Implements `int listen(int sockfd, int backlog)`. The backlog is ignored.
+    fn listen(
+        &mut self,
+        fd: &OpTy<'tcx, Provenance>,
+        backlog: &OpTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx, Scalar<Provenance>> {
+        let this = self.eval_context_mut();
+
+        let fd = this.read_scalar(fd)?.to_i32()?;
+        let _backlog = this.read_scalar(backlog)?.to_i32()?;
+
+        match this.inet_socket(fd).and_then(|socket| socket.listen()) {
+            Ok(()) => Ok(Scalar::from_i32(0)),
+            Err(errno) => this.set_errno_and_fail(errno),
+        }
+    }
+
+    fn connect(
+        &mut self,
+        fd: &OpTy<'tcx, Provenance>,
+        addr: &OpTy<'tcx, Provenance>,
+        addrlen: &OpTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx, Scalar<Provenance>> {
+        let this = self.eval_context_mut();
+
+        let fd = this.read_scalar(fd)?.to_i32()?;
+        let addr = this.read_pointer(addr)?;
+        let addrlen = this.read_scalar(addrlen)?.to_u32()?;
+
+        let socket = match this.inet_socket(fd) {
+            Ok(socket) => socket,
+            Err(errno) => return this.set_errno_and_fail(errno),
+        };
+        let Some(addr) = this.read_socket_addr(addr, addrlen.into())? else {
+            return this.set_errno_and_fail("EINVAL");
+        };
+        trace!("connect: connecting fd {fd} to {addr}");
+        match socket.connect(addr, this.data_race_and_thread()) {
+            Ok(()) => {
+                // The listener has a new pending connection.
+                this.notify_io_event();
+                Ok(Scalar::from_i32(0))
+            }
+            Err(errno) => this.set_errno_and_fail(errno),
+        }
+    }
+
+    /// Implements `accept` and, if `flags` is given, `accept4`.
+    fn accept4(
+        &mut self,
+        fd: &OpTy<'tcx, Provenance>,
+        addr: &OpTy<'tcx, Provenance>,
+        addrlen: &OpTy<'tcx, Provenance>,
+        flags: Option<&OpTy<'tcx, Provenance>>,
+        dest: &PlaceTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+
+        let fd = this.read_scalar(fd)?.to_i32()?;
+        let addr_ptr = this.read_pointer(addr)?;
+        let nonblocking = match flags {
+            Some(flags) => {
+                let flags = this.read_scalar(flags)?.to_i32()?;
+                let (flags, nonblocking) = this.split_socket_type_flags(flags)?;
+                if flags != 0 {
+                    let result = this.set_errno_and_fail("EINVAL")?;
+                    return this.write_scalar(Scalar::from_i32(result), dest);
+                }
+                nonblocking
+            }
+            None => false,
+        };
+
+        if let Some(result) = this.try_accept(fd, nonblocking, addr_ptr, addrlen)? {
+            this.write_scalar(Scalar::from_i32(result), dest)?;
+        } else {
+            // There are no pending connections, wait until a thread connects.
+            struct Callback<'tcx> {
+                thread: ThreadId,
+                fd: i32,
+                nonblocking: bool,
+                addr: Pointer<Option<Provenance>>,
+                addrlen: OpTy<'tcx, Provenance>,
+                dest: PlaceTy<'tcx, Provenance>,
+            }
+
+            impl<'tcx> VisitTags for Callback<'tcx> {
+                fn visit_tags(&self, visit: &mut dyn FnMut(BorTag)) {
+                    let Callback { thread: _, fd: _, nonblocking: _, addr, addrlen, dest } = self;
+                    addr.visit_tags(visit);
+                    addrlen.visit_tags(visit);
+                    dest.visit_tags(visit);
+                }
+            }
+
+            impl<'mir, 'tcx: 'mir> MachineCallback<'mir, 'tcx> for Callback<'tcx> {
+                fn call(&self, this: &mut MiriInterpCx<'mir, 'tcx>) -> InterpResult<'tcx> {
+                    if let Some(result) =
+                        this.try_accept(self.fd, self.nonblocking, self.addr, &self.addrlen)?
+                    {
+                        this.unblock_thread(self.thread);
+                        this.write_scalar(Scalar::from_i32(result), &self.dest)?;
+                    }
+                    Ok(())
+                }
+            }
+
+            let thread = this.get_active_thread();
+            this.block_thread_on_io(
+                thread,
+                Box::new(Callback {
+                    thread,
+                    fd,
+                    nonblocking,
+                    addr: addr_ptr,
+                    addrlen: addrlen.clone(),
+                    dest: dest.clone(),
+                }),
+            );
+        }
+        Ok(())
+    }
+
+    /// Implements `sendto` and, if `to` is `None`, `send`.
+    fn sendto(
+        &mut self,
+        fd: &OpTy<'tcx, Provenance>,
+        buf: &OpTy<'tcx, Provenance>,
+        len: &OpTy<'tcx, Provenance>,
+        flags: &OpTy<'tcx, Provenance>,
+        to: Option<(&OpTy<'tcx, Provenance>, &OpTy<'tcx, Provenance>)>,
+        dest: &PlaceTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+
+        let fd = this.read_scalar(fd)?.to_i32()?;
+        let buf = this.read_pointer(buf)?;
+        // Like for `write`, we cap the length to what fits into an `isize`.
+        let len = this
+            .read_scalar(len)?
+            .to_machine_usize(this)?
+            .min(u64::try_from(this.machine_isize_max()).unwrap())
+            .min(u64::try_from(isize::MAX).unwrap());
+        let mut flags = this.read_scalar(flags)?.to_i32()?;
+
+        let dontwait = flags & this.eval_libc_i32("MSG_DONTWAIT")? != 0;
+        flags &= !this.eval_libc_i32("MSG_DONTWAIT")?;
+        if this.tcx.sess.target.os == "linux" {
+            // Rust programs ignore `SIGPIPE`, so there is no signal to suppress.
+            flags &= !this.eval_libc_i32("MSG_NOSIGNAL")?;
+        }
+        if flags != 0 {
+            throw_unsup_format!("unsupported flags {:#x} for `send`", flags);
+        }
+
+        let socket = match this.inet_socket(fd) {
+            Ok(socket) => socket,
+            Err("ENOTSOCK") if to.is_none() && !dontwait => {
+                // Unix stream sockets created by `socketpair` are handled by `write`.
+                let name = this.machine.file_handler.handles[&fd].name();
+                if name == "socket" {
+                    return this.write(fd, buf, len, dest);
+                }
+                let result = this.set_errno_and_fail("ENOTSOCK")?;
+                return this.write_scalar(Scalar::from_machine_isize(result, this), dest);
+            }
+            Err(errno) => {
+                let result = this.set_errno_and_fail(errno)?;
+                return this.write_scalar(Scalar::from_machine_isize(result, this), dest);
+            }
+        };
+        let to = match to {
+            Some((addr, addrlen)) => {
+                let addr = this.read_pointer(addr)?;
+                let addrlen = this.read_scalar(addrlen)?.to_u32()?;
+                if this.ptr_is_null(addr)? {
+                    None
+                } else {
+                    let Some(addr) = this.read_socket_addr(addr, addrlen.into())? else {
+                        let result = this.set_errno_and_fail("EINVAL")?;
+                        return this.write_scalar(Scalar::from_machine_isize(result, this), dest);
+                    };
+                    Some(addr)
+                }
+            }
+            None => None,
+        };
+        let blocking = socket.is_blocking() && !dontwait;
+
+        if let Some(result) = this.try_send(fd, buf, len, to, blocking)? {
+            this.write_scalar(Scalar::from_machine_isize(result, this), dest)?;
+        } else {
+            // The send buffer is full, wait until the peer reads some of it.
+            struct Callback<'tcx> {
+                thread: ThreadId,
+                fd: i32,
+                buf: Pointer<Option<Provenance>>,
+                len: u64,
+                dest: PlaceTy<'tcx, Provenance>,
+            }
+
+            impl<'tcx> VisitTags for Callback<'tcx> {
+                fn visit_tags(&self, visit: &mut dyn FnMut(BorTag)) {
+                    let Callback { thread: _, fd: _, buf, len: _, dest } = self;
+                    buf.visit_tags(visit);
+                    dest.visit_tags(visit);
+                }
+            }
+
+            impl<'mir, 'tcx: 'mir> MachineCallback<'mir, 'tcx> for Callback<'tcx> {
+                fn call(&self, this: &mut MiriInterpCx<'mir, 'tcx>) -> InterpResult<'tcx> {
+                    // Only TCP sockets block, and they ignore the destination address.
+                    if let Some(result) =
+                        this.try_send(self.fd, self.buf, self.len, None, /* blocking */ true)?
+                    {
+                        this.unblock_thread(self.thread);
+                        this.write_scalar(Scalar::from_machine_isize(result, this), &self.dest)?;
+                    }
+                    Ok(())
+                }
+            }
+
+            let thread = this.get_active_thread();
+            this.block_thread_on_io(
+                thread,
+                Box::new(Callback { thread, fd, buf, len, dest: dest.clone() }),
+            );
+        }
+        Ok(())
+    }
+
+    /// Implements `recvfrom` and, if `from` is `None`, `recv`.
+    fn recvfrom(
+        &mut self,
+        fd: &OpTy<'tcx, Provenance>,
+        buf: &OpTy<'tcx, Provenance>,
+        len: &OpTy<'tcx, Provenance>,
+        flags: &OpTy<'tcx, Provenance>,
+        from: Option<(&OpTy<'tcx, Provenance>, &OpTy<'tcx, Provenance>)>,
+        dest: &PlaceTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+
+        let fd = this.read_scalar(fd)?.to_i32()?;
+        let buf = this.read_pointer(buf)?;
+        // Like for `read`, we cap the length to what fits into an `isize`.
+        let len = this
+            .read_scalar(len)?
+            .to_machine_usize(this)?
+            .min(u64::try_from(this.machine_isize_max()).unwrap())
+            .min(u64::try_from(isize::MAX).unwrap());
+        let mut flags = this.read_scalar(flags)?.to_i32()?;
+
+        let peek = flags & this.eval_libc_i32("MSG_PEEK")? != 0;
+        let dontwait = flags & this.eval_libc_i32("MSG_DONTWAIT")? != 0;
+        flags &= !(this.eval_libc_i32("MSG_PEEK")? | this.eval_libc_i32("MSG_DONTWAIT")?);
+        if flags != 0 {
+            throw_unsup_format!("unsupported flags {:#x} for `recv`", flags);
+        }
+
+        // Check that the *entire* buffer is actually valid memory.
+        this.check_ptr_access_align(
+            buf,
+            Size::from_bytes(len),
+            Align::ONE,
+            CheckInAllocMsg::MemoryAccessTest,
+        )?;
+
+        let socket = match this.inet_socket(fd) {
+            Ok(socket) => socket,
+            Err("ENOTSOCK") if !peek && !dontwait => {
+                // Unix stream sockets created by `socketpair` are handled by `read`. They have
+                // no address to report.
+                let name = this.machine.file_handler.handles[&fd].name();
+                if name == "socket" {
+                    if let Some((_, addrlen)) = from {
+                        if !this.ptr_is_null(this.read_pointer(addrlen)?)? {
+                            let addrlen = this.deref_operand(addrlen)?;
+                            this.write_int(0, &addrlen.into())?;
+                        }
+                    }
+                    return this.read(fd, buf, len, dest);
+                }
+                let result = this.set_errno_and_fail("ENOTSOCK")?;
+                return this.write_scalar(Scalar::from_machine_isize(result, this), dest);
+            }
+            Err(errno) => {
+                let result = this.set_errno_and_fail(errno)?;
+                return this.write_scalar(Scalar::from_machine_isize(result, this), dest);
+            }
+        };
+        let (addr, addrlen) = match from {
+            Some((addr, addrlen)) => (this.read_pointer(addr)?, Some(addrlen)),
+            None => (Pointer::null(), None),
+        };
+        let blocking = socket.is_blocking() && !dontwait;
+
+        if let Some(result) = this.try_recv(fd, buf, len, peek, blocking, addr, addrlen)? {
+            this.write_scalar(Scalar::from_machine_isize(result, this), dest)?;
+        } else {
+            // There is nothing to receive, wait until some data arrives.
+            struct Callback<'tcx> {
+                thread: ThreadId,
+                fd: i32,
+                buf: Pointer<Option<Provenance>>,
+                len: u64,
+                peek: bool,
+                addr: Pointer<Option<Provenance>>,
+                addrlen: Option<OpTy<'tcx, Provenance>>,
+                dest: PlaceTy<'tcx, Provenance>,
+            }
+
+            impl<'tcx> VisitTags for Callback<'tcx> {
+                fn visit_tags(&self, visit: &mut dyn FnMut(BorTag)) {
+                    let Callback { thread: _, fd: _, buf, len: _, peek: _, addr, addrlen, dest } =
+                        self;
+                    buf.visit_tags(visit);
+                    addr.visit_tags(visit);
+                    addrlen.visit_tags(visit);
+                    dest.visit_tags(visit);
+                }
+            }
+
+            impl<'mir, 'tcx: 'mir> MachineCallback<'mir, 'tcx> for Callback<'tcx> {
+                fn call(&self, this: &mut MiriInterpCx<'mir, 'tcx>) -> InterpResult<'tcx> {
+                    if let Some(result) = this.try_recv(
+                        self.fd,
+                        self.buf,
+                        self.len,
+                        self.peek,
+                        /* blocking */ true,
+                        self.addr,
+                        self.addrlen.as_ref(),
+                    )? {
+                        this.unblock_thread(self.thread);
+                        this.write_scalar(Scalar::from_machine_isize(result, this), &self.dest)?;
+                    }
+                    Ok(())
+                }
+            }
+
+            let thread = this.get_active_thread();
+            this.block_thread_on_io(
+                thread,
+                Box::new(Callback {
+                    thread,
+                    fd,
+                    buf,
+                    len,
+                    peek,
+                    addr,
+                    addrlen: addrlen.cloned(),
+                    dest: dest.clone(),
+                }),
+            );
+        }
+        Ok(())
+    }
+
+    fn shutdown(
+        &mut self,
+        fd: &OpTy<'tcx, Provenance>,
+        how: &OpTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx, Scalar<Provenance>> {
+        let this = self.eval_context_mut();
+
+        let fd = this.read_scalar(fd)?.to_i32()?;
+        let how = this.read_scalar(how)?.to_i32()?;
+
+        let (read, write) = if how == this.eval_libc_i32("SHUT_RD")? {
+            (true, false)
+        } else if how == this.eval_libc_i32("SHUT_WR")? {
+            (false, true)
+        } else if how == this.eval_libc_i32("SHUT_RDWR")? {
+            (true, true)
+        } else {
+            return this.set_errno_and_fail("EINVAL");
+        };
+        match this.inet_socket(fd).and_then(|socket| socket.shutdown(read, write)) {
+            Ok(()) => {
+                // Shutting down may have woken up readers and writers on both ends.
+                this.notify_io_event();
+                Ok(Scalar::from_i32(0))
+            }
+            Err(errno) => this.set_errno_and_fail(errno),
+        }
+    }
+
+    /// Implements `getsockname` and, if `peer` is set, `getpeername`.
+    fn getsockname(
+        &mut self,
+        fd: &OpTy<'tcx, Provenance>,
+        addr: &OpTy<'tcx, Provenance>,
+        addrlen: &OpTy<'tcx, Provenance>,
+        peer: bool,
+    ) -> InterpResult<'tcx, Scalar<Provenance>> {
+        let this = self.eval_context_mut();
+
+        let fd = this.read_scalar(fd)?.to_i32()?;
+        let addr = this.read_pointer(addr)?;
+
+        let result = this
+            .inet_socket(fd)
+            .and_then(|socket| if peer { socket.peer_addr() } else { Ok(socket.local_addr()) });
+        match result {
+            Ok(socket_addr) => {
+                this.write_socket_addr(socket_addr, addr, addrlen)?;
+                Ok(Scalar::from_i32(0))
+            }
+            Err(errno) => this.set_errno_and_fail(errno),
+        }
+    }
+
+    fn setsockopt(
+        &mut self,
+        fd: &OpTy<'tcx, Provenance>,
+        level: &OpTy<'tcx, Provenance>,
+        name: &OpTy<'tcx, Provenance>,
+        value: &OpTy<'tcx, Provenance>,
+        len: &OpTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx, Scalar<Provenance>> {
+        let this = self.eval_context_mut();
+
+        let fd = this.read_scalar(fd)?.to_i32()?;
+        let level = this.read_scalar(level)?.to_i32()?;
+        let name = this.read_scalar(name)?.to_i32()?;
+        let value = this.read_pointer(value)?;
+        let len = this.read_scalar(len)?.to_u32()?;
+
+        let socket = match this.inet_socket(fd) {
+            Ok(socket) => socket,
+            Err(errno) => return this.set_errno_and_fail(errno),
+        };
+        this.check_socket_option(level, name)?;
+        // All supported options are `int`s.
+        if u64::from(len) != this.machine.layouts.i32.size.bytes() {
+            return this.set_errno_and_fail("EINVAL");
+        }
+        let value = this
+            .read_scalar(&MPlaceTy::from_aligned_ptr(value, this.machine.layouts.i32).into())?
+            .to_i32()?;
+        // The options are only recorded, since they make no difference on the loopback network.
+        socket.state.borrow_mut().options.insert((level, name), value);
+        Ok(Scalar::from_i32(0))
+    }
+
+    fn getsockopt(
+        &mut self,
+        fd: &OpTy<'tcx, Provenance>,
+        level: &OpTy<'tcx, Provenance>,
+        name: &OpTy<'tcx, Provenance>,
+        value: &OpTy<'tcx, Provenance>,
+        len: &OpTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx, Scalar<Provenance>> {
+        let this = self.eval_context_mut();
+
+        let fd = this.read_scalar(fd)?.to_i32()?;
+        let level = this.read_scalar(level)?.to_i32()?;
+        let name = this.read_scalar(name)?.to_i32()?;
+        let value = this.read_pointer(value)?;
+        let len = this.deref_operand(len)?;
+
+        let socket = match this.inet_socket(fd) {
+            Ok(socket) => socket,
+            Err(errno) => return this.set_errno_and_fail(errno),
+        };
+        let result = if level == this.eval_libc_i32("SOL_SOCKET")?
+            && name == this.eval_libc_i32("SO_ERROR")?
+        {
+            // Errors are always reported right away, so there are no pending errors.
+            0
+        } else if level == this.eval_libc_i32("SOL_SOCKET")?
+            && name == this.eval_libc_i32("SO_TYPE")?
+        {
+            this.eval_libc_i32(if socket.is_datagram() { "SOCK_DGRAM" } else { "SOCK_STREAM" })?
+        } else {
+            this.check_socket_option(level, name)?;
+            let default = if level == this.eval_libc_i32("IPPROTO_IP")? {
+                // The default TTL on Linux.
+                64
+            } else {
+                0
+            };
+            socket.state.borrow().options.get(&(level, name)).copied().unwrap_or(default)
+        };
+        let int_size = this.machine.layouts.i32.size.bytes();
+        if u64::from(this.read_scalar(&len.into())?.to_u32()?) < int_size {
+            return this.set_errno_and_fail("EINVAL");
+        }
+        this.write_scalar(
+            Scalar::from_i32(result),
+            &MPlaceTy::from_aligned_ptr(value, this.machine.layouts.i32).into(),
+        )?;
+        this.write_int(int_size, &len.into())?;
+        Ok(Scalar::from_i32(0))
+    }
+
+    /// Implements `int ioctl(int fd, unsigned long request, ...)`. Only `FIONBIO` on sockets is
+    /// supported.
+    fn ioctl(&mut self, args: &[OpTy<'tcx, Provenance>]) -> InterpResult<'tcx, i32> {
+        let this = self.eval_context_mut();
+
+        let [fd, request, arg, ..] = args else {
+            throw_ub_format!(
+                "incorrect number of arguments for ioctl: got {}, expected at least 3",
+                args.len()
+            );
+        };
+        let fd = this.read_scalar(fd)?.to_i32()?;
+        let request = this.read_scalar(request)?.to_machine_usize(this)?;
+        if request != this.eval_libc("FIONBIO")?.to_machine_usize(this)? {
+            throw_unsup_format!("the {:#x} request is not supported for `ioctl`", request);
+        }
+        let arg = this.read_pointer(arg)?;
+        let nonblocking = this
+            .read_scalar(&MPlaceTy::from_aligned_ptr(arg, this.machine.layouts.i32).into())?
+            .to_i32()?;
+        match this.inet_socket(fd) {
+            Ok(socket) => {
+                socket.state.borrow_mut().nonblocking = nonblocking != 0;
+                Ok(0)
+            }
+            Err("ENOTSOCK") =>
+                throw_unsup_format!("`FIONBIO` is only supported for TCP and UDP sockets"),
+            Err(errno) => this.set_errno_and_fail(errno),
+        }
+    }
+}
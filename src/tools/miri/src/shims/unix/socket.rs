@@ -0,0 +1,265 @@
+//! Emulation of the connected pairs of Unix stream sockets created by `socketpair`. The buffers of
+//! these pairs also carry the data of TCP connections, see `net.rs`.
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::io;
+use std::rc::Rc;
+
+use crate::concurrency::data_race;
+use crate::concurrency::vector_clock::VClock;
+use crate::*;
+use shims::unix::fs::{FileDescriptor, Readiness};
+
+/// The number of bytes that can be buffered in each direction before writes block. This is the
+/// default socket buffer size on Linux.
+pub(super) const MAX_SOCKET_BUFFER_SIZE: usize = 212992;
+
+/// The data sent in one direction of a connected pair of stream sockets.
+#[derive(Debug, Default)]
+struct SocketBuffer {
+    data: VecDeque<u8>,
+    /// Whether all file descriptors of the writing end were closed, or writing was shut down.
+    writer_closed: bool,
+    /// Whether all file descriptors of the reading end were closed, or reading was shut down.
+    reader_closed: bool,
+    /// Incremented whenever the buffer changes, for edge-triggered `epoll`.
+    generation: u64,
+    /// The clocks of all threads that wrote to the buffer, which readers synchronize with.
+    clock: VClock,
+}
+
+/// One end of a connected pair of stream sockets, shared by all file descriptors duplicated from
+/// it. This is used both for `socketpair` and for TCP connections.
+#[derive(Debug)]
+pub(super) struct SocketEnd {
+    /// The data sent by the other end.
+    readbuf: Rc<RefCell<SocketBuffer>>,
+    /// The data sent to the other end.
+    writebuf: Rc<RefCell<SocketBuffer>>,
+}
+
+impl SocketEnd {
+    /// Creates the two ends of a new connection.
+    pub(super) fn pair() -> (SocketEnd, SocketEnd) {
+        let buffer1 = Rc::new(RefCell::new(SocketBuffer::default()));
+        let buffer2 = Rc::new(RefCell::new(SocketBuffer::default()));
+        let end1 = SocketEnd { readbuf: Rc::clone(&buffer1), writebuf: Rc::clone(&buffer2) };
+        let end2 = SocketEnd { readbuf: buffer2, writebuf: buffer1 };
+        (end1, end2)
+    }
+
+    /// Reads the data sent by the other end. With `peek`, the data stays in the buffer.
+    pub(super) fn read(&self, bytes: &mut [u8], peek: bool) -> io::Result<usize> {
+        let mut readbuf = self.readbuf.borrow_mut();
+        if bytes.is_empty() || readbuf.reader_closed {
+            return Ok(0);
+        }
+        if readbuf.data.is_empty() {
+            // Reading from a socket whose other end is closed returns end-of-file.
+            return if readbuf.writer_closed {
+                Ok(0)
+            } else {
+                Err(io::ErrorKind::WouldBlock.into())
+            };
+        }
+        let count = bytes.len().min(readbuf.data.len());
+        for (byte, data) in bytes.iter_mut().zip(readbuf.data.iter()) {
+            *byte = *data;
+        }
+        if !peek {
+            readbuf.data.drain(..count);
+            readbuf.generation += 1;
+        }
+        Ok(count)
+    }
+
+    /// Sends data to the other end.
+    pub(super) fn write(&self, bytes: &[u8]) -> io::Result<usize> {
+        let mut writebuf = self.writebuf.borrow_mut();
+        if writebuf.reader_closed || writebuf.writer_closed {
+            // Rust programs ignore `SIGPIPE`, so we only report the error.
+            return Err(io::ErrorKind::BrokenPipe.into());
+        }
+        if bytes.is_empty() {
+            return Ok(0);
+        }
+        let count = bytes.len().min(MAX_SOCKET_BUFFER_SIZE - writebuf.data.len());
+        if count == 0 {
+            return Err(io::ErrorKind::WouldBlock.into());
+        }
+        writebuf.data.extend(&bytes[..count]);
+        writebuf.generation += 1;
+        Ok(count)
+    }
+
+    /// Shuts down reading and/or writing, which the other end observes like a partial close.
+    pub(super) fn shutdown(&self, read: bool, write: bool) {
+        if read {
+            let mut readbuf = self.readbuf.borrow_mut();
+            readbuf.reader_closed = true;
+            readbuf.generation += 1;
+        }
+        if write {
+            let mut writebuf = self.writebuf.borrow_mut();
+            writebuf.writer_closed = true;
+            writebuf.generation += 1;
+        }
+    }
+
+    pub(super) fn readiness(&self) -> (Readiness, u64) {
+        let readbuf = self.readbuf.borrow();
+        let writebuf = self.writebuf.borrow();
+        let readiness = Readiness {
+            readable: !readbuf.data.is_empty() || readbuf.writer_closed || readbuf.reader_closed,
+            // Writing to a socket whose other end is closed fails right away.
+            writable: writebuf.data.len() < MAX_SOCKET_BUFFER_SIZE
+                || writebuf.reader_closed
+                || writebuf.writer_closed,
+            read_closed: readbuf.writer_closed,
+            hangup: readbuf.writer_closed && writebuf.reader_closed,
+        };
+        (readiness, readbuf.generation + writebuf.generation)
+    }
+
+    pub(super) fn data_race_acquire(&self, data_race: &data_race::GlobalState, thread: ThreadId) {
+        data_race.validate_lock_acquire(&self.readbuf.borrow().clock, thread);
+    }
+
+    pub(super) fn data_race_release(&self, data_race: &data_race::GlobalState, thread: ThreadId) {
+        data_race.validate_lock_release_shared(&mut self.writebuf.borrow_mut().clock, thread);
+    }
+}
+
+impl Drop for SocketEnd {
+    fn drop(&mut self) {
+        // The last file descriptor of this end was closed, which the other end observes as a
+        // hangup.
+        self.shutdown(true, true);
+    }
+}
+
+#[derive(Debug)]
+struct Socket {
+    end: Rc<SocketEnd>,
+    /// Whether `SOCK_NONBLOCK` was passed.
+    nonblocking: bool,
+}
+
+impl FileDescriptor for Socket {
+    fn name(&self) -> &'static str {
+        "socket"
+    }
+
+    fn read<'tcx>(
+        &mut self,
+        _communicate_allowed: bool,
+        bytes: &mut [u8],
+    ) -> InterpResult<'tcx, io::Result<usize>> {
+        Ok(self.end.read(bytes, /* peek */ false))
+    }
+
+    fn write<'tcx>(
+        &self,
+        _communicate_allowed: bool,
+        bytes: &[u8],
+    ) -> InterpResult<'tcx, io::Result<usize>> {
+        Ok(self.end.write(bytes))
+    }
+
+    fn close<'tcx>(
+        self: Box<Self>,
+        _communicate_allowed: bool,
+    ) -> InterpResult<'tcx, io::Result<i32>> {
+        Ok(Ok(0))
+    }
+
+    fn dup(&mut self) -> io::Result<Box<dyn FileDescriptor>> {
+        Ok(Box::new(Socket { end: Rc::clone(&self.end), nonblocking: self.nonblocking }))
+    }
+
+    fn is_tty(&self) -> bool {
+        false
+    }
+
+    fn is_blocking(&self) -> bool {
+        !self.nonblocking
+    }
+
+    fn readiness(&self) -> Option<(Readiness, u64)> {
+        Some(self.end.readiness())
+    }
+
+    fn data_race_acquire(&self, data_race: &data_race::GlobalState, thread: ThreadId) {
+        self.end.data_race_acquire(data_race, thread);
+    }
+
+    fn data_race_release(&self, data_race: &data_race::GlobalState, thread: ThreadId) {
+        self.end.data_race_release(data_race, thread);
+    }
+}
+
+impl<'mir, 'tcx: 'mir> EvalContextExt<'mir, 'tcx> for crate::MiriInterpCx<'mir, 'tcx> {}
+pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
+    /// Removes the `SOCK_NONBLOCK` and `SOCK_CLOEXEC` flags, which can be or-ed into the type of
+    /// a socket on Linux, from `type_`. Returns the remaining type and whether `SOCK_NONBLOCK` was
+    /// set.
+    fn split_socket_type_flags(&self, mut type_: i32) -> InterpResult<'tcx, (i32, bool)> {
+        let this = self.eval_context_ref();
+
+        let mut nonblocking = false;
+        if this.tcx.sess.target.os == "linux" {
+            let sock_nonblock = this.eval_libc_i32("SOCK_NONBLOCK")?;
+            let sock_cloexec = this.eval_libc_i32("SOCK_CLOEXEC")?;
+            if type_ & sock_nonblock != 0 {
+                nonblocking = true;
+                type_ &= !sock_nonblock;
+            }
+            // `SOCK_CLOEXEC` has no effect since Miri does not support `exec`.
+            type_ &= !sock_cloexec;
+        }
+        Ok((type_, nonblocking))
+    }
+
+    /// Implements `int socketpair(int domain, int type, int protocol, int sv[2])`.
+    ///
+    /// Only pairs of Unix stream sockets are supported.
+    fn socketpair(
+        &mut self,
+        domain: &OpTy<'tcx, Provenance>,
+        type_: &OpTy<'tcx, Provenance>,
+        protocol: &OpTy<'tcx, Provenance>,
+        sv: &OpTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx, Scalar<Provenance>> {
+        let this = self.eval_context_mut();
+
+        let domain = this.read_scalar(domain)?.to_i32()?;
+        let type_ = this.read_scalar(type_)?.to_i32()?;
+        let protocol = this.read_scalar(protocol)?.to_i32()?;
+        // `sv` points to the first element of an array of two `int`s.
+        let sv = this.deref_operand(sv)?;
+
+        let (type_, nonblocking) = this.split_socket_type_flags(type_)?;
+
+        if domain != this.eval_libc_i32("AF_UNIX")? {
+            throw_unsup_format!("`socketpair` is only supported with the `AF_UNIX` domain");
+        }
+        if type_ != this.eval_libc_i32("SOCK_STREAM")? {
+            throw_unsup_format!("`socketpair` is only supported with the `SOCK_STREAM` type");
+        }
+        if protocol != 0 {
+            throw_unsup_format!("`socketpair` is only supported with the default protocol");
+        }
+
+        let (end1, end2) = SocketEnd::pair();
+        let fh = &mut this.machine.file_handler;
+        let fd1 = fh.insert_fd(Box::new(Socket { end: Rc::new(end1), nonblocking }));
+        let fd2 = fh.insert_fd(Box::new(Socket { end: Rc::new(end2), nonblocking }));
+
+        let sv1 = sv.offset(sv.layout.size, sv.layout, this)?;
+        this.write_scalar(Scalar::from_i32(fd1), &sv.into())?;
+        this.write_scalar(Scalar::from_i32(fd2), &sv1.into())?;
+
+        Ok(Scalar::from_i32(0))
+    }
+}
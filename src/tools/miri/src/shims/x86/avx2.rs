@@ -0,0 +1,196 @@
+use rustc_span::Symbol;
+use rustc_target::spec::abi::Abi;
+
+use super::{
+    blend_by_mask, expect_target_feature, gather, horizontal_bin_op, int_abs_simd,
+    int_bin_op_simd, movemask, multiple_sum_abs_diff, multiply_add_pairs, multiply_even_lanes,
+    pack_simd, permute, read_imm8, read_low_u64, shift_simd_by_scalar, shift_simd_by_simd,
+    shuffle_bytes, sum_abs_diff, swap_halves, IntBinOp, ShiftOp,
+};
+use crate::*;
+use shims::foreign_items::EmulateByNameResult;
+
+impl<'mir, 'tcx: 'mir> EvalContextExt<'mir, 'tcx> for crate::MiriInterpCx<'mir, 'tcx> {}
+pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
+    fn emulate_x86_avx2_intrinsic(
+        &mut self,
+        link_name: Symbol,
+        abi: Abi,
+        args: &[OpTy<'tcx, Provenance>],
+        dest: &PlaceTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx, EmulateByNameResult<'mir, 'tcx>> {
+        let this = self.eval_context_mut();
+        expect_target_feature(this, link_name, "avx2")?;
+        // Prefix should have already been checked.
+        let unprefixed_name = link_name.as_str().strip_prefix("llvm.x86.avx2.").unwrap();
+
+        match unprefixed_name {
+            // Used to implement `_mm256_abs_epi8`, `_mm256_abs_epi16` and `_mm256_abs_epi32`.
+            "pabs.b" | "pabs.w" | "pabs.d" => {
+                let [op] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                int_abs_simd(this, op, dest)?;
+            }
+            // Used to implement `_mm256_avg_epu8`, `_mm256_mulhrs_epi16`, `_mm256_sign_epi8`,
+            // `_mm256_max_epi16`, `_mm256_adds_epu8` and friends.
+            "pavg.b" | "pavg.w" | "pmulh.w" | "pmulhu.w" | "pmul.hr.sw" | "psign.b" | "psign.w"
+            | "psign.d" | "pmaxs.b" | "pmaxs.w" | "pmaxs.d" | "pmaxu.b" | "pmaxu.w"
+            | "pmaxu.d" | "pmins.b" | "pmins.w" | "pmins.d" | "pminu.b" | "pminu.w"
+            | "pminu.d" | "padds.b" | "padds.w" | "paddus.b" | "paddus.w" | "psubs.b"
+            | "psubs.w" | "psubus.b" | "psubus.w" => {
+                let [left, right] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                let which = match unprefixed_name.split_once('.').unwrap().0 {
+                    "pavg" => IntBinOp::AvgU,
+                    "pmulh" => IntBinOp::MulHighS,
+                    "pmulhu" => IntBinOp::MulHighU,
+                    "pmul" => IntBinOp::MulHighRoundScale,
+                    "psign" => IntBinOp::Sign,
+                    "pmaxs" => IntBinOp::MaxS,
+                    "pmaxu" => IntBinOp::MaxU,
+                    "pmins" => IntBinOp::MinS,
+                    "pminu" => IntBinOp::MinU,
+                    "padds" => IntBinOp::AddSatS,
+                    "paddus" => IntBinOp::AddSatU,
+                    "psubs" => IntBinOp::SubSatS,
+                    "psubus" => IntBinOp::SubSatU,
+                    _ => unreachable!(),
+                };
+                int_bin_op_simd(this, which, left, right, dest)?;
+            }
+            // Used to implement `_mm256_hadd_epi16`, `_mm256_hsubs_epi16` and friends.
+            "phadd.w" | "phadd.d" | "phadd.sw" | "phsub.w" | "phsub.d" | "phsub.sw" => {
+                let [left, right] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                let which = match unprefixed_name {
+                    "phadd.w" | "phadd.d" => IntBinOp::Add,
+                    "phadd.sw" => IntBinOp::AddSatS,
+                    "phsub.w" | "phsub.d" => IntBinOp::Sub,
+                    "phsub.sw" => IntBinOp::SubSatS,
+                    _ => unreachable!(),
+                };
+                horizontal_bin_op(this, which, left, right, dest)?;
+            }
+            // Used to implement `_mm256_madd_epi16` and `_mm256_maddubs_epi16`.
+            "pmadd.wd" | "pmadd.ub.sw" => {
+                let [left, right] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                let unsigned_saturating = unprefixed_name == "pmadd.ub.sw";
+                multiply_add_pairs(this, left, right, unsigned_saturating, dest)?;
+            }
+            // Used to implement `_mm256_mul_epi32` and `_mm256_mul_epu32`.
+            "pmul.dq" | "pmulu.dq" => {
+                let [left, right] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                let signed = unprefixed_name == "pmul.dq";
+                multiply_even_lanes(this, left, right, signed, dest)?;
+            }
+            // Used to implement `_mm256_movemask_epi8`.
+            "pmovmskb" => {
+                let [op] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                movemask(this, op, dest)?;
+            }
+            // Used to implement `_mm256_packs_epi16`, `_mm256_packus_epi32` and friends.
+            "packsswb" | "packssdw" | "packuswb" | "packusdw" => {
+                let [left, right] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                let unsigned_saturation = unprefixed_name.starts_with("packus");
+                pack_simd(this, left, right, unsigned_saturation, dest)?;
+            }
+            // Used to implement `_mm256_sad_epu8`.
+            "psad.bw" => {
+                let [left, right] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                sum_abs_diff(this, left, right, dest)?;
+            }
+            // Used to implement `_mm256_mpsadbw_epu8`.
+            "mpsadbw" => {
+                let [left, right, imm] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                multiple_sum_abs_diff(this, left, right, imm, dest)?;
+            }
+            // Used to implement `_mm256_shuffle_epi8`.
+            "pshuf.b" => {
+                let [left, right] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                shuffle_bytes(this, left, right, dest)?;
+            }
+            // Used to implement `_mm256_sll_epi16`, `_mm256_sra_epi32` and friends, which shift
+            // all lanes by the low 64 bits of `count`.
+            "psll.w" | "psll.d" | "psll.q" | "psrl.w" | "psrl.d" | "psrl.q" | "psra.w"
+            | "psra.d" => {
+                let [left, count] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                let count = read_low_u64(this, count)?;
+                let which = ShiftOp::from_name(unprefixed_name);
+                shift_simd_by_scalar(this, which, left, count.into(), dest)?;
+            }
+            // Used to implement `_mm256_slli_epi16`, `_mm256_srai_epi32` and friends.
+            "pslli.w" | "pslli.d" | "pslli.q" | "psrli.w" | "psrli.d" | "psrli.q" | "psrai.w"
+            | "psrai.d" => {
+                let [left, count] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                let count = this.read_scalar(count)?.to_u32()?;
+                let which = ShiftOp::from_name(unprefixed_name);
+                shift_simd_by_scalar(this, which, left, count.into(), dest)?;
+            }
+            // Used to implement `_mm_sllv_epi32`, `_mm256_srav_epi32` and friends, which shift
+            // each lane by its own count.
+            "psllv.d" | "psllv.d.256" | "psllv.q" | "psllv.q.256" | "psrlv.d" | "psrlv.d.256"
+            | "psrlv.q" | "psrlv.q.256" | "psrav.d" | "psrav.d.256" => {
+                let [left, right] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                let which = ShiftOp::from_name(unprefixed_name);
+                shift_simd_by_simd(this, which, left, right, dest)?;
+            }
+            // Used to implement `_mm256_blendv_epi8`.
+            "pblendvb" => {
+                let [left, right, mask] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                blend_by_mask(this, left, right, mask, dest)?;
+            }
+            // Used to implement `_mm256_permutevar8x32_epi32` and
+            // `_mm256_permutevar8x32_ps`.
+            "permd" | "permps" => {
+                let [left, indices] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                permute(this, left, indices, dest)?;
+            }
+            // Used to implement `_mm256_permute2x128_si256`.
+            "vperm2i128" => {
+                let [left, right, imm] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                let imm = read_imm8(this, imm)?;
+                swap_halves(this, left, right, imm, dest)?;
+            }
+            // Used to implement the `_mm_i32gather_epi32`, `_mm256_mask_i64gather_pd` and
+            // related functions.
+            "gather.d.d" | "gather.d.d.256" | "gather.d.q" | "gather.d.q.256" | "gather.q.d"
+            | "gather.q.d.256" | "gather.q.q" | "gather.q.q.256" | "gather.d.pd"
+            | "gather.d.pd.256" | "gather.q.pd" | "gather.q.pd.256" | "gather.d.ps"
+            | "gather.d.ps.256" | "gather.q.ps" | "gather.q.ps.256" => {
+                let [src, base, offsets, mask, scale] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                gather(this, src, base, offsets, mask, scale, dest)?;
+            }
+            _ => return Ok(EmulateByNameResult::NotSupported),
+        }
+        Ok(EmulateByNameResult::NeedsJumping)
+    }
+}
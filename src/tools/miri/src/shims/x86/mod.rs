@@ -0,0 +1,1211 @@
+//! Shims for the `llvm.x86.*` intrinsics used by `core::arch::{x86, x86_64}`.
+//!
+//! The intrinsics are grouped by the target feature that provides them. Calling an intrinsic
+//! whose target feature is not enabled is UB, since on real hardware it would execute an
+//! illegal instruction (or worse, a different one).
+//!
+//! The helpers in this module implement operations that are shared between several feature
+//! levels, and work on vectors of any length, so that e.g. the SSE2 and AVX2 versions of an
+//! operation can use the same code.
+
+use std::cmp::Ordering;
+
+use rustc_apfloat::{Float, Round, Status};
+use rustc_span::Symbol;
+use rustc_target::abi::{Align, Size};
+use rustc_target::spec::abi::Abi;
+
+use crate::helpers::convert::Truncate;
+use crate::*;
+use shims::foreign_items::EmulateByNameResult;
+
+pub mod avx2;
+pub mod sse;
+pub mod sse2;
+pub mod sse41;
+pub mod ssse3;
+
+impl<'mir, 'tcx: 'mir> EvalContextExt<'mir, 'tcx> for crate::MiriInterpCx<'mir, 'tcx> {}
+pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
+    fn emulate_x86_intrinsic(
+        &mut self,
+        link_name: Symbol,
+        abi: Abi,
+        args: &[OpTy<'tcx, Provenance>],
+        dest: &PlaceTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx, EmulateByNameResult<'mir, 'tcx>> {
+        let this = self.eval_context_mut();
+        // Prefix should have already been checked.
+        let unprefixed_name = link_name.as_str().strip_prefix("llvm.x86.").unwrap();
+
+        match unprefixed_name {
+            "addcarry.64" if this.tcx.sess.target.arch == "x86_64" => {
+                // Computes u8+u64+u64, returning tuple (u8,u64) comprising the output carry and
+                // truncated sum.
+                let [c_in, a, b] = this.check_shim(abi, Abi::Unadjusted, link_name, args)?;
+                let c_in = this.read_scalar(c_in)?.to_u8()?;
+                let a = this.read_scalar(a)?.to_u64()?;
+                let b = this.read_scalar(b)?.to_u64()?;
+
+                // Adding two u64 and a u8 cannot wrap in a u128.
+                #[allow(clippy::integer_arithmetic)]
+                let wide_sum = u128::from(c_in) + u128::from(a) + u128::from(b);
+                #[allow(clippy::integer_arithmetic)] // it's a u128, we can shift by 64
+                let (c_out, sum) = ((wide_sum >> 64).truncate::<u8>(), wide_sum.truncate::<u64>());
+
+                let c_out_field = this.place_field(dest, 0)?;
+                this.write_scalar(Scalar::from_u8(c_out), &c_out_field)?;
+                let sum_field = this.place_field(dest, 1)?;
+                this.write_scalar(Scalar::from_u64(sum), &sum_field)?;
+            }
+            // `pause` does not need SSE2 to be enabled, so it is not handled by the SSE2 shims.
+            "sse2.pause" => {
+                let [] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                this.yield_active_thread();
+            }
+
+            name if name.starts_with("sse.") =>
+                return sse::EvalContextExt::emulate_x86_sse_intrinsic(
+                    this, link_name, abi, args, dest,
+                ),
+            name if name.starts_with("sse2.") =>
+                return sse2::EvalContextExt::emulate_x86_sse2_intrinsic(
+                    this, link_name, abi, args, dest,
+                ),
+            name if name.starts_with("ssse3.") =>
+                return ssse3::EvalContextExt::emulate_x86_ssse3_intrinsic(
+                    this, link_name, abi, args, dest,
+                ),
+            name if name.starts_with("sse41.") =>
+                return sse41::EvalContextExt::emulate_x86_sse41_intrinsic(
+                    this, link_name, abi, args, dest,
+                ),
+            name if name.starts_with("avx2.") =>
+                return avx2::EvalContextExt::emulate_x86_avx2_intrinsic(
+                    this, link_name, abi, args, dest,
+                ),
+
+            _ => return Ok(EmulateByNameResult::NotSupported),
+        }
+        Ok(EmulateByNameResult::NeedsJumping)
+    }
+}
+
+/// Throws UB if `target_feature` is not enabled for the current target.
+fn expect_target_feature<'tcx>(
+    this: &crate::MiriInterpCx<'_, 'tcx>,
+    link_name: Symbol,
+    target_feature: &str,
+) -> InterpResult<'tcx> {
+    if !this.tcx.sess.unstable_target_features.contains(&Symbol::intern(target_feature)) {
+        throw_ub_format!(
+            "attempted to call intrinsic `{link_name}` that requires missing target feature {target_feature}"
+        );
+    }
+    Ok(())
+}
+
+/// Reads an immediate operand, of which only the low 8 bits are relevant.
+fn read_imm8<'tcx>(
+    this: &crate::MiriInterpCx<'_, 'tcx>,
+    op: &OpTy<'tcx, Provenance>,
+) -> InterpResult<'tcx, u8> {
+    let bits = this.read_scalar(op)?.to_bits(op.layout.size)?;
+    Ok(bits.truncate())
+}
+
+/// Reads the low 64 bits of the vector `op`, which is how the shift intrinsics with a vector
+/// count interpret their count.
+fn read_low_u64<'tcx>(
+    this: &crate::MiriInterpCx<'_, 'tcx>,
+    op: &OpTy<'tcx, Provenance>,
+) -> InterpResult<'tcx, u64> {
+    let (op, _) = this.operand_to_simd(op)?;
+    let low = op.offset(Size::ZERO, this.machine.layouts.u64, this)?;
+    this.read_scalar(&low.into())?.to_u64()
+}
+
+/// Creates a scalar of `size` from the low bits of `value`, wrapping around like an `as` cast.
+fn int_to_scalar_wrapping(value: i128, size: Size) -> Scalar<Provenance> {
+    #[allow(clippy::cast_sign_loss)] // we only want the bit pattern
+    Scalar::from_uint(size.truncate(value as u128), size)
+}
+
+/// Reads a lane as a signed integer.
+fn read_int_lane<'tcx>(
+    this: &crate::MiriInterpCx<'_, 'tcx>,
+    op: &MPlaceTy<'tcx, Provenance>,
+    i: u64,
+) -> InterpResult<'tcx, i128> {
+    let lane = this.mplace_index(op, i)?;
+    this.read_scalar(&lane.into())?.to_int(lane.layout.size)
+}
+
+/// Reads a lane as an unsigned integer. The result is returned as `i128`, which can hold all
+/// lanes of x86 vectors, so that it can be combined with signed lanes.
+fn read_uint_lane<'tcx>(
+    this: &crate::MiriInterpCx<'_, 'tcx>,
+    op: &MPlaceTy<'tcx, Provenance>,
+    i: u64,
+) -> InterpResult<'tcx, i128> {
+    let lane = this.mplace_index(op, i)?;
+    let value = this.read_scalar(&lane.into())?.to_uint(lane.layout.size)?;
+    Ok(i128::try_from(value).unwrap())
+}
+
+/// Reads a lane as a float of type `F`.
+fn read_float_lane<'tcx, F: Float>(
+    this: &crate::MiriInterpCx<'_, 'tcx>,
+    op: &MPlaceTy<'tcx, Provenance>,
+    i: u64,
+) -> InterpResult<'tcx, F> {
+    let lane = this.mplace_index(op, i)?;
+    Ok(F::from_bits(this.read_scalar(&lane.into())?.to_bits(lane.layout.size)?))
+}
+
+/// Writes `value` to a lane, wrapping it around to the width of the lane.
+fn write_int_lane<'tcx>(
+    this: &mut crate::MiriInterpCx<'_, 'tcx>,
+    dest: &MPlaceTy<'tcx, Provenance>,
+    i: u64,
+    value: i128,
+) -> InterpResult<'tcx> {
+    let lane = this.mplace_index(dest, i)?;
+    this.write_scalar(int_to_scalar_wrapping(value, lane.layout.size), &lane.into())
+}
+
+/// Writes the float `value` to a lane.
+fn write_float_lane<'tcx, F: Float>(
+    this: &mut crate::MiriInterpCx<'_, 'tcx>,
+    dest: &MPlaceTy<'tcx, Provenance>,
+    i: u64,
+    value: F,
+) -> InterpResult<'tcx> {
+    let lane = this.mplace_index(dest, i)?;
+    this.write_scalar(Scalar::from_uint(value.to_bits(), lane.layout.size), &lane.into())
+}
+
+/// Copies lane `i` of `op` to lane `i` of `dest`.
+fn copy_lane<'tcx>(
+    this: &mut crate::MiriInterpCx<'_, 'tcx>,
+    op: &MPlaceTy<'tcx, Provenance>,
+    dest: &MPlaceTy<'tcx, Provenance>,
+    i: u64,
+) -> InterpResult<'tcx> {
+    let value = this.read_immediate(&this.mplace_index(op, i)?.into())?;
+    this.write_immediate(*value, &this.mplace_index(dest, i)?.into())
+}
+
+/// The number of 128-bit chunks in `op`. Most AVX2 operations that move data between lanes
+/// work on each of these chunks separately, exactly like the SSE version of the operation.
+#[allow(clippy::integer_arithmetic)] // the divisor is not zero
+fn num_chunks(op: &MPlaceTy<'_, Provenance>) -> u64 {
+    let bytes = op.layout.size.bytes();
+    assert_eq!(bytes % 16, 0, "x86 vectors are made of 128-bit chunks");
+    bytes / 16
+}
+
+#[derive(Copy, Clone)]
+enum FloatBinOp {
+    /// Comparison with one of the first 8 predicates of `cmpps`. The result is all-1 if the
+    /// comparison is true and all-0 otherwise.
+    Cmp(u8),
+    /// Minimum of x86: returns `right` if either operand is NaN or both are zero.
+    Min,
+    /// Maximum of x86: returns `right` if either operand is NaN or both are zero.
+    Max,
+}
+
+impl FloatBinOp {
+    /// Decodes the predicate of `cmpps`, `cmpss`, `cmppd` and `cmpsd`.
+    fn cmp_from_imm<'tcx>(imm: u8, intrinsic: Symbol) -> InterpResult<'tcx, Self> {
+        if imm > 7 {
+            throw_unsup_format!(
+                "invalid predicate {imm} for `{intrinsic}`, SSE only supports predicates 0 to 7"
+            );
+        }
+        Ok(FloatBinOp::Cmp(imm))
+    }
+}
+
+/// Compares `left` and `right` with one of the predicates of `cmpps`, in the order
+/// `EQ`, `LT`, `LE`, `UNORD`, `NEQ`, `NLT`, `NLE`, `ORD`.
+fn float_cmp<F: Float>(left: F, right: F, predicate: u8) -> bool {
+    let ord = left.partial_cmp(&right);
+    match predicate {
+        0 => ord == Some(Ordering::Equal),
+        1 => ord == Some(Ordering::Less),
+        2 => matches!(ord, Some(Ordering::Less | Ordering::Equal)),
+        3 => ord.is_none(),
+        4 => ord != Some(Ordering::Equal),
+        5 => ord != Some(Ordering::Less),
+        6 => !matches!(ord, Some(Ordering::Less | Ordering::Equal)),
+        7 => ord.is_some(),
+        _ => unreachable!(),
+    }
+}
+
+fn bin_op_float<'tcx, F: Float>(
+    this: &mut crate::MiriInterpCx<'_, 'tcx>,
+    which: FloatBinOp,
+    left: &MPlaceTy<'tcx, Provenance>,
+    right: &MPlaceTy<'tcx, Provenance>,
+    dest: &MPlaceTy<'tcx, Provenance>,
+    i: u64,
+) -> InterpResult<'tcx> {
+    let l: F = read_float_lane(this, left, i)?;
+    let r: F = read_float_lane(this, right, i)?;
+    match which {
+        FloatBinOp::Cmp(predicate) => {
+            let res = float_cmp(l, r, predicate);
+            write_int_lane(this, dest, i, if res { -1 } else { 0 })
+        }
+        FloatBinOp::Min => write_float_lane(this, dest, i, if l < r { l } else { r }),
+        FloatBinOp::Max => write_float_lane(this, dest, i, if l > r { l } else { r }),
+    }
+}
+
+/// Performs `which` on the first lanes of `left` and `right` and copies the remaining lanes
+/// from `left` (`cmpss`, `minsd`, ...).
+fn bin_op_simd_float_first<'tcx, F: Float>(
+    this: &mut crate::MiriInterpCx<'_, 'tcx>,
+    which: FloatBinOp,
+    left: &OpTy<'tcx, Provenance>,
+    right: &OpTy<'tcx, Provenance>,
+    dest: &PlaceTy<'tcx, Provenance>,
+) -> InterpResult<'tcx> {
+    let (left, left_len) = this.operand_to_simd(left)?;
+    let (right, right_len) = this.operand_to_simd(right)?;
+    let (dest, dest_len) = this.place_to_simd(dest)?;
+
+    assert_eq!(dest_len, left_len);
+    assert_eq!(dest_len, right_len);
+
+    bin_op_float::<F>(this, which, &left, &right, &dest, 0)?;
+    for i in 1..dest_len {
+        copy_lane(this, &left, &dest, i)?;
+    }
+    Ok(())
+}
+
+/// Performs `which` on all lanes of `left` and `right` (`cmpps`, `minpd`, ...).
+fn bin_op_simd_float_all<'tcx, F: Float>(
+    this: &mut crate::MiriInterpCx<'_, 'tcx>,
+    which: FloatBinOp,
+    left: &OpTy<'tcx, Provenance>,
+    right: &OpTy<'tcx, Provenance>,
+    dest: &PlaceTy<'tcx, Provenance>,
+) -> InterpResult<'tcx> {
+    let (left, left_len) = this.operand_to_simd(left)?;
+    let (right, right_len) = this.operand_to_simd(right)?;
+    let (dest, dest_len) = this.place_to_simd(dest)?;
+
+    assert_eq!(dest_len, left_len);
+    assert_eq!(dest_len, right_len);
+
+    for i in 0..dest_len {
+        bin_op_float::<F>(this, which, &left, &right, &dest, i)?;
+    }
+    Ok(())
+}
+
+/// Compares the first lanes of `left` and `right` (`comiss`, `ucomisd`, ...), returning 1 if
+/// the comparison is true and 0 otherwise. Unordered operands compare as not equal.
+///
+/// The `comi` and `ucomi` variants only differ in which NaNs raise a floating-point exception,
+/// which Miri does not track.
+fn compare_first<'tcx, F: Float>(
+    this: &mut crate::MiriInterpCx<'_, 'tcx>,
+    ordering: &str,
+    left: &OpTy<'tcx, Provenance>,
+    right: &OpTy<'tcx, Provenance>,
+    dest: &PlaceTy<'tcx, Provenance>,
+) -> InterpResult<'tcx> {
+    let (left, _) = this.operand_to_simd(left)?;
+    let (right, _) = this.operand_to_simd(right)?;
+    let l: F = read_float_lane(this, &left, 0)?;
+    let r: F = read_float_lane(this, &right, 0)?;
+    let res = match ordering {
+        "eq" => l == r,
+        "lt" => l < r,
+        "le" => l <= r,
+        "gt" => l > r,
+        "ge" => l >= r,
+        "neq" => l != r,
+        _ => unreachable!(),
+    };
+    this.write_scalar(Scalar::from_i32(res.into()), dest)
+}
+
+#[derive(Copy, Clone)]
+enum FloatUnaryOp {
+    Sqrt,
+    /// Reciprocal. The hardware only computes an approximation, but we compute the exact value,
+    /// which is within the error bounds.
+    Rcp,
+    /// Reciprocal of the square root, with the same precision as `Rcp`.
+    Rsqrt,
+}
+
+fn unary_op_float<'tcx>(
+    this: &mut crate::MiriInterpCx<'_, 'tcx>,
+    which: FloatUnaryOp,
+    op: &MPlaceTy<'tcx, Provenance>,
+    dest: &MPlaceTy<'tcx, Provenance>,
+    i: u64,
+) -> InterpResult<'tcx> {
+    let op = this.read_scalar(&this.mplace_index(op, i)?.into())?;
+    let dest = this.mplace_index(dest, i)?;
+    // FIXME: using host floats, since apfloat does not implement `sqrt`.
+    let res = match dest.layout.size.bytes() {
+        4 => {
+            let f = f32::from_bits(op.to_u32()?);
+            let res = match which {
+                FloatUnaryOp::Sqrt => f.sqrt(),
+                FloatUnaryOp::Rcp => f.recip(),
+                FloatUnaryOp::Rsqrt => f.sqrt().recip(),
+            };
+            Scalar::from_u32(res.to_bits())
+        }
+        8 => {
+            let f = f64::from_bits(op.to_u64()?);
+            let res = match which {
+                FloatUnaryOp::Sqrt => f.sqrt(),
+                FloatUnaryOp::Rcp => f.recip(),
+                FloatUnaryOp::Rsqrt => f.sqrt().recip(),
+            };
+            Scalar::from_u64(res.to_bits())
+        }
+        size => span_bug!(this.cur_span(), "unexpected float size {size}"),
+    };
+    this.write_scalar(res, &dest.into())
+}
+
+/// Performs `which` on the first lane of `op` and copies the remaining lanes (`sqrtss`, ...).
+fn unary_op_simd_float_first<'tcx>(
+    this: &mut crate::MiriInterpCx<'_, 'tcx>,
+    which: FloatUnaryOp,
+    op: &OpTy<'tcx, Provenance>,
+    dest: &PlaceTy<'tcx, Provenance>,
+) -> InterpResult<'tcx> {
+    let (op, op_len) = this.operand_to_simd(op)?;
+    let (dest, dest_len) = this.place_to_simd(dest)?;
+
+    assert_eq!(dest_len, op_len);
+
+    unary_op_float(this, which, &op, &dest, 0)?;
+    for i in 1..dest_len {
+        copy_lane(this, &op, &dest, i)?;
+    }
+    Ok(())
+}
+
+/// Performs `which` on all lanes of `op` (`sqrtps`, ...).
+fn unary_op_simd_float_all<'tcx>(
+    this: &mut crate::MiriInterpCx<'_, 'tcx>,
+    which: FloatUnaryOp,
+    op: &OpTy<'tcx, Provenance>,
+    dest: &PlaceTy<'tcx, Provenance>,
+) -> InterpResult<'tcx> {
+    let (op, op_len) = this.operand_to_simd(op)?;
+    let (dest, dest_len) = this.place_to_simd(dest)?;
+
+    assert_eq!(dest_len, op_len);
+
+    for i in 0..dest_len {
+        unary_op_float(this, which, &op, &dest, i)?;
+    }
+    Ok(())
+}
+
+/// Decodes the rounding mode of `roundps` and friends.
+fn rounding_from_imm<'tcx>(rounding: u8) -> InterpResult<'tcx, Round> {
+    // Bit 3 only suppresses the precision exception, which Miri does not track.
+    match rounding & 0b0111 {
+        0b000 => Ok(Round::NearestTiesToEven),
+        0b001 => Ok(Round::TowardNegative),
+        0b010 => Ok(Round::TowardPositive),
+        0b011 => Ok(Round::TowardZero),
+        // Bit 2 selects the rounding mode of MXCSR, which Miri does not support changing from
+        // the default.
+        _ => Ok(Round::NearestTiesToEven),
+    }
+}
+
+/// Rounds the first lane of `right` to an integer and copies the remaining lanes from `left`
+/// (`roundss`, `roundsd`).
+fn round_first<'tcx, F: Float>(
+    this: &mut crate::MiriInterpCx<'_, 'tcx>,
+    left: &OpTy<'tcx, Provenance>,
+    right: &OpTy<'tcx, Provenance>,
+    rounding: &OpTy<'tcx, Provenance>,
+    dest: &PlaceTy<'tcx, Provenance>,
+) -> InterpResult<'tcx> {
+    let (left, left_len) = this.operand_to_simd(left)?;
+    let (right, right_len) = this.operand_to_simd(right)?;
+    let (dest, dest_len) = this.place_to_simd(dest)?;
+
+    assert_eq!(dest_len, left_len);
+    assert_eq!(dest_len, right_len);
+
+    let rounding = rounding_from_imm(read_imm8(this, rounding)?)?;
+    let op: F = read_float_lane(this, &right, 0)?;
+    write_float_lane(this, &dest, 0, op.round_to_integral(rounding).value)?;
+    for i in 1..dest_len {
+        copy_lane(this, &left, &dest, i)?;
+    }
+    Ok(())
+}
+
+/// Rounds all lanes of `op` to integers (`roundps`, `roundpd`).
+fn round_all<'tcx, F: Float>(
+    this: &mut crate::MiriInterpCx<'_, 'tcx>,
+    op: &OpTy<'tcx, Provenance>,
+    rounding: &OpTy<'tcx, Provenance>,
+    dest: &PlaceTy<'tcx, Provenance>,
+) -> InterpResult<'tcx> {
+    let (op, op_len) = this.operand_to_simd(op)?;
+    let (dest, dest_len) = this.place_to_simd(dest)?;
+
+    assert_eq!(dest_len, op_len);
+
+    let rounding = rounding_from_imm(read_imm8(this, rounding)?)?;
+    for i in 0..dest_len {
+        let op: F = read_float_lane(this, &op, i)?;
+        write_float_lane(this, &dest, i, op.round_to_integral(rounding).value)?;
+    }
+    Ok(())
+}
+
+/// Converts a float to a signed integer of `size` like the x86 conversion instructions:
+/// NaNs and values that do not fit return the "integer indefinite" value, which is the minimum
+/// of the integer type.
+fn float_to_int<F: Float>(f: F, round: Round, size: Size) -> i128 {
+    let mut is_exact = false;
+    let res = f.to_i128_r(size.bits_usize(), round, &mut is_exact);
+    if res.status.contains(Status::INVALID_OP) { size.signed_int_min() } else { res.value }
+}
+
+/// Converts the first lane of `op` to an integer (`cvtss2si`, `cvttsd2si64`, ...).
+fn float_to_int_first<'tcx, F: Float>(
+    this: &mut crate::MiriInterpCx<'_, 'tcx>,
+    op: &OpTy<'tcx, Provenance>,
+    round: Round,
+    dest: &PlaceTy<'tcx, Provenance>,
+) -> InterpResult<'tcx> {
+    let (op, _) = this.operand_to_simd(op)?;
+    let f: F = read_float_lane(this, &op, 0)?;
+    let res = float_to_int(f, round, dest.layout.size);
+    this.write_scalar(Scalar::from_int(res, dest.layout.size), dest)
+}
+
+/// Converts all lanes of `op` to integers (`cvtps2dq`, `cvttpd2dq`, ...). If `dest` has more
+/// lanes than `op`, the remaining ones are zeroed.
+fn float_to_int_all<'tcx, F: Float>(
+    this: &mut crate::MiriInterpCx<'_, 'tcx>,
+    op: &OpTy<'tcx, Provenance>,
+    round: Round,
+    dest: &PlaceTy<'tcx, Provenance>,
+) -> InterpResult<'tcx> {
+    let (op, op_len) = this.operand_to_simd(op)?;
+    let (dest, dest_len) = this.place_to_simd(dest)?;
+
+    assert!(dest_len >= op_len);
+
+    for i in 0..dest_len {
+        let res = if i < op_len {
+            let f: F = read_float_lane(this, &op, i)?;
+            let size = this.mplace_index(&dest, i)?.layout.size;
+            float_to_int(f, round, size)
+        } else {
+            0
+        };
+        write_int_lane(this, &dest, i, res)?;
+    }
+    Ok(())
+}
+
+#[derive(Copy, Clone)]
+enum IntBinOp {
+    /// Wrapping addition.
+    Add,
+    /// Wrapping subtraction.
+    Sub,
+    /// Addition with signed saturation.
+    AddSatS,
+    /// Addition with unsigned saturation.
+    AddSatU,
+    /// Subtraction with signed saturation.
+    SubSatS,
+    /// Subtraction with unsigned saturation.
+    SubSatU,
+    MaxS,
+    MaxU,
+    MinS,
+    MinU,
+    /// Rounded average of unsigned integers.
+    AvgU,
+    /// High half of the product of signed integers.
+    MulHighS,
+    /// High half of the product of unsigned integers.
+    MulHighU,
+    /// High half of the product of signed 16-bit integers, scaled and rounded (`pmulhrsw`).
+    MulHighRoundScale,
+    /// Negates, zeroes or keeps `left` depending on whether `right` is negative, zero or
+    /// positive (`psign`).
+    Sign,
+}
+
+#[allow(clippy::integer_arithmetic)] // the lanes are at most 64 bits wide, so `i128` cannot overflow
+fn int_bin_op<'tcx>(
+    which: IntBinOp,
+    left: Scalar<Provenance>,
+    right: Scalar<Provenance>,
+    size: Size,
+) -> InterpResult<'tcx, i128> {
+    let signed = |op: Scalar<Provenance>| op.to_int(size);
+    let unsigned =
+        |op: Scalar<Provenance>| op.to_uint(size).map(|op| i128::try_from(op).unwrap());
+    let signed_range = |res: i128| res.clamp(size.signed_int_min(), size.signed_int_max());
+    let unsigned_range =
+        |res: i128| res.clamp(0, i128::try_from(size.unsigned_int_max()).unwrap());
+    Ok(match which {
+        IntBinOp::Add => signed(left)? + signed(right)?,
+        IntBinOp::Sub => signed(left)? - signed(right)?,
+        IntBinOp::AddSatS => signed_range(signed(left)? + signed(right)?),
+        IntBinOp::AddSatU => unsigned_range(unsigned(left)? + unsigned(right)?),
+        IntBinOp::SubSatS => signed_range(signed(left)? - signed(right)?),
+        IntBinOp::SubSatU => unsigned_range(unsigned(left)? - unsigned(right)?),
+        IntBinOp::MaxS => signed(left)?.max(signed(right)?),
+        IntBinOp::MaxU => unsigned(left)?.max(unsigned(right)?),
+        IntBinOp::MinS => signed(left)?.min(signed(right)?),
+        IntBinOp::MinU => unsigned(left)?.min(unsigned(right)?),
+        IntBinOp::AvgU => (unsigned(left)? + unsigned(right)? + 1) >> 1,
+        IntBinOp::MulHighS => (signed(left)? * signed(right)?) >> size.bits(),
+        IntBinOp::MulHighU => (unsigned(left)? * unsigned(right)?) >> size.bits(),
+        IntBinOp::MulHighRoundScale => (((signed(left)? * signed(right)?) >> 14) + 1) >> 1,
+        IntBinOp::Sign => {
+            let left = signed(left)?;
+            match signed(right)?.cmp(&0) {
+                Ordering::Less => -left,
+                Ordering::Equal => 0,
+                Ordering::Greater => left,
+            }
+        }
+    })
+}
+
+/// Performs `which` on all lanes of `left` and `right`.
+fn int_bin_op_simd<'tcx>(
+    this: &mut crate::MiriInterpCx<'_, 'tcx>,
+    which: IntBinOp,
+    left: &OpTy<'tcx, Provenance>,
+    right: &OpTy<'tcx, Provenance>,
+    dest: &PlaceTy<'tcx, Provenance>,
+) -> InterpResult<'tcx> {
+    let (left, left_len) = this.operand_to_simd(left)?;
+    let (right, right_len) = this.operand_to_simd(right)?;
+    let (dest, dest_len) = this.place_to_simd(dest)?;
+
+    assert_eq!(dest_len, left_len);
+    assert_eq!(dest_len, right_len);
+
+    for i in 0..dest_len {
+        let left = this.read_scalar(&this.mplace_index(&left, i)?.into())?;
+        let right = this.read_scalar(&this.mplace_index(&right, i)?.into())?;
+        let size = this.mplace_index(&dest, i)?.layout.size;
+        write_int_lane(this, &dest, i, int_bin_op(which, left, right, size)?)?;
+    }
+    Ok(())
+}
+
+/// Performs `which` on adjacent pairs of lanes (`phaddw`, `phsubsw`, ...). Within each 128-bit
+/// chunk, the results from `left` are stored in the lower half and those from `right` in the
+/// upper half.
+#[allow(clippy::integer_arithmetic)] // lane indices are bounded by the vector length
+fn horizontal_bin_op<'tcx>(
+    this: &mut crate::MiriInterpCx<'_, 'tcx>,
+    which: IntBinOp,
+    left: &OpTy<'tcx, Provenance>,
+    right: &OpTy<'tcx, Provenance>,
+    dest: &PlaceTy<'tcx, Provenance>,
+) -> InterpResult<'tcx> {
+    let (left, left_len) = this.operand_to_simd(left)?;
+    let (right, right_len) = this.operand_to_simd(right)?;
+    let (dest, dest_len) = this.place_to_simd(dest)?;
+
+    assert_eq!(dest_len, left_len);
+    assert_eq!(dest_len, right_len);
+
+    let chunk_len = dest_len / num_chunks(&dest);
+    let half_len = chunk_len / 2;
+    for chunk in 0..num_chunks(&dest) {
+        for (half, op) in [&left, &right].into_iter().enumerate() {
+            let half = u64::try_from(half).unwrap();
+            for j in 0..half_len {
+                let src = chunk * chunk_len + 2 * j;
+                let first = this.read_scalar(&this.mplace_index(op, src)?.into())?;
+                let second = this.read_scalar(&this.mplace_index(op, src + 1)?.into())?;
+                let dest_index = chunk * chunk_len + half * half_len + j;
+                let size = this.mplace_index(&dest, dest_index)?.layout.size;
+                let res = int_bin_op(which, first, second, size)?;
+                write_int_lane(this, &dest, dest_index, res)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Computes the absolute value of all lanes of `op`, where the minimum wraps around to itself
+/// (`pabsb`, ...).
+fn int_abs_simd<'tcx>(
+    this: &mut crate::MiriInterpCx<'_, 'tcx>,
+    op: &OpTy<'tcx, Provenance>,
+    dest: &PlaceTy<'tcx, Provenance>,
+) -> InterpResult<'tcx> {
+    let (op, op_len) = this.operand_to_simd(op)?;
+    let (dest, dest_len) = this.place_to_simd(dest)?;
+
+    assert_eq!(dest_len, op_len);
+
+    for i in 0..dest_len {
+        let res = read_int_lane(this, &op, i)?.abs();
+        write_int_lane(this, &dest, i, res)?;
+    }
+    Ok(())
+}
+
+#[derive(Copy, Clone)]
+enum ShiftOp {
+    Left,
+    RightLogic,
+    RightArith,
+}
+
+impl ShiftOp {
+    /// Decodes the kind of shift from the name of a shift intrinsic (`psll.w`, `psrai.d`,
+    /// `psrlv.q.256`, ...).
+    fn from_name(name: &str) -> Self {
+        match &name[..4] {
+            "psll" => ShiftOp::Left,
+            "psrl" => ShiftOp::RightLogic,
+            "psra" => ShiftOp::RightArith,
+            _ => bug!("`{name}` is not a shift intrinsic"),
+        }
+    }
+}
+
+/// Shifts `value`, a lane of `size`, by `count` bits. Unlike in Rust, counts that are at least
+/// as large as the lane width are fine: they shift out all bits, or fill the lane with the sign
+/// bit for arithmetic right shifts.
+#[allow(clippy::integer_arithmetic)] // the shift amounts are checked to be in range
+fn shift_int<'tcx>(
+    which: ShiftOp,
+    value: Scalar<Provenance>,
+    count: u128,
+    size: Size,
+) -> InterpResult<'tcx, i128> {
+    let bits = u128::from(size.bits());
+    Ok(match which {
+        ShiftOp::Left if count >= bits => 0,
+        ShiftOp::Left => i128::try_from(size.truncate(value.to_uint(size)? << count)).unwrap(),
+        ShiftOp::RightLogic if count >= bits => 0,
+        ShiftOp::RightLogic => i128::try_from(value.to_uint(size)? >> count).unwrap(),
+        ShiftOp::RightArith => value.to_int(size)? >> count.min(bits - 1),
+    })
+}
+
+/// Shifts all lanes of `left` by the same `count` (`psllw`, `psrai.d`, ...).
+fn shift_simd_by_scalar<'tcx>(
+    this: &mut crate::MiriInterpCx<'_, 'tcx>,
+    which: ShiftOp,
+    left: &OpTy<'tcx, Provenance>,
+    count: u128,
+    dest: &PlaceTy<'tcx, Provenance>,
+) -> InterpResult<'tcx> {
+    let (left, left_len) = this.operand_to_simd(left)?;
+    let (dest, dest_len) = this.place_to_simd(dest)?;
+
+    assert_eq!(dest_len, left_len);
+
+    for i in 0..dest_len {
+        let left = this.mplace_index(&left, i)?;
+        let value = this.read_scalar(&left.into())?;
+        write_int_lane(this, &dest, i, shift_int(which, value, count, left.layout.size)?)?;
+    }
+    Ok(())
+}
+
+/// Shifts each lane of `left` by the corresponding lane of `right` (`psllvd`, ...).
+fn shift_simd_by_simd<'tcx>(
+    this: &mut crate::MiriInterpCx<'_, 'tcx>,
+    which: ShiftOp,
+    left: &OpTy<'tcx, Provenance>,
+    right: &OpTy<'tcx, Provenance>,
+    dest: &PlaceTy<'tcx, Provenance>,
+) -> InterpResult<'tcx> {
+    let (left, left_len) = this.operand_to_simd(left)?;
+    let (right, right_len) = this.operand_to_simd(right)?;
+    let (dest, dest_len) = this.place_to_simd(dest)?;
+
+    assert_eq!(dest_len, left_len);
+    assert_eq!(dest_len, right_len);
+
+    for i in 0..dest_len {
+        let left = this.mplace_index(&left, i)?;
+        let value = this.read_scalar(&left.into())?;
+        let right = this.mplace_index(&right, i)?;
+        let count = this.read_scalar(&right.into())?.to_uint(right.layout.size)?;
+        write_int_lane(this, &dest, i, shift_int(which, value, count, left.layout.size)?)?;
+    }
+    Ok(())
+}
+
+/// Narrows the signed lanes of `left` and `right` to half their width, with signed or unsigned
+/// saturation (`packsswb`, `packusdw`, ...). Within each 128-bit chunk, the lanes from `left`
+/// are stored in the lower half and those from `right` in the upper half.
+#[allow(clippy::integer_arithmetic)] // lane indices are bounded by the vector length
+fn pack_simd<'tcx>(
+    this: &mut crate::MiriInterpCx<'_, 'tcx>,
+    left: &OpTy<'tcx, Provenance>,
+    right: &OpTy<'tcx, Provenance>,
+    unsigned_saturation: bool,
+    dest: &PlaceTy<'tcx, Provenance>,
+) -> InterpResult<'tcx> {
+    let (left, left_len) = this.operand_to_simd(left)?;
+    let (right, right_len) = this.operand_to_simd(right)?;
+    let (dest, dest_len) = this.place_to_simd(dest)?;
+
+    assert_eq!(left_len, right_len);
+    assert_eq!(dest_len, left_len * 2);
+
+    let chunk_len = left_len / num_chunks(&left);
+    for chunk in 0..num_chunks(&left) {
+        for (half, op) in [&left, &right].into_iter().enumerate() {
+            let half = u64::try_from(half).unwrap();
+            for j in 0..chunk_len {
+                let value = read_int_lane(this, op, chunk * chunk_len + j)?;
+                let dest_index = (2 * chunk + half) * chunk_len + j;
+                let size = this.mplace_index(&dest, dest_index)?.layout.size;
+                let res = if unsigned_saturation {
+                    value.clamp(0, i128::try_from(size.unsigned_int_max()).unwrap())
+                } else {
+                    value.clamp(size.signed_int_min(), size.signed_int_max())
+                };
+                write_int_lane(this, &dest, dest_index, res)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Multiplies the lanes of `left` and `right` and adds adjacent pairs of products into lanes
+/// of twice the width. `pmaddwd` reads both operands as signed and wraps around, while
+/// `pmaddubsw` reads `left` as unsigned and saturates the sums.
+#[allow(clippy::integer_arithmetic)] // the lanes are at most 16 bits wide, so `i128` cannot overflow
+fn multiply_add_pairs<'tcx>(
+    this: &mut crate::MiriInterpCx<'_, 'tcx>,
+    left: &OpTy<'tcx, Provenance>,
+    right: &OpTy<'tcx, Provenance>,
+    unsigned_saturating: bool,
+    dest: &PlaceTy<'tcx, Provenance>,
+) -> InterpResult<'tcx> {
+    let (left, left_len) = this.operand_to_simd(left)?;
+    let (right, right_len) = this.operand_to_simd(right)?;
+    let (dest, dest_len) = this.place_to_simd(dest)?;
+
+    assert_eq!(left_len, right_len);
+    assert_eq!(left_len, dest_len * 2);
+
+    for i in 0..dest_len {
+        let mut sum = 0;
+        for j in [2 * i, 2 * i + 1] {
+            let left = if unsigned_saturating {
+                read_uint_lane(this, &left, j)?
+            } else {
+                read_int_lane(this, &left, j)?
+            };
+            sum += left * read_int_lane(this, &right, j)?;
+        }
+        if unsigned_saturating {
+            let size = this.mplace_index(&dest, i)?.layout.size;
+            sum = sum.clamp(size.signed_int_min(), size.signed_int_max());
+        }
+        write_int_lane(this, &dest, i, sum)?;
+    }
+    Ok(())
+}
+
+/// Sums the absolute differences of the unsigned bytes of `left` and `right`, storing each sum
+/// of 8 bytes in one 64-bit lane (`psadbw`).
+#[allow(clippy::integer_arithmetic)] // the lanes are 8 bits wide, so `i128` cannot overflow
+fn sum_abs_diff<'tcx>(
+    this: &mut crate::MiriInterpCx<'_, 'tcx>,
+    left: &OpTy<'tcx, Provenance>,
+    right: &OpTy<'tcx, Provenance>,
+    dest: &PlaceTy<'tcx, Provenance>,
+) -> InterpResult<'tcx> {
+    let (left, left_len) = this.operand_to_simd(left)?;
+    let (right, right_len) = this.operand_to_simd(right)?;
+    let (dest, dest_len) = this.place_to_simd(dest)?;
+
+    assert_eq!(left_len, right_len);
+    assert_eq!(left_len, dest_len * 8);
+
+    for i in 0..dest_len {
+        let mut sum = 0;
+        for j in 8 * i..8 * (i + 1) {
+            sum += (read_uint_lane(this, &left, j)? - read_uint_lane(this, &right, j)?).abs();
+        }
+        write_int_lane(this, &dest, i, sum)?;
+    }
+    Ok(())
+}
+
+/// Multiplies the even lanes of `left` and `right` into lanes of twice the width (`pmuludq`,
+/// `pmuldq`).
+#[allow(clippy::integer_arithmetic)] // the lanes are 32 bits wide, so `i128` cannot overflow
+fn multiply_even_lanes<'tcx>(
+    this: &mut crate::MiriInterpCx<'_, 'tcx>,
+    left: &OpTy<'tcx, Provenance>,
+    right: &OpTy<'tcx, Provenance>,
+    signed: bool,
+    dest: &PlaceTy<'tcx, Provenance>,
+) -> InterpResult<'tcx> {
+    let (left, left_len) = this.operand_to_simd(left)?;
+    let (right, right_len) = this.operand_to_simd(right)?;
+    let (dest, dest_len) = this.place_to_simd(dest)?;
+
+    assert_eq!(left_len, right_len);
+    assert_eq!(left_len, dest_len * 2);
+
+    for i in 0..dest_len {
+        let res = if signed {
+            read_int_lane(this, &left, 2 * i)? * read_int_lane(this, &right, 2 * i)?
+        } else {
+            read_uint_lane(this, &left, 2 * i)? * read_uint_lane(this, &right, 2 * i)?
+        };
+        write_int_lane(this, &dest, i, res)?;
+    }
+    Ok(())
+}
+
+/// Collects the most significant bit of every lane of `op` into an `i32` (`pmovmskb`,
+/// `movmskps`, ...).
+#[allow(clippy::integer_arithmetic)] // vectors have at most 32 lanes
+fn movemask<'tcx>(
+    this: &mut crate::MiriInterpCx<'_, 'tcx>,
+    op: &OpTy<'tcx, Provenance>,
+    dest: &PlaceTy<'tcx, Provenance>,
+) -> InterpResult<'tcx> {
+    let (op, op_len) = this.operand_to_simd(op)?;
+
+    assert!(op_len <= 32);
+
+    let mut res = 0u32;
+    for i in 0..op_len {
+        // The most significant bit is the sign bit, including for floats.
+        if read_int_lane(this, &op, i)? < 0 {
+            res |= 1 << i;
+        }
+    }
+    this.write_scalar(Scalar::from_u32(res), dest)
+}
+
+/// Selects each lane from `right` if the most significant bit of the corresponding lane of
+/// `mask` is set, and from `left` otherwise (`pblendvb`, `blendvps`, ...).
+fn blend_by_mask<'tcx>(
+    this: &mut crate::MiriInterpCx<'_, 'tcx>,
+    left: &OpTy<'tcx, Provenance>,
+    right: &OpTy<'tcx, Provenance>,
+    mask: &OpTy<'tcx, Provenance>,
+    dest: &PlaceTy<'tcx, Provenance>,
+) -> InterpResult<'tcx> {
+    let (left, left_len) = this.operand_to_simd(left)?;
+    let (right, right_len) = this.operand_to_simd(right)?;
+    let (mask, mask_len) = this.operand_to_simd(mask)?;
+    let (dest, dest_len) = this.place_to_simd(dest)?;
+
+    assert_eq!(dest_len, left_len);
+    assert_eq!(dest_len, right_len);
+    assert_eq!(dest_len, mask_len);
+
+    for i in 0..dest_len {
+        let src = if read_int_lane(this, &mask, i)? < 0 { &right } else { &left };
+        copy_lane(this, src, &dest, i)?;
+    }
+    Ok(())
+}
+
+/// Returns whether `op & mask` is all-zero and whether `!op & mask` is all-zero, which are the
+/// ZF and CF flags computed by `ptest`.
+fn test_bits_masked<'tcx>(
+    this: &crate::MiriInterpCx<'_, 'tcx>,
+    op: &OpTy<'tcx, Provenance>,
+    mask: &OpTy<'tcx, Provenance>,
+) -> InterpResult<'tcx, (bool, bool)> {
+    let (op, op_len) = this.operand_to_simd(op)?;
+    let (mask, mask_len) = this.operand_to_simd(mask)?;
+
+    assert_eq!(op_len, mask_len);
+
+    let mut all_zero = true;
+    let mut masked_set = true;
+    for i in 0..op_len {
+        let op = read_uint_lane(this, &op, i)?;
+        let mask = read_uint_lane(this, &mask, i)?;
+        all_zero &= op & mask == 0;
+        masked_set &= !op & mask == 0;
+    }
+    Ok((all_zero, masked_set))
+}
+
+/// Shuffles the bytes of `left` within each 128-bit chunk using the indices in `right`, where
+/// indices with the most significant bit set produce zero (`pshufb`).
+#[allow(clippy::integer_arithmetic)] // lane indices are bounded by the vector length
+fn shuffle_bytes<'tcx>(
+    this: &mut crate::MiriInterpCx<'_, 'tcx>,
+    left: &OpTy<'tcx, Provenance>,
+    right: &OpTy<'tcx, Provenance>,
+    dest: &PlaceTy<'tcx, Provenance>,
+) -> InterpResult<'tcx> {
+    let (left, left_len) = this.operand_to_simd(left)?;
+    let (right, right_len) = this.operand_to_simd(right)?;
+    let (dest, dest_len) = this.place_to_simd(dest)?;
+
+    assert_eq!(dest_len, left_len);
+    assert_eq!(dest_len, right_len);
+
+    for i in 0..dest_len {
+        let index = read_uint_lane(this, &right, i)?;
+        let res = if index & 0x80 != 0 {
+            0
+        } else {
+            let chunk_start = i / 16 * 16;
+            read_uint_lane(this, &left, chunk_start + u64::try_from(index & 0xf).unwrap())?
+        };
+        write_int_lane(this, &dest, i, res)?;
+    }
+    Ok(())
+}
+
+/// Sets each lane to the lane of `left` selected by the corresponding lane of `indices`, which
+/// may cross 128-bit chunks (`vpermd`, `vpermps`).
+#[allow(clippy::integer_arithmetic)] // `left_len` is not zero
+fn permute<'tcx>(
+    this: &mut crate::MiriInterpCx<'_, 'tcx>,
+    left: &OpTy<'tcx, Provenance>,
+    indices: &OpTy<'tcx, Provenance>,
+    dest: &PlaceTy<'tcx, Provenance>,
+) -> InterpResult<'tcx> {
+    let (left, left_len) = this.operand_to_simd(left)?;
+    let (indices, indices_len) = this.operand_to_simd(indices)?;
+    let (dest, dest_len) = this.place_to_simd(dest)?;
+
+    assert_eq!(dest_len, left_len);
+    assert_eq!(dest_len, indices_len);
+
+    for i in 0..dest_len {
+        let index = u64::try_from(read_uint_lane(this, &indices, i)?).unwrap() % left_len;
+        let value = this.read_immediate(&this.mplace_index(&left, index)?.into())?;
+        this.write_immediate(*value, &this.mplace_index(&dest, i)?.into())?;
+    }
+    Ok(())
+}
+
+/// Fills each half of `dest` with one of the halves of `left` and `right`, or with zeroes,
+/// as selected by 4 bits of `imm` for each half (`vperm2i128`).
+#[allow(clippy::integer_arithmetic)] // lane indices are bounded by the vector length
+fn swap_halves<'tcx>(
+    this: &mut crate::MiriInterpCx<'_, 'tcx>,
+    left: &OpTy<'tcx, Provenance>,
+    right: &OpTy<'tcx, Provenance>,
+    imm: u8,
+    dest: &PlaceTy<'tcx, Provenance>,
+) -> InterpResult<'tcx> {
+    let (left, left_len) = this.operand_to_simd(left)?;
+    let (right, right_len) = this.operand_to_simd(right)?;
+    let (dest, dest_len) = this.place_to_simd(dest)?;
+
+    assert_eq!(dest_len, left_len);
+    assert_eq!(dest_len, right_len);
+
+    let half_len = dest_len / 2;
+    for half in 0..2 {
+        let control = imm >> (4 * half);
+        for j in 0..half_len {
+            let dest_index = half * half_len + j;
+            if control & 0b1000 != 0 {
+                write_int_lane(this, &dest, dest_index, 0)?;
+            } else {
+                let src = if control & 0b10 == 0 { &left } else { &right };
+                let src_index = u64::from(control & 1) * half_len + j;
+                let value = this.read_immediate(&this.mplace_index(src, src_index)?.into())?;
+                this.write_immediate(*value, &this.mplace_index(&dest, dest_index)?.into())?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Replaces the lane of `left` selected by bits 4 and 5 of `imm` with the lane of `right`
+/// selected by bits 6 and 7, and then zeroes the lanes selected by bits 0 to 3 (`insertps`).
+#[allow(clippy::integer_arithmetic)] // the shifts are by constants smaller than 8
+fn insert_ps<'tcx>(
+    this: &mut crate::MiriInterpCx<'_, 'tcx>,
+    left: &OpTy<'tcx, Provenance>,
+    right: &OpTy<'tcx, Provenance>,
+    imm: &OpTy<'tcx, Provenance>,
+    dest: &PlaceTy<'tcx, Provenance>,
+) -> InterpResult<'tcx> {
+    let (left, left_len) = this.operand_to_simd(left)?;
+    let (right, right_len) = this.operand_to_simd(right)?;
+    let (dest, dest_len) = this.place_to_simd(dest)?;
+
+    assert_eq!(dest_len, left_len);
+    assert_eq!(dest_len, right_len);
+    assert_eq!(dest_len, 4);
+
+    let imm = read_imm8(this, imm)?;
+    let src_index = u64::from(imm >> 6);
+    let dest_index = u64::from((imm >> 4) & 0b11);
+    for i in 0..dest_len {
+        if imm & (1 << i) != 0 {
+            write_int_lane(this, &dest, i, 0)?;
+        } else if i == dest_index {
+            let value = this.read_immediate(&this.mplace_index(&right, src_index)?.into())?;
+            this.write_immediate(*value, &this.mplace_index(&dest, i)?.into())?;
+        } else {
+            copy_lane(this, &left, &dest, i)?;
+        }
+    }
+    Ok(())
+}
+
+/// Computes the dot product of the lanes of `left` and `right` selected by the upper 4 bits
+/// of `imm`, and stores it in the lanes selected by the lower 4 bits (`dpps`, `dppd`).
+#[allow(clippy::integer_arithmetic)] // vectors have at most 4 lanes
+fn dot_product<'tcx, F: Float>(
+    this: &mut crate::MiriInterpCx<'_, 'tcx>,
+    left: &OpTy<'tcx, Provenance>,
+    right: &OpTy<'tcx, Provenance>,
+    imm: &OpTy<'tcx, Provenance>,
+    dest: &PlaceTy<'tcx, Provenance>,
+) -> InterpResult<'tcx> {
+    let (left, left_len) = this.operand_to_simd(left)?;
+    let (right, right_len) = this.operand_to_simd(right)?;
+    let (dest, dest_len) = this.place_to_simd(dest)?;
+
+    assert_eq!(dest_len, left_len);
+    assert_eq!(dest_len, right_len);
+    assert!(dest_len <= 4);
+
+    let imm = read_imm8(this, imm)?;
+    let mut products = Vec::new();
+    for i in 0..dest_len {
+        let product = if imm & (0x10 << i) != 0 {
+            let l: F = read_float_lane(this, &left, i)?;
+            let r: F = read_float_lane(this, &right, i)?;
+            (l * r).value
+        } else {
+            F::ZERO
+        };
+        products.push(product);
+    }
+    // The hardware adds the products pairwise, e.g. `(p0 + p1) + (p2 + p3)` for `dpps`, which
+    // matters for the rounding of the result.
+    while products.len() > 1 {
+        products = products.chunks(2).map(|pair| (pair[0] + pair[1]).value).collect();
+    }
+    let sum = products[0];
+    for i in 0..dest_len {
+        let res = if imm & (1 << i) != 0 { sum } else { F::ZERO };
+        write_float_lane(this, &dest, i, res)?;
+    }
+    Ok(())
+}
+
+/// Computes the sums of absolute differences of 8 overlapping groups of 4 unsigned bytes in
+/// `left` with one group of 4 bytes in `right`, selected by `imm` (`mpsadbw`). Each 128-bit
+/// chunk uses the next 3 bits of `imm`.
+#[allow(clippy::integer_arithmetic)] // lane indices are bounded by the vector length
+fn multiple_sum_abs_diff<'tcx>(
+    this: &mut crate::MiriInterpCx<'_, 'tcx>,
+    left: &OpTy<'tcx, Provenance>,
+    right: &OpTy<'tcx, Provenance>,
+    imm: &OpTy<'tcx, Provenance>,
+    dest: &PlaceTy<'tcx, Provenance>,
+) -> InterpResult<'tcx> {
+    let (left, left_len) = this.operand_to_simd(left)?;
+    let (right, right_len) = this.operand_to_simd(right)?;
+    let (dest, dest_len) = this.place_to_simd(dest)?;
+
+    assert_eq!(left_len, right_len);
+    assert_eq!(left_len, dest_len * 2);
+
+    let imm = read_imm8(this, imm)?;
+    for chunk in 0..num_chunks(&left) {
+        let control = imm >> (3 * chunk);
+        let left_start = chunk * 16 + u64::from((control >> 2) & 1) * 4;
+        let right_start = chunk * 16 + u64::from(control & 0b11) * 4;
+        for j in 0..8 {
+            let mut sum = 0;
+            for k in 0..4 {
+                let l = read_uint_lane(this, &left, left_start + j + k)?;
+                let r = read_uint_lane(this, &right, right_start + k)?;
+                sum += (l - r).abs();
+            }
+            write_int_lane(this, &dest, chunk * 8 + j, sum)?;
+        }
+    }
+    Ok(())
+}
+
+/// Loads the lanes of `dest` selected by the most significant bit of `mask` from
+/// `base + offsets[i] * scale`, and copies the other lanes from `src` (the AVX2 gathers). If
+/// there are fewer offsets than lanes, the remaining lanes are zeroed.
+fn gather<'tcx>(
+    this: &mut crate::MiriInterpCx<'_, 'tcx>,
+    src: &OpTy<'tcx, Provenance>,
+    base: &OpTy<'tcx, Provenance>,
+    offsets: &OpTy<'tcx, Provenance>,
+    mask: &OpTy<'tcx, Provenance>,
+    scale: &OpTy<'tcx, Provenance>,
+    dest: &PlaceTy<'tcx, Provenance>,
+) -> InterpResult<'tcx> {
+    let (src, src_len) = this.operand_to_simd(src)?;
+    let (offsets, offsets_len) = this.operand_to_simd(offsets)?;
+    let (mask, mask_len) = this.operand_to_simd(mask)?;
+    let (dest, dest_len) = this.place_to_simd(dest)?;
+
+    assert_eq!(dest_len, src_len);
+    assert_eq!(dest_len, mask_len);
+
+    let base = this.read_pointer(base)?;
+    let scale = this.read_scalar(scale)?.to_i8()?;
+    if !matches!(scale, 1 | 2 | 4 | 8) {
+        throw_unsup_format!("invalid gather scale {scale}");
+    }
+
+    for i in 0..dest_len {
+        let dest_lane = this.mplace_index(&dest, i)?;
+        if i >= offsets_len {
+            write_int_lane(this, &dest, i, 0)?;
+        } else if read_int_lane(this, &mask, i)? < 0 {
+            let offset = i64::try_from(read_int_lane(this, &offsets, i)?).unwrap();
+            let ptr = base.wrapping_signed_offset(offset.wrapping_mul(scale.into()), this);
+            // The loads do not need to be aligned.
+            this.mem_copy(
+                ptr,
+                Align::ONE,
+                dest_lane.ptr,
+                Align::ONE,
+                dest_lane.layout.size,
+                /*nonoverlapping*/ true,
+            )?;
+        } else {
+            copy_lane(this, &src, &dest, i)?;
+        }
+    }
+    Ok(())
+}
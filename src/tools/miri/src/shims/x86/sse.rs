@@ -0,0 +1,143 @@
+use rustc_apfloat::{ieee::Single, Round};
+use rustc_span::Symbol;
+use rustc_target::abi::Size;
+use rustc_target::spec::abi::Abi;
+
+use super::{
+    bin_op_simd_float_all, bin_op_simd_float_first, compare_first, expect_target_feature,
+    float_to_int_first, movemask, read_imm8, unary_op_simd_float_all, unary_op_simd_float_first,
+    FloatBinOp, FloatUnaryOp,
+};
+use crate::*;
+use shims::foreign_items::EmulateByNameResult;
+
+/// The value of the MXCSR register at startup: all exceptions are masked, and floats are
+/// rounded to nearest. Miri does not support any other floating-point environment.
+const DEFAULT_MXCSR: u32 = 0x1F80;
+
+impl<'mir, 'tcx: 'mir> EvalContextExt<'mir, 'tcx> for crate::MiriInterpCx<'mir, 'tcx> {}
+pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
+    fn emulate_x86_sse_intrinsic(
+        &mut self,
+        link_name: Symbol,
+        abi: Abi,
+        args: &[OpTy<'tcx, Provenance>],
+        dest: &PlaceTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx, EmulateByNameResult<'mir, 'tcx>> {
+        let this = self.eval_context_mut();
+        expect_target_feature(this, link_name, "sse")?;
+        // Prefix should have already been checked.
+        let unprefixed_name = link_name.as_str().strip_prefix("llvm.x86.sse.").unwrap();
+
+        match unprefixed_name {
+            // Used to implement `_mm_min_ss`, `_mm_max_ps` and friends.
+            "min.ss" | "max.ss" | "min.ps" | "max.ps" => {
+                let [left, right] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                let which = if unprefixed_name.starts_with("min") {
+                    FloatBinOp::Min
+                } else {
+                    FloatBinOp::Max
+                };
+                if unprefixed_name.ends_with(".ss") {
+                    bin_op_simd_float_first::<Single>(this, which, left, right, dest)?;
+                } else {
+                    bin_op_simd_float_all::<Single>(this, which, left, right, dest)?;
+                }
+            }
+            // Used to implement the `_mm_cmp*_ss` and `_mm_cmp*_ps` functions.
+            "cmp.ss" | "cmp.ps" => {
+                let [left, right, imm] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                let which = FloatBinOp::cmp_from_imm(read_imm8(this, imm)?, link_name)?;
+                if unprefixed_name == "cmp.ss" {
+                    bin_op_simd_float_first::<Single>(this, which, left, right, dest)?;
+                } else {
+                    bin_op_simd_float_all::<Single>(this, which, left, right, dest)?;
+                }
+            }
+            // Used to implement `_mm_sqrt_ss`, `_mm_rcp_ps` and friends.
+            "sqrt.ss" | "rcp.ss" | "rsqrt.ss" | "sqrt.ps" | "rcp.ps" | "rsqrt.ps" => {
+                let [op] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                let (name, kind) = unprefixed_name.split_once('.').unwrap();
+                let which = match name {
+                    "sqrt" => FloatUnaryOp::Sqrt,
+                    "rcp" => FloatUnaryOp::Rcp,
+                    "rsqrt" => FloatUnaryOp::Rsqrt,
+                    _ => unreachable!(),
+                };
+                if kind == "ss" {
+                    unary_op_simd_float_first(this, which, op, dest)?;
+                } else {
+                    unary_op_simd_float_all(this, which, op, dest)?;
+                }
+            }
+            // Used to implement the `_mm_comi*_ss` and `_mm_ucomi*_ss` functions.
+            "comieq.ss" | "comilt.ss" | "comile.ss" | "comigt.ss" | "comige.ss" | "comineq.ss"
+            | "ucomieq.ss" | "ucomilt.ss" | "ucomile.ss" | "ucomigt.ss" | "ucomige.ss"
+            | "ucomineq.ss" => {
+                let [left, right] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                let ordering = unprefixed_name
+                    .trim_start_matches('u')
+                    .strip_prefix("comi")
+                    .and_then(|name| name.strip_suffix(".ss"))
+                    .unwrap();
+                compare_first::<Single>(this, ordering, left, right, dest)?;
+            }
+            // Used to implement `_mm_cvtss_si32`, `_mm_cvttss_si64` and friends. The `cvt`
+            // versions round to nearest, the `cvtt` versions truncate.
+            "cvtss2si" | "cvtss2si64" | "cvttss2si" | "cvttss2si64" => {
+                let [op] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                let round = if unprefixed_name.starts_with("cvtt") {
+                    Round::TowardZero
+                } else {
+                    Round::NearestTiesToEven
+                };
+                float_to_int_first::<Single>(this, op, round, dest)?;
+            }
+            // Used to implement `_mm_movemask_ps`.
+            "movmsk.ps" => {
+                let [op] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                movemask(this, op, dest)?;
+            }
+            // Used to implement `_mm_sfence`.
+            "sfence" => {
+                let [] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                this.atomic_fence(AtomicFenceOrd::SeqCst)?;
+            }
+            // Used to implement `_mm_getcsr`.
+            "stmxcsr" => {
+                let [ptr] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                // The pointer does not need to be aligned.
+                let place = this.deref_operand(ptr)?;
+                let place = place.offset(Size::ZERO, this.machine.layouts.u32, this)?;
+                this.write_scalar(Scalar::from_u32(DEFAULT_MXCSR), &place.into())?;
+            }
+            // Used to implement `_mm_setcsr`.
+            "ldmxcsr" => {
+                let [ptr] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                let place = this.deref_operand(ptr)?;
+                let place = place.offset(Size::ZERO, this.machine.layouts.u32, this)?;
+                let mxcsr = this.read_scalar(&place.into())?.to_u32()?;
+                // The lowest 6 bits are the exception flags, which Miri does not track.
+                if mxcsr & !0b11_1111 != DEFAULT_MXCSR {
+                    throw_unsup_format!(
+                        "setting MXCSR to {mxcsr:#x} is not supported, Miri only supports the default floating-point environment"
+                    );
+                }
+            }
+            _ => return Ok(EmulateByNameResult::NotSupported),
+        }
+        Ok(EmulateByNameResult::NeedsJumping)
+    }
+}
@@ -0,0 +1,249 @@
+use rustc_apfloat::{
+    ieee::{Double, Single},
+    FloatConvert, Round,
+};
+use rustc_span::Symbol;
+use rustc_target::abi::Size;
+use rustc_target::spec::abi::Abi;
+
+use super::{
+    bin_op_simd_float_all, bin_op_simd_float_first, compare_first, copy_lane,
+    expect_target_feature, float_to_int_all, float_to_int_first, int_bin_op_simd, movemask,
+    multiply_add_pairs, multiply_even_lanes, pack_simd, read_float_lane, read_imm8, read_int_lane,
+    read_low_u64, shift_simd_by_scalar, sum_abs_diff, unary_op_simd_float_all,
+    unary_op_simd_float_first, write_float_lane, FloatBinOp, FloatUnaryOp, IntBinOp, ShiftOp,
+};
+use crate::*;
+use shims::foreign_items::EmulateByNameResult;
+
+impl<'mir, 'tcx: 'mir> EvalContextExt<'mir, 'tcx> for crate::MiriInterpCx<'mir, 'tcx> {}
+pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
+    fn emulate_x86_sse2_intrinsic(
+        &mut self,
+        link_name: Symbol,
+        abi: Abi,
+        args: &[OpTy<'tcx, Provenance>],
+        dest: &PlaceTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx, EmulateByNameResult<'mir, 'tcx>> {
+        let this = self.eval_context_mut();
+        expect_target_feature(this, link_name, "sse2")?;
+        // Prefix should have already been checked.
+        let unprefixed_name = link_name.as_str().strip_prefix("llvm.x86.sse2.").unwrap();
+
+        match unprefixed_name {
+            // Used to implement `_mm_min_sd`, `_mm_max_pd` and friends.
+            "min.sd" | "max.sd" | "min.pd" | "max.pd" => {
+                let [left, right] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                let which = if unprefixed_name.starts_with("min") {
+                    FloatBinOp::Min
+                } else {
+                    FloatBinOp::Max
+                };
+                if unprefixed_name.ends_with(".sd") {
+                    bin_op_simd_float_first::<Double>(this, which, left, right, dest)?;
+                } else {
+                    bin_op_simd_float_all::<Double>(this, which, left, right, dest)?;
+                }
+            }
+            // Used to implement the `_mm_cmp*_sd` and `_mm_cmp*_pd` functions.
+            "cmp.sd" | "cmp.pd" => {
+                let [left, right, imm] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                let which = FloatBinOp::cmp_from_imm(read_imm8(this, imm)?, link_name)?;
+                if unprefixed_name == "cmp.sd" {
+                    bin_op_simd_float_first::<Double>(this, which, left, right, dest)?;
+                } else {
+                    bin_op_simd_float_all::<Double>(this, which, left, right, dest)?;
+                }
+            }
+            // Used to implement `_mm_sqrt_sd` and `_mm_sqrt_pd`.
+            "sqrt.sd" | "sqrt.pd" => {
+                let [op] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                if unprefixed_name == "sqrt.sd" {
+                    unary_op_simd_float_first(this, FloatUnaryOp::Sqrt, op, dest)?;
+                } else {
+                    unary_op_simd_float_all(this, FloatUnaryOp::Sqrt, op, dest)?;
+                }
+            }
+            // Used to implement the `_mm_comi*_sd` and `_mm_ucomi*_sd` functions.
+            "comieq.sd" | "comilt.sd" | "comile.sd" | "comigt.sd" | "comige.sd" | "comineq.sd"
+            | "ucomieq.sd" | "ucomilt.sd" | "ucomile.sd" | "ucomigt.sd" | "ucomige.sd"
+            | "ucomineq.sd" => {
+                let [left, right] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                let ordering = unprefixed_name
+                    .trim_start_matches('u')
+                    .strip_prefix("comi")
+                    .and_then(|name| name.strip_suffix(".sd"))
+                    .unwrap();
+                compare_first::<Double>(this, ordering, left, right, dest)?;
+            }
+            // Used to implement `_mm_cvtsd_si32`, `_mm_cvttsd_si64` and friends. The `cvt`
+            // versions round to nearest, the `cvtt` versions truncate.
+            "cvtsd2si" | "cvtsd2si64" | "cvttsd2si" | "cvttsd2si64" => {
+                let [op] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                let round = if unprefixed_name.starts_with("cvtt") {
+                    Round::TowardZero
+                } else {
+                    Round::NearestTiesToEven
+                };
+                float_to_int_first::<Double>(this, op, round, dest)?;
+            }
+            // Used to implement `_mm_cvtps_epi32`, `_mm_cvttpd_epi32` and friends.
+            "cvtps2dq" | "cvttps2dq" | "cvtpd2dq" | "cvttpd2dq" => {
+                let [op] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                let round = if unprefixed_name.starts_with("cvtt") {
+                    Round::TowardZero
+                } else {
+                    Round::NearestTiesToEven
+                };
+                if unprefixed_name.ends_with("ps2dq") {
+                    float_to_int_all::<Single>(this, op, round, dest)?;
+                } else {
+                    float_to_int_all::<Double>(this, op, round, dest)?;
+                }
+            }
+            // Used to implement `_mm_cvtsd_ss`, which converts the first lane of `right` and
+            // copies the remaining lanes from `left`.
+            "cvtsd2ss" => {
+                let [left, right] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                let (left, left_len) = this.operand_to_simd(left)?;
+                let (right, _) = this.operand_to_simd(right)?;
+                let (dest, dest_len) = this.place_to_simd(dest)?;
+
+                assert_eq!(dest_len, left_len);
+
+                let op: Double = read_float_lane(this, &right, 0)?;
+                let res: Single = op.convert(&mut false).value;
+                write_float_lane(this, &dest, 0, res)?;
+                for i in 1..dest_len {
+                    copy_lane(this, &left, &dest, i)?;
+                }
+            }
+            // Used to implement `_mm_movemask_pd` and `_mm_movemask_epi8`.
+            "movmsk.pd" | "pmovmskb.128" => {
+                let [op] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                movemask(this, op, dest)?;
+            }
+            // Used to implement `_mm_packs_epi16`, `_mm_packs_epi32` and `_mm_packus_epi16`.
+            "packsswb.128" | "packssdw.128" | "packuswb.128" => {
+                let [left, right] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                pack_simd(this, left, right, unprefixed_name == "packuswb.128", dest)?;
+            }
+            // Used to implement `_mm_madd_epi16`.
+            "pmadd.wd" => {
+                let [left, right] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                multiply_add_pairs(this, left, right, /*unsigned_saturating*/ false, dest)?;
+            }
+            // Used to implement `_mm_sad_epu8`.
+            "psad.bw" => {
+                let [left, right] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                sum_abs_diff(this, left, right, dest)?;
+            }
+            // Used to implement `_mm_mul_epu32`.
+            "pmulu.dq" => {
+                let [left, right] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                multiply_even_lanes(this, left, right, /*signed*/ false, dest)?;
+            }
+            // Used to implement `_mm_avg_epu8`, `_mm_mulhi_epi16`, `_mm_max_epi16`,
+            // `_mm_adds_epu8` and friends.
+            "pavg.b" | "pavg.w" | "pmulh.w" | "pmulhu.w" | "pmaxs.w" | "pmaxu.b" | "pmins.w"
+            | "pminu.b" | "padds.b" | "padds.w" | "paddus.b" | "paddus.w" | "psubs.b"
+            | "psubs.w" | "psubus.b" | "psubus.w" => {
+                let [left, right] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                let which = match unprefixed_name.split_once('.').unwrap().0 {
+                    "pavg" => IntBinOp::AvgU,
+                    "pmulh" => IntBinOp::MulHighS,
+                    "pmulhu" => IntBinOp::MulHighU,
+                    "pmaxs" => IntBinOp::MaxS,
+                    "pmaxu" => IntBinOp::MaxU,
+                    "pmins" => IntBinOp::MinS,
+                    "pminu" => IntBinOp::MinU,
+                    "padds" => IntBinOp::AddSatS,
+                    "paddus" => IntBinOp::AddSatU,
+                    "psubs" => IntBinOp::SubSatS,
+                    "psubus" => IntBinOp::SubSatU,
+                    _ => unreachable!(),
+                };
+                int_bin_op_simd(this, which, left, right, dest)?;
+            }
+            // Used to implement `_mm_sll_epi16`, `_mm_sra_epi32` and friends, which shift all
+            // lanes by the low 64 bits of `count`.
+            "psll.w" | "psll.d" | "psll.q" | "psrl.w" | "psrl.d" | "psrl.q" | "psra.w"
+            | "psra.d" => {
+                let [left, count] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                let count = read_low_u64(this, count)?;
+                let which = ShiftOp::from_name(unprefixed_name);
+                shift_simd_by_scalar(this, which, left, count.into(), dest)?;
+            }
+            // Used to implement `_mm_slli_epi16`, `_mm_srai_epi32` and friends.
+            "pslli.w" | "pslli.d" | "pslli.q" | "psrli.w" | "psrli.d" | "psrli.q" | "psrai.w"
+            | "psrai.d" => {
+                let [left, count] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                let count = this.read_scalar(count)?.to_u32()?;
+                let which = ShiftOp::from_name(unprefixed_name);
+                shift_simd_by_scalar(this, which, left, count.into(), dest)?;
+            }
+            // Used to implement `_mm_maskmoveu_si128`, which stores the bytes of `op` whose
+            // mask has the most significant bit set.
+            "maskmov.dqu" => {
+                let [op, mask, ptr] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                let (op, op_len) = this.operand_to_simd(op)?;
+                let (mask, mask_len) = this.operand_to_simd(mask)?;
+                let ptr = this.read_pointer(ptr)?;
+
+                assert_eq!(op_len, mask_len);
+
+                for i in 0..op_len {
+                    if read_int_lane(this, &mask, i)? < 0 {
+                        let ptr = ptr.wrapping_offset(Size::from_bytes(i), this);
+                        let place = MPlaceTy::from_aligned_ptr(ptr, this.machine.layouts.u8);
+                        let value = this.read_scalar(&this.mplace_index(&op, i)?.into())?;
+                        this.write_scalar(value, &place.into())?;
+                    }
+                }
+            }
+            // Used to implement `_mm_lfence` and `_mm_mfence`.
+            "lfence" | "mfence" => {
+                let [] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                this.atomic_fence(AtomicFenceOrd::SeqCst)?;
+            }
+            // Used to implement `_mm_clflush`. Miri has no caches, so this only checks that the
+            // argument is a pointer.
+            "clflush" => {
+                let [ptr] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                this.read_pointer(ptr)?;
+            }
+            _ => return Ok(EmulateByNameResult::NotSupported),
+        }
+        Ok(EmulateByNameResult::NeedsJumping)
+    }
+}
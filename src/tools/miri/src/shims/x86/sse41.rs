@@ -0,0 +1,156 @@
+use rustc_apfloat::ieee::{Double, Single};
+use rustc_span::Symbol;
+use rustc_target::spec::abi::Abi;
+
+use super::{
+    blend_by_mask, dot_product, expect_target_feature, insert_ps, int_bin_op_simd,
+    multiple_sum_abs_diff, multiply_even_lanes, pack_simd, read_uint_lane, round_all,
+    round_first, test_bits_masked, write_int_lane, IntBinOp,
+};
+use crate::*;
+use shims::foreign_items::EmulateByNameResult;
+
+impl<'mir, 'tcx: 'mir> EvalContextExt<'mir, 'tcx> for crate::MiriInterpCx<'mir, 'tcx> {}
+pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
+    fn emulate_x86_sse41_intrinsic(
+        &mut self,
+        link_name: Symbol,
+        abi: Abi,
+        args: &[OpTy<'tcx, Provenance>],
+        dest: &PlaceTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx, EmulateByNameResult<'mir, 'tcx>> {
+        let this = self.eval_context_mut();
+        expect_target_feature(this, link_name, "sse4.1")?;
+        // Prefix should have already been checked.
+        let unprefixed_name = link_name.as_str().strip_prefix("llvm.x86.sse41.").unwrap();
+
+        match unprefixed_name {
+            // Used to implement `_mm_round_ss`, `_mm_floor_sd` and friends, which round the
+            // first lane of `right` and copy the remaining lanes from `left`.
+            "round.ss" | "round.sd" => {
+                let [left, right, rounding] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                if unprefixed_name == "round.ss" {
+                    round_first::<Single>(this, left, right, rounding, dest)?;
+                } else {
+                    round_first::<Double>(this, left, right, rounding, dest)?;
+                }
+            }
+            // Used to implement `_mm_round_ps`, `_mm_ceil_pd` and friends.
+            "round.ps" | "round.pd" => {
+                let [op, rounding] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                if unprefixed_name == "round.ps" {
+                    round_all::<Single>(this, op, rounding, dest)?;
+                } else {
+                    round_all::<Double>(this, op, rounding, dest)?;
+                }
+            }
+            // Used to implement `_mm_insert_ps`.
+            "insertps" => {
+                let [left, right, imm] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                insert_ps(this, left, right, imm, dest)?;
+            }
+            // Used to implement `_mm_dp_ps` and `_mm_dp_pd`.
+            "dpps" | "dppd" => {
+                let [left, right, imm] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                if unprefixed_name == "dpps" {
+                    dot_product::<Single>(this, left, right, imm, dest)?;
+                } else {
+                    dot_product::<Double>(this, left, right, imm, dest)?;
+                }
+            }
+            // Used to implement `_mm_mpsadbw_epu8`.
+            "mpsadbw" => {
+                let [left, right, imm] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                multiple_sum_abs_diff(this, left, right, imm, dest)?;
+            }
+            // Used to implement `_mm_packus_epi32`.
+            "packusdw" => {
+                let [left, right] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                pack_simd(this, left, right, /*unsigned_saturation*/ true, dest)?;
+            }
+            // Used to implement `_mm_minpos_epu16`, which stores the minimum of the unsigned lanes
+            // of `op` in the first lane, the index of its first occurrence in the second lane, and
+            // zeroes the remaining lanes.
+            "phminposuw" => {
+                let [op] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                let (op, op_len) = this.operand_to_simd(op)?;
+                let (dest, dest_len) = this.place_to_simd(dest)?;
+
+                assert_eq!(dest_len, op_len);
+
+                let mut min = read_uint_lane(this, &op, 0)?;
+                let mut min_index = 0;
+                for i in 1..op_len {
+                    let value = read_uint_lane(this, &op, i)?;
+                    if value < min {
+                        min = value;
+                        min_index = i;
+                    }
+                }
+                write_int_lane(this, &dest, 0, min)?;
+                write_int_lane(this, &dest, 1, min_index.into())?;
+                for i in 2..dest_len {
+                    write_int_lane(this, &dest, i, 0)?;
+                }
+            }
+            // Used to implement `_mm_blendv_epi8`, `_mm_blendv_ps` and `_mm_blendv_pd`.
+            "pblendvb" | "blendvps" | "blendvpd" => {
+                let [left, right, mask] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                blend_by_mask(this, left, right, mask, dest)?;
+            }
+            // Used to implement `_mm_testz_si128`, `_mm_testc_si128` and
+            // `_mm_testnzc_si128`.
+            "ptestz" | "ptestc" | "ptestnzc" => {
+                let [op, mask] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                let (all_zero, masked_set) = test_bits_masked(this, op, mask)?;
+                let res = match unprefixed_name {
+                    "ptestz" => all_zero,
+                    "ptestc" => masked_set,
+                    "ptestnzc" => !all_zero && !masked_set,
+                    _ => unreachable!(),
+                };
+                this.write_scalar(Scalar::from_i32(res.into()), dest)?;
+            }
+            // Used to implement `_mm_mul_epi32`.
+            "pmuldq" => {
+                let [left, right] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                multiply_even_lanes(this, left, right, /*signed*/ true, dest)?;
+            }
+            // Used to implement `_mm_max_epi8`, `_mm_min_epu32` and friends.
+            "pmaxsb" | "pmaxsd" | "pmaxuw" | "pmaxud" | "pminsb" | "pminsd" | "pminuw"
+            | "pminud" => {
+                let [left, right] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                let which = match &unprefixed_name[..5] {
+                    "pmaxs" => IntBinOp::MaxS,
+                    "pmaxu" => IntBinOp::MaxU,
+                    "pmins" => IntBinOp::MinS,
+                    "pminu" => IntBinOp::MinU,
+                    _ => unreachable!(),
+                };
+                int_bin_op_simd(this, which, left, right, dest)?;
+            }
+            _ => return Ok(EmulateByNameResult::NotSupported),
+        }
+        Ok(EmulateByNameResult::NeedsJumping)
+    }
+}
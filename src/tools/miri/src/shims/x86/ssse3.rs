@@ -0,0 +1,79 @@
+use rustc_span::Symbol;
+use rustc_target::spec::abi::Abi;
+
+use super::{
+    expect_target_feature, horizontal_bin_op, int_abs_simd, int_bin_op_simd, multiply_add_pairs,
+    shuffle_bytes, IntBinOp,
+};
+use crate::*;
+use shims::foreign_items::EmulateByNameResult;
+
+impl<'mir, 'tcx: 'mir> EvalContextExt<'mir, 'tcx> for crate::MiriInterpCx<'mir, 'tcx> {}
+pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
+    fn emulate_x86_ssse3_intrinsic(
+        &mut self,
+        link_name: Symbol,
+        abi: Abi,
+        args: &[OpTy<'tcx, Provenance>],
+        dest: &PlaceTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx, EmulateByNameResult<'mir, 'tcx>> {
+        let this = self.eval_context_mut();
+        expect_target_feature(this, link_name, "ssse3")?;
+        // Prefix should have already been checked.
+        let unprefixed_name = link_name.as_str().strip_prefix("llvm.x86.ssse3.").unwrap();
+
+        match unprefixed_name {
+            // Used to implement `_mm_abs_epi8`, `_mm_abs_epi16` and `_mm_abs_epi32`.
+            "pabs.b.128" | "pabs.w.128" | "pabs.d.128" => {
+                let [op] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                int_abs_simd(this, op, dest)?;
+            }
+            // Used to implement `_mm_shuffle_epi8`.
+            "pshuf.b.128" => {
+                let [left, right] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                shuffle_bytes(this, left, right, dest)?;
+            }
+            // Used to implement `_mm_hadd_epi16`, `_mm_hsubs_epi16` and friends.
+            "phadd.w.128" | "phadd.d.128" | "phadd.sw.128" | "phsub.w.128" | "phsub.d.128"
+            | "phsub.sw.128" => {
+                let [left, right] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                let which = match unprefixed_name {
+                    "phadd.w.128" | "phadd.d.128" => IntBinOp::Add,
+                    "phadd.sw.128" => IntBinOp::AddSatS,
+                    "phsub.w.128" | "phsub.d.128" => IntBinOp::Sub,
+                    "phsub.sw.128" => IntBinOp::SubSatS,
+                    _ => unreachable!(),
+                };
+                horizontal_bin_op(this, which, left, right, dest)?;
+            }
+            // Used to implement `_mm_maddubs_epi16`.
+            "pmadd.ub.sw.128" => {
+                let [left, right] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                multiply_add_pairs(this, left, right, /*unsigned_saturating*/ true, dest)?;
+            }
+            // Used to implement `_mm_mulhrs_epi16`.
+            "pmul.hr.sw.128" => {
+                let [left, right] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                int_bin_op_simd(this, IntBinOp::MulHighRoundScale, left, right, dest)?;
+            }
+            // Used to implement `_mm_sign_epi8`, `_mm_sign_epi16` and `_mm_sign_epi32`.
+            "psign.b.128" | "psign.w.128" | "psign.d.128" => {
+                let [left, right] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                int_bin_op_simd(this, IntBinOp::Sign, left, right, dest)?;
+            }
+            _ => return Ok(EmulateByNameResult::NotSupported),
+        }
+        Ok(EmulateByNameResult::NeedsJumping)
+    }
+}
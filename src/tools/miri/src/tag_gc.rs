@@ -125,6 +125,12 @@ fn visit_tags(&self, visit: &mut dyn FnMut(BorTag)) {
     }
 }
 
+impl VisitTags for OpTy<'_, Provenance> {
+    fn visit_tags(&self, visit: &mut dyn FnMut(BorTag)) {
+        (**self).visit_tags(visit)
+    }
+}
+
 impl VisitTags for Allocation<Provenance, AllocExtra> {
     fn visit_tags(&self, visit: &mut dyn FnMut(BorTag)) {
         for prov in self.provenance().provenances() {
@@ -193,6 +193,55 @@ fn ui(mode: Mode, path: &str, target: &str, with_dependencies: Dependencies) ->
     run_tests(mode, path, target, with_dependencies)
 }
 
+/// Interprets `tests/coverage/coverage.rs` with `-Zmiri-coverage`, and checks the JSON report and
+/// the lcov tracefile that are written at the end of the run.
+fn coverage(target: &str) -> Result<()> {
+    let msg = format!("## Running coverage test against miri for {target}");
+    eprintln!("{}", msg.green().bold());
+
+    let prefix = env::temp_dir().join("miri-coverage-test");
+    let mut flag = std::ffi::OsString::from("-Zmiri-coverage=");
+    flag.push(&prefix);
+    let status = Command::new(miri_path())
+        .args(["--edition", "2018", "--target", target])
+        .arg(flag)
+        .arg("tests/coverage/coverage.rs")
+        .status()?;
+    assert!(status.success(), "miri failed on the coverage test: {status}");
+
+    // Every function is reported on its own line of the JSON report.
+    let json = std::fs::read_to_string(prefix.with_extension("json"))?;
+    let function = |name: &str| {
+        let start = format!("{{\"name\": \"{name}\", ");
+        json.lines()
+            .find(|line| line.trim_start().starts_with(&start))
+            .unwrap_or_else(|| panic!("`{name}` is missing from the coverage report:\n{json}"))
+    };
+    assert!(function("main").contains("\"unsafe_fn\": false, \"calls\": 1, "));
+    assert!(function("read").contains("\"unsafe_fn\": true, \"calls\": 1, "));
+    let never_called = function("never_called");
+    assert!(never_called.contains("\"calls\": 0, "));
+    assert!(never_called.contains("\"unsafe\": true"));
+
+    // The lines of `coverage.rs` that were executed, and some that were not.
+    let lcov = std::fs::read_to_string(prefix.with_extension("info"))?;
+    for record in [
+        "FNDA:1,main",
+        "FNDA:1,read",
+        "FNDA:0,never_called",
+        "DA:6,1",
+        "DA:8,0",
+        "DA:14,1",
+        "DA:20,0",
+    ] {
+        assert!(
+            lcov.lines().any(|line| line == record),
+            "`{record}` is missing from the lcov tracefile:\n{lcov}"
+        );
+    }
+    Ok(())
+}
+
 fn get_target() -> String {
     env::var("MIRI_TEST_TARGET").ok().unwrap_or_else(get_host)
 }
@@ -210,6 +259,7 @@ fn main() -> Result<()> {
     ui(Mode::Pass, "tests/pass-dep", &target, WithDependencies)?;
     ui(Mode::Panic, "tests/panic", &target, WithDependencies)?;
     ui(Mode::Fail { require_patterns: true }, "tests/fail", &target, WithDependencies)?;
+    coverage(&target)?;
     if cfg!(target_os = "linux") {
         ui(Mode::Pass, "tests/extern-so/pass", &target, WithoutDependencies)?;
         ui(
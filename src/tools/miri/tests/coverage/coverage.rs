@@ -0,0 +1,21 @@
+// Interpreted with `-Zmiri-coverage` by `tests/compiletest.rs`, which checks the report.
+
+fn main() {
+    let mut x = 0;
+    if x == 0 {
+        x = unsafe { read(&x) } + 1;
+    } else {
+        x = 2;
+    }
+    assert_eq!(x, 1);
+}
+
+unsafe fn read(x: *const i32) -> i32 {
+    *x
+}
+
+#[allow(dead_code)]
+fn never_called() -> i32 {
+    let x = 1;
+    unsafe { read(&x) }
+}
@@ -0,0 +1,24 @@
+// The schedule asks for a store that the load cannot read from.
+//@compile-flags: -Zmiri-preemption-rate=0 -Zmiri-compare-exchange-weak-failure-rate=0 -Zmiri-replay-schedule=tests/fail/concurrency/replay_schedule_diverged.schedule
+
+#![feature(core_intrinsics)]
+
+use std::intrinsics::atomic_load_relaxed;
+use std::sync::atomic::AtomicU32;
+use std::sync::atomic::Ordering::Relaxed;
+use std::thread::spawn;
+
+static X: AtomicU32 = AtomicU32::new(0);
+
+fn main() {
+    let j1 = spawn(|| {
+        X.store(1, Relaxed);
+        X.store(2, Relaxed);
+    });
+    let j2 = spawn(|| {
+        let x_ptr = &X as *const AtomicU32 as *const u32;
+        unsafe { atomic_load_relaxed(x_ptr) } //~ ERROR: must be at most 2 here
+    });
+    j1.join().unwrap();
+    j2.join().unwrap();
+}
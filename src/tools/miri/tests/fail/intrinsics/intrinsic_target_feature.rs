@@ -0,0 +1,30 @@
+// Ignore everything except x86 and x86_64. Any additional targets added to CI should be
+// ignored here, since the `target-feature` flags only exist on x86.
+//@ignore-target-aarch64
+//@ignore-target-arm
+//@ignore-target-avr
+//@ignore-target-mips
+//@ignore-target-powerpc
+//@ignore-target-s390x
+//@ignore-target-thumbv7em
+//@ignore-target-wasm32
+
+#![feature(link_llvm_intrinsics, repr_simd)]
+#![allow(non_camel_case_types)]
+
+#[repr(simd)]
+#[derive(Copy, Clone)]
+struct i8x16(i8, i8, i8, i8, i8, i8, i8, i8, i8, i8, i8, i8, i8, i8, i8, i8);
+
+extern "C" {
+    #[link_name = "llvm.x86.ssse3.pabs.b.128"]
+    fn pabsb(a: i8x16) -> i8x16;
+}
+
+fn main() {
+    // Without `-C target-feature=+ssse3`, calling the intrinsic is UB.
+    let a = i8x16(-1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0);
+    unsafe {
+        pabsb(a); //~ERROR: requires missing target feature ssse3
+    }
+}
@@ -0,0 +1,13 @@
+//@compile-flags: -Zmiri-tree-borrows
+// A protected `&mut` that has been written to must not be disabled by a write through an alias.
+fn write_through_alias(x: &mut i32, y: *mut i32) {
+    *x = 1;
+    unsafe { *y = 2 }; //~ ERROR: /write access through .* is forbidden/
+}
+
+fn main() {
+    let mut x = 0;
+    let xraw = &mut x as *mut i32;
+    let xref = unsafe { &mut *xraw };
+    write_through_alias(xref, xraw);
+}
@@ -0,0 +1,8 @@
+//@compile-flags: -Zmiri-tree-borrows
+// Shared references are `Frozen`, which also applies to the raw pointers derived from them.
+fn main() {
+    let x = 0u8;
+    let r = &x;
+    let p = r as *const u8 as *mut u8;
+    unsafe { *p = 1 }; //~ ERROR: /write access through .* is forbidden/
+}
@@ -0,0 +1,186 @@
+//@only-target-linux
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+fn errno() -> i32 {
+    std::io::Error::last_os_error().raw_os_error().unwrap()
+}
+
+fn eventfd() -> i32 {
+    let fd = unsafe { libc::eventfd(0, libc::EFD_NONBLOCK | libc::EFD_CLOEXEC) };
+    assert!(fd >= 0);
+    fd
+}
+
+fn signal(fd: i32) {
+    let buf = 1u64.to_ne_bytes();
+    assert_eq!(unsafe { libc::write(fd, buf.as_ptr().cast(), 8) }, 8);
+}
+
+fn drain(fd: i32) {
+    let mut buf = [0u8; 8];
+    assert_eq!(unsafe { libc::read(fd, buf.as_mut_ptr().cast(), 8) }, 8);
+}
+
+fn add(epfd: i32, fd: i32, events: i32, data: u64) {
+    let mut event = libc::epoll_event { events: events as u32, u64: data };
+    assert_eq!(unsafe { libc::epoll_ctl(epfd, libc::EPOLL_CTL_ADD, fd, &mut event) }, 0);
+}
+
+fn wait(epfd: i32, timeout: i32) -> Vec<(u32, u64)> {
+    let mut events = [libc::epoll_event { events: 0, u64: 0 }; 8];
+    let n = unsafe { libc::epoll_wait(epfd, events.as_mut_ptr(), 8, timeout) };
+    assert!(n >= 0);
+    events[..n as usize].iter().map(|event| (event.events, event.u64)).collect()
+}
+
+fn test_level_triggered() {
+    let epfd = unsafe { libc::epoll_create1(libc::EPOLL_CLOEXEC) };
+    assert!(epfd >= 0);
+    let fd = eventfd();
+    add(epfd, fd, libc::EPOLLIN, 42);
+
+    assert_eq!(wait(epfd, 0), vec![]);
+    signal(fd);
+    // The event is reported until the counter is drained.
+    assert_eq!(wait(epfd, 0), vec![(libc::EPOLLIN as u32, 42)]);
+    assert_eq!(wait(epfd, 0), vec![(libc::EPOLLIN as u32, 42)]);
+    drain(fd);
+    assert_eq!(wait(epfd, 0), vec![]);
+
+    assert_eq!(unsafe { libc::close(fd) }, 0);
+    assert_eq!(unsafe { libc::close(epfd) }, 0);
+}
+
+fn test_edge_triggered() {
+    let epfd = unsafe { libc::epoll_create1(0) };
+    let fd = eventfd();
+    add(epfd, fd, libc::EPOLLIN | libc::EPOLLOUT | libc::EPOLLET, 7);
+
+    // The initial readiness is reported once.
+    assert_eq!(wait(epfd, 0), vec![(libc::EPOLLOUT as u32, 7)]);
+    assert_eq!(wait(epfd, 0), vec![]);
+    // Every change is reported again.
+    signal(fd);
+    assert_eq!(wait(epfd, 0), vec![((libc::EPOLLIN | libc::EPOLLOUT) as u32, 7)]);
+    assert_eq!(wait(epfd, 0), vec![]);
+
+    assert_eq!(unsafe { libc::close(fd) }, 0);
+    assert_eq!(unsafe { libc::close(epfd) }, 0);
+}
+
+fn test_oneshot() {
+    let epfd = unsafe { libc::epoll_create1(0) };
+    let fd = eventfd();
+    add(epfd, fd, libc::EPOLLIN | libc::EPOLLONESHOT, 1);
+
+    signal(fd);
+    assert_eq!(wait(epfd, 0), vec![(libc::EPOLLIN as u32, 1)]);
+    assert_eq!(wait(epfd, 0), vec![]);
+    // Modifying the registration enables it again.
+    let mut event = libc::epoll_event { events: libc::EPOLLIN as u32, u64: 2 };
+    assert_eq!(unsafe { libc::epoll_ctl(epfd, libc::EPOLL_CTL_MOD, fd, &mut event) }, 0);
+    assert_eq!(wait(epfd, 0), vec![(libc::EPOLLIN as u32, 2)]);
+
+    assert_eq!(unsafe { libc::close(fd) }, 0);
+    assert_eq!(unsafe { libc::close(epfd) }, 0);
+}
+
+fn test_ctl_errors() {
+    let epfd = unsafe { libc::epoll_create1(0) };
+    let fd = eventfd();
+    let mut event = libc::epoll_event { events: libc::EPOLLIN as u32, u64: 0 };
+
+    assert_eq!(unsafe { libc::epoll_ctl(epfd, libc::EPOLL_CTL_MOD, fd, &mut event) }, -1);
+    assert_eq!(errno(), libc::ENOENT);
+    assert_eq!(unsafe { libc::epoll_ctl(epfd, libc::EPOLL_CTL_ADD, fd, &mut event) }, 0);
+    assert_eq!(unsafe { libc::epoll_ctl(epfd, libc::EPOLL_CTL_ADD, fd, &mut event) }, -1);
+    assert_eq!(errno(), libc::EEXIST);
+    assert_eq!(unsafe { libc::epoll_ctl(epfd, libc::EPOLL_CTL_ADD, epfd, &mut event) }, -1);
+    assert_eq!(errno(), libc::EINVAL);
+    assert_eq!(unsafe { libc::epoll_ctl(fd, libc::EPOLL_CTL_ADD, epfd, &mut event) }, -1);
+    assert_eq!(errno(), libc::EINVAL);
+    assert_eq!(unsafe { libc::epoll_ctl(epfd, libc::EPOLL_CTL_DEL, fd, std::ptr::null_mut()) }, 0);
+    assert_eq!(unsafe { libc::epoll_ctl(epfd, libc::EPOLL_CTL_DEL, fd, std::ptr::null_mut()) }, -1);
+    assert_eq!(errno(), libc::ENOENT);
+    assert_eq!(unsafe { libc::epoll_ctl(epfd, libc::EPOLL_CTL_ADD, 1234, &mut event) }, -1);
+    assert_eq!(errno(), libc::EBADF);
+
+    assert_eq!(unsafe { libc::close(fd) }, 0);
+    assert_eq!(unsafe { libc::close(epfd) }, 0);
+}
+
+fn test_socketpair() {
+    let epfd = unsafe { libc::epoll_create1(0) };
+    let mut fds = [-1, -1];
+    let res = unsafe {
+        libc::socketpair(
+            libc::AF_UNIX,
+            libc::SOCK_STREAM | libc::SOCK_NONBLOCK,
+            0,
+            fds.as_mut_ptr(),
+        )
+    };
+    assert_eq!(res, 0);
+    add(epfd, fds[0], libc::EPOLLIN | libc::EPOLLRDHUP, 0);
+
+    assert_eq!(wait(epfd, 0), vec![]);
+    assert_eq!(unsafe { libc::write(fds[1], b"abc".as_ptr().cast(), 3) }, 3);
+    assert_eq!(wait(epfd, 0), vec![(libc::EPOLLIN as u32, 0)]);
+    // Closing the other end is reported as a hangup.
+    assert_eq!(unsafe { libc::close(fds[1]) }, 0);
+    assert_eq!(
+        wait(epfd, 0),
+        vec![((libc::EPOLLIN | libc::EPOLLRDHUP | libc::EPOLLHUP) as u32, 0)]
+    );
+
+    assert_eq!(unsafe { libc::close(fds[0]) }, 0);
+    assert_eq!(unsafe { libc::close(epfd) }, 0);
+}
+
+fn test_blocking_wait() {
+    static mut VAL: u32 = 0;
+
+    let epfd = unsafe { libc::epoll_create1(0) };
+    let fd = eventfd();
+    add(epfd, fd, libc::EPOLLIN, 3);
+
+    let waiter = thread::spawn(move || {
+        // This blocks until the main thread signals the eventfd.
+        assert_eq!(wait(epfd, -1), vec![(libc::EPOLLIN as u32, 3)]);
+        // Observing the event synchronizes with the thread that signalled it.
+        assert_eq!(unsafe { VAL }, 1);
+    });
+
+    thread::yield_now();
+    unsafe { VAL = 1 };
+    signal(fd);
+    waiter.join().unwrap();
+
+    assert_eq!(unsafe { libc::close(fd) }, 0);
+    assert_eq!(unsafe { libc::close(epfd) }, 0);
+}
+
+fn test_timeout() {
+    let epfd = unsafe { libc::epoll_create1(0) };
+    let fd = eventfd();
+    add(epfd, fd, libc::EPOLLIN, 0);
+
+    let start = Instant::now();
+    assert_eq!(wait(epfd, 100), vec![]);
+    assert!(start.elapsed() >= Duration::from_millis(100));
+
+    assert_eq!(unsafe { libc::close(fd) }, 0);
+    assert_eq!(unsafe { libc::close(epfd) }, 0);
+}
+
+fn main() {
+    test_level_triggered();
+    test_edge_triggered();
+    test_oneshot();
+    test_ctl_errors();
+    test_socketpair();
+    test_blocking_wait();
+    test_timeout();
+}
@@ -0,0 +1,117 @@
+//@only-target-linux
+
+use std::thread;
+
+fn read_counter(fd: i32) -> Result<u64, i32> {
+    let mut buf = [0u8; 8];
+    let res = unsafe { libc::read(fd, buf.as_mut_ptr().cast(), 8) };
+    if res < 0 {
+        Err(std::io::Error::last_os_error().raw_os_error().unwrap())
+    } else {
+        assert_eq!(res, 8);
+        Ok(u64::from_ne_bytes(buf))
+    }
+}
+
+fn write_counter(fd: i32, value: u64) -> Result<(), i32> {
+    let buf = value.to_ne_bytes();
+    let res = unsafe { libc::write(fd, buf.as_ptr().cast(), 8) };
+    if res < 0 {
+        Err(std::io::Error::last_os_error().raw_os_error().unwrap())
+    } else {
+        assert_eq!(res, 8);
+        Ok(())
+    }
+}
+
+fn test_nonblocking() {
+    let fd = unsafe { libc::eventfd(3, libc::EFD_NONBLOCK | libc::EFD_CLOEXEC) };
+    assert!(fd >= 0);
+
+    // Reading takes the whole counter.
+    assert_eq!(read_counter(fd), Ok(3));
+    assert_eq!(read_counter(fd), Err(libc::EAGAIN));
+
+    write_counter(fd, 1).unwrap();
+    write_counter(fd, 2).unwrap();
+    assert_eq!(read_counter(fd), Ok(3));
+
+    // The counter cannot exceed `u64::MAX - 1`.
+    write_counter(fd, u64::MAX - 1).unwrap();
+    assert_eq!(write_counter(fd, 1), Err(libc::EAGAIN));
+    assert_eq!(write_counter(fd, u64::MAX), Err(libc::EINVAL));
+    assert_eq!(read_counter(fd), Ok(u64::MAX - 1));
+
+    // Buffers that are too small are rejected.
+    let mut buf = [0u8; 4];
+    let res = unsafe { libc::read(fd, buf.as_mut_ptr().cast(), 4) };
+    assert_eq!(res, -1);
+    assert_eq!(std::io::Error::last_os_error().raw_os_error(), Some(libc::EINVAL));
+
+    assert_eq!(unsafe { libc::close(fd) }, 0);
+}
+
+fn test_semaphore() {
+    let fd = unsafe { libc::eventfd(2, libc::EFD_NONBLOCK | libc::EFD_SEMAPHORE) };
+    assert!(fd >= 0);
+
+    // Reading decrements the counter by one.
+    assert_eq!(read_counter(fd), Ok(1));
+    assert_eq!(read_counter(fd), Ok(1));
+    assert_eq!(read_counter(fd), Err(libc::EAGAIN));
+
+    assert_eq!(unsafe { libc::close(fd) }, 0);
+}
+
+fn test_invalid_flags() {
+    let fd = unsafe { libc::eventfd(0, 0x1234_0000) };
+    assert_eq!(fd, -1);
+    assert_eq!(std::io::Error::last_os_error().raw_os_error(), Some(libc::EINVAL));
+}
+
+fn test_blocking_read() {
+    static mut VAL: u32 = 0;
+
+    let fd = unsafe { libc::eventfd(0, 0) };
+    assert!(fd >= 0);
+
+    let reader = thread::spawn(move || {
+        // This blocks until the main thread writes to the counter.
+        assert_eq!(read_counter(fd), Ok(7));
+        // The write happens-before the read returns, so this is not a data race.
+        assert_eq!(unsafe { VAL }, 1);
+    });
+
+    thread::yield_now();
+    unsafe { VAL = 1 };
+    write_counter(fd, 7).unwrap();
+    reader.join().unwrap();
+
+    assert_eq!(unsafe { libc::close(fd) }, 0);
+}
+
+fn test_blocking_write() {
+    let fd = unsafe { libc::eventfd(0, 0) };
+    assert!(fd >= 0);
+    write_counter(fd, u64::MAX - 1).unwrap();
+
+    let writer = thread::spawn(move || {
+        // This blocks until the main thread reads the counter.
+        write_counter(fd, 1).unwrap();
+    });
+
+    thread::yield_now();
+    assert_eq!(read_counter(fd), Ok(u64::MAX - 1));
+    writer.join().unwrap();
+    assert_eq!(read_counter(fd), Ok(1));
+
+    assert_eq!(unsafe { libc::close(fd) }, 0);
+}
+
+fn main() {
+    test_nonblocking();
+    test_semaphore();
+    test_invalid_flags();
+    test_blocking_read();
+    test_blocking_write();
+}
@@ -0,0 +1,133 @@
+//@ignore-target-windows: No libc on Windows
+
+use std::mem;
+use std::net::{TcpListener, TcpStream};
+use std::os::unix::io::AsRawFd;
+use std::thread;
+
+fn errno() -> i32 {
+    std::io::Error::last_os_error().raw_os_error().unwrap()
+}
+
+fn test_socket_addrs() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let fd = listener.as_raw_fd();
+
+    let mut addr: libc::sockaddr_in = unsafe { mem::zeroed() };
+    let mut len = mem::size_of::<libc::sockaddr_in>() as libc::socklen_t;
+    let res =
+        unsafe { libc::getsockname(fd, (&mut addr as *mut libc::sockaddr_in).cast(), &mut len) };
+    assert_eq!(res, 0);
+    assert_eq!(len as usize, mem::size_of::<libc::sockaddr_in>());
+    assert_eq!(addr.sin_family as i32, libc::AF_INET);
+    assert_eq!(u16::from_be(addr.sin_port), port);
+    assert_eq!(u32::from_be(addr.sin_addr.s_addr), 0x7f000001);
+
+    // Addresses that do not fit are truncated, but the full size is reported.
+    let mut buf = [0xffu8; 2];
+    let mut len = 2;
+    let res = unsafe { libc::getsockname(fd, buf.as_mut_ptr().cast(), &mut len) };
+    assert_eq!(res, 0);
+    assert_eq!(len as usize, mem::size_of::<libc::sockaddr_in>());
+
+    // A listening socket has no peer.
+    let mut len = mem::size_of::<libc::sockaddr_in>() as libc::socklen_t;
+    let res =
+        unsafe { libc::getpeername(fd, (&mut addr as *mut libc::sockaddr_in).cast(), &mut len) };
+    assert_eq!(res, -1);
+    assert_eq!(errno(), libc::ENOTCONN);
+}
+
+fn test_socket_options() {
+    let fd = unsafe { libc::socket(libc::AF_INET, libc::SOCK_DGRAM, 0) };
+    assert!(fd >= 0);
+
+    let mut value: libc::c_int = 0;
+    let mut len = mem::size_of::<libc::c_int>() as libc::socklen_t;
+    let res = unsafe {
+        libc::getsockopt(
+            fd,
+            libc::SOL_SOCKET,
+            libc::SO_TYPE,
+            (&mut value as *mut i32).cast(),
+            &mut len,
+        )
+    };
+    assert_eq!(res, 0);
+    assert_eq!(value, libc::SOCK_DGRAM);
+
+    let ttl: libc::c_int = 5;
+    let res = unsafe {
+        libc::setsockopt(fd, libc::IPPROTO_IP, libc::IP_TTL, (&ttl as *const i32).cast(), len)
+    };
+    assert_eq!(res, 0);
+    let res = unsafe {
+        libc::getsockopt(
+            fd,
+            libc::IPPROTO_IP,
+            libc::IP_TTL,
+            (&mut value as *mut i32).cast(),
+            &mut len,
+        )
+    };
+    assert_eq!(res, 0);
+    assert_eq!(value, 5);
+
+    // UDP sockets cannot listen.
+    assert_eq!(unsafe { libc::listen(fd, 1) }, -1);
+    assert_eq!(errno(), libc::EOPNOTSUPP);
+
+    assert_eq!(unsafe { libc::close(fd) }, 0);
+}
+
+fn test_not_a_socket() {
+    let mut fds = [-1, -1];
+    let res = unsafe { libc::socketpair(libc::AF_UNIX, libc::SOCK_STREAM, 0, fds.as_mut_ptr()) };
+    assert_eq!(res, 0);
+    let [fd0, fd1] = fds;
+
+    // `send` and `recv` also work on socket pairs.
+    assert_eq!(unsafe { libc::send(fd0, b"abc".as_ptr().cast(), 3, 0) }, 3);
+    let mut buf = [0u8; 3];
+    assert_eq!(unsafe { libc::recv(fd1, buf.as_mut_ptr().cast(), 3, 0) }, 3);
+    assert_eq!(&buf, b"abc");
+
+    // But they are no TCP or UDP sockets.
+    assert_eq!(unsafe { libc::listen(fd0, 1) }, -1);
+    assert_eq!(errno(), libc::ENOTSOCK);
+
+    assert_eq!(unsafe { libc::close(fd0) }, 0);
+    assert_eq!(unsafe { libc::close(fd1) }, 0);
+}
+
+fn test_synchronization() {
+    static mut VAL: u32 = 0;
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let client = thread::spawn(move || {
+        let stream = TcpStream::connect(addr).unwrap();
+        let mut buf = [0u8; 1];
+        // This blocks until the main thread sends something.
+        let res = unsafe { libc::recv(stream.as_raw_fd(), buf.as_mut_ptr().cast(), 1, 0) };
+        assert_eq!(res, 1);
+        // The send happens-before the receive returns, so this is not a data race.
+        assert_eq!(unsafe { VAL }, 1);
+    });
+
+    let (stream, _) = listener.accept().unwrap();
+    thread::yield_now();
+    unsafe { VAL = 1 };
+    let res = unsafe { libc::send(stream.as_raw_fd(), b"x".as_ptr().cast(), 1, 0) };
+    assert_eq!(res, 1);
+    client.join().unwrap();
+}
+
+fn main() {
+    test_socket_addrs();
+    test_socket_options();
+    test_not_a_socket();
+    test_synchronization();
+}
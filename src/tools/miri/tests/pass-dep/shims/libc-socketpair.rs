@@ -0,0 +1,117 @@
+//@ignore-target-windows: No libc on Windows
+
+use std::thread;
+
+fn errno() -> i32 {
+    std::io::Error::last_os_error().raw_os_error().unwrap()
+}
+
+fn socketpair() -> [i32; 2] {
+    let mut fds = [-1, -1];
+    let res = unsafe { libc::socketpair(libc::AF_UNIX, libc::SOCK_STREAM, 0, fds.as_mut_ptr()) };
+    assert_eq!(res, 0);
+    fds
+}
+
+fn read(fd: i32, buf: &mut [u8]) -> isize {
+    unsafe { libc::read(fd, buf.as_mut_ptr().cast(), buf.len()) }
+}
+
+fn write(fd: i32, buf: &[u8]) -> isize {
+    unsafe { libc::write(fd, buf.as_ptr().cast(), buf.len()) }
+}
+
+fn test_read_write() {
+    let [fd0, fd1] = socketpair();
+
+    // Data written to one end can be read from the other, in both directions.
+    assert_eq!(write(fd0, b"abcde"), 5);
+    let mut buf = [0; 3];
+    assert_eq!(read(fd1, &mut buf), 3);
+    assert_eq!(&buf, b"abc");
+    assert_eq!(read(fd1, &mut buf), 2);
+    assert_eq!(&buf[..2], b"de");
+
+    assert_eq!(write(fd1, b"12345"), 5);
+    let mut buf = [0; 5];
+    assert_eq!(read(fd0, &mut buf), 5);
+    assert_eq!(&buf, b"12345");
+
+    // Once one end is closed, the other end reads end-of-file and can no longer write.
+    assert_eq!(write(fd0, b"x"), 1);
+    assert_eq!(unsafe { libc::close(fd0) }, 0);
+    assert_eq!(read(fd1, &mut buf), 1);
+    assert_eq!(read(fd1, &mut buf), 0);
+    assert_eq!(write(fd1, b"x"), -1);
+    assert_eq!(errno(), libc::EPIPE);
+
+    assert_eq!(unsafe { libc::close(fd1) }, 0);
+}
+
+#[cfg(target_os = "linux")]
+fn test_nonblocking() {
+    let mut fds = [-1, -1];
+    let res = unsafe {
+        libc::socketpair(
+            libc::AF_UNIX,
+            libc::SOCK_STREAM | libc::SOCK_NONBLOCK | libc::SOCK_CLOEXEC,
+            0,
+            fds.as_mut_ptr(),
+        )
+    };
+    assert_eq!(res, 0);
+    let [fd0, fd1] = fds;
+
+    let mut buf = [0; 4];
+    assert_eq!(read(fd0, &mut buf), -1);
+    assert_eq!(errno(), libc::EAGAIN);
+
+    // Writes stop once the socket buffer is full.
+    let data = vec![0; 64 * 1024];
+    let mut written = 0;
+    loop {
+        let res = write(fd1, &data);
+        if res < 0 {
+            assert_eq!(errno(), libc::EAGAIN);
+            break;
+        }
+        written += res;
+    }
+    assert!(written > 0);
+
+    assert_eq!(unsafe { libc::close(fd0) }, 0);
+    assert_eq!(unsafe { libc::close(fd1) }, 0);
+}
+
+fn test_blocking_read() {
+    static mut VAL: u32 = 0;
+
+    let [fd0, fd1] = socketpair();
+
+    let reader = thread::spawn(move || {
+        // This blocks until the main thread writes to the other end.
+        let mut buf = [0; 5];
+        assert_eq!(read(fd1, &mut buf), 5);
+        assert_eq!(&buf, b"hello");
+        // The write happens-before the read returns, so this is not a data race.
+        assert_eq!(unsafe { VAL }, 1);
+        // This blocks until the main thread closes the other end.
+        assert_eq!(read(fd1, &mut buf), 0);
+    });
+
+    thread::yield_now();
+    unsafe { VAL = 1 };
+    assert_eq!(write(fd0, b"hello"), 5);
+    thread::yield_now();
+    assert_eq!(unsafe { libc::close(fd0) }, 0);
+    reader.join().unwrap();
+
+    assert_eq!(unsafe { libc::close(fd1) }, 0);
+}
+
+fn main() {
+    test_read_write();
+    #[cfg(target_os = "linux")]
+    test_nonblocking();
+    test_blocking_read();
+}
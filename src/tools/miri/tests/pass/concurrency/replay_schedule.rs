@@ -0,0 +1,20 @@
+// Replaying a schedule lets us pick which store the weak memory emulation returns.
+//@compile-flags: -Zmiri-preemption-rate=0 -Zmiri-compare-exchange-weak-failure-rate=0 -Zmiri-replay-schedule=tests/pass/concurrency/replay_schedule.schedule
+
+use std::sync::atomic::AtomicU32;
+use std::sync::atomic::Ordering::Relaxed;
+use std::thread::spawn;
+
+static X: AtomicU32 = AtomicU32::new(0);
+
+fn main() {
+    // Without preemption, the first thread runs to completion before the second one starts.
+    let j1 = spawn(|| {
+        X.store(1, Relaxed);
+        X.store(2, Relaxed);
+    });
+    let j2 = spawn(|| X.load(Relaxed));
+    j1.join().unwrap();
+    // The load may return any of the three values; the schedule makes it return the oldest one.
+    println!("{}", j2.join().unwrap());
+}
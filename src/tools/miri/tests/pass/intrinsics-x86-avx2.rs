@@ -0,0 +1,103 @@
+// Ignore everything except x86 and x86_64. Any additional targets added to CI should be
+// ignored here, since the `target-feature` flags only exist on x86.
+//@ignore-target-aarch64
+//@ignore-target-arm
+//@ignore-target-avr
+//@ignore-target-mips
+//@ignore-target-powerpc
+//@ignore-target-s390x
+//@ignore-target-thumbv7em
+//@ignore-target-wasm32
+//@compile-flags: -C target-feature=+avx2
+
+#[cfg(target_arch = "x86")]
+use std::arch::x86::*;
+#[cfg(target_arch = "x86_64")]
+use std::arch::x86_64::*;
+use std::mem::transmute;
+
+fn main() {
+    assert!(is_x86_feature_detected!("avx2"));
+
+    unsafe {
+        test_avx2();
+    }
+}
+
+#[target_feature(enable = "avx2")]
+unsafe fn test_avx2() {
+    let bytes: [i8; 32] = std::array::from_fn(|i| i as i8);
+    let bytes: __m256i = transmute(bytes);
+
+    // Horizontal operations, packs and shuffles work within each 128-bit half.
+    let a = _mm256_setr_epi32(1, 2, 3, 4, 5, 6, 7, 8);
+    let b = _mm256_setr_epi32(10, 20, 30, 40, 50, 60, 70, 80);
+    let r: [i32; 8] = transmute(_mm256_hadd_epi32(a, b));
+    assert_eq!(r, [3, 7, 30, 70, 11, 15, 110, 150]);
+    let r: [i16; 16] = transmute(_mm256_packs_epi32(a, b));
+    assert_eq!(r, [1, 2, 3, 4, 10, 20, 30, 40, 5, 6, 7, 8, 50, 60, 70, 80]);
+    let r: [i8; 32] = transmute(_mm256_shuffle_epi8(bytes, _mm256_setzero_si256()));
+    assert_eq!(r[..16], [0; 16]);
+    assert_eq!(r[16..], [16; 16]);
+    let r: [u16; 16] = transmute(_mm256_mpsadbw_epu8::<0>(bytes, bytes));
+    assert_eq!(r, [0, 4, 8, 12, 16, 20, 24, 28, 0, 4, 8, 12, 16, 20, 24, 28]);
+
+    // Permutations only use the low bits of each index.
+    let r: [i32; 8] =
+        transmute(_mm256_permutevar8x32_epi32(b, _mm256_setr_epi32(7, 6, 5, 4, 3, 2, 1, 8)));
+    assert_eq!(r, [80, 70, 60, 50, 40, 30, 20, 10]);
+    let a = _mm256_setr_epi64x(1, 2, 3, 4);
+    let b = _mm256_setr_epi64x(5, 6, 7, 8);
+    let r: [i64; 4] = transmute(_mm256_permute2x128_si256::<0x21>(a, b));
+    assert_eq!(r, [3, 4, 5, 6]);
+    let r: [i64; 4] = transmute(_mm256_permute2x128_si256::<0x08>(a, b));
+    assert_eq!(r, [0, 0, 1, 2]);
+
+    // Shifts by counts that are at least as large as the lane width shift out all bits.
+    let r: [u32; 8] = transmute(_mm256_sllv_epi32(
+        _mm256_set1_epi32(1),
+        _mm256_setr_epi32(0, 1, 31, 32, 33, -1, 4, 5),
+    ));
+    assert_eq!(r, [1, 2, 1 << 31, 0, 0, 0, 16, 32]);
+    let r: [i32; 8] = transmute(_mm256_srav_epi32(
+        _mm256_set1_epi32(-16),
+        _mm256_setr_epi32(0, 1, 2, 100, 4, 5, 31, 32),
+    ));
+    assert_eq!(r, [-16, -8, -4, -1, -1, -1, -1, -1]);
+    let r: [u64; 2] = transmute(_mm_srlv_epi64(_mm_set1_epi64x(60), _mm_setr_epi64x(2, 64)));
+    assert_eq!(r, [15, 0]);
+    let r: [i16; 16] = transmute(_mm256_sra_epi16(_mm256_set1_epi16(-4), _mm_setr_epi64x(1, 9)));
+    assert_eq!(r, [-2; 16]);
+
+    assert_eq!(_mm256_movemask_epi8(_mm256_set1_epi8(-1)), -1);
+    let r: [i8; 32] = transmute(_mm256_abs_epi8(_mm256_set1_epi8(-3)));
+    assert_eq!(r, [3; 32]);
+    let r: [u8; 32] = transmute(_mm256_adds_epu8(_mm256_set1_epi8(-2), _mm256_set1_epi8(5)));
+    assert_eq!(r, [u8::MAX; 32]);
+
+    // Gathers only load the lanes whose mask is set, and zero the lanes for which there are no
+    // offsets.
+    let data: [i32; 8] = [10, 11, 12, 13, 14, 15, 16, 17];
+    let r: [i32; 8] = transmute(_mm256_i32gather_epi32::<4>(
+        data.as_ptr(),
+        _mm256_setr_epi32(7, 6, 5, 4, 3, 2, 1, 0),
+    ));
+    assert_eq!(r, [17, 16, 15, 14, 13, 12, 11, 10]);
+    let r: [i32; 4] = transmute(_mm_mask_i32gather_epi32::<4>(
+        _mm_set1_epi32(-1),
+        data.as_ptr(),
+        _mm_setr_epi32(0, 1, 2, 3),
+        _mm_setr_epi32(-1, 0, -1, 0),
+    ));
+    assert_eq!(r, [10, -1, 12, -1]);
+    let r: [i32; 4] = transmute(_mm_i64gather_epi32::<4>(data.as_ptr(), _mm_setr_epi64x(1, 3)));
+    assert_eq!(r, [11, 13, 0, 0]);
+    // With a scale of 1, the offsets are in bytes.
+    let r: [i32; 4] =
+        transmute(_mm_i32gather_epi32::<1>(data.as_ptr(), _mm_setr_epi32(0, 4, 8, 28)));
+    assert_eq!(r, [10, 11, 12, 17]);
+    let data: [f64; 4] = [1.0, 2.0, 3.0, 4.0];
+    let r: [f64; 4] =
+        transmute(_mm256_i32gather_pd::<8>(data.as_ptr(), _mm_setr_epi32(3, 2, 1, 0)));
+    assert_eq!(r, [4.0, 3.0, 2.0, 1.0]);
+}
@@ -0,0 +1,93 @@
+// Ignore everything except x86 and x86_64. Any additional targets added to CI should be
+// ignored here, since the `target-feature` flags only exist on x86.
+//@ignore-target-aarch64
+//@ignore-target-arm
+//@ignore-target-avr
+//@ignore-target-mips
+//@ignore-target-powerpc
+//@ignore-target-s390x
+//@ignore-target-thumbv7em
+//@ignore-target-wasm32
+
+#[cfg(target_arch = "x86")]
+use std::arch::x86::*;
+#[cfg(target_arch = "x86_64")]
+use std::arch::x86_64::*;
+use std::mem::transmute;
+
+fn main() {
+    assert!(is_x86_feature_detected!("sse"));
+
+    unsafe {
+        test_sse();
+    }
+}
+
+#[target_feature(enable = "sse")]
+unsafe fn test_sse() {
+    // Minimum and maximum return the second operand if either operand is NaN or both are zero.
+    let a = _mm_setr_ps(1.0, f32::NAN, -0.0, 4.0);
+    let b = _mm_setr_ps(2.0, 1.0, 0.0, f32::NAN);
+    let r: [f32; 4] = transmute(_mm_min_ps(a, b));
+    assert_eq!(r[..2], [1.0, 1.0]);
+    assert!(r[2] == 0.0 && r[2].is_sign_positive());
+    assert!(r[3].is_nan());
+    let r: [f32; 4] = transmute(_mm_max_ps(a, b));
+    assert_eq!(r[..2], [2.0, 1.0]);
+    assert!(r[2] == 0.0 && r[2].is_sign_positive());
+    assert!(r[3].is_nan());
+    let r: [f32; 4] = transmute(_mm_min_ss(a, b));
+    assert_eq!(r[0], 1.0);
+    assert!(r[1].is_nan());
+    assert_eq!(r[2..], [-0.0, 4.0]);
+
+    // Comparisons return all-1 lanes for true and all-0 lanes for false.
+    let r: [u32; 4] = transmute(_mm_cmplt_ps(a, b));
+    assert_eq!(r, [!0, 0, 0, 0]);
+    let r: [u32; 4] = transmute(_mm_cmpneq_ps(a, b));
+    assert_eq!(r, [!0, !0, 0, !0]);
+    let r: [u32; 4] = transmute(_mm_cmpunord_ps(a, b));
+    assert_eq!(r, [0, !0, 0, !0]);
+    let r: [u32; 4] = transmute(_mm_cmpeq_ss(a, a));
+    assert_eq!(r, [!0, f32::NAN.to_bits(), (-0.0f32).to_bits(), 4.0f32.to_bits()]);
+
+    assert_eq!(_mm_comilt_ss(a, b), 1);
+    assert_eq!(_mm_comieq_ss(a, b), 0);
+    let nan = _mm_set_ss(f32::NAN);
+    assert_eq!(_mm_ucomieq_ss(nan, nan), 0);
+    assert_eq!(_mm_ucomineq_ss(nan, nan), 1);
+    assert_eq!(_mm_comige_ss(nan, a), 0);
+
+    // Conversions round to nearest, unless they truncate, and return the minimum of the
+    // integer type for values that do not fit.
+    assert_eq!(_mm_cvtss_si32(_mm_set_ss(2.5)), 2);
+    assert_eq!(_mm_cvtss_si32(_mm_set_ss(-3.5)), -4);
+    assert_eq!(_mm_cvttss_si32(_mm_set_ss(-2.7)), -2);
+    assert_eq!(_mm_cvtss_si32(nan), i32::MIN);
+    assert_eq!(_mm_cvttss_si32(_mm_set_ss(3e10)), i32::MIN);
+    #[cfg(target_arch = "x86_64")]
+    {
+        assert_eq!(_mm_cvtss_si64(_mm_set_ss(3e10)), 30_000_001_024);
+        assert_eq!(_mm_cvttss_si64(nan), i64::MIN);
+    }
+
+    let r: [f32; 4] = transmute(_mm_sqrt_ps(_mm_setr_ps(4.0, 9.0, 16.0, 0.0)));
+    assert_eq!(r, [2.0, 3.0, 4.0, 0.0]);
+    let r: [f32; 4] = transmute(_mm_sqrt_ss(_mm_setr_ps(4.0, 9.0, 16.0, 0.0)));
+    assert_eq!(r, [2.0, 9.0, 16.0, 0.0]);
+    // The reciprocals are only approximations on real hardware.
+    let r: [f32; 4] = transmute(_mm_rcp_ps(_mm_setr_ps(2.0, 4.0, 0.5, -1.0)));
+    for (r, expected) in r.into_iter().zip([0.5, 0.25, 2.0, -1.0]) {
+        assert!((r - expected).abs() < 1e-3);
+    }
+    let r: [f32; 4] = transmute(_mm_rsqrt_ps(_mm_setr_ps(4.0, 16.0, 0.25, 1.0)));
+    for (r, expected) in r.into_iter().zip([0.5, 0.25, 2.0, 1.0]) {
+        assert!((r - expected).abs() < 1e-3);
+    }
+
+    assert_eq!(_mm_movemask_ps(_mm_setr_ps(-1.0, 2.0, -0.0, 3.0)), 0b0101);
+
+    _mm_sfence();
+    assert_eq!(_mm_getcsr(), 0x1F80);
+    _mm_setcsr(0x1F80);
+}
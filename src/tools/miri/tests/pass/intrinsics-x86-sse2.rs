@@ -0,0 +1,137 @@
+// Ignore everything except x86 and x86_64. Any additional targets added to CI should be
+// ignored here, since the `target-feature` flags only exist on x86.
+//@ignore-target-aarch64
+//@ignore-target-arm
+//@ignore-target-avr
+//@ignore-target-mips
+//@ignore-target-powerpc
+//@ignore-target-s390x
+//@ignore-target-thumbv7em
+//@ignore-target-wasm32
+
+#[cfg(target_arch = "x86")]
+use std::arch::x86::*;
+#[cfg(target_arch = "x86_64")]
+use std::arch::x86_64::*;
+use std::mem::transmute;
+
+fn main() {
+    assert!(is_x86_feature_detected!("sse2"));
+
+    unsafe {
+        test_sse2();
+    }
+}
+
+#[target_feature(enable = "sse2")]
+unsafe fn test_sse2() {
+    let a = _mm_setr_pd(1.0, f64::NAN);
+    let b = _mm_setr_pd(2.0, 1.0);
+    let r: [f64; 2] = transmute(_mm_min_pd(a, b));
+    assert_eq!(r, [1.0, 1.0]);
+    let r: [f64; 2] = transmute(_mm_max_sd(a, b));
+    assert_eq!(r[0], 2.0);
+    assert!(r[1].is_nan());
+    let r: [u64; 2] = transmute(_mm_cmplt_pd(a, b));
+    assert_eq!(r, [!0, 0]);
+    assert_eq!(_mm_comilt_sd(a, b), 1);
+    let r: [f64; 2] = transmute(_mm_sqrt_pd(_mm_setr_pd(4.0, 2.25)));
+    assert_eq!(r, [2.0, 1.5]);
+
+    assert_eq!(_mm_cvtsd_si32(_mm_set_sd(-1.5)), -2);
+    assert_eq!(_mm_cvttsd_si32(_mm_set_sd(-1.5)), -1);
+    assert_eq!(_mm_cvtsd_si32(_mm_set_sd(1e10)), i32::MIN);
+    let r: [i32; 4] = transmute(_mm_cvtps_epi32(_mm_setr_ps(1.5, 2.5, -1.5, f32::NAN)));
+    assert_eq!(r, [2, 2, -2, i32::MIN]);
+    let r: [i32; 4] = transmute(_mm_cvttps_epi32(_mm_setr_ps(1.5, 2.5, -1.5, f32::NAN)));
+    assert_eq!(r, [1, 2, -1, i32::MIN]);
+    let r: [i32; 4] = transmute(_mm_cvtpd_epi32(_mm_setr_pd(1.5, -3.7)));
+    assert_eq!(r, [2, -4, 0, 0]);
+    let r: [i32; 4] = transmute(_mm_cvttpd_epi32(_mm_setr_pd(1.5, -3.7)));
+    assert_eq!(r, [1, -3, 0, 0]);
+    let r: [f32; 4] =
+        transmute(_mm_cvtsd_ss(_mm_setr_ps(1.0, 2.0, 3.0, 4.0), _mm_setr_pd(5.5, 6.5)));
+    assert_eq!(r, [5.5, 2.0, 3.0, 4.0]);
+
+    let a = _mm_setr_epi16(200, -200, 1, -1, 127, -128, 0, 300);
+    let b = _mm_set1_epi16(-300);
+    let r: [i8; 16] = transmute(_mm_packs_epi16(a, b));
+    assert_eq!(
+        r,
+        [127, -128, 1, -1, 127, -128, 0, 127, -128, -128, -128, -128, -128, -128, -128, -128]
+    );
+    let r: [u8; 16] = transmute(_mm_packus_epi16(a, b));
+    assert_eq!(r, [200, 0, 1, 0, 127, 0, 0, 255, 0, 0, 0, 0, 0, 0, 0, 0]);
+    let r: [i16; 8] = transmute(_mm_packs_epi32(
+        _mm_setr_epi32(70000, -70000, 5, -5),
+        _mm_setr_epi32(0, 1, 2, 3),
+    ));
+    assert_eq!(r, [32767, -32768, 5, -5, 0, 1, 2, 3]);
+
+    // The only overflowing case wraps around.
+    let r: [i32; 4] = transmute(_mm_madd_epi16(
+        _mm_setr_epi16(1, 2, 3, 4, -32768, -32768, 5, 6),
+        _mm_setr_epi16(5, 6, 7, 8, -32768, -32768, 1, 1),
+    ));
+    assert_eq!(r, [17, 53, i32::MIN, 11]);
+    let r: [u64; 2] = transmute(_mm_sad_epu8(
+        _mm_setr_epi8(1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16),
+        _mm_set1_epi8(4),
+    ));
+    assert_eq!(r, [16, 68]);
+    let r: [u64; 2] =
+        transmute(_mm_mul_epu32(_mm_setr_epi32(-1, 5, 3, 7), _mm_setr_epi32(2, 9, 4, 9)));
+    assert_eq!(r, [0x1_FFFF_FFFE, 12]);
+
+    let r: [u8; 16] = transmute(_mm_avg_epu8(_mm_set1_epi8(1), _mm_set1_epi8(2)));
+    assert_eq!(r, [2; 16]);
+    let r: [u16; 8] = transmute(_mm_avg_epu16(_mm_set1_epi16(-1), _mm_set1_epi16(-1)));
+    assert_eq!(r, [u16::MAX; 8]);
+    let r: [i16; 8] = transmute(_mm_mulhi_epi16(_mm_set1_epi16(-2), _mm_set1_epi16(3)));
+    assert_eq!(r, [-1; 8]);
+    let r: [u16; 8] = transmute(_mm_mulhi_epu16(_mm_set1_epi16(-1), _mm_set1_epi16(-1)));
+    assert_eq!(r, [0xFFFE; 8]);
+    let r: [i8; 16] = transmute(_mm_adds_epi8(_mm_set1_epi8(100), _mm_set1_epi8(100)));
+    assert_eq!(r, [127; 16]);
+    let r: [u8; 16] = transmute(_mm_subs_epu8(_mm_set1_epi8(1), _mm_set1_epi8(2)));
+    assert_eq!(r, [0; 16]);
+    let r: [i16; 8] = transmute(_mm_max_epi16(_mm_set1_epi16(-5), _mm_set1_epi16(3)));
+    assert_eq!(r, [3; 8]);
+    let r: [u8; 16] = transmute(_mm_min_epu8(_mm_set1_epi8(-1), _mm_set1_epi8(1)));
+    assert_eq!(r, [1; 16]);
+
+    // Shifts by vector only use the low 64 bits of the count, and counts that are at least as
+    // large as the lane width shift out all bits.
+    let r: [i16; 8] = transmute(_mm_sll_epi16(_mm_set1_epi16(1), _mm_set_epi64x(1, 3)));
+    assert_eq!(r, [8; 8]);
+    let r: [i16; 8] = transmute(_mm_sll_epi16(_mm_set1_epi16(1), _mm_set_epi64x(0, 16)));
+    assert_eq!(r, [0; 8]);
+    let r: [i16; 8] = transmute(_mm_sra_epi16(_mm_set1_epi16(-32768), _mm_set_epi64x(0, 20)));
+    assert_eq!(r, [-1; 8]);
+    let r: [u32; 4] = transmute(_mm_srl_epi32(_mm_set1_epi32(-1), _mm_set_epi64x(0, 28)));
+    assert_eq!(r, [15; 4]);
+    let r: [i32; 4] = transmute(_mm_slli_epi32::<4>(_mm_set1_epi32(1)));
+    assert_eq!(r, [16; 4]);
+    let r: [i16; 8] = transmute(_mm_srai_epi16::<20>(_mm_set1_epi16(-5)));
+    assert_eq!(r, [-1; 8]);
+    let r: [u64; 2] = transmute(_mm_srli_epi64::<64>(_mm_set1_epi64x(-1)));
+    assert_eq!(r, [0; 2]);
+
+    assert_eq!(
+        _mm_movemask_epi8(_mm_setr_epi8(-1, 0, -1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, -128)),
+        0x8005
+    );
+    assert_eq!(_mm_movemask_pd(_mm_setr_pd(-1.0, 1.0)), 0b01);
+
+    let mut mem = [0i8; 16];
+    _mm_maskmoveu_si128(
+        _mm_set1_epi8(7),
+        _mm_setr_epi8(-1, 0, 0, -1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, -1),
+        mem.as_mut_ptr(),
+    );
+    assert_eq!(mem, [7, 0, 0, 7, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 7]);
+
+    _mm_lfence();
+    _mm_mfence();
+    _mm_clflush(mem.as_ptr() as *const u8);
+}
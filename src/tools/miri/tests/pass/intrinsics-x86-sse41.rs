@@ -0,0 +1,113 @@
+// Ignore everything except x86 and x86_64. Any additional targets added to CI should be
+// ignored here, since the `target-feature` flags only exist on x86.
+//@ignore-target-aarch64
+//@ignore-target-arm
+//@ignore-target-avr
+//@ignore-target-mips
+//@ignore-target-powerpc
+//@ignore-target-s390x
+//@ignore-target-thumbv7em
+//@ignore-target-wasm32
+//@compile-flags: -C target-feature=+sse4.1
+
+#[cfg(target_arch = "x86")]
+use std::arch::x86::*;
+#[cfg(target_arch = "x86_64")]
+use std::arch::x86_64::*;
+use std::mem::transmute;
+
+fn main() {
+    assert!(is_x86_feature_detected!("sse4.1"));
+
+    unsafe {
+        test_sse41();
+    }
+}
+
+#[target_feature(enable = "sse4.1")]
+unsafe fn test_sse41() {
+    let a = _mm_setr_ps(1.5, -1.5, 2.5, -0.5);
+    let r: [f32; 4] = transmute(_mm_floor_ps(a));
+    assert_eq!(r, [1.0, -2.0, 2.0, -1.0]);
+    let r: [f32; 4] = transmute(_mm_ceil_ps(a));
+    assert_eq!(r, [2.0, -1.0, 3.0, -0.0]);
+    let r: [f32; 4] = transmute(_mm_round_ps::<{ _MM_FROUND_TO_NEAREST_INT | _MM_FROUND_NO_EXC }>(
+        _mm_setr_ps(0.5, 1.5, 2.5, -2.5),
+    ));
+    assert_eq!(r, [0.0, 2.0, 2.0, -2.0]);
+    let r: [f64; 2] = transmute(_mm_round_sd::<{ _MM_FROUND_TO_ZERO | _MM_FROUND_NO_EXC }>(
+        _mm_setr_pd(10.0, 20.0),
+        _mm_setr_pd(-1.7, 5.0),
+    ));
+    assert_eq!(r, [-1.0, 20.0]);
+    let r: [f32; 4] = transmute(_mm_floor_ss(_mm_set1_ps(7.0), a));
+    assert_eq!(r, [1.0, 7.0, 7.0, 7.0]);
+
+    let a = _mm_setr_ps(1.0, 2.0, 3.0, 4.0);
+    let b = _mm_setr_ps(5.0, 6.0, 7.0, 8.0);
+    // Inserts the second lane of `b` into the third lane and zeroes the first lane.
+    let r: [f32; 4] = transmute(_mm_insert_ps::<0b01_10_0001>(a, b));
+    assert_eq!(r, [0.0, 2.0, 6.0, 4.0]);
+
+    let r: [f32; 4] = transmute(_mm_dp_ps::<0xF1>(a, b));
+    assert_eq!(r, [70.0, 0.0, 0.0, 0.0]);
+    let r: [f32; 4] = transmute(_mm_dp_ps::<0x5E>(a, b));
+    assert_eq!(r, [0.0, 26.0, 26.0, 26.0]);
+    let r: [f64; 2] = transmute(_mm_dp_pd::<0x32>(_mm_setr_pd(1.0, 2.0), _mm_setr_pd(3.0, 4.0)));
+    assert_eq!(r, [0.0, 11.0]);
+
+    let a = _mm_setr_epi8(0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15);
+    let r: [u16; 8] = transmute(_mm_mpsadbw_epu8::<0>(a, a));
+    assert_eq!(r, [0, 4, 8, 12, 16, 20, 24, 28]);
+    let r: [u16; 8] = transmute(_mm_mpsadbw_epu8::<0b111>(a, a));
+    assert_eq!(r, [32, 28, 24, 20, 16, 12, 8, 4]);
+
+    let r: [u16; 8] = transmute(_mm_packus_epi32(
+        _mm_setr_epi32(-1, 70000, 5, 65535),
+        _mm_setr_epi32(0, 1, 2, 3),
+    ));
+    assert_eq!(r, [0, 65535, 5, 65535, 0, 1, 2, 3]);
+
+    let r: [u16; 8] = transmute(_mm_minpos_epu16(_mm_setr_epi16(5, 3, 7, 3, 9, -1, 4, 8)));
+    assert_eq!(r, [3, 1, 0, 0, 0, 0, 0, 0]);
+
+    let r: [i8; 16] = transmute(_mm_blendv_epi8(
+        a,
+        _mm_set1_epi8(-1),
+        _mm_setr_epi8(-1, 0, 1, -128, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 127),
+    ));
+    assert_eq!(r, [-1, 1, 2, -1, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15]);
+    let r: [f32; 4] = transmute(_mm_blendv_ps(
+        _mm_setr_ps(1.0, 2.0, 3.0, 4.0),
+        _mm_setr_ps(5.0, 6.0, 7.0, 8.0),
+        _mm_setr_ps(-0.0, 0.0, 1.0, -1.0),
+    ));
+    assert_eq!(r, [5.0, 2.0, 3.0, 8.0]);
+    let r: [f64; 2] = transmute(_mm_blendv_pd(
+        _mm_setr_pd(1.0, 2.0),
+        _mm_setr_pd(3.0, 4.0),
+        _mm_setr_pd(0.0, -2.0),
+    ));
+    assert_eq!(r, [1.0, 4.0]);
+
+    let a = _mm_set1_epi8(0b1100);
+    assert_eq!(_mm_testz_si128(a, _mm_set1_epi8(0b0110)), 0);
+    assert_eq!(_mm_testc_si128(a, _mm_set1_epi8(0b0110)), 0);
+    assert_eq!(_mm_testnzc_si128(a, _mm_set1_epi8(0b0110)), 1);
+    assert_eq!(_mm_testc_si128(a, _mm_set1_epi8(0b0100)), 1);
+    assert_eq!(_mm_testnzc_si128(a, _mm_set1_epi8(0b0100)), 0);
+    assert_eq!(_mm_testz_si128(a, _mm_set1_epi8(0b0011)), 1);
+
+    let r: [i64; 2] =
+        transmute(_mm_mul_epi32(_mm_setr_epi32(-2, 9, 3, 9), _mm_setr_epi32(5, 9, -4, 9)));
+    assert_eq!(r, [-10, -12]);
+
+    let r: [i8; 16] = transmute(_mm_max_epi8(_mm_set1_epi8(-5), _mm_set1_epi8(3)));
+    assert_eq!(r, [3; 16]);
+    let r: [i32; 4] = transmute(_mm_min_epi32(_mm_set1_epi32(-5), _mm_set1_epi32(3)));
+    assert_eq!(r, [-5; 4]);
+    let r: [u16; 8] = transmute(_mm_max_epu16(_mm_set1_epi16(-1), _mm_set1_epi16(3)));
+    assert_eq!(r, [u16::MAX; 8]);
+    let r: [u32; 4] = transmute(_mm_min_epu32(_mm_set1_epi32(-1), _mm_set1_epi32(3)));
+    assert_eq!(r, [3; 4]);
+}
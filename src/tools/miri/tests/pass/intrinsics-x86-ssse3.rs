@@ -0,0 +1,78 @@
+// Ignore everything except x86 and x86_64. Any additional targets added to CI should be
+// ignored here, since the `target-feature` flags only exist on x86.
+//@ignore-target-aarch64
+//@ignore-target-arm
+//@ignore-target-avr
+//@ignore-target-mips
+//@ignore-target-powerpc
+//@ignore-target-s390x
+//@ignore-target-thumbv7em
+//@ignore-target-wasm32
+//@compile-flags: -C target-feature=+ssse3
+
+#[cfg(target_arch = "x86")]
+use std::arch::x86::*;
+#[cfg(target_arch = "x86_64")]
+use std::arch::x86_64::*;
+use std::mem::transmute;
+
+fn main() {
+    assert!(is_x86_feature_detected!("ssse3"));
+
+    unsafe {
+        test_ssse3();
+    }
+}
+
+#[target_feature(enable = "ssse3")]
+unsafe fn test_ssse3() {
+    let r: [i8; 16] = transmute(_mm_abs_epi8(_mm_setr_epi8(
+        -128, -1, 0, 1, 127, -127, 5, -5, 0, 0, 0, 0, 0, 0, 0, 0,
+    )));
+    assert_eq!(r, [-128, 1, 0, 1, 127, 127, 5, 5, 0, 0, 0, 0, 0, 0, 0, 0]);
+    let r: [i16; 8] = transmute(_mm_abs_epi16(_mm_set1_epi16(-5)));
+    assert_eq!(r, [5; 8]);
+    let r: [i32; 4] = transmute(_mm_abs_epi32(_mm_setr_epi32(i32::MIN, -1, 0, 7)));
+    assert_eq!(r, [i32::MIN, 1, 0, 7]);
+
+    // Indices with the most significant bit set produce zero, the remaining ones only use their
+    // low 4 bits.
+    let a = _mm_setr_epi8(0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15);
+    let indices = _mm_setr_epi8(15, 0, -1, 17, 3, 3, -128, 0x7F, 1, 2, 3, 4, 5, 6, 7, 8);
+    let r: [i8; 16] = transmute(_mm_shuffle_epi8(a, indices));
+    assert_eq!(r, [15, 0, 0, 1, 3, 3, 0, 15, 1, 2, 3, 4, 5, 6, 7, 8]);
+
+    let a = _mm_setr_epi16(1, 2, 3, 4, 5, 6, 7, 8);
+    let b = _mm_setr_epi16(10, 20, 30, 40, 50, 60, 70, 80);
+    let r: [i16; 8] = transmute(_mm_hadd_epi16(a, b));
+    assert_eq!(r, [3, 7, 11, 15, 30, 70, 110, 150]);
+    let r: [i16; 8] = transmute(_mm_hsub_epi16(a, b));
+    assert_eq!(r, [-1, -1, -1, -1, -10, -10, -10, -10]);
+    let r: [i16; 8] =
+        transmute(_mm_hadds_epi16(_mm_setr_epi16(32767, 1, -32768, -1, 0, 0, 0, 0), b));
+    assert_eq!(r, [32767, -32768, 0, 0, 30, 70, 110, 150]);
+    let r: [i16; 8] = transmute(_mm_hsubs_epi16(_mm_setr_epi16(-32768, 1, 0, 0, 0, 0, 0, 0), b));
+    assert_eq!(r, [-32768, 0, 0, 0, -10, -10, -10, -10]);
+    let r: [i32; 4] =
+        transmute(_mm_hsub_epi32(_mm_setr_epi32(5, 2, 7, 1), _mm_setr_epi32(i32::MIN, 1, 0, 0)));
+    assert_eq!(r, [3, 6, i32::MAX, 0]);
+
+    // The first operand is unsigned, the second one is signed.
+    let r: [i16; 8] = transmute(_mm_maddubs_epi16(
+        _mm_setr_epi8(-1, -1, 2, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0),
+        _mm_setr_epi8(127, 127, -3, -3, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0),
+    ));
+    assert_eq!(r, [32767, -12, 0, 0, 0, 0, 0, 0]);
+
+    let r: [i16; 8] = transmute(_mm_mulhrs_epi16(
+        _mm_setr_epi16(16384, -32768, 3, -3, 0, 0, 0, 0),
+        _mm_setr_epi16(16384, -32768, 16384, 16384, 0, 0, 0, 0),
+    ));
+    assert_eq!(r, [8192, -32768, 2, -1, 0, 0, 0, 0]);
+
+    let r: [i16; 8] = transmute(_mm_sign_epi16(a, _mm_setr_epi16(-1, 0, 1, -5, 5, 0, -32768, 1)));
+    assert_eq!(r, [-1, 0, 3, -4, 5, 0, -7, 8]);
+    let r: [i32; 4] =
+        transmute(_mm_sign_epi32(_mm_set1_epi32(i32::MIN), _mm_setr_epi32(-1, 0, 1, -1)));
+    assert_eq!(r, [i32::MIN, 0, i32::MIN, i32::MIN]);
+}
@@ -0,0 +1,156 @@
+//@ignore-target-windows: Networking is not implemented yet
+
+use std::io::{ErrorKind, Read, Write};
+use std::net::{Ipv4Addr, Shutdown, SocketAddr, TcpListener, TcpStream, UdpSocket};
+use std::thread;
+
+fn main() {
+    test_tcp();
+    test_tcp_shutdown();
+    test_tcp_errors();
+    test_tcp_ipv6();
+    test_udp();
+    test_udp_connected();
+}
+
+fn test_tcp() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    assert_eq!(addr.ip(), Ipv4Addr::LOCALHOST);
+    assert_ne!(addr.port(), 0);
+
+    let client = thread::spawn(move || {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        assert_eq!(stream.peer_addr().unwrap(), addr);
+        stream.write_all(b"hello").unwrap();
+        let mut buf = [0; 5];
+        stream.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"world");
+        stream.local_addr().unwrap()
+    });
+
+    let (mut stream, peer) = listener.accept().unwrap();
+    assert_eq!(stream.local_addr().unwrap(), addr);
+    let mut buf = [0; 5];
+    stream.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf, b"hello");
+    stream.write_all(b"world").unwrap();
+    assert_eq!(client.join().unwrap(), peer);
+
+    // The client is gone, so we read end-of-file.
+    assert_eq!(stream.read(&mut buf).unwrap(), 0);
+}
+
+fn test_tcp_shutdown() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    let (mut server, _) = listener.accept().unwrap();
+
+    client.write_all(b"abc").unwrap();
+    client.shutdown(Shutdown::Write).unwrap();
+    let mut data = Vec::new();
+    server.read_to_end(&mut data).unwrap();
+    assert_eq!(data, b"abc");
+
+    // The other direction still works.
+    server.write_all(b"def").unwrap();
+    let mut buf = [0; 3];
+    client.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf, b"def");
+
+    // Once the server is gone, writing fails.
+    drop(server);
+    assert_eq!(client.write(b"x").unwrap_err().kind(), ErrorKind::BrokenPipe);
+}
+
+fn test_tcp_errors() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    // The address is in use until the listener is dropped.
+    assert_eq!(TcpListener::bind(addr).unwrap_err().kind(), ErrorKind::AddrInUse);
+    drop(listener);
+    assert_eq!(TcpStream::connect(addr).unwrap_err().kind(), ErrorKind::ConnectionRefused);
+    let listener = TcpListener::bind(addr).unwrap();
+
+    // Only the loopback network exists.
+    let remote = SocketAddr::from(([192, 0, 2, 1], addr.port()));
+    assert!(TcpStream::connect(remote).is_err());
+    assert!(TcpListener::bind(remote).is_err());
+
+    // Non-blocking listeners report when there are no connections.
+    listener.set_nonblocking(true).unwrap();
+    assert_eq!(listener.accept().unwrap_err().kind(), ErrorKind::WouldBlock);
+    let _client = TcpStream::connect(addr).unwrap();
+    listener.accept().unwrap();
+}
+
+fn test_tcp_ipv6() {
+    let listener = TcpListener::bind("[::1]:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    assert!(addr.is_ipv6());
+
+    // IPv4 and IPv6 are separate networks.
+    let v4_addr = SocketAddr::from((Ipv4Addr::LOCALHOST, addr.port()));
+    assert_eq!(TcpStream::connect(v4_addr).unwrap_err().kind(), ErrorKind::ConnectionRefused);
+
+    let mut client = TcpStream::connect(addr).unwrap();
+    let (mut server, _) = listener.accept().unwrap();
+    client.write_all(b"v6").unwrap();
+    let mut buf = [0; 2];
+    server.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf, b"v6");
+}
+
+fn test_udp() {
+    let socket1 = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let socket2 = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let addr1 = socket1.local_addr().unwrap();
+    let addr2 = socket2.local_addr().unwrap();
+    assert_ne!(addr1, addr2);
+
+    let receiver = thread::spawn(move || {
+        let mut buf = [0; 16];
+        let (len, from) = socket2.recv_from(&mut buf).unwrap();
+        assert_eq!(&buf[..len], b"ping");
+        assert_eq!(from, addr1);
+        socket2.send_to(b"pong", from).unwrap();
+    });
+
+    socket1.send_to(b"ping", addr2).unwrap();
+    let mut buf = [0; 16];
+    let (len, from) = socket1.recv_from(&mut buf).unwrap();
+    assert_eq!(&buf[..len], b"pong");
+    assert_eq!(from, addr2);
+    receiver.join().unwrap();
+
+    // Datagrams keep their boundaries, and what does not fit into the buffer is lost.
+    socket1.send_to(b"abcdef", addr1).unwrap();
+    socket1.send_to(b"gh", addr1).unwrap();
+    let mut buf = [0; 4];
+    assert_eq!(socket1.peek_from(&mut buf).unwrap(), (4, addr1));
+    assert_eq!(socket1.recv_from(&mut buf).unwrap(), (4, addr1));
+    assert_eq!(&buf, b"abcd");
+    assert_eq!(socket1.recv_from(&mut buf).unwrap(), (2, addr1));
+    assert_eq!(&buf[..2], b"gh");
+
+    socket1.set_nonblocking(true).unwrap();
+    assert_eq!(socket1.recv_from(&mut buf).unwrap_err().kind(), ErrorKind::WouldBlock);
+}
+
+fn test_udp_connected() {
+    let socket1 = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let socket2 = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let socket3 = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let addr2 = socket2.local_addr().unwrap();
+    socket1.connect(addr2).unwrap();
+    socket2.connect(socket1.local_addr().unwrap()).unwrap();
+    assert_eq!(socket1.peer_addr().unwrap(), addr2);
+
+    // Connected sockets ignore datagrams from other sockets.
+    socket3.send_to(b"ignored", addr2).unwrap();
+    socket1.send(b"data").unwrap();
+    let mut buf = [0; 16];
+    let len = socket2.recv(&mut buf).unwrap();
+    assert_eq!(&buf[..len], b"data");
+}
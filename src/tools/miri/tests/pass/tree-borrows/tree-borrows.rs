@@ -0,0 +1,77 @@
+//@compile-flags: -Zmiri-tree-borrows
+use std::cell::Cell;
+use std::ptr;
+
+// Test various aliasing patterns that Stacked Borrows rejects but Tree Borrows accepts.
+fn main() {
+    two_phase();
+    as_ptr_then_as_mut_ptr();
+    raw_pointer_outlives_reborrow();
+    reserved_tolerates_foreign_reads();
+    interior_mutability();
+    protected_reserved_foreign_read();
+}
+
+fn two_phase() {
+    let mut v = vec![];
+    v.push(v.len());
+    let r = &mut v;
+    r.push(r.len());
+    assert_eq!(v, [0, 1]);
+}
+
+// Stacked Borrows invalidates `src` when `as_mut_ptr` creates a mutable reference.
+// In Tree Borrows, the new reference is merely `Reserved` until it is written to.
+fn as_ptr_then_as_mut_ptr() {
+    let mut v = [1u8, 2];
+    let s = &mut v[..];
+    let src = s.as_ptr();
+    let dst = s.as_mut_ptr();
+    unsafe {
+        dst.add(1).write(src.read());
+    }
+    assert_eq!(v, [1, 1]);
+}
+
+// Raw pointers do not get their own tag, so reborrowing the reference they were created from does
+// not invalidate them.
+fn raw_pointer_outlives_reborrow() {
+    let mut x = 0;
+    let r = &mut x;
+    let p1 = r as *mut i32;
+    let _p2 = &mut *r as *mut i32;
+    unsafe {
+        *p1 = 1;
+    }
+    assert_eq!(x, 1);
+}
+
+fn reserved_tolerates_foreign_reads() {
+    let mut x = 0;
+    let base = ptr::addr_of_mut!(x);
+    let r = unsafe { &mut *base };
+    // A read through the parent pointer does not affect the `Reserved` reference.
+    let val = unsafe { *base };
+    *r = val + 1;
+    assert_eq!(x, 1);
+}
+
+fn interior_mutability() {
+    let c = Cell::new(1);
+    let r1 = &c;
+    let r2 = &c;
+    r1.set(2);
+    assert_eq!(r2.get(), 2);
+    r2.set(3);
+    assert_eq!(r1.get(), 3);
+}
+
+// A protected `&mut` that was never written to only becomes read-only on a foreign read.
+fn protected_reserved_foreign_read() {
+    fn read_through_alias(_x: &mut i32, alias: *const i32) -> i32 {
+        unsafe { *alias }
+    }
+    let mut x = 42;
+    let alias = ptr::addr_of!(x);
+    assert_eq!(read_through_alias(&mut x, alias), 42);
+}
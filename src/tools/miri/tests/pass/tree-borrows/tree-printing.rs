@@ -0,0 +1,34 @@
+//@compile-flags: -Zmiri-tree-borrows -Zmiri-tag-gc=0
+use std::alloc::{self, Layout};
+
+extern "Rust" {
+    fn miri_get_alloc_id(ptr: *const u8) -> u64;
+    fn miri_print_borrow_stacks(alloc_id: u64);
+}
+
+fn get_alloc_id(ptr: *const u8) -> u64 {
+    unsafe { miri_get_alloc_id(ptr) }
+}
+
+fn print_borrow_tree(alloc_id: u64) {
+    unsafe { miri_print_borrow_stacks(alloc_id) }
+}
+
+fn main() {
+    let ptr = unsafe { alloc::alloc(Layout::new::<u8>()) };
+    let alloc_id = get_alloc_id(ptr);
+    print_borrow_tree(alloc_id);
+
+    let r = unsafe { &mut *ptr };
+    print_borrow_tree(alloc_id);
+
+    *r = 42;
+    let _s = &*r;
+    print_borrow_tree(alloc_id);
+
+    // A write through the base pointer disables all other tags.
+    unsafe { *ptr = 0 };
+    print_borrow_tree(alloc_id);
+
+    unsafe { alloc::dealloc(ptr, Layout::new::<u8>()) };
+}